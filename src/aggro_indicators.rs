@@ -0,0 +1,129 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::audio::{SfxEvent, SoundEffect};
+use crate::enemy::{Enemy, EnemyAi, EnemyAiState};
+use crate::game::{GameState, InGame};
+use crate::layers::Layer;
+
+// Aggro indicator constants
+const AGGRO_INDICATOR_LIFETIME: f32 = 0.9;
+const AGGRO_INDICATOR_OFFSET_Y: f32 = 95.0; // Above the skeleton's head
+const AGGRO_INDICATOR_POP_TIME: f32 = 0.12; // Grows to full size this fast
+const AGGRO_INDICATOR_FADE_TIME: f32 = 0.25;
+const AGGRO_INDICATOR_FONT_SIZE: f32 = 36.0;
+const SPOTTED_COLOR: Color = Color::srgb(1.0, 0.3, 0.2);
+const LOST_COLOR: Color = Color::srgb(0.95, 0.9, 0.5);
+
+// "!" or "?" floating over an enemy whose awareness of the player just changed
+#[derive(Component)]
+struct AggroIndicator {
+    enemy: Entity,
+    timer: Timer,
+}
+
+pub struct AggroIndicatorsPlugin;
+
+impl Plugin for AggroIndicatorsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (spawn_aggro_indicators, update_aggro_indicators)
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+// Compares each enemy's AI state with the one it had last frame
+fn spawn_aggro_indicators(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    enemies: Query<(Entity, &Enemy, &EnemyAi, &Transform)>,
+    indicators: Query<(Entity, &AggroIndicator)>,
+    mut previous_states: Local<HashMap<Entity, EnemyAiState>>,
+    mut sfx_events: EventWriter<SfxEvent>,
+) {
+    let mut states = HashMap::new();
+
+    for (entity, enemy, ai, transform) in &enemies {
+        states.insert(entity, ai.state);
+        if enemy.is_dead {
+            continue;
+        }
+        let Some(&previous) = previous_states.get(&entity) else {
+            continue;
+        };
+
+        // Coming back to the chase from a charge isn't noticing the player again
+        let (text, color) = match (previous, ai.state) {
+            (
+                EnemyAiState::Patrolling | EnemyAiState::Searching | EnemyAiState::Returning,
+                EnemyAiState::Chasing,
+            ) => {
+                sfx_events.send(SfxEvent {
+                    effect: SoundEffect::AggroSting,
+                    position: Some(transform.translation.truncate()),
+                });
+                ("!", SPOTTED_COLOR)
+            }
+            (EnemyAiState::Chasing, EnemyAiState::Searching) => ("?", LOST_COLOR),
+            _ => continue,
+        };
+
+        // Only the latest reaction stays over the enemy
+        for (indicator_entity, indicator) in &indicators {
+            if indicator.enemy == entity {
+                commands.entity(indicator_entity).despawn_recursive();
+            }
+        }
+
+        commands.spawn((
+            AggroIndicator {
+                enemy: entity,
+                timer: Timer::from_seconds(AGGRO_INDICATOR_LIFETIME, TimerMode::Once),
+            },
+            StateScoped(InGame),
+            Text2d::new(text),
+            TextFont {
+                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                font_size: AGGRO_INDICATOR_FONT_SIZE,
+                ..default()
+            },
+            TextColor(color),
+            Transform::from_scale(Vec3::ZERO),
+        ));
+    }
+
+    *previous_states = states;
+}
+
+// Follows the enemy, pops in and fades out near the end
+fn update_aggro_indicators(
+    mut commands: Commands,
+    time: Res<Time>,
+    enemies: Query<(&Enemy, &Transform), Without<AggroIndicator>>,
+    mut indicators: Query<(Entity, &mut AggroIndicator, &mut Transform, &mut TextColor)>,
+) {
+    for (entity, mut indicator, mut transform, mut color) in &mut indicators {
+        indicator.timer.tick(time.delta());
+
+        let Ok((enemy, enemy_transform)) = enemies.get(indicator.enemy) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+        if indicator.timer.finished() || enemy.is_dead {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        transform.translation = enemy_transform.translation.truncate().extend(0.0)
+            + Vec3::new(0.0, AGGRO_INDICATOR_OFFSET_Y, Layer::Overlay.z());
+
+        let elapsed = indicator.timer.elapsed_secs();
+        transform.scale = Vec3::splat((elapsed / AGGRO_INDICATOR_POP_TIME).min(1.0));
+        color
+            .0
+            .set_alpha((indicator.timer.remaining_secs() / AGGRO_INDICATOR_FADE_TIME).min(1.0));
+    }
+}
@@ -0,0 +1,143 @@
+use bevy::prelude::*;
+
+use crate::game::{GameState, InGame};
+use crate::level::{CurrentRoom, RoomRegistry};
+use crate::paralax_background::{ParallaxLayer, StaticBackground};
+
+// Ambient constants
+const AMBIENT_TRANSITION_RATE: f32 = 1.5; // How quickly a new palette takes over, per second
+const DAY_NIGHT_LENGTH: f32 = 240.0; // Seconds for a full day in rooms that cycle
+
+// Tint multiplied over the backdrop: one color for the image behind everything and one
+// for the parallax layers, which usually stay a bit brighter
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Palette {
+    pub background: Color,
+    pub layers: Color,
+}
+
+impl Palette {
+    pub const DAY: Self = Self {
+        background: Color::WHITE,
+        layers: Color::WHITE,
+    };
+    pub const DUSK: Self = Self {
+        background: Color::srgb(1.0, 0.72, 0.6),
+        layers: Color::srgb(0.95, 0.75, 0.72),
+    };
+    pub const NIGHT: Self = Self {
+        background: Color::srgb(0.3, 0.35, 0.6),
+        layers: Color::srgb(0.4, 0.45, 0.7),
+    };
+    pub const DAWN: Self = Self {
+        background: Color::srgb(0.95, 0.8, 0.85),
+        layers: Color::srgb(0.9, 0.85, 0.9),
+    };
+
+    fn mix(self, other: Self, t: f32) -> Self {
+        Self {
+            background: self.background.mix(&other.background, t),
+            layers: self.layers.mix(&other.layers, t),
+        }
+    }
+}
+
+// Light a room is shown in
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Ambient {
+    Fixed(Palette),
+    DayNight, // Goes from day through dusk, night and dawn as the run goes on
+}
+
+impl Default for Ambient {
+    fn default() -> Self {
+        Ambient::Fixed(Palette::DAY)
+    }
+}
+
+// Palette the backdrop is tinted with right now, easing toward the current room's
+#[derive(Resource)]
+pub struct AmbientPalette {
+    pub current: Palette,
+    time_of_day: f32, // 0 to 1, for rooms on the day and night cycle
+}
+
+impl Default for AmbientPalette {
+    fn default() -> Self {
+        Self {
+            current: Palette::DAY,
+            time_of_day: 0.0,
+        }
+    }
+}
+
+impl AmbientPalette {
+    fn target(&self, ambient: Ambient) -> Palette {
+        match ambient {
+            Ambient::Fixed(palette) => palette,
+            Ambient::DayNight => {
+                let cycle = [Palette::DAY, Palette::DUSK, Palette::NIGHT, Palette::DAWN];
+                let position = self.time_of_day * cycle.len() as f32;
+                let index = position.floor() as usize % cycle.len();
+                let next = (index + 1) % cycle.len();
+                cycle[index].mix(cycle[next], position.fract())
+            }
+        }
+    }
+}
+
+pub struct AmbientPlugin;
+
+impl Plugin for AmbientPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AmbientPalette>()
+            .add_systems(OnEnter(InGame), reset_ambient)
+            .add_systems(
+                Update,
+                (update_ambient, tint_backdrop)
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+// Every run starts in daylight, whatever the last one ended in
+fn reset_ambient(mut ambient: ResMut<AmbientPalette>) {
+    *ambient = AmbientPalette::default();
+}
+
+fn update_ambient(
+    time: Res<Time>,
+    current_room: Res<CurrentRoom>,
+    registry: Res<RoomRegistry>,
+    mut ambient: ResMut<AmbientPalette>,
+) {
+    ambient.time_of_day = (ambient.time_of_day + time.delta_secs() / DAY_NIGHT_LENGTH).fract();
+
+    let room_ambient = current_room
+        .id
+        .as_deref()
+        .and_then(|id| registry.get(id))
+        .map_or_else(Ambient::default, |room| room.ambient);
+    let target = ambient.target(room_ambient);
+
+    // Same transition speed whatever the frame rate
+    let blend = 1.0 - (-AMBIENT_TRANSITION_RATE * time.delta_secs()).exp();
+    ambient.current = ambient.current.mix(target, blend);
+}
+
+// Layers keep their own alpha, foreground fog stays see-through under any light
+fn tint_backdrop(
+    ambient: Res<AmbientPalette>,
+    mut layers: Query<&mut Sprite, (With<ParallaxLayer>, Without<StaticBackground>)>,
+    mut backgrounds: Query<&mut Sprite, With<StaticBackground>>,
+) {
+    let palette = ambient.current;
+    for mut sprite in &mut layers {
+        let alpha = sprite.color.alpha();
+        sprite.color = palette.layers.with_alpha(alpha);
+    }
+    for mut sprite in &mut backgrounds {
+        sprite.color = palette.background;
+    }
+}
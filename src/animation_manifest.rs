@@ -0,0 +1,503 @@
+use std::fmt;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext, RecursiveDependencyLoadState};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::Deserialize;
+
+use crate::animations::{
+    AnimationController, AnimationCue, AnimationData, AnimationMirror, CharacterAnimations,
+    CharacterState, CurrentAnimation, Facing,
+};
+use crate::game::GameState;
+use crate::hitbox::{AttackWindow, HitReaction, Knockback};
+use crate::level::LevelFile;
+use crate::status_effects::StatusEffectKind;
+
+// Animation manifest paths
+const PLAYER_ANIMATIONS_PATH: &str = "animations/hero.anim.ron";
+const SKELETON_ANIMATIONS_PATH: &str = "animations/skeleton.anim.ron";
+const DEFAULT_ATTACK_DURATION: f32 = 0.05;
+
+// Every animation of a character, with the atlas layouts already built from the
+// grid sizes written in its .anim.ron file
+#[derive(Asset, TypePath)]
+pub struct AnimationManifest {
+    pub facing: Facing, // Way the character looks in its sheets
+    pub animations: Vec<AnimationData>,
+    pub attacks: Vec<AttackWindow>, // Hitboxes the attack animations put out
+}
+
+// Points a character at its manifest; its CharacterAnimations are filled in once the
+// manifest has loaded
+#[derive(Component)]
+pub struct AnimationSet(pub Handle<AnimationManifest>);
+
+// Manifests every character spawns from, loaded once when the game starts. Holding the
+// handles here keeps them loaded while no character of that kind is around, so the next
+// spawn doesn't load the textures and build the layouts all over again
+#[derive(Resource)]
+pub struct CharacterAssetCache {
+    pub player: Handle<AnimationManifest>,
+    pub skeleton: Handle<AnimationManifest>,
+}
+
+impl FromWorld for CharacterAssetCache {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        Self {
+            player: asset_server.load(PLAYER_ANIMATIONS_PATH),
+            skeleton: asset_server.load(SKELETON_ANIMATIONS_PATH),
+        }
+    }
+}
+
+// Attack windows per character ("player", "skeleton"), taken from the attacks of their
+// manifests. Characters whose manifest lists none keep the attacks defined in code
+#[derive(Resource, Default, Clone, Debug)]
+pub struct AttackDefinitions {
+    characters: HashMap<String, Vec<AttackWindow>>,
+}
+
+impl AttackDefinitions {
+    pub fn windows(&self, character: &str) -> Option<Vec<AttackWindow>> {
+        self.characters.get(character).cloned()
+    }
+}
+
+// One entry of the file, the way it is written
+#[derive(Deserialize)]
+struct AnimationEntry {
+    state: CharacterState,
+    texture: String,
+    mirror_texture: Option<String>, // Same grid drawn facing the other way
+    frame_size: (u32, u32),
+    columns: u32,
+    #[serde(default = "default_rows")]
+    rows: u32,
+    frames: usize,
+    fps: f32,
+    #[serde(default)]
+    frame_durations: Option<Vec<f32>>, // Overrides fps for strips that hold some frames longer
+    #[serde(default)]
+    looping: bool,
+    #[serde(default)]
+    ping_pong: bool,
+    #[serde(default)]
+    reversed: bool, // Plays the strip from its last frame to its first
+    #[serde(default)]
+    events: Vec<(usize, AnimationCue)>,
+}
+
+// One hitbox of an attack animation, the way it is written
+#[derive(Deserialize)]
+struct AttackEntry {
+    state: CharacterState,
+    frames: (usize, usize), // Animation frames the hitbox can appear on, end excluded
+    #[serde(default = "default_damage")]
+    damage: f32, // Multiplier of the character's attack stat
+    size: (f32, f32),
+    #[serde(default)]
+    offset: (f32, f32), // Positive x in front of the character
+    #[serde(default = "default_attack_duration")]
+    duration: f32,
+    #[serde(default)]
+    knockback: Knockback,
+    #[serde(default)]
+    launch: f32,
+    #[serde(default)]
+    guard_break: bool,
+    #[serde(default)]
+    reaction: Option<HitReaction>, // None picks one from the damage dealt
+    #[serde(default)]
+    status: Option<StatusEffectKind>,
+}
+
+#[derive(Deserialize)]
+struct AnimationManifestFile {
+    #[serde(default)]
+    facing: Facing,
+    animations: Vec<AnimationEntry>,
+    #[serde(default)]
+    attacks: Vec<AttackEntry>,
+}
+
+fn default_rows() -> u32 {
+    1
+}
+
+fn default_damage() -> f32 {
+    1.0
+}
+
+fn default_attack_duration() -> f32 {
+    DEFAULT_ATTACK_DURATION
+}
+
+#[derive(Debug)]
+pub enum AnimationManifestError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    Invalid(String),
+}
+
+impl fmt::Display for AnimationManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AnimationManifestError::Io(error) => write!(f, "could not read manifest: {error}"),
+            AnimationManifestError::Parse(error) => write!(f, "could not parse manifest: {error}"),
+            AnimationManifestError::Invalid(message) => write!(f, "invalid manifest: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for AnimationManifestError {}
+
+impl From<std::io::Error> for AnimationManifestError {
+    fn from(error: std::io::Error) -> Self {
+        AnimationManifestError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for AnimationManifestError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        AnimationManifestError::Parse(error)
+    }
+}
+
+#[derive(Default)]
+struct AnimationManifestLoader;
+
+impl AssetLoader for AnimationManifestLoader {
+    type Asset = AnimationManifest;
+    type Settings = ();
+    type Error = AnimationManifestError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<AnimationManifest, AnimationManifestError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let file: AnimationManifestFile = ron::de::from_bytes(&bytes)?;
+
+        let attacks = file
+            .attacks
+            .into_iter()
+            .map(|entry| build_attack(entry, &file.animations))
+            .collect::<Result<_, _>>()?;
+
+        let mut animations = Vec::with_capacity(file.animations.len());
+        for entry in file.animations {
+            animations.push(build_animation(entry, load_context)?);
+        }
+
+        Ok(AnimationManifest {
+            facing: file.facing,
+            animations,
+            attacks,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["anim.ron"]
+    }
+}
+
+fn build_animation(
+    entry: AnimationEntry,
+    load_context: &mut LoadContext,
+) -> Result<AnimationData, AnimationManifestError> {
+    let cells = (entry.columns * entry.rows) as usize;
+    if entry.frames == 0 || entry.frames > cells {
+        return Err(AnimationManifestError::Invalid(format!(
+            "{:?} has {} frames but its grid only fits {cells}",
+            entry.state, entry.frames
+        )));
+    }
+    if entry.fps <= 0.0 {
+        return Err(AnimationManifestError::Invalid(format!(
+            "{:?} needs a positive fps",
+            entry.state
+        )));
+    }
+    if let Some(durations) = &entry.frame_durations {
+        if durations.len() != entry.frames {
+            return Err(AnimationManifestError::Invalid(format!(
+                "{:?} has {} frame durations for {} frames",
+                entry.state,
+                durations.len(),
+                entry.frames
+            )));
+        }
+        if durations.iter().any(|duration| *duration <= 0.0) {
+            return Err(AnimationManifestError::Invalid(format!(
+                "{:?} needs positive frame durations",
+                entry.state
+            )));
+        }
+    }
+    if let Some(&(frame, _)) = entry
+        .events
+        .iter()
+        .find(|(frame, _)| *frame >= entry.frames)
+    {
+        return Err(AnimationManifestError::Invalid(format!(
+            "{:?} has an event on frame {frame}, past its last one",
+            entry.state
+        )));
+    }
+
+    let mut layout = TextureAtlasLayout::from_grid(
+        UVec2::new(entry.frame_size.0, entry.frame_size.1),
+        entry.columns,
+        entry.rows,
+        None,
+        None,
+    );
+    if entry.reversed {
+        layout.textures.truncate(entry.frames);
+        layout.textures.reverse();
+    }
+    let atlas_layout = load_context.add_labeled_asset(format!("{:?}", entry.state), layout);
+
+    let mirror = match entry.mirror_texture {
+        Some(path) => AnimationMirror::Sheet {
+            texture: load_context.load(path),
+            atlas_layout: atlas_layout.clone(),
+        },
+        None => AnimationMirror::Flip,
+    };
+
+    Ok(AnimationData {
+        state: entry.state,
+        texture: load_context.load(entry.texture),
+        atlas_layout,
+        frames: entry.frames,
+        fps: entry.fps,
+        frame_durations: entry.frame_durations,
+        looping: entry.looping,
+        ping_pong: entry.ping_pong,
+        mirror,
+        events: entry.events,
+    })
+}
+
+// Attacks have to land on frames their animation actually has
+fn build_attack(
+    entry: AttackEntry,
+    animations: &[AnimationEntry],
+) -> Result<AttackWindow, AnimationManifestError> {
+    let invalid = |message: &str| {
+        Err(AnimationManifestError::Invalid(format!(
+            "{:?} attack {message}",
+            entry.state
+        )))
+    };
+
+    let Some(animation) = animations
+        .iter()
+        .find(|animation| animation.state == entry.state)
+    else {
+        return invalid("has no animation");
+    };
+    let (start, end) = entry.frames;
+    if start >= end || end > animation.frames {
+        return invalid(&format!(
+            "frames {start}..{end} don't fit its {} frames",
+            animation.frames
+        ));
+    }
+    if entry.size.0 <= 0.0 || entry.size.1 <= 0.0 {
+        return invalid("needs a positive size");
+    }
+    if entry.duration <= 0.0 {
+        return invalid("needs a positive duration");
+    }
+    if !entry.knockback.is_none() && entry.knockback.decay <= 0.0 {
+        return invalid("needs a knockback decay above 0");
+    }
+
+    Ok(AttackWindow {
+        state: entry.state,
+        frames: start..end,
+        damage_multiplier: entry.damage,
+        size: Vec2::new(entry.size.0, entry.size.1),
+        offset: Vec2::new(entry.offset.0, entry.offset.1),
+        duration: entry.duration,
+        knockback: entry.knockback,
+        launch: entry.launch,
+        guard_break: entry.guard_break,
+        reaction: entry.reaction,
+        status: entry.status,
+    })
+}
+
+pub struct AnimationManifestPlugin;
+
+impl Plugin for AnimationManifestPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<AnimationManifest>()
+            .init_asset_loader::<AnimationManifestLoader>()
+            .init_resource::<CharacterAssetCache>()
+            .init_resource::<AttackDefinitions>()
+            .add_systems(
+                Update,
+                (
+                    finish_loading.run_if(in_state(GameState::Loading)),
+                    collect_attack_definitions,
+                ),
+            )
+            // After Update so characters spawned this frame are drawn with their first frame
+            .add_systems(PostUpdate, apply_animation_sets);
+    }
+}
+
+// Opens the menu once every character's manifest and textures are in memory. A manifest
+// or texture that fails to load doesn't hold the game up either
+fn finish_loading(
+    asset_server: Res<AssetServer>,
+    cache: Res<CharacterAssetCache>,
+    level_file: Res<LevelFile>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let handles = [
+        cache.player.clone().untyped(),
+        cache.skeleton.clone().untyped(),
+        level_file.handle.clone().untyped(),
+    ];
+    let states = handles
+        .each_ref()
+        .map(|handle| asset_server.recursive_dependency_load_state(handle.id()));
+    if states.iter().any(|state| {
+        matches!(
+            state,
+            RecursiveDependencyLoadState::NotLoaded | RecursiveDependencyLoadState::Loading
+        )
+    }) {
+        return;
+    }
+
+    for (handle, state) in handles.iter().zip(&states) {
+        if let RecursiveDependencyLoadState::Failed(error) = state {
+            // The game still runs without it, the characters just can't be drawn or the
+            // level is left to its handmade rooms
+            warn!("Could not load {:?}: {error}", handle.path());
+        }
+    }
+    next_state.set(GameState::Menu);
+}
+
+// Picks up the attacks of each manifest once it has loaded and again whenever it is saved
+fn collect_attack_definitions(
+    mut events: EventReader<AssetEvent<AnimationManifest>>,
+    cache: Res<CharacterAssetCache>,
+    manifests: Res<Assets<AnimationManifest>>,
+    mut definitions: ResMut<AttackDefinitions>,
+) {
+    for event in events.read() {
+        let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = *event
+        else {
+            continue;
+        };
+        let character = if id == cache.player.id() {
+            "player"
+        } else if id == cache.skeleton.id() {
+            "skeleton"
+        } else {
+            continue;
+        };
+        let Some(manifest) = manifests.get(id) else {
+            continue;
+        };
+
+        if manifest.attacks.is_empty() {
+            definitions.characters.remove(character);
+        } else {
+            definitions
+                .characters
+                .insert(character.to_string(), manifest.attacks.clone());
+        }
+    }
+}
+
+// Gives characters waiting on a manifest their animations and shows the current one
+fn apply_animation_sets(
+    mut commands: Commands,
+    manifests: Res<Assets<AnimationManifest>>,
+    mut characters: Query<
+        (
+            Entity,
+            &AnimationSet,
+            &AnimationController,
+            &mut Sprite,
+            &mut CurrentAnimation,
+        ),
+        Without<CharacterAnimations>,
+    >,
+) {
+    for (entity, animation_set, controller, mut sprite, mut current_animation) in &mut characters {
+        let Some(manifest) = manifests.get(&animation_set.0) else {
+            continue;
+        };
+
+        let state = controller.get_current_state();
+        if let Some(animation_data) = manifest
+            .animations
+            .iter()
+            .find(|animation| animation.state == state)
+        {
+            animation_data.start(&mut sprite, &mut current_animation);
+        }
+
+        commands.entity(entity).insert(CharacterAnimations {
+            animations: manifest.animations.clone(),
+            drawn_facing: manifest.facing,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attacks(contents: &str) -> Result<Vec<AttackWindow>, AnimationManifestError> {
+        let file: AnimationManifestFile = ron::from_str(contents)?;
+        file.attacks
+            .into_iter()
+            .map(|entry| build_attack(entry, &file.animations))
+            .collect()
+    }
+
+    #[test]
+    fn shipped_manifests_have_their_attacks() {
+        let hero = attacks(include_str!("../assets/animations/hero.anim.ron")).unwrap();
+        assert_eq!(hero.len(), 2);
+        assert_eq!(hero[1].state, CharacterState::ChargeAttacking);
+        assert_eq!(hero[1].reaction, Some(HitReaction::Launch));
+        assert_eq!(hero[0].duration, DEFAULT_ATTACK_DURATION);
+
+        let skeleton = attacks(include_str!("../assets/animations/skeleton.anim.ron")).unwrap();
+        assert_eq!(skeleton.len(), 2);
+        assert_eq!(skeleton[1].frames, 13..16);
+    }
+
+    #[test]
+    fn attacks_past_the_last_frame_are_turned_down() {
+        let contents = r#"(
+            animations: [
+                (state: Attacking, texture: "a.png", frame_size: (8, 8), columns: 4, frames: 4, fps: 10.0),
+            ],
+            attacks: [
+                (state: Attacking, frames: (3, 5), size: (10.0, 10.0)),
+            ],
+        )"#;
+        assert!(matches!(
+            attacks(contents),
+            Err(AnimationManifestError::Invalid(_))
+        ));
+    }
+}
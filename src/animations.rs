@@ -1,7 +1,12 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::audio::SoundEffect;
 
 // Estado del personaje
-#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
 pub enum CharacterState {
     Idle,
     Attacking,
@@ -9,9 +14,60 @@ pub enum CharacterState {
     Running,
     Jumping,
     Hurt,
+    Staggered,
+    Launched,
     Dead,
     Falling,
+    Landing, // Touching down after a fall, before going back to idle or running
+    WakingUp,
+    LedgeHanging, // Holding on to the edge of a platform or wall
+}
+
+impl CharacterState {
+    // Any of the hit reactions
+    pub fn is_hurt(self) -> bool {
+        matches!(
+            self,
+            CharacterState::Hurt | CharacterState::Staggered | CharacterState::Launched
+        )
+    }
+}
+// Way a character is looking. The sprite is flipped to match instead of the transform,
+// so the character keeps a positive scale and its children are mirrored one by one
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum Facing {
+    Left,
+    #[default]
+    Right,
+}
+
+impl Facing {
+    pub fn towards(from_x: f32, to_x: f32) -> Self {
+        if to_x > from_x {
+            Facing::Right
+        } else {
+            Facing::Left
+        }
+    }
+
+    // 1 facing right, -1 facing left
+    pub fn sign(self) -> f32 {
+        match self {
+            Facing::Left => -1.0,
+            Facing::Right => 1.0,
+        }
+    }
+
+    // Offset written for a character facing right, turned the way this one faces
+    pub fn mirror(self, offset: Vec2) -> Vec2 {
+        Vec2::new(offset.x * self.sign(), offset.y)
+    }
 }
+
+// Child kept in front of (positive x) or behind its character whichever way it faces
+#[derive(Component)]
+pub struct FacingOffset(pub Vec2);
+
 #[derive(Component)]
 pub struct CharacterDimensions {
     pub height: f32,
@@ -55,6 +111,7 @@ impl AnimationController {
 #[derive(Component)]
 pub struct CharacterAnimations {
     pub animations: Vec<AnimationData>,
+    pub drawn_facing: Facing, // Way the character looks in the sheets
 }
 
 #[derive(Clone)]
@@ -64,27 +121,123 @@ pub struct AnimationData {
     pub atlas_layout: Handle<TextureAtlasLayout>,
     pub frames: usize,
     pub fps: f32,
+    pub frame_durations: Option<Vec<f32>>, // Seconds per frame, in place of fps when set
     pub looping: bool,
     pub ping_pong: bool,
+    pub mirror: AnimationMirror,
+    pub events: Vec<(usize, AnimationCue)>, // Frame index and what happens when it is shown
 }
 
-#[derive(Component)]
+// Something that should happen in sync with a frame of an animation
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum AnimationCue {
+    Footstep, // Played with the sound of the surface under the character
+    PlaySfx(SoundEffect),
+}
+
+// Sent when a character's animation reaches a frame that has a cue
+#[derive(Event)]
+pub struct AnimationEvent {
+    pub entity: Entity,
+    pub state: CharacterState,
+    pub frame: usize,
+    pub cue: AnimationCue,
+}
+
+// Sent once when an animation that doesn't loop shows its last frame. What the character
+// does next is up to its own systems
+#[derive(Event)]
+pub struct AnimationFinishedEvent {
+    pub entity: Entity,
+    pub state: CharacterState,
+}
+
+impl AnimationData {
+    // Shows the first frame on the sprite and restarts the playback from it
+    pub fn start(&self, sprite: &mut Sprite, current_animation: &mut CurrentAnimation) {
+        sprite.image = self.texture.clone();
+        sprite.texture_atlas = Some(TextureAtlas {
+            layout: self.atlas_layout.clone(),
+            index: 0,
+        });
+
+        *current_animation = CurrentAnimation {
+            current_frame: 0,
+            timer: Timer::from_seconds(self.frame_duration(0), TimerMode::Repeating),
+            total_frames: self.frames,
+            looping: self.looping,
+            reverse_direction: false,
+            finished: false,
+        };
+    }
+
+    // How long a frame stays on screen before the next one is shown
+    pub fn frame_duration(&self, frame: usize) -> f32 {
+        self.frame_durations
+            .as_ref()
+            .and_then(|durations| durations.get(frame).copied())
+            .unwrap_or(1.0 / self.fps)
+    }
+
+    fn send_events(
+        &self,
+        entity: Entity,
+        frame: usize,
+        animation_events: &mut EventWriter<AnimationEvent>,
+    ) {
+        for &(cue_frame, cue) in &self.events {
+            if cue_frame == frame {
+                animation_events.send(AnimationEvent {
+                    entity,
+                    state: self.state,
+                    frame,
+                    cue,
+                });
+            }
+        }
+    }
+}
+
+// How an animation is shown when the character faces away from the way it was drawn
+#[derive(Clone, Default)]
+pub enum AnimationMirror {
+    // Plain horizontal flip of the sheet
+    #[default]
+    Flip,
+    // Dedicated sheet drawn facing the other way, same frames in the same order,
+    // for characters that keep a weapon in the same hand whichever way they face
+    Sheet {
+        texture: Handle<Image>,
+        atlas_layout: Handle<TextureAtlasLayout>,
+    },
+}
+
+#[derive(Component, Default)]
 pub struct CurrentAnimation {
     pub current_frame: usize,
     pub timer: Timer,
     pub total_frames: usize,
     pub looping: bool,
     pub reverse_direction: bool,
+    pub finished: bool, // Holding the last frame of an animation that doesn't loop
 }
 
 pub struct AnimationPlugin;
 
 impl Plugin for AnimationPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (update_animation_state, animate_current_state).chain(),
-        );
+        app.add_event::<AnimationEvent>()
+            .add_event::<AnimationFinishedEvent>()
+            .add_systems(
+                Update,
+                (
+                    update_animation_state,
+                    animate_current_state,
+                    apply_animation_mirroring,
+                    apply_facing_offsets,
+                )
+                    .chain(),
+            );
     }
 }
 
@@ -97,8 +250,9 @@ pub fn update_animation_state(
         &mut CurrentAnimation,
         &mut Sprite,
     )>,
+    mut animation_events: EventWriter<AnimationEvent>,
 ) {
-    for (_entity, mut controller, animations, mut current_animation, mut sprite) in &mut query {
+    for (entity, mut controller, animations, mut current_animation, mut sprite) in &mut query {
         if controller.apply_next_state() {
             let current_state = controller.get_current_state();
 
@@ -108,21 +262,8 @@ pub fn update_animation_state(
                 .iter()
                 .find(|anim| anim.state == current_state)
             {
-                // Actualizar sprite y animación
-                sprite.image = animation_data.texture.clone();
-                sprite.texture_atlas = Some(TextureAtlas {
-                    layout: animation_data.atlas_layout.clone(),
-                    index: 0,
-                });
-
-                // Configurar la nueva animación
-                *current_animation = CurrentAnimation {
-                    current_frame: 0,
-                    timer: Timer::from_seconds(1.0 / animation_data.fps, TimerMode::Repeating),
-                    total_frames: animation_data.frames,
-                    looping: animation_data.looping,
-                    reverse_direction: false,
-                };
+                animation_data.start(&mut sprite, &mut current_animation);
+                animation_data.send_events(entity, 0, &mut animation_events);
             }
         }
     }
@@ -131,66 +272,151 @@ pub fn update_animation_state(
 pub fn animate_current_state(
     time: Res<Time>,
     mut query: Query<(
+        Entity,
         &mut CurrentAnimation,
-        &mut AnimationController,
+        &AnimationController,
         &mut Sprite,
         &CharacterAnimations,
     )>,
+    mut animation_events: EventWriter<AnimationEvent>,
+    mut finished_events: EventWriter<AnimationFinishedEvent>,
 ) {
-    for (mut animation, mut controller, mut sprite, character_animations) in &mut query {
+    for (entity, mut animation, controller, mut sprite, character_animations) in &mut query {
         // Update the animation timer
         animation.timer.tick(time.delta());
 
-        if animation.timer.just_finished() {
-            if let Some(atlas) = &mut sprite.texture_atlas {
-                // Buscar la configuración de animación actual
-                let current_state = controller.get_current_state();
-                let current_animation_data = character_animations
-                    .animations
-                    .iter()
-                    .find(|anim| anim.state == current_state);
-
-                let ping_pong = current_animation_data
-                    .map(|data| data.ping_pong)
-                    .unwrap_or(false);
-
-                // Determine direction of animation
-                if animation.reverse_direction && ping_pong {
-                    animation.current_frame -= 1;
-                    // If we've reached the first frame, change direction
-                    if animation.current_frame <= 0 {
-                        animation.current_frame = 0;
-                        animation.reverse_direction = false;
-                    }
-                } else {
-                    animation.current_frame += 1;
-                    // If we've reached the last frame
-                    if animation.current_frame >= animation.total_frames {
-                        if animation.looping {
-                            if ping_pong {
-                                // Para animaciones ping-pong (como idle)
-                                animation.current_frame = animation.total_frames - 1;
-                                animation.reverse_direction = true;
-                            } else {
-                                // Para animaciones de loop regular (como running)
-                                animation.current_frame = 0;
-                            }
-                        } else {
-                            // Para animaciones sin loop (como ataques)
+        if animation.timer.just_finished()
+            && let Some(atlas) = &mut sprite.texture_atlas
+        {
+            // Buscar la configuración de animación actual
+            let current_state = controller.get_current_state();
+            let current_animation_data = character_animations
+                .animations
+                .iter()
+                .find(|anim| anim.state == current_state);
+
+            let ping_pong = current_animation_data
+                .map(|data| data.ping_pong)
+                .unwrap_or(false);
+            let previous_frame = animation.current_frame;
+
+            // Determine direction of animation
+            if animation.reverse_direction && ping_pong {
+                animation.current_frame -= 1;
+                // If we've reached the first frame, change direction
+                if animation.current_frame == 0 {
+                    animation.current_frame = 0;
+                    animation.reverse_direction = false;
+                }
+            } else {
+                animation.current_frame += 1;
+                // If we've reached the last frame
+                if animation.current_frame >= animation.total_frames {
+                    if animation.looping {
+                        if ping_pong {
+                            // Para animaciones ping-pong (como idle)
                             animation.current_frame = animation.total_frames - 1;
-                            if controller.get_current_state() == CharacterState::Attacking {
-                                controller.change_state(CharacterState::Idle);
-                            }
-                            if controller.get_current_state() == CharacterState::ChargeAttacking {
-                                controller.change_state(CharacterState::Idle);
-                            }
+                            animation.reverse_direction = true;
+                        } else {
+                            // Para animaciones de loop regular (como running)
+                            animation.current_frame = 0;
+                        }
+                    } else {
+                        // Para animaciones sin loop (como ataques)
+                        animation.current_frame = animation.total_frames - 1;
+                        if !animation.finished {
+                            animation.finished = true;
+                            finished_events.send(AnimationFinishedEvent {
+                                entity,
+                                state: current_state,
+                            });
                         }
                     }
                 }
+            }
+
+            // Update atlas index
+            atlas.index = animation.current_frame;
 
-                // Update atlas index
-                atlas.index = animation.current_frame;
+            // The next frame may be held longer or shorter than this one
+            if let Some(animation_data) = current_animation_data {
+                let duration = animation_data.frame_duration(animation.current_frame);
+                animation
+                    .timer
+                    .set_duration(Duration::from_secs_f32(duration));
+            }
+
+            // Cues fire once per frame shown, not while a finished animation holds its last one
+            if animation.current_frame != previous_frame
+                && let Some(animation_data) = current_animation_data
+            {
+                animation_data.send_events(entity, animation.current_frame, &mut animation_events);
             }
         }
     }
 }
+
+// Characters looking away from the way their sheets are drawn get the mirrored sheet if
+// the animation has one, or a plain flip of the sprite otherwise
+pub fn apply_animation_mirroring(
+    mut query: Query<(
+        &AnimationController,
+        &CharacterAnimations,
+        &Facing,
+        &mut Sprite,
+    )>,
+) {
+    for (controller, animations, facing, mut sprite) in &mut query {
+        let current_state = controller.get_current_state();
+        let Some(animation_data) = animations
+            .animations
+            .iter()
+            .find(|anim| anim.state == current_state)
+        else {
+            continue;
+        };
+
+        let mirrored = *facing != animations.drawn_facing;
+        let (texture, atlas_layout, flip_x) = match &animation_data.mirror {
+            AnimationMirror::Sheet {
+                texture,
+                atlas_layout,
+            } if mirrored => (texture, atlas_layout, false),
+            _ => (
+                &animation_data.texture,
+                &animation_data.atlas_layout,
+                mirrored,
+            ),
+        };
+
+        if sprite.image != *texture {
+            sprite.image = texture.clone();
+        }
+        if sprite.flip_x != flip_x {
+            sprite.flip_x = flip_x;
+        }
+        if let Some(atlas) = &mut sprite.texture_atlas
+            && atlas.layout != *atlas_layout
+        {
+            atlas.layout = atlas_layout.clone();
+        }
+    }
+}
+
+// Moves hitboxes, shields and the like to the side their character is facing
+pub fn apply_facing_offsets(
+    characters: Query<&Facing>,
+    mut children: Query<(&FacingOffset, &Parent, &mut Transform)>,
+) {
+    for (offset, parent, mut transform) in &mut children {
+        let Ok(facing) = characters.get(parent.get()) else {
+            continue;
+        };
+
+        let position = facing.mirror(offset.0);
+        if transform.translation.truncate() != position {
+            transform.translation.x = position.x;
+            transform.translation.y = position.y;
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::enemy::{EnemyKind, RespawnRule};
+use crate::level::RoomDefinition;
+use crate::physics::SurfaceMaterial;
+
+// Arena generator constants
+const ARENA_SEGMENT_WIDTH: f32 = 500.0;
+const ARENA_SAFE_SEGMENTS: usize = 1; // Plain ground at each end so the player never spawns over a pit
+const ARENA_PIT_CHANCE: f64 = 0.25;
+const ARENA_PIT_WIDTH_MIN: f32 = 140.0;
+const ARENA_PIT_WIDTH_MAX: f32 = 220.0; // Still clears with a running jump
+const ARENA_SURFACE_CHANCE: f64 = 0.3;
+const ARENA_PLATFORM_CHANCE: f64 = 0.4;
+const ARENA_PLATFORM_Y_MIN: f32 = -220.0;
+const ARENA_PLATFORM_Y_MAX: f32 = -130.0;
+const ARENA_PLATFORM_WIDTH_MIN: f32 = 140.0;
+const ARENA_PLATFORM_WIDTH_MAX: f32 = 260.0;
+const ARENA_ENEMY_CHANCE: f64 = 0.5;
+const ARENA_ENEMY_RESPAWN_DELAY: f32 = 20.0;
+
+// Slice of the arena whose contents are rolled on their own
+struct ArenaSegment {
+    min_x: f32,
+    max_x: f32,
+}
+
+impl ArenaSegment {
+    fn center(&self) -> f32 {
+        (self.min_x + self.max_x) / 2.0
+    }
+}
+
+// Lays out a room from ground segments, ledges and pits; the same seed always builds
+// the same arena, so a layout worth keeping can be replayed
+pub fn generate_arena(seed: u64, id: &str, name: &str, min_x: f32, max_x: f32) -> RoomDefinition {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut room = RoomDefinition::strip(id, name, Some(name), min_x, max_x);
+
+    let segment_count = ((max_x - min_x) / ARENA_SEGMENT_WIDTH).floor() as usize;
+    for index in 0..segment_count {
+        let segment = ArenaSegment {
+            min_x: min_x + index as f32 * ARENA_SEGMENT_WIDTH,
+            max_x: min_x + (index + 1) as f32 * ARENA_SEGMENT_WIDTH,
+        };
+        if index < ARENA_SAFE_SEGMENTS || index + ARENA_SAFE_SEGMENTS >= segment_count {
+            continue;
+        }
+
+        // A pit in the middle of the segment, leaving ground on both sides to jump from
+        let has_pit = rng.gen_bool(ARENA_PIT_CHANCE);
+        if has_pit {
+            let half_width = rng.gen_range(ARENA_PIT_WIDTH_MIN..ARENA_PIT_WIDTH_MAX) / 2.0;
+            room = room.with_pit(segment.center() - half_width, segment.center() + half_width);
+        } else if rng.gen_bool(ARENA_SURFACE_CHANCE) {
+            let material = if rng.gen_bool(0.5) {
+                SurfaceMaterial::Ice
+            } else {
+                SurfaceMaterial::Mud
+            };
+            room = room.with_surface(segment.min_x, segment.max_x, material);
+        }
+
+        if rng.gen_bool(ARENA_PLATFORM_CHANCE) {
+            let width = rng.gen_range(ARENA_PLATFORM_WIDTH_MIN..ARENA_PLATFORM_WIDTH_MAX);
+            let y = rng.gen_range(ARENA_PLATFORM_Y_MIN..ARENA_PLATFORM_Y_MAX);
+            let x = rng.gen_range(segment.min_x + width / 2.0..segment.max_x - width / 2.0);
+            room = room.with_platform(x, y, width);
+        }
+
+        // Enemies stand on solid ground, away from the pit
+        if !has_pit && rng.gen_bool(ARENA_ENEMY_CHANCE) {
+            let kind = match rng.gen_range(0..3) {
+                0 => EnemyKind::ShieldedSkeleton,
+                1 => EnemyKind::ChargingSkeleton,
+                _ => EnemyKind::Skeleton,
+            };
+            room = room.with_enemy(
+                kind,
+                segment.center(),
+                RespawnRule::AfterDelay(ARENA_ENEMY_RESPAWN_DELAY),
+            );
+        }
+    }
+
+    room
+}
@@ -0,0 +1,286 @@
+use std::fmt;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::animation_manifest::AnimationManifest;
+use crate::animations::{AnimationData, AnimationMirror, CharacterState, Facing};
+
+// Sprite sheet exported from Aseprite with its JSON data ("Array" frames, tags included).
+// Each tag named after a character state becomes that animation, with the frame rects,
+// durations and direction set in Aseprite, so no grid has to be written by hand
+#[derive(Deserialize)]
+struct AsepriteFile {
+    frames: Vec<AsepriteFrame>,
+    meta: AsepriteMeta,
+}
+
+#[derive(Deserialize)]
+struct AsepriteFrame {
+    frame: AsepriteRect,
+    duration: u32, // Milliseconds
+}
+
+#[derive(Deserialize)]
+struct AsepriteRect {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AsepriteMeta {
+    image: String, // Next to the JSON file
+    size: AsepriteSize,
+    #[serde(default)]
+    frame_tags: Vec<AsepriteTag>,
+}
+
+#[derive(Deserialize)]
+struct AsepriteSize {
+    w: u32,
+    h: u32,
+}
+
+#[derive(Deserialize)]
+struct AsepriteTag {
+    name: String,
+    from: usize,
+    to: usize,
+    #[serde(default)]
+    direction: AsepriteDirection,
+    repeat: Option<String>, // Missing or "0" loops forever
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum AsepriteDirection {
+    #[default]
+    Forward,
+    Reverse,
+    Pingpong,
+    PingpongReverse,
+}
+
+#[derive(Debug)]
+pub enum AsepriteError {
+    Io(std::io::Error),
+    Parse(serde_json::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for AsepriteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AsepriteError::Io(error) => write!(f, "could not read sheet data: {error}"),
+            AsepriteError::Parse(error) => write!(f, "could not parse sheet data: {error}"),
+            AsepriteError::Invalid(message) => write!(f, "invalid sheet data: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for AsepriteError {}
+
+impl From<std::io::Error> for AsepriteError {
+    fn from(error: std::io::Error) -> Self {
+        AsepriteError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for AsepriteError {
+    fn from(error: serde_json::Error) -> Self {
+        AsepriteError::Parse(error)
+    }
+}
+
+#[derive(Default)]
+struct AsepriteLoader;
+
+impl AssetLoader for AsepriteLoader {
+    type Asset = AnimationManifest;
+    type Settings = ();
+    type Error = AsepriteError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<AnimationManifest, AsepriteError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let file: AsepriteFile = serde_json::from_slice(&bytes)?;
+
+        let image_path = load_context
+            .path()
+            .parent()
+            .map(|folder| folder.join(&file.meta.image))
+            .unwrap_or_else(|| file.meta.image.clone().into());
+        let texture: Handle<Image> = load_context.load(image_path);
+
+        let mut animations = Vec::new();
+        for tag in &file.meta.frame_tags {
+            // Tags for anything the game doesn't animate are left alone
+            let Ok(state) = ron::from_str::<CharacterState>(&tag.name) else {
+                warn!(
+                    "Aseprite tag {} in {} is not a character state",
+                    tag.name,
+                    load_context.path().display()
+                );
+                continue;
+            };
+            animations.push(build_animation(
+                state,
+                tag,
+                &file,
+                texture.clone(),
+                load_context,
+            )?);
+        }
+
+        Ok(AnimationManifest {
+            facing: Facing::Right, // Aseprite doesn't say, sheets are drawn looking right
+            animations,
+            attacks: Vec::new(), // The characters' built-in attacks apply
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["aseprite.json"]
+    }
+}
+
+fn build_animation(
+    state: CharacterState,
+    tag: &AsepriteTag,
+    file: &AsepriteFile,
+    texture: Handle<Image>,
+    load_context: &mut LoadContext,
+) -> Result<AnimationData, AsepriteError> {
+    let frames = tag_frames(tag, file)?;
+
+    let mut layout = TextureAtlasLayout::new_empty(UVec2::new(file.meta.size.w, file.meta.size.h));
+    for frame in &frames {
+        let rect = &frame.frame;
+        layout.add_texture(URect::new(rect.x, rect.y, rect.x + rect.w, rect.y + rect.h));
+    }
+    let atlas_layout = load_context.add_labeled_asset(format!("{state:?}"), layout);
+
+    let frame_durations: Vec<f32> = frames
+        .iter()
+        .map(|frame| frame.duration as f32 / 1000.0)
+        .collect();
+
+    Ok(AnimationData {
+        state,
+        texture,
+        atlas_layout,
+        frames: frames.len(),
+        fps: 1.0 / frame_durations[0],
+        frame_durations: Some(frame_durations),
+        looping: tag
+            .repeat
+            .as_deref()
+            .is_none_or(|repeat| repeat.trim() == "0"),
+        ping_pong: matches!(
+            tag.direction,
+            AsepriteDirection::Pingpong | AsepriteDirection::PingpongReverse
+        ),
+        mirror: AnimationMirror::Flip,
+        events: Vec::new(), // Cues are only written in .anim.ron manifests
+    })
+}
+
+// Frames of a tag in the order they play, checked against the sheet
+fn tag_frames<'a>(
+    tag: &AsepriteTag,
+    file: &'a AsepriteFile,
+) -> Result<Vec<&'a AsepriteFrame>, AsepriteError> {
+    if tag.from > tag.to || tag.to >= file.frames.len() {
+        return Err(AsepriteError::Invalid(format!(
+            "tag {} covers frames {}..={} but the sheet has {}",
+            tag.name,
+            tag.from,
+            tag.to,
+            file.frames.len()
+        )));
+    }
+
+    let mut frames: Vec<&AsepriteFrame> = file.frames[tag.from..=tag.to].iter().collect();
+    if frames.iter().any(|frame| frame.duration == 0) {
+        return Err(AsepriteError::Invalid(format!(
+            "tag {} has a frame with no duration",
+            tag.name
+        )));
+    }
+    if matches!(
+        tag.direction,
+        AsepriteDirection::Reverse | AsepriteDirection::PingpongReverse
+    ) {
+        frames.reverse();
+    }
+    Ok(frames)
+}
+
+pub struct AsepritePlugin;
+
+impl Plugin for AsepritePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset_loader::<AsepriteLoader>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Trimmed down from what Aseprite writes with "Array" frames and tags
+    const SHEET: &str = r#"{
+        "frames": [
+            { "filename": "hero 0", "frame": { "x": 0, "y": 0, "w": 32, "h": 32 }, "duration": 100 },
+            { "filename": "hero 1", "frame": { "x": 32, "y": 0, "w": 32, "h": 32 }, "duration": 150 },
+            { "filename": "hero 2", "frame": { "x": 64, "y": 0, "w": 32, "h": 32 }, "duration": 100 }
+        ],
+        "meta": {
+            "app": "https://www.aseprite.org/",
+            "image": "hero.png",
+            "size": { "w": 96, "h": 32 },
+            "frameTags": [
+                { "name": "Idle", "from": 0, "to": 1, "direction": "forward" },
+                { "name": "Run", "from": 0, "to": 2, "direction": "pingpong_reverse", "repeat": "2" },
+                { "name": "Jump", "from": 1, "to": 3, "direction": "forward" }
+            ]
+        }
+    }"#;
+
+    fn sheet() -> AsepriteFile {
+        serde_json::from_str(SHEET).unwrap()
+    }
+
+    #[test]
+    fn exported_sheet_parses() {
+        let file = sheet();
+        assert_eq!(file.frames.len(), 3);
+        assert_eq!(file.meta.image, "hero.png");
+        assert_eq!(file.meta.frame_tags.len(), 3);
+        assert_eq!(file.meta.frame_tags[1].repeat.as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn reversed_tags_play_backwards() {
+        let file = sheet();
+        let frames = tag_frames(&file.meta.frame_tags[1], &file).unwrap();
+        let xs: Vec<u32> = frames.iter().map(|frame| frame.frame.x).collect();
+        assert_eq!(xs, [64, 32, 0]);
+    }
+
+    #[test]
+    fn tags_past_the_last_frame_are_turned_down() {
+        let file = sheet();
+        assert!(tag_frames(&file.meta.frame_tags[2], &file).is_err());
+    }
+}
@@ -0,0 +1,139 @@
+use bevy::asset::LoadedFolder;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+// Space left between packed frames so neighbours never bleed into each other
+const ATLAS_FRAME_PADDING: UVec2 = UVec2::new(2, 2);
+
+// A texture atlas packed at load time from a folder of loose frame images
+#[derive(Clone)]
+pub struct PackedAtlas {
+    pub texture: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+    pub frame_count: usize,
+}
+
+impl PackedAtlas {
+    pub fn sprite(&self) -> Sprite {
+        Sprite::from_atlas_image(
+            self.texture.clone(),
+            TextureAtlas {
+                layout: self.layout.clone(),
+                index: 0,
+            },
+        )
+    }
+}
+
+// Frame folders waiting to finish loading, and the atlases already built from them
+#[derive(Resource, Default)]
+pub struct LooseFrameAtlases {
+    pending: Vec<(String, Handle<LoadedFolder>)>,
+    packed: HashMap<String, PackedAtlas>,
+}
+
+impl LooseFrameAtlases {
+    // Starts loading every frame in the folder; the atlas is packed once they are all in
+    pub fn request(&mut self, asset_server: &AssetServer, folder: &str) {
+        let already_requested = self.packed.contains_key(folder)
+            || self.pending.iter().any(|(pending, _)| pending == folder);
+        if !already_requested {
+            self.pending
+                .push((folder.to_string(), asset_server.load_folder(folder)));
+        }
+    }
+
+    pub fn get(&self, folder: &str) -> Option<&PackedAtlas> {
+        self.packed.get(folder)
+    }
+}
+
+// Sent once a requested folder has been packed and can be used
+#[derive(Event)]
+pub struct AtlasPackedEvent {
+    pub folder: String,
+}
+
+pub struct AtlasPackingPlugin;
+
+impl Plugin for AtlasPackingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LooseFrameAtlases>()
+            .add_event::<AtlasPackedEvent>()
+            .add_systems(Update, pack_loaded_folders);
+    }
+}
+
+// Frames are ordered by the number at the end of their file name, so
+// "run_2.png" comes before "run_10.png"
+fn frame_sort_key(path: &str) -> (String, u64) {
+    let stem = path.rsplit_once('.').map_or(path, |(stem, _)| stem);
+    let digits_start = stem.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    let number = stem[digits_start..].parse().unwrap_or(0);
+
+    (stem[..digits_start].to_string(), number)
+}
+
+fn pack_loaded_folders(
+    asset_server: Res<AssetServer>,
+    folders: Res<Assets<LoadedFolder>>,
+    mut atlases: ResMut<LooseFrameAtlases>,
+    mut images: ResMut<Assets<Image>>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+    mut packed_events: EventWriter<AtlasPackedEvent>,
+) {
+    let atlases = &mut *atlases;
+
+    atlases.pending.retain(|(folder, handle)| {
+        if asset_server.load_state(handle).is_failed() {
+            warn!("Could not load frame folder {folder}");
+            return false;
+        }
+        if !asset_server.is_loaded_with_dependencies(handle) {
+            return true;
+        }
+        let Some(loaded_folder) = folders.get(handle) else {
+            return true;
+        };
+
+        let mut frames: Vec<(String, Handle<Image>)> = loaded_folder
+            .handles
+            .iter()
+            .filter_map(|handle| {
+                let path = handle.path()?.to_string();
+                let image = handle.clone().try_typed::<Image>().ok()?;
+                Some((path, image))
+            })
+            .collect();
+        frames.sort_by_cached_key(|(path, _)| frame_sort_key(path));
+
+        // Frames are added in order so atlas indices match the frame numbers
+        let mut builder = TextureAtlasBuilder::default();
+        builder.padding(ATLAS_FRAME_PADDING);
+        for (_, frame) in &frames {
+            if let Some(image) = images.get(frame) {
+                builder.add_texture(Some(frame.id()), image);
+            }
+        }
+
+        match builder.build() {
+            Ok((layout, _, texture)) => {
+                let frame_count = layout.textures.len();
+                atlases.packed.insert(
+                    folder.clone(),
+                    PackedAtlas {
+                        texture: images.add(texture),
+                        layout: layouts.add(layout),
+                        frame_count,
+                    },
+                );
+                packed_events.send(AtlasPackedEvent {
+                    folder: folder.clone(),
+                });
+            }
+            Err(error) => warn!("Could not pack frame folder {folder}: {error}"),
+        }
+
+        false
+    });
+}
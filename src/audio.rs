@@ -0,0 +1,181 @@
+use bevy::audio::{SpatialScale, Volume};
+use bevy::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::animations::{self, AnimationCue, AnimationEvent};
+use crate::game::GameState;
+use crate::physics::{Physics, SurfaceContact, SurfaceMaterial};
+use crate::settings::GameSettings;
+
+// Sound effect volume shared by every one-shot sound
+const SFX_VOLUME: f32 = 0.6;
+
+// Footsteps don't play when sliding to a stop
+const FOOTSTEP_MIN_SPEED: f32 = 20.0;
+
+// Positional sound constants
+const SFX_FULL_VOLUME_DISTANCE: f32 = 500.0; // Farther sounds fade with the square of the distance
+const LISTENER_EAR_GAP: f32 = 400.0; // Sounds past either ear play mostly on that side
+
+// One-shot sounds gameplay systems can request
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum SoundEffect {
+    ShieldBlock,
+    BarrierShatter,
+    AggroSting,
+    WallBreak,
+    Footstep(SurfaceMaterial),
+}
+
+impl SoundEffect {
+    // Every variation of the sound; one is picked at random each time it plays
+    fn paths(self) -> &'static [&'static str] {
+        match self {
+            SoundEffect::ShieldBlock => &["audio/shield_block.wav"],
+            SoundEffect::BarrierShatter => &["audio/barrier_shatter.wav"],
+            SoundEffect::AggroSting => &["audio/aggro_sting.wav"],
+            SoundEffect::WallBreak => &["audio/wall_break.wav"],
+            SoundEffect::Footstep(SurfaceMaterial::Stone) => {
+                &["audio/footstep_stone_1.wav", "audio/footstep_stone_2.wav"]
+            }
+            SoundEffect::Footstep(SurfaceMaterial::Ice) => {
+                &["audio/footstep_ice_1.wav", "audio/footstep_ice_2.wav"]
+            }
+            SoundEffect::Footstep(SurfaceMaterial::Mud) => {
+                &["audio/footstep_mud_1.wav", "audio/footstep_mud_2.wav"]
+            }
+        }
+    }
+}
+
+// Volume slider a sound answers to, on top of the master volume
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AudioChannel {
+    Music,
+    Sfx,
+}
+
+// Volume a sound was asked to play at, before the settings scale it. Playing sounds follow
+// the volume settings as they change
+#[derive(Component)]
+pub struct ChannelVolume {
+    pub channel: AudioChannel,
+    pub base: f32,
+}
+
+// Sent by gameplay systems to play a sound effect. Sounds with a position are heard from
+// where they happen relative to the camera, panned and quieter the farther off they are
+#[derive(Event)]
+pub struct SfxEvent {
+    pub effect: SoundEffect,
+    pub position: Option<Vec2>,
+}
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SfxEvent>()
+            .add_systems(
+                Update,
+                (
+                    play_animation_cues.run_if(in_state(GameState::Playing)),
+                    play_sound_effects,
+                )
+                    .chain()
+                    .after(animations::animate_current_state),
+            )
+            .add_systems(
+                Update,
+                (
+                    attach_listener,
+                    apply_volume_settings.run_if(resource_changed::<GameSettings>),
+                ),
+            );
+    }
+}
+
+// Footsteps use the sound set of the surface the character is standing on
+fn play_animation_cues(
+    mut animation_events: EventReader<AnimationEvent>,
+    characters: Query<(&Physics, &SurfaceContact, &Transform)>,
+    mut sfx_events: EventWriter<SfxEvent>,
+) {
+    for event in animation_events.read() {
+        let character = characters.get(event.entity).ok();
+        let effect = match event.cue {
+            AnimationCue::PlaySfx(effect) => effect,
+            AnimationCue::Footstep => {
+                let Some((physics, contact, _)) = character else {
+                    continue;
+                };
+                if !physics.on_ground || physics.velocity.x.abs() < FOOTSTEP_MIN_SPEED {
+                    continue;
+                }
+                SoundEffect::Footstep(contact.surface)
+            }
+        };
+        sfx_events.send(SfxEvent {
+            effect,
+            position: character.map(|(_, _, transform)| transform.translation.truncate()),
+        });
+    }
+}
+
+fn play_sound_effects(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<GameSettings>,
+    mut sfx_events: EventReader<SfxEvent>,
+) {
+    let volume = SFX_VOLUME * settings.volume(AudioChannel::Sfx);
+
+    let mut rng = rand::thread_rng();
+
+    for event in sfx_events.read() {
+        let paths = event.effect.paths();
+        let path = paths[rng.gen_range(0..paths.len())];
+
+        let playback = PlaybackSettings::DESPAWN.with_volume(Volume::new(volume));
+        let mut sound = commands.spawn((
+            AudioPlayer::new(asset_server.load(path)),
+            ChannelVolume {
+                channel: AudioChannel::Sfx,
+                base: SFX_VOLUME,
+            },
+        ));
+        match event.position {
+            // The camera's listener does the panning and falloff as the sound plays
+            Some(position) => sound.insert((
+                playback
+                    .with_spatial(true)
+                    .with_spatial_scale(SpatialScale::new_2d(1.0 / SFX_FULL_VOLUME_DISTANCE)),
+                Transform::from_translation(position.extend(0.0)),
+            )),
+            None => sound.insert(playback),
+        };
+    }
+}
+
+// Positional sounds are heard from the camera, wherever it goes
+fn attach_listener(mut commands: Commands, cameras: Query<Entity, Added<Camera2d>>) {
+    for camera in &cameras {
+        commands
+            .entity(camera)
+            .insert(SpatialListener::new(LISTENER_EAR_GAP));
+    }
+}
+
+fn apply_volume_settings(
+    settings: Res<GameSettings>,
+    sounds: Query<(&ChannelVolume, &AudioSink)>,
+    spatial_sounds: Query<(&ChannelVolume, &SpatialAudioSink)>,
+) {
+    for (volume, sink) in &sounds {
+        sink.set_volume(volume.base * settings.volume(volume.channel));
+    }
+    for (volume, sink) in &spatial_sounds {
+        sink.set_volume(volume.base * settings.volume(volume.channel));
+    }
+}
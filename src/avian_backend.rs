@@ -0,0 +1,202 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::game::{GameState, InGame};
+use crate::ground::Ground;
+use crate::physics::{
+    self, BodyCollider, DroppingThrough, KinematicCharacter, OneWayPlatform, Physics, Solid,
+    SurfaceContact, SurfaceMaterial,
+};
+use crate::player::Player;
+
+// Avian backend constants
+const SKIN_WIDTH: f32 = 0.5; // Gap kept between the player and whatever it runs into
+const GROUND_PROBE_DISTANCE: f32 = 2.0; // How far below the feet still counts as standing
+
+// Optional physics engine backend, enabled with the `avian` feature. The ground and solids
+// get avian colliders and the player is moved by a kinematic controller that shape casts
+// against them. Gravity, traction and everyone else's collisions stay with GravityPlugin
+pub struct AvianBackendPlugin;
+
+impl Plugin for AvianBackendPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(PhysicsPlugins::default())
+            // GravityPlugin already pulls bodies down, avian mustn't do it twice
+            .insert_resource(Gravity(Vec2::ZERO))
+            .add_systems(
+                Update,
+                (
+                    add_ground_colliders,
+                    add_solid_colliders,
+                    remove_solid_colliders,
+                    add_player_controller,
+                )
+                    .run_if(in_state(InGame)),
+            )
+            .add_systems(
+                FixedUpdate,
+                move_kinematic_characters
+                    .after(physics::apply_physics)
+                    .before(physics::resolve_solid_collisions)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+// Colliders follow the transform's scale, while sizes in this game are in world units
+fn world_box(size: Vec2, transform: &Transform) -> Collider {
+    Collider::rectangle(size.x / transform.scale.x, size.y / transform.scale.y)
+}
+
+fn add_ground_colliders(
+    mut commands: Commands,
+    grounds: Query<(Entity, &Transform, &Ground), Added<Ground>>,
+) {
+    for (entity, transform, ground) in &grounds {
+        commands
+            .entity(entity)
+            .insert((RigidBody::Static, world_box(ground.size, transform)));
+    }
+}
+
+// Solids that move (pushable blocks) are kinematic, the rest never move
+fn add_solid_colliders(
+    mut commands: Commands,
+    solids: Query<(Entity, &Transform, &Solid, Has<Physics>), Added<Solid>>,
+) {
+    for (entity, transform, solid, moves) in &solids {
+        let body = if moves {
+            RigidBody::Kinematic
+        } else {
+            RigidBody::Static
+        };
+        commands
+            .entity(entity)
+            .insert((body, world_box(solid.size, transform)));
+    }
+}
+
+// Opened doors and fallen planks stop being solid without being despawned
+fn remove_solid_colliders(
+    mut commands: Commands,
+    mut removed: RemovedComponents<Solid>,
+    colliders: Query<(), (With<Collider>, Without<Ground>)>,
+) {
+    for entity in removed.read() {
+        if colliders.contains(entity) {
+            commands.entity(entity).remove::<(RigidBody, Collider)>();
+        }
+    }
+}
+
+fn add_player_controller(
+    mut commands: Commands,
+    players: Query<(Entity, &Transform, &BodyCollider), Added<Player>>,
+) {
+    for (entity, transform, collider) in &players {
+        commands.entity(entity).insert((
+            KinematicCharacter,
+            RigidBody::Kinematic,
+            world_box(collider.size, transform),
+        ));
+    }
+}
+
+// Moves each kinematic character by its velocity, sideways first and then vertically, stopping
+// short of the first collider in the way. One-way platforms only stop a fall that started
+// above them. A short probe under the feet decides whether the character is standing
+#[allow(clippy::type_complexity)]
+fn move_kinematic_characters(
+    time: Res<Time>,
+    spatial_query: SpatialQuery,
+    one_way_platforms: Query<(&Transform, &Solid), With<OneWayPlatform>>,
+    surfaces: Query<&SurfaceMaterial>,
+    mut characters: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Physics,
+            &BodyCollider,
+            Option<&mut SurfaceContact>,
+            Has<DroppingThrough>,
+        ),
+        (With<KinematicCharacter>, Without<OneWayPlatform>),
+    >,
+) {
+    let delta = time.delta_secs();
+
+    for (entity, mut transform, mut physics, collider, mut surface_contact, dropping_through) in
+        &mut characters
+    {
+        let shape = Collider::rectangle(collider.size.x, collider.size.y);
+        let filter = SpatialQueryFilter::from_excluded_entities([entity]);
+        let mut position = transform.translation.truncate();
+
+        // Casts from the current position; platforms count only when landing on them
+        let cast = |position: Vec2, direction: Dir2, distance: f32| {
+            let feet = position.y - collider.size.y / 2.0;
+            let predicate = |hit: Entity| match one_way_platforms.get(hit) {
+                Ok((platform_transform, platform)) => {
+                    direction == Dir2::NEG_Y
+                        && !dropping_through
+                        && feet
+                            >= platform_transform.translation.y + platform.size.y / 2.0 - SKIN_WIDTH
+                }
+                Err(_) => true,
+            };
+            spatial_query.cast_shape_predicate(
+                &shape,
+                position,
+                0.0,
+                direction,
+                &ShapeCastConfig::from_max_distance(distance + SKIN_WIDTH),
+                &filter,
+                &predicate,
+            )
+        };
+
+        let step_x = physics.velocity.x * delta;
+        if let Ok(direction) = Dir2::new(Vec2::new(step_x, 0.0)) {
+            match cast(position, direction, step_x.abs()) {
+                Some(hit) => {
+                    position.x += direction.x * (hit.distance - SKIN_WIDTH).max(0.0);
+                    physics.velocity.x = 0.0;
+                }
+                None => position.x += step_x,
+            }
+        }
+
+        let step_y = physics.velocity.y * delta;
+        if let Ok(direction) = Dir2::new(Vec2::new(0.0, step_y)) {
+            match cast(position, direction, step_y.abs()) {
+                Some(hit) => {
+                    position.y += direction.y * (hit.distance - SKIN_WIDTH).max(0.0);
+                    physics.velocity.y = 0.0;
+                }
+                None => position.y += step_y,
+            }
+        }
+
+        physics.on_ground = false;
+        let mut on_one_way_platform = false;
+        if physics.velocity.y <= 0.0
+            && let Some(hit) = cast(position, Dir2::NEG_Y, GROUND_PROBE_DISTANCE)
+        {
+            position.y -= (hit.distance - SKIN_WIDTH).max(0.0);
+            physics.velocity.y = 0.0;
+            physics.on_ground = true;
+            on_one_way_platform = one_way_platforms.contains(hit.entity);
+            if let (Some(contact), Ok(surface)) =
+                (surface_contact.as_mut(), surfaces.get(hit.entity))
+            {
+                contact.surface = *surface;
+            }
+        }
+        if let Some(mut contact) = surface_contact {
+            contact.on_one_way_platform = on_one_way_platform;
+        }
+
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
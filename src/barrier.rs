@@ -0,0 +1,201 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::audio::{SfxEvent, SoundEffect};
+use crate::game::{GameState, InGame};
+use crate::layers::Layer;
+
+// Barrier constants
+const BARRIER_DURATION: f32 = 30.0;
+const BARRIER_RADIUS: f32 = 34.0; // In the carrier's local units, so it scales with the sprite
+const BARRIER_COLOR: Color = Color::srgba(0.55, 0.85, 1.0, 0.3);
+const BARRIER_Z: f32 = 0.5;
+const BARRIER_FADE_TIME: f32 = 3.0; // Flickers this long before running out
+const BARRIER_FLICKER_SPEED: f32 = 12.0;
+const BARRIER_SHARD_COUNT: usize = 10;
+const BARRIER_SHARD_SIZE: Vec2 = Vec2::new(10.0, 4.0);
+const BARRIER_SHARD_SPEED: f32 = 320.0;
+const BARRIER_SHARD_LIFETIME: f32 = 0.4;
+const BARRIER_SHARD_COLOR: Color = Color::srgb(0.75, 0.95, 1.0);
+
+// Absorbs the next hit completely, then shatters. Checked by the systems that turn hits
+// into damage, before any DamageEvent is sent
+#[derive(Component)]
+pub struct Barrier {
+    pub timer: Timer,
+}
+
+impl Default for Barrier {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(BARRIER_DURATION, TimerMode::Once),
+        }
+    }
+}
+
+// Sent when a barrier takes a hit in place of its carrier
+#[derive(Event)]
+pub struct BarrierBrokenEvent {
+    pub entity: Entity,
+    pub position: Vec2,
+}
+
+// Bubble drawn around a character with a barrier
+#[derive(Component)]
+struct BarrierBubble;
+
+// Piece of a shattered barrier flying outwards
+#[derive(Component)]
+struct BarrierShard {
+    velocity: Vec2,
+    timer: Timer,
+}
+
+pub struct BarrierPlugin;
+
+impl Plugin for BarrierPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BarrierBrokenEvent>().add_systems(
+            Update,
+            (
+                spawn_barrier_bubbles,
+                expire_barriers,
+                shatter_barriers,
+                update_barrier_shards,
+            )
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+// Takes the hit if the target has a barrier; the caller skips the damage when this is true
+pub fn absorb_hit(
+    commands: &mut Commands,
+    barrier_broken_events: &mut EventWriter<BarrierBrokenEvent>,
+    entity: Entity,
+    barrier: Option<&Barrier>,
+    position: Vec2,
+) -> bool {
+    if barrier.is_none() {
+        return false;
+    }
+
+    commands.entity(entity).remove::<Barrier>();
+    barrier_broken_events.send(BarrierBrokenEvent { entity, position });
+    true
+}
+
+fn spawn_barrier_bubbles(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    carriers: Query<Entity, Added<Barrier>>,
+) {
+    for entity in &carriers {
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn((
+                BarrierBubble,
+                Mesh2d(meshes.add(Circle::new(BARRIER_RADIUS))),
+                MeshMaterial2d(materials.add(BARRIER_COLOR)),
+                Transform::from_xyz(0.0, 0.0, BARRIER_Z),
+            ));
+        });
+    }
+}
+
+// Flickers near the end, then fades away without a sound
+fn expire_barriers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut carriers: Query<(Entity, &mut Barrier, &Children)>,
+    mut bubbles: Query<&mut Visibility, With<BarrierBubble>>,
+) {
+    for (entity, mut barrier, children) in &mut carriers {
+        barrier.timer.tick(time.delta());
+
+        if barrier.timer.finished() {
+            commands.entity(entity).remove::<Barrier>();
+            for &child in children {
+                if bubbles.contains(child) {
+                    commands.entity(child).despawn_recursive();
+                }
+            }
+            continue;
+        }
+
+        let remaining = barrier.timer.remaining_secs();
+        let visible =
+            remaining > BARRIER_FADE_TIME || (remaining * BARRIER_FLICKER_SPEED).sin() > 0.0;
+        for &child in children {
+            if let Ok(mut visibility) = bubbles.get_mut(child) {
+                visibility.set_if_neq(if visible {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                });
+            }
+        }
+    }
+}
+
+fn shatter_barriers(
+    mut commands: Commands,
+    mut barrier_broken_events: EventReader<BarrierBrokenEvent>,
+    carriers: Query<&Children>,
+    bubbles: Query<(), With<BarrierBubble>>,
+    mut sfx_events: EventWriter<SfxEvent>,
+) {
+    let mut rng = rand::thread_rng();
+
+    for event in barrier_broken_events.read() {
+        if let Ok(children) = carriers.get(event.entity) {
+            for &child in children {
+                if bubbles.contains(child) {
+                    commands.entity(child).despawn_recursive();
+                }
+            }
+        }
+
+        for index in 0..BARRIER_SHARD_COUNT {
+            let angle = std::f32::consts::TAU * index as f32 / BARRIER_SHARD_COUNT as f32
+                + rng.gen_range(-0.2..0.2);
+            let direction = Vec2::from_angle(angle);
+            commands.spawn((
+                BarrierShard {
+                    velocity: direction * BARRIER_SHARD_SPEED * rng.gen_range(0.6..1.0),
+                    timer: Timer::from_seconds(BARRIER_SHARD_LIFETIME, TimerMode::Once),
+                },
+                StateScoped(InGame),
+                Sprite {
+                    color: BARRIER_SHARD_COLOR,
+                    custom_size: Some(BARRIER_SHARD_SIZE),
+                    ..default()
+                },
+                Transform::from_translation(event.position.extend(Layer::Effects.z()))
+                    .with_rotation(Quat::from_rotation_z(angle)),
+            ));
+        }
+
+        sfx_events.send(SfxEvent {
+            effect: SoundEffect::BarrierShatter,
+            position: Some(event.position),
+        });
+    }
+}
+
+fn update_barrier_shards(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut shards: Query<(Entity, &mut BarrierShard, &mut Transform, &mut Sprite)>,
+) {
+    for (entity, mut shard, mut transform, mut sprite) in &mut shards {
+        shard.timer.tick(time.delta());
+        if shard.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        transform.translation += (shard.velocity * time.delta_secs()).extend(0.0);
+        sprite.color = BARRIER_SHARD_COLOR.with_alpha(1.0 - shard.timer.fraction());
+    }
+}
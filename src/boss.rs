@@ -0,0 +1,275 @@
+use bevy::prelude::*;
+
+use crate::camera_director::{CameraDirector, CameraShot};
+use crate::enemy::{Enemy, EnemyAi, EnemyAiState, EnemySpawnPoint};
+use crate::game::{GameState, InGame};
+use crate::layers::UiLayer;
+
+// Boss bar constants
+const BOSS_BAR_WIDTH: f32 = 60.0; // Percent of the screen width
+const BOSS_BAR_HEIGHT: f32 = 14.0;
+const BOSS_BAR_BOTTOM: f32 = 40.0;
+const BOSS_BAR_BORDER: f32 = 2.0;
+const BOSS_BAR_DRAIN_RATE: f32 = 12.0; // How quickly the bar catches up with a hit, per second
+const BOSS_GHOST_DELAY: f32 = 0.6; // The ghost holds this long after the last hit
+const BOSS_GHOST_DRAIN_SPEED: f32 = 0.5; // Fraction of the bar per second
+const BOSS_BAR_FRAME_COLOR: Color = Color::srgba(0.05, 0.05, 0.08, 0.85);
+const BOSS_BAR_BORDER_COLOR: Color = Color::srgb(0.6, 0.55, 0.5);
+const BOSS_BAR_HEALTH_COLOR: Color = Color::srgb(0.75, 0.1, 0.12);
+const BOSS_BAR_GHOST_COLOR: Color = Color::srgb(0.95, 0.85, 0.7);
+const BOSS_NAME_FONT_SIZE: f32 = 20.0;
+const BOSS_NAME_COLOR: Color = Color::srgb(0.95, 0.92, 0.85);
+
+// Boss intro constants
+const BOSS_INTRO_ZOOM: f32 = 0.85; // Closes in a little on the boss
+const BOSS_INTRO_TRAVEL: f32 = 0.8; // Seconds the camera takes to pan over
+const BOSS_INTRO_HOLD: f32 = 1.0; // Seconds it stays on the boss before panning back
+
+// Enemy the player has to get through to move on. Put on a spawn point by the level data,
+// and handed down to the enemy it spawns
+#[derive(Component, Clone)]
+pub struct Boss {
+    pub name: String,
+}
+
+// Bar across the bottom of the screen for the boss being fought. The health part follows
+// the boss's health quickly, and a ghost behind it shows the chunk just lost for a moment
+#[derive(Resource, Default)]
+pub struct BossHealthBar {
+    pub boss: Option<Entity>,
+    pub name: String,
+    target: f32, // Fractions of the bar, 0 to 1
+    health: f32,
+    ghost: f32,
+    ghost_delay: f32,
+    shown: bool,
+}
+
+impl BossHealthBar {
+    pub fn engage(&mut self, boss: Entity, name: &str) {
+        self.boss = Some(boss);
+        self.name = name.to_string();
+        self.target = 1.0;
+        self.health = 1.0;
+        self.ghost = 1.0;
+        self.ghost_delay = 0.0;
+        self.shown = true;
+    }
+}
+
+#[derive(Component)]
+struct BossBar;
+
+#[derive(Component)]
+struct BossBarName;
+
+#[derive(Component)]
+struct BossBarHealth;
+
+#[derive(Component)]
+struct BossBarGhost;
+
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BossHealthBar>()
+            .add_systems(OnEnter(InGame), spawn_boss_bar)
+            .add_systems(OnExit(InGame), reset_boss_bar)
+            .add_systems(
+                Update,
+                (
+                    mark_spawned_bosses,
+                    engage_bosses,
+                    update_boss_bar,
+                    update_boss_bar_ui,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn spawn_boss_bar(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+
+    commands
+        .spawn((
+            BossBar,
+            StateScoped(InGame),
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                bottom: Val::Px(BOSS_BAR_BOTTOM),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            Visibility::Hidden,
+            GlobalZIndex(UiLayer::Hud.z_index()),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                BossBarName,
+                Text::new(""),
+                TextFont {
+                    font,
+                    font_size: BOSS_NAME_FONT_SIZE,
+                    ..default()
+                },
+                TextColor(BOSS_NAME_COLOR),
+            ));
+
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Percent(BOSS_BAR_WIDTH),
+                        height: Val::Px(BOSS_BAR_HEIGHT),
+                        border: UiRect::all(Val::Px(BOSS_BAR_BORDER)),
+                        ..default()
+                    },
+                    BorderColor(BOSS_BAR_BORDER_COLOR),
+                    BackgroundColor(BOSS_BAR_FRAME_COLOR),
+                ))
+                .with_children(|parent| {
+                    // Ghost first so the health part is drawn over it
+                    parent.spawn((
+                        BossBarGhost,
+                        Node {
+                            position_type: PositionType::Absolute,
+                            height: Val::Percent(100.0),
+                            width: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(BOSS_BAR_GHOST_COLOR),
+                    ));
+                    parent.spawn((
+                        BossBarHealth,
+                        Node {
+                            position_type: PositionType::Absolute,
+                            height: Val::Percent(100.0),
+                            width: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(BOSS_BAR_HEALTH_COLOR),
+                    ));
+                });
+        });
+}
+
+// The spawn point knows it's a boss; the enemy it brings out takes the name with it
+fn mark_spawned_bosses(
+    mut commands: Commands,
+    spawn_points: Query<(&Boss, &EnemySpawnPoint)>,
+    enemies: Query<Entity, (Added<Enemy>, Without<Boss>)>,
+) {
+    for enemy in &enemies {
+        if let Some((boss, _)) = spawn_points
+            .iter()
+            .find(|(_, spawn_point)| spawn_point.spawned_enemy == Some(enemy))
+        {
+            commands.entity(enemy).insert(boss.clone());
+        }
+    }
+}
+
+// The bar comes up once the boss notices the player
+// The camera pans over to the boss under the letterbox as the fight starts, once per boss
+fn engage_bosses(
+    mut bar: ResMut<BossHealthBar>,
+    mut director: ResMut<CameraDirector>,
+    bosses: Query<(Entity, &Boss, &Enemy, &EnemyAi, &Transform)>,
+) {
+    if bar.boss.is_some() {
+        return;
+    }
+
+    let engaged = bosses.iter().find(|(_, _, enemy, ai, _)| {
+        !enemy.is_dead
+            && matches!(
+                ai.state,
+                EnemyAiState::Chasing | EnemyAiState::Telegraphing | EnemyAiState::Charging
+            )
+    });
+    if let Some((entity, boss, _, _, transform)) = engaged {
+        bar.engage(entity, &boss.name);
+        director.play([CameraShot {
+            target: transform.translation.truncate(),
+            zoom: BOSS_INTRO_ZOOM,
+            travel: BOSS_INTRO_TRAVEL,
+            hold: BOSS_INTRO_HOLD,
+        }]);
+    }
+}
+
+fn update_boss_bar(time: Res<Time>, mut bar: ResMut<BossHealthBar>, enemies: Query<&Enemy>) {
+    if !bar.shown {
+        return;
+    }
+
+    // A boss that's dead or gone drains to nothing and the bar goes once the ghost follows
+    let target = match bar.boss.and_then(|boss| enemies.get(boss).ok()) {
+        Some(enemy) if !enemy.is_dead => (enemy.health / enemy.max_health).clamp(0.0, 1.0),
+        _ => {
+            bar.boss = None;
+            0.0
+        }
+    };
+
+    if target < bar.target {
+        bar.ghost_delay = BOSS_GHOST_DELAY;
+    }
+    bar.target = target;
+    let blend = 1.0 - (-BOSS_BAR_DRAIN_RATE * time.delta_secs()).exp();
+    bar.health += (bar.target - bar.health) * blend;
+
+    bar.ghost_delay = (bar.ghost_delay - time.delta_secs()).max(0.0);
+    if bar.ghost_delay == 0.0 {
+        bar.ghost = (bar.ghost - BOSS_GHOST_DRAIN_SPEED * time.delta_secs()).max(bar.health);
+    }
+    // Healing pushes the ghost up with it
+    bar.ghost = bar.ghost.max(bar.health);
+
+    if bar.boss.is_none() && bar.ghost <= 0.001 {
+        bar.shown = false;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_boss_bar_ui(
+    bar: Res<BossHealthBar>,
+    mut roots: Query<&mut Visibility, With<BossBar>>,
+    mut names: Query<&mut Text, With<BossBarName>>,
+    mut fills: ParamSet<(
+        Query<&mut Node, With<BossBarHealth>>,
+        Query<&mut Node, With<BossBarGhost>>,
+    )>,
+) {
+    for mut visibility in &mut roots {
+        *visibility = if bar.shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+    if !bar.shown {
+        return;
+    }
+
+    for mut text in &mut names {
+        if text.0 != bar.name {
+            text.0 = bar.name.clone();
+        }
+    }
+    for mut node in &mut fills.p0() {
+        node.width = Val::Percent(bar.health * 100.0);
+    }
+    for mut node in &mut fills.p1() {
+        node.width = Val::Percent(bar.ghost * 100.0);
+    }
+}
+
+fn reset_boss_bar(mut bar: ResMut<BossHealthBar>) {
+    *bar = BossHealthBar::default();
+}
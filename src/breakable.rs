@@ -0,0 +1,223 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::audio::{SfxEvent, SoundEffect};
+use crate::enemy::SightBlocker;
+use crate::game::{GameState, InGame};
+use crate::hitbox::{self, CollisionHitbox, Faction, HitEvent};
+use crate::layers::Layer;
+use crate::level::RoomRegistry;
+use crate::physics::Solid;
+
+// Breakable constants
+const BREAKABLE_COLOR: Color = Color::srgb(0.34, 0.3, 0.27); // A shade off the terrain around it
+const BREAKABLE_CRACKED_COLOR: Color = Color::srgb(0.24, 0.21, 0.19); // Once it is nearly gone
+const BREAKABLE_HIT_COOLDOWN: f32 = 0.25; // A swing overlaps it for several frames
+const BREAKABLE_SHAKE_TIME: f32 = 0.15;
+const BREAKABLE_SHAKE: f32 = 3.0;
+const BREAKABLE_SHAKE_SPEED: f32 = 70.0;
+const BREAKABLE_CHIPS_PER_HIT: usize = 3;
+const BREAKABLE_CHUNK_AREA: f32 = 900.0; // One chunk per this many square units of wall
+const BREAKABLE_MAX_CHUNKS: usize = 24;
+const CHUNK_SIZE_MIN: f32 = 6.0;
+const CHUNK_SIZE_MAX: f32 = 14.0;
+const CHUNK_SPEED: f32 = 260.0;
+const CHUNK_LIFT: f32 = 220.0;
+const CHUNK_GRAVITY: f32 = 900.0;
+const CHUNK_LIFETIME: f32 = 0.7;
+
+// Wall or terrain piece that blocks like any other solid until attacks wear it down
+#[derive(Component)]
+pub struct Breakable {
+    pub health: f32,
+    pub max_health: f32,
+    home: Vec2,
+    hit_cooldown: Timer,
+    shake: Timer,
+}
+
+impl Breakable {
+    fn new(health: f32, home: Vec2) -> Self {
+        let mut hit_cooldown = Timer::from_seconds(BREAKABLE_HIT_COOLDOWN, TimerMode::Once);
+        hit_cooldown.tick(hit_cooldown.duration());
+        let mut shake = Timer::from_seconds(BREAKABLE_SHAKE_TIME, TimerMode::Once);
+        shake.tick(shake.duration());
+        Self {
+            health,
+            max_health: health,
+            home,
+            hit_cooldown,
+            shake,
+        }
+    }
+}
+
+// Piece knocked off a breakable, thrown out and pulled down until it fades
+#[derive(Component)]
+struct BreakableChunk {
+    velocity: Vec2,
+    timer: Timer,
+}
+
+pub struct BreakablePlugin;
+
+impl Plugin for BreakablePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(InGame), spawn_breakables)
+            .add_systems(
+                Update,
+                (damage_breakables, shake_breakables, update_breakable_chunks)
+                    .chain()
+                    .after(hitbox::detect_hits)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn spawn_breakables(mut commands: Commands, registry: Res<RoomRegistry>) {
+    for wall in registry.rooms.iter().flat_map(|room| &room.breakable_walls) {
+        let size = wall.area.size();
+        let home = wall.area.center();
+        commands
+            .spawn((
+                Breakable::new(wall.health, home),
+                StateScoped(InGame),
+                // Neither side: any attack of the player's lands on it
+                Faction::Neutral,
+                Solid { size },
+                SightBlocker { size },
+                Sprite {
+                    color: BREAKABLE_COLOR,
+                    custom_size: Some(size),
+                    ..default()
+                },
+                Transform::from_translation(home.extend(Layer::Terrain.z())),
+            ))
+            .with_children(|parent| {
+                parent.spawn((CollisionHitbox { active: true, size }, Transform::default()));
+            });
+    }
+}
+
+// Only the player's side breaks walls, so enemies swinging at the player can't open
+// secret passages by accident
+fn damage_breakables(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut hit_events: EventReader<HitEvent>,
+    factions: Query<&Faction>,
+    mut breakables: Query<(Entity, &mut Breakable, &mut Sprite)>,
+    mut sfx_events: EventWriter<SfxEvent>,
+) {
+    for (_, mut breakable, _) in &mut breakables {
+        breakable.hit_cooldown.tick(time.delta());
+    }
+
+    let mut rng = rand::thread_rng();
+    for event in hit_events.read() {
+        let Ok((entity, mut breakable, mut sprite)) = breakables.get_mut(event.target) else {
+            continue;
+        };
+        if factions.get(event.attacker) != Ok(&Faction::Player)
+            || !breakable.hit_cooldown.finished()
+        {
+            continue;
+        }
+
+        breakable.hit_cooldown.reset();
+        breakable.health -= event.damage;
+
+        // Chips fly from where the attack struck, back toward the attacker
+        let side = (event.attacker_pos.x - event.target_pos.x).signum();
+        let impact = Vec2::new(
+            event.target_pos.x + side * event.target_size.x / 2.0,
+            event.attack_pos.y,
+        );
+
+        if breakable.health > 0.0 {
+            breakable.shake.reset();
+            sprite.color = BREAKABLE_COLOR.mix(
+                &BREAKABLE_CRACKED_COLOR,
+                1.0 - breakable.health / breakable.max_health,
+            );
+            for _ in 0..BREAKABLE_CHIPS_PER_HIT {
+                spawn_chunk(&mut commands, &mut rng, impact, side);
+            }
+            continue;
+        }
+
+        // The whole piece comes apart, from all over its area
+        let chunks = ((event.target_size.x * event.target_size.y / BREAKABLE_CHUNK_AREA) as usize)
+            .clamp(BREAKABLE_CHIPS_PER_HIT, BREAKABLE_MAX_CHUNKS);
+        let half_size = event.target_size / 2.0;
+        for _ in 0..chunks {
+            let position = event.target_pos
+                + Vec2::new(
+                    rng.gen_range(-half_size.x..half_size.x),
+                    rng.gen_range(-half_size.y..half_size.y),
+                );
+            spawn_chunk(&mut commands, &mut rng, position, -side);
+        }
+        commands.entity(entity).despawn_recursive();
+        sfx_events.send(SfxEvent {
+            effect: SoundEffect::WallBreak,
+            position: Some(event.target_pos),
+        });
+    }
+}
+
+fn spawn_chunk(commands: &mut Commands, rng: &mut impl Rng, position: Vec2, side: f32) {
+    let size = rng.gen_range(CHUNK_SIZE_MIN..CHUNK_SIZE_MAX);
+    commands.spawn((
+        BreakableChunk {
+            velocity: Vec2::new(
+                side * rng.gen_range(0.3..1.0) * CHUNK_SPEED,
+                rng.gen_range(0.4..1.0) * CHUNK_LIFT,
+            ),
+            timer: Timer::from_seconds(CHUNK_LIFETIME, TimerMode::Once),
+        },
+        StateScoped(InGame),
+        Sprite {
+            color: BREAKABLE_COLOR,
+            custom_size: Some(Vec2::splat(size)),
+            ..default()
+        },
+        Transform::from_translation(position.extend(Layer::Effects.z())).with_rotation(
+            Quat::from_rotation_z(rng.gen_range(0.0..std::f32::consts::TAU)),
+        ),
+    ));
+}
+
+fn shake_breakables(time: Res<Time>, mut breakables: Query<(&mut Breakable, &mut Transform)>) {
+    for (mut breakable, mut transform) in &mut breakables {
+        if breakable.shake.finished() {
+            continue;
+        }
+        breakable.shake.tick(time.delta());
+
+        let offset = if breakable.shake.finished() {
+            0.0
+        } else {
+            (breakable.shake.elapsed_secs() * BREAKABLE_SHAKE_SPEED).sin() * BREAKABLE_SHAKE
+        };
+        transform.translation.x = breakable.home.x + offset;
+    }
+}
+
+fn update_breakable_chunks(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut chunks: Query<(Entity, &mut BreakableChunk, &mut Transform, &mut Sprite)>,
+) {
+    for (entity, mut chunk, mut transform, mut sprite) in &mut chunks {
+        chunk.timer.tick(time.delta());
+        if chunk.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        chunk.velocity.y -= CHUNK_GRAVITY * time.delta_secs();
+        transform.translation += (chunk.velocity * time.delta_secs()).extend(0.0);
+        sprite.color = BREAKABLE_COLOR.with_alpha(1.0 - chunk.timer.fraction());
+    }
+}
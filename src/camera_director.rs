@@ -0,0 +1,251 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+use crate::game::{GameState, InGame};
+use crate::intro;
+use crate::layers::UiLayer;
+use crate::level::{self, RoomEnteredEvent, RoomRegistry};
+use crate::paralax_background::CameraHold;
+use crate::physics::{self, Physics};
+use crate::player::Player;
+
+// Camera director constants
+const LETTERBOX_HEIGHT: f32 = 12.0; // Percent of the screen each bar covers
+const LETTERBOX_SLIDE_TIME: f32 = 0.4;
+const LETTERBOX_COLOR: Color = Color::BLACK;
+const DIRECTOR_RETURN_TIME: f32 = 1.0; // Pan back to where the camera was taken from
+const REVEAL_PAN_TIME: f32 = 1.8;
+const REVEAL_HOLD_TIME: f32 = 1.2;
+const REVEAL_ZOOM: f32 = 1.15; // Pulled back a little to take the place in
+
+// One move of a scripted camera: travel to a spot and zoom, then hold there
+#[derive(Clone, Copy, Debug)]
+pub struct CameraShot {
+    pub target: Vec2,
+    pub zoom: f32, // Orthographic scale, 1 is the usual view and higher sees more
+    pub travel: f32,
+    pub hold: f32,
+}
+
+// Shot being played, with where it started from
+struct ActiveShot {
+    shot: CameraShot,
+    from: Vec2,
+    from_zoom: f32,
+    timer: Timer,
+}
+
+// Takes the camera away from the follow system for scripted pans and zooms, like boss
+// introductions and area reveals. Player input waits while it has the camera, and it pans
+// back to where it took the camera from before handing it over again
+#[derive(Resource, Default)]
+pub struct CameraDirector {
+    shots: VecDeque<CameraShot>,
+    current: Option<ActiveShot>,
+    home: Option<(Vec2, f32)>, // Where the camera was and its zoom before the first shot
+    holding: bool,
+    letterbox: f32, // How far the bars are in, 0 to 1
+    revealed_rooms: HashSet<String>,
+}
+
+impl CameraDirector {
+    pub fn play(&mut self, shots: impl IntoIterator<Item = CameraShot>) {
+        self.shots.extend(shots);
+    }
+
+    pub fn is_directing(&self) -> bool {
+        self.holding || !self.shots.is_empty()
+    }
+}
+
+// Run condition for systems that should wait while the director has the camera
+pub fn director_idle(director: Res<CameraDirector>) -> bool {
+    !director.is_directing()
+}
+
+// Black bar at the top or bottom of the screen
+#[derive(Component)]
+struct LetterboxBar;
+
+pub struct CameraDirectorPlugin;
+
+impl Plugin for CameraDirectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraDirector>()
+            .add_systems(OnEnter(InGame), spawn_letterbox)
+            .add_systems(OnExit(InGame), reset_camera_director)
+            .add_systems(
+                Update,
+                (
+                    reveal_rooms.after(level::update_current_room),
+                    update_camera_director.run_if(intro::intro_finished),
+                    update_letterbox,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                stop_player
+                    .before(physics::apply_surface_traction)
+                    .run_if(in_state(GameState::Playing).and(not(director_idle))),
+            );
+    }
+}
+
+fn spawn_letterbox(mut commands: Commands) {
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::SpaceBetween,
+                ..default()
+            },
+            GlobalZIndex(UiLayer::Letterbox.z_index()),
+        ))
+        .with_children(|parent| {
+            for _ in 0..2 {
+                parent.spawn((
+                    LetterboxBar,
+                    Node {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(0.0),
+                        ..default()
+                    },
+                    BackgroundColor(LETTERBOX_COLOR),
+                ));
+            }
+        });
+}
+
+// Rooms with something to show get a pan over to it on the first visit of each run
+fn reveal_rooms(
+    mut room_events: EventReader<RoomEnteredEvent>,
+    registry: Res<RoomRegistry>,
+    mut director: ResMut<CameraDirector>,
+) {
+    for event in room_events.read() {
+        let Some(reveal) = registry.get(&event.room_id).and_then(|room| room.reveal) else {
+            continue;
+        };
+        if !director.revealed_rooms.insert(event.room_id.clone()) {
+            continue;
+        }
+
+        director.play([CameraShot {
+            target: reveal,
+            zoom: REVEAL_ZOOM,
+            travel: REVEAL_PAN_TIME,
+            hold: REVEAL_HOLD_TIME,
+        }]);
+    }
+}
+
+fn update_camera_director(
+    time: Res<Time>,
+    mut director: ResMut<CameraDirector>,
+    mut camera_hold: ResMut<CameraHold>,
+    mut cameras: Query<(&mut Transform, &mut OrthographicProjection), With<Camera2d>>,
+) {
+    let Ok((mut transform, mut projection)) = cameras.get_single_mut() else {
+        return;
+    };
+    let position = transform.translation.truncate();
+
+    if director.current.is_none() {
+        // Out of shots, the last one is the way back
+        let next = match director.shots.pop_front() {
+            Some(shot) => Some(shot),
+            None => director.home.take().map(|(home, zoom)| CameraShot {
+                target: home,
+                zoom,
+                travel: DIRECTOR_RETURN_TIME,
+                hold: 0.0,
+            }),
+        };
+        let Some(shot) = next else {
+            if director.holding {
+                director.holding = false;
+                camera_hold.active = false;
+            }
+            return;
+        };
+
+        if !director.holding {
+            director.holding = true;
+            director.home = Some((position, projection.scale));
+            camera_hold.active = true;
+        }
+        director.current = Some(ActiveShot {
+            shot,
+            from: position,
+            from_zoom: projection.scale,
+            timer: Timer::from_seconds(shot.travel + shot.hold, TimerMode::Once),
+        });
+    }
+
+    let Some(active) = &mut director.current else {
+        return;
+    };
+    active.timer.tick(time.delta());
+
+    // Eases in and out so the move doesn't start or stop with a jolt
+    let t = if active.shot.travel > 0.0 {
+        (active.timer.elapsed_secs() / active.shot.travel).min(1.0)
+    } else {
+        1.0
+    };
+    let eased = t * t * (3.0 - 2.0 * t);
+    let position = active.from.lerp(active.shot.target, eased);
+    transform.translation.x = position.x;
+    transform.translation.y = position.y;
+    projection.scale = active.from_zoom + (active.shot.zoom - active.from_zoom) * eased;
+
+    if active.timer.finished() {
+        director.current = None;
+    }
+}
+
+// Input waits for the shots, so the player would keep running without it. Only the
+// horizontal speed is cut: a player in the air still falls and lands as usual
+fn stop_player(mut players: Query<&mut Physics, With<Player>>) {
+    for mut physics in &mut players {
+        physics.velocity.x = 0.0;
+    }
+}
+
+fn update_letterbox(
+    time: Res<Time>,
+    mut director: ResMut<CameraDirector>,
+    mut bars: Query<&mut Node, With<LetterboxBar>>,
+) {
+    let target = if director.is_directing() { 1.0 } else { 0.0 };
+    let step = time.delta_secs() / LETTERBOX_SLIDE_TIME;
+    let letterbox = director.letterbox;
+    director.letterbox = letterbox + (target - letterbox).clamp(-step, step);
+
+    for mut node in &mut bars {
+        node.height = Val::Percent(LETTERBOX_HEIGHT * director.letterbox);
+    }
+}
+
+// A run that ends mid-shot gives the camera back as the next run expects it
+fn reset_camera_director(
+    mut director: ResMut<CameraDirector>,
+    mut camera_hold: ResMut<CameraHold>,
+    mut projections: Query<&mut OrthographicProjection, With<Camera2d>>,
+) {
+    if director.holding {
+        camera_hold.active = false;
+    }
+    *director = CameraDirector::default();
+    for mut projection in &mut projections {
+        projection.scale = 1.0;
+    }
+}
@@ -0,0 +1,145 @@
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+
+use crate::animations::{AnimationController, CharacterState};
+use crate::game::{GameState, InGame};
+use crate::hitbox::DamageEvent;
+use crate::physics::Physics;
+use crate::player::Player;
+use crate::settings::GameSettings;
+
+// Camera shake constants
+const CAMERA_SHAKE_DECAY: f32 = 1.6; // Trauma lost per second
+const CAMERA_SHAKE_MAX_OFFSET: Vec2 = Vec2::new(16.0, 12.0); // At full trauma
+const CAMERA_SHAKE_FREQUENCY: f32 = 22.0;
+const PLAYER_HIT_TRAUMA: f32 = 0.45;
+const ENEMY_HIT_TRAUMA: f32 = 0.12; // Just enough to make a swing feel like it connected
+const CHARGE_HIT_TRAUMA: f32 = 0.4;
+const HARD_LANDING_SPEED: f32 = 900.0; // Falls faster than this shake the screen on touchdown
+const HARD_LANDING_TRAUMA: f32 = 0.3;
+
+// Screen shake. Anything can add trauma; the camera shakes with its square, so small
+// amounts barely register and big hits stack up to a strong jolt
+#[derive(Resource, Default)]
+pub struct CameraShake {
+    trauma: f32,
+    offset: Vec2, // Applied to the camera this frame, taken back out before the next
+}
+
+impl CameraShake {
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.0);
+    }
+}
+
+pub struct CameraShakePlugin;
+
+impl Plugin for CameraShakePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraShake>()
+            // Camera followers and warps see the camera without the shake in it
+            .add_systems(PreUpdate, remove_camera_shake)
+            .add_systems(
+                Update,
+                (shake_on_damage, shake_on_hard_landing).run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                PostUpdate,
+                apply_camera_shake
+                    .before(TransformSystem::TransformPropagate)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnExit(InGame), clear_camera_shake);
+    }
+}
+
+fn remove_camera_shake(
+    mut shake: ResMut<CameraShake>,
+    mut cameras: Query<&mut Transform, With<Camera2d>>,
+) {
+    if shake.offset == Vec2::ZERO {
+        return;
+    }
+    for mut transform in &mut cameras {
+        transform.translation -= shake.offset.extend(0.0);
+    }
+    shake.offset = Vec2::ZERO;
+}
+
+fn apply_camera_shake(
+    time: Res<Time>,
+    settings: Res<GameSettings>,
+    mut shake: ResMut<CameraShake>,
+    mut cameras: Query<&mut Transform, With<Camera2d>>,
+) {
+    shake.trauma = (shake.trauma - CAMERA_SHAKE_DECAY * time.delta_secs()).max(0.0);
+    if shake.trauma == 0.0 {
+        return;
+    }
+
+    let t = time.elapsed_secs() * CAMERA_SHAKE_FREQUENCY;
+    let noise = Vec2::new(shake_noise(t, 0.0), shake_noise(t, 17.3));
+    shake.offset =
+        noise * CAMERA_SHAKE_MAX_OFFSET * shake.trauma * shake.trauma * settings.shake_intensity;
+    for mut transform in &mut cameras {
+        transform.translation += shake.offset.extend(0.0);
+    }
+}
+
+// Smooth value in -1..1 that wanders without repeating often, different for each seed
+fn shake_noise(t: f32, seed: f32) -> f32 {
+    ((t + seed).sin() + (t * 2.3 + seed * 1.7).sin() * 0.5 + (t * 4.1 + seed * 0.6).sin() * 0.25)
+        / 1.75
+}
+
+// Getting hit shakes hard, landing a hit only a little, and the charged attack in between
+fn shake_on_damage(
+    mut damage_events: EventReader<DamageEvent>,
+    mut shake: ResMut<CameraShake>,
+    players: Query<&AnimationController, With<Player>>,
+) {
+    for event in damage_events.read() {
+        // Burning and other damage over time doesn't shake
+        if event.reaction.is_none() {
+            continue;
+        }
+
+        if players.contains(event.target) {
+            shake.add_trauma(PLAYER_HIT_TRAUMA);
+        } else if let Some(animation_controller) =
+            event.source.and_then(|source| players.get(source).ok())
+        {
+            let charged =
+                animation_controller.get_current_state() == CharacterState::ChargeAttacking;
+            shake.add_trauma(if charged {
+                CHARGE_HIT_TRAUMA
+            } else {
+                ENEMY_HIT_TRAUMA
+            });
+        }
+    }
+}
+
+fn shake_on_hard_landing(
+    mut shake: ResMut<CameraShake>,
+    players: Query<&Physics, With<Player>>,
+    mut fall_speed: Local<f32>,
+) {
+    let Ok(physics) = players.get_single() else {
+        return;
+    };
+
+    if physics.on_ground && *fall_speed > HARD_LANDING_SPEED {
+        shake.add_trauma(HARD_LANDING_TRAUMA);
+    }
+    *fall_speed = if physics.on_ground {
+        0.0
+    } else {
+        -physics.velocity.y
+    };
+}
+
+// A run that ends mid-shake doesn't carry it into the next one
+fn clear_camera_shake(mut shake: ResMut<CameraShake>) {
+    shake.trauma = 0.0;
+}
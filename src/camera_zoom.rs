@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+
+use crate::camera_director;
+use crate::enemy::{Enemy, EnemyAi, EnemyAiState};
+use crate::game::{GameState, InGame};
+use crate::level::Bench;
+use crate::paralax_background::ParallaxSystems;
+use crate::physics::Physics;
+use crate::player::Player;
+
+// Camera zoom constants
+const ZOOM_SMOOTHING: f32 = 2.5; // How quickly the zoom catches up, per second
+const COMBAT_ZOOM: f32 = 1.12; // Pulled back to keep the fight in view
+const COMBAT_ZOOM_RANGE: f32 = 600.0; // Enemies after the player closer than this count
+const COMBAT_ZOOM_LINGER: f32 = 1.5; // Stays out a moment after a fight so it doesn't pump
+const BENCH_FOCUS_ZOOM: f32 = 0.9;
+const BENCH_FOCUS_RANGE: f32 = 60.0;
+
+// Orthographic scale of the gameplay camera: 1 is the usual view and higher sees more.
+// Eases toward the base scale, the combat one while enemies are on the player, or a focus
+// asked for this frame, which wins over both
+#[derive(Resource)]
+pub struct CameraZoom {
+    pub base: f32,
+    focus: Option<f32>,
+    combat_time_left: f32,
+}
+
+impl Default for CameraZoom {
+    fn default() -> Self {
+        Self {
+            base: 1.0,
+            focus: None,
+            combat_time_left: 0.0,
+        }
+    }
+}
+
+impl CameraZoom {
+    // Held only for the frame it's asked for; the closest of several requests wins
+    pub fn focus(&mut self, scale: f32) {
+        self.focus = Some(self.focus.map_or(scale, |focus| focus.min(scale)));
+    }
+
+    fn target(&self) -> f32 {
+        match self.focus {
+            Some(focus) => focus,
+            None if self.combat_time_left > 0.0 => self.base * COMBAT_ZOOM,
+            None => self.base,
+        }
+    }
+}
+
+pub struct CameraZoomPlugin;
+
+impl Plugin for CameraZoomPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraZoom>()
+            .add_systems(OnExit(InGame), reset_camera_zoom)
+            .add_systems(
+                Update,
+                (detect_combat, focus_at_benches, update_camera_zoom)
+                    .chain()
+                    // The follow keeps the view in bounds for the zoom it has this frame
+                    .before(ParallaxSystems::CameraMovement)
+                    .run_if(in_state(GameState::Playing).and(camera_director::director_idle)),
+            );
+    }
+}
+
+fn detect_combat(
+    time: Res<Time>,
+    mut zoom: ResMut<CameraZoom>,
+    players: Query<&Transform, With<Player>>,
+    enemies: Query<(&Enemy, &EnemyAi, &Transform)>,
+) {
+    let Ok(player_transform) = players.get_single() else {
+        return;
+    };
+    let player_pos = player_transform.translation.truncate();
+
+    let engaged = enemies.iter().any(|(enemy, ai, transform)| {
+        !enemy.is_dead
+            && matches!(
+                ai.state,
+                EnemyAiState::Chasing
+                    | EnemyAiState::Telegraphing
+                    | EnemyAiState::Charging
+                    | EnemyAiState::Stunned
+            )
+            && transform.translation.truncate().distance(player_pos) < COMBAT_ZOOM_RANGE
+    });
+
+    zoom.combat_time_left = if engaged {
+        COMBAT_ZOOM_LINGER
+    } else {
+        (zoom.combat_time_left - time.delta_secs()).max(0.0)
+    };
+}
+
+// Standing still at a bench draws the camera in a little
+fn focus_at_benches(
+    mut zoom: ResMut<CameraZoom>,
+    players: Query<(&Transform, &Physics), With<Player>>,
+    benches: Query<&Transform, With<Bench>>,
+) {
+    let Ok((player_transform, physics)) = players.get_single() else {
+        return;
+    };
+    if !physics.on_ground || physics.velocity.x.abs() > 0.1 {
+        return;
+    }
+
+    let player_x = player_transform.translation.x;
+    if benches
+        .iter()
+        .any(|bench| (bench.translation.x - player_x).abs() < BENCH_FOCUS_RANGE)
+    {
+        zoom.focus(BENCH_FOCUS_ZOOM);
+    }
+}
+
+fn update_camera_zoom(
+    time: Res<Time>,
+    mut zoom: ResMut<CameraZoom>,
+    mut projections: Query<&mut OrthographicProjection, With<Camera2d>>,
+) {
+    let target = zoom.target();
+    zoom.focus = None;
+
+    // Same catch up rate whatever the frame rate
+    let blend = 1.0 - (-ZOOM_SMOOTHING * time.delta_secs()).exp();
+    for mut projection in &mut projections {
+        projection.scale += (target - projection.scale) * blend;
+    }
+}
+
+// A fight that was going on when the run ended doesn't hold the next one zoomed out
+fn reset_camera_zoom(mut zoom: ResMut<CameraZoom>) {
+    zoom.focus = None;
+    zoom.combat_time_left = 0.0;
+}
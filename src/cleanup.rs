@@ -0,0 +1,37 @@
+use bevy::prelude::*;
+
+use crate::game::GameState;
+use crate::level::{self, RoomEnteredEvent};
+
+// Entity removed when the player walks out of the given room, like loot left lying there
+#[derive(Component)]
+pub struct DespawnOnRoomExit(pub String);
+
+pub struct CleanupPlugin;
+
+impl Plugin for CleanupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            despawn_on_room_exit
+                .after(level::update_current_room)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+fn despawn_on_room_exit(
+    mut commands: Commands,
+    mut room_events: EventReader<RoomEnteredEvent>,
+    scoped: Query<(Entity, &DespawnOnRoomExit)>,
+) {
+    let Some(event) = room_events.read().last() else {
+        return;
+    };
+
+    for (entity, scope) in &scoped {
+        if scope.0 != event.room_id {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use bevy::prelude::*;
+
+use crate::animations::Facing;
+use crate::enemy::Enemy;
+use crate::game::{GameState, InGame};
+use crate::hitbox::{self, AttackHitbox, CollisionHitbox, Faction, HitEvent, Knockback};
+use crate::input::{ActionState, InputAction};
+use crate::layers::Layer;
+use crate::player::Player;
+use crate::status_effects::StatusEffectKind;
+
+// Spirit wisp constants
+const WISP_SUMMON_COOLDOWN: f32 = 8.0; // Counted from the moment the last wisp is gone
+const WISP_LIFETIME: f32 = 20.0;
+const WISP_SIZE: Vec2 = Vec2::new(16.0, 16.0);
+const WISP_COLOR: Color = Color::srgb(0.6, 0.95, 1.0);
+const WISP_FADE_DURATION: f32 = 1.5; // Flickers out over the end of its lifetime
+const WISP_FOLLOW_OFFSET: Vec2 = Vec2::new(-50.0, 70.0); // Behind and above the player
+const WISP_FOLLOW_SMOOTHING: f32 = 4.0;
+const WISP_BOB_AMPLITUDE: f32 = 6.0;
+const WISP_BOB_SPEED: f32 = 3.0;
+const WISP_ATTACK_RANGE: f32 = 260.0; // Only enemies this close to the player get attacked
+const WISP_ATTACK_COOLDOWN: f32 = 1.8;
+const WISP_ATTACK_DAMAGE: f32 = 15.0;
+const WISP_ATTACK_HITBOX_SIZE: Vec2 = Vec2::new(36.0, 36.0);
+const WISP_ATTACK_KNOCKBACK: Knockback = Knockback {
+    strength: 650.0,
+    lift: 120.0,
+    decay: 12.0,
+};
+const WISP_ATTACK_HITBOX_DURATION: f32 = 0.05;
+const WISP_DART_SPEED: f32 = 650.0;
+const WISP_DART_REACH: f32 = 24.0; // Strikes once this close to the target
+const WISP_DART_MAX_DURATION: f32 = 1.0; // Gives up on targets it can't catch
+
+// What the wisp is currently doing
+#[derive(Clone, Copy, PartialEq)]
+enum WispState {
+    Following,
+    Darting { target: Entity, elapsed: f32 },
+}
+
+// Friendly summon fighting on the player's side
+#[derive(Component)]
+pub struct Companion {
+    state: WispState,
+    lifetime: Timer,
+    attack_cooldown: Timer,
+    bob_phase: f32,
+}
+
+// Time left until the player can summon another wisp
+#[derive(Resource)]
+struct SummonCooldown(Timer);
+
+impl Default for SummonCooldown {
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(WISP_SUMMON_COOLDOWN, TimerMode::Once);
+        // Ready from the start
+        timer.tick(timer.duration());
+        Self(timer)
+    }
+}
+
+pub struct CompanionPlugin;
+
+impl Plugin for CompanionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SummonCooldown>().add_systems(
+            Update,
+            (
+                summon_companion,
+                update_companion_movement,
+                dispel_hit_companions.after(hitbox::detect_hits),
+                expire_companions,
+            )
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+fn summon_companion(
+    mut commands: Commands,
+    time: Res<Time>,
+    actions: Res<ActionState>,
+    mut cooldown: ResMut<SummonCooldown>,
+    player_query: Query<&Transform, With<Player>>,
+    companions: Query<(), With<Companion>>,
+) {
+    // Only one wisp at a time, and the cooldown waits for it to be gone
+    if !companions.is_empty() {
+        return;
+    }
+    cooldown.0.tick(time.delta());
+
+    if !actions.just_pressed(InputAction::Summon) || !cooldown.0.finished() {
+        return;
+    }
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    cooldown.0.reset();
+
+    let position = player_transform.translation.truncate() + WISP_FOLLOW_OFFSET;
+    commands
+        .spawn((
+            Companion {
+                state: WispState::Following,
+                lifetime: Timer::from_seconds(WISP_LIFETIME, TimerMode::Once),
+                attack_cooldown: Timer::from_seconds(WISP_ATTACK_COOLDOWN, TimerMode::Once),
+                bob_phase: 0.0,
+            },
+            Faction::Player,
+            StateScoped(InGame),
+            Sprite {
+                color: WISP_COLOR,
+                custom_size: Some(WISP_SIZE),
+                ..default()
+            },
+            Transform::from_translation(position.extend(Layer::Pickups.z()))
+                .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                CollisionHitbox {
+                    active: true,
+                    size: WISP_SIZE,
+                },
+                Transform::default(),
+            ));
+        });
+}
+
+// Hovers behind the player and darts at the closest enemy within reach
+#[allow(clippy::type_complexity)]
+fn update_companion_movement(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut companions: Query<(Entity, &mut Companion, &mut Transform), Without<Player>>,
+    player_query: Query<(&Transform, &Facing), With<Player>>,
+    enemies: Query<(Entity, &Transform, &Enemy), (Without<Companion>, Without<Player>)>,
+) {
+    let Ok((player_transform, facing)) = player_query.get_single() else {
+        return;
+    };
+    let player_pos = player_transform.translation.truncate();
+    let delta = time.delta_secs();
+
+    for (entity, mut companion, mut transform) in &mut companions {
+        companion.attack_cooldown.tick(time.delta());
+        let wisp_pos = transform.translation.truncate();
+
+        match companion.state {
+            WispState::Following => {
+                companion.bob_phase += WISP_BOB_SPEED * delta;
+                let side = facing.sign();
+                let bob = companion.bob_phase.sin() * WISP_BOB_AMPLITUDE;
+                let target =
+                    player_pos + Vec2::new(WISP_FOLLOW_OFFSET.x * side, WISP_FOLLOW_OFFSET.y + bob);
+                let new_pos = wisp_pos.lerp(target, (WISP_FOLLOW_SMOOTHING * delta).min(1.0));
+                transform.translation.x = new_pos.x;
+                transform.translation.y = new_pos.y;
+
+                if companion.attack_cooldown.finished()
+                    && let Some((target, _)) = enemies
+                        .iter()
+                        .filter(|(_, _, enemy)| !enemy.is_dead)
+                        .map(|(entity, enemy_transform, _)| {
+                            let distance =
+                                enemy_transform.translation.truncate().distance(player_pos);
+                            (entity, distance)
+                        })
+                        .filter(|(_, distance)| *distance <= WISP_ATTACK_RANGE)
+                        .min_by(|a, b| a.1.total_cmp(&b.1))
+                {
+                    companion.state = WispState::Darting {
+                        target,
+                        elapsed: 0.0,
+                    };
+                }
+            }
+            WispState::Darting { target, elapsed } => {
+                let target_pos = match enemies.get(target) {
+                    Ok((_, enemy_transform, enemy)) if !enemy.is_dead => {
+                        enemy_transform.translation.truncate()
+                    }
+                    _ => {
+                        companion.state = WispState::Following;
+                        continue;
+                    }
+                };
+
+                let to_target = target_pos - wisp_pos;
+                if to_target.length() <= WISP_DART_REACH {
+                    commands.entity(entity).with_children(|parent| {
+                        parent.spawn((
+                            AttackHitbox {
+                                damage: WISP_ATTACK_DAMAGE,
+                                active: true,
+                                size: WISP_ATTACK_HITBOX_SIZE,
+                                timer: Timer::from_seconds(
+                                    WISP_ATTACK_HITBOX_DURATION,
+                                    TimerMode::Once,
+                                ),
+                                launch: 0.0,
+                                guard_break: false,
+                                reaction: None,
+                                status: Some(StatusEffectKind::Burn),
+                            },
+                            WISP_ATTACK_KNOCKBACK,
+                            Transform::default(),
+                        ));
+                    });
+                    companion.attack_cooldown.reset();
+                    companion.state = WispState::Following;
+                    continue;
+                }
+
+                let elapsed = elapsed + delta;
+                if elapsed >= WISP_DART_MAX_DURATION {
+                    companion.attack_cooldown.reset();
+                    companion.state = WispState::Following;
+                    continue;
+                }
+                companion.state = WispState::Darting { target, elapsed };
+
+                let step =
+                    to_target.normalize() * (WISP_DART_SPEED * delta).min(to_target.length());
+                transform.translation.x += step.x;
+                transform.translation.y += step.y;
+            }
+        }
+    }
+}
+
+// A single enemy hit dispels the wisp
+fn dispel_hit_companions(
+    mut commands: Commands,
+    mut hit_events: EventReader<HitEvent>,
+    companions: Query<(), With<Companion>>,
+) {
+    for event in hit_events.read() {
+        if companions.contains(event.target) {
+            commands.entity(event.target).despawn_recursive();
+        }
+    }
+}
+
+// Wisps flicker out at the end of their lifetime
+fn expire_companions(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut companions: Query<(Entity, &mut Companion, &mut Sprite)>,
+) {
+    for (entity, mut companion, mut sprite) in &mut companions {
+        companion.lifetime.tick(time.delta());
+
+        if companion.lifetime.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let remaining = companion.lifetime.remaining_secs();
+        if remaining < WISP_FADE_DURATION {
+            sprite.color = WISP_COLOR.with_alpha(remaining / WISP_FADE_DURATION);
+        }
+    }
+}
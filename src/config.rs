@@ -0,0 +1,278 @@
+use std::fmt;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::hitbox::Knockback;
+use crate::physics::MovementTuning;
+
+// Game config constants
+const GAME_CONFIG_PATH: &str = "config/game.config.ron";
+
+// Player tuning
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct PlayerConfig {
+    pub max_health: f32,
+    pub attack: f32,
+    pub defense: f32,
+    pub speed: f32,
+    pub jump_force: f32,
+    pub hurt_immunity_time: f32,
+    pub stagger_time: f32,
+    pub landing_squash: f32, // How much the sprite flattens on touchdown, 0 turns it off
+    pub contact_knockback: Knockback,
+    pub movement: MovementTuning,
+}
+
+impl Default for PlayerConfig {
+    fn default() -> Self {
+        Self {
+            max_health: 100.0,
+            attack: 10.0,
+            defense: 5.0,
+            speed: 250.0,
+            jump_force: 500.0,
+            hurt_immunity_time: 0.4,
+            stagger_time: 0.7, // Heavy hits stun for longer
+            landing_squash: 0.15,
+            // Pushes the player off the enemy body
+            contact_knockback: Knockback {
+                strength: 350.0,
+                lift: 250.0,
+                decay: 3.0,
+            },
+            // Quick to start and stop on the ground, some drift in the air
+            movement: MovementTuning {
+                ground_acceleration: 3000.0,
+                ground_friction: 4000.0,
+                air_acceleration: 1800.0,
+                air_friction: 900.0,
+            },
+        }
+    }
+}
+
+// Base skeleton tuning; variants and elites scale from these
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct EnemyConfig {
+    pub max_health: f32,
+    pub attack: f32,
+    pub contact_damage: f32,
+    pub defense: f32,
+    pub speed: f32,
+    pub attack_range: f32,
+    pub detection_range: f32,
+    pub leash_radius: f32,
+    pub hurt_time: f32,
+    pub stagger_time: f32,
+    pub movement: MovementTuning,
+}
+
+impl Default for EnemyConfig {
+    fn default() -> Self {
+        Self {
+            max_health: 200.0,
+            attack: 10.0,
+            contact_damage: 12.0, // Touching the body hurts, like in Hollow Knight
+            defense: 5.0,
+            speed: 150.0,
+            attack_range: 146.0,
+            detection_range: 400.0,
+            leash_radius: 700.0, // Gives up the chase this far from the spawn point
+            hurt_time: 0.3,
+            stagger_time: 0.8, // Heavy hits leave the enemy open for longer
+            // Heavier than the player, takes a moment to turn around
+            movement: MovementTuning {
+                ground_acceleration: 1500.0,
+                ground_friction: 2500.0,
+                air_acceleration: 600.0,
+                air_friction: 300.0,
+            },
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct PhysicsConfig {
+    pub gravity: f32,
+    pub max_fall_speed: f32,
+    pub ground_drag: f32, // Horizontal speed lost per second by bodies nothing steers
+    pub air_drag: f32,
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self {
+            gravity: 980.0, // Approximately 9.8 m/s² in pixels
+            max_fall_speed: -1000.0,
+            ground_drag: 8.0,
+            air_drag: 1.0,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct GroundConfig {
+    pub collision_tolerance: f32,
+    pub collision_range: f32,
+}
+
+impl Default for GroundConfig {
+    fn default() -> Self {
+        Self {
+            collision_tolerance: 10.0,
+            collision_range: 15.0,
+        }
+    }
+}
+
+// Game rules that aren't about a single character
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct RulesConfig {
+    pub pause_grace_period: f32,
+}
+
+impl Default for RulesConfig {
+    fn default() -> Self {
+        Self {
+            // Enemies get this long to finish their attacks before a pause freezes them
+            pause_grace_period: 0.4,
+        }
+    }
+}
+
+// Gameplay numbers read from assets/config/game.config.ron and reloaded when the file is
+// saved, so balancing doesn't need a recompile. Missing settings keep their default value
+#[derive(Asset, TypePath, Resource, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct GameConfig {
+    pub player: PlayerConfig,
+    pub enemy: EnemyConfig,
+    pub physics: PhysicsConfig,
+    pub ground: GroundConfig,
+    pub rules: RulesConfig,
+}
+
+#[derive(Debug)]
+pub enum GameConfigError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+}
+
+impl fmt::Display for GameConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameConfigError::Io(error) => write!(f, "could not read game config: {error}"),
+            GameConfigError::Parse(error) => write!(f, "could not parse game config: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for GameConfigError {}
+
+impl From<std::io::Error> for GameConfigError {
+    fn from(error: std::io::Error) -> Self {
+        GameConfigError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for GameConfigError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        GameConfigError::Parse(error)
+    }
+}
+
+#[derive(Default)]
+struct GameConfigLoader;
+
+impl AssetLoader for GameConfigLoader {
+    type Asset = GameConfig;
+    type Settings = ();
+    type Error = GameConfigError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<GameConfig, GameConfigError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["config.ron"]
+    }
+}
+
+// Keeps the config file loaded so its edits are picked up
+#[derive(Resource)]
+struct GameConfigFile(Handle<GameConfig>);
+
+impl FromWorld for GameConfigFile {
+    fn from_world(world: &mut World) -> Self {
+        Self(world.resource::<AssetServer>().load(GAME_CONFIG_PATH))
+    }
+}
+
+pub struct GameConfigPlugin;
+
+impl Plugin for GameConfigPlugin {
+    fn build(&self, app: &mut App) {
+        // The built-in values hold until the file has loaded
+        app.init_asset::<GameConfig>()
+            .init_asset_loader::<GameConfigLoader>()
+            .init_resource::<GameConfig>()
+            .init_resource::<GameConfigFile>()
+            .add_systems(Update, apply_config_file);
+    }
+}
+
+// Copies the file's values in once it has loaded and again whenever it is saved. An edit
+// that doesn't parse fails to load and leaves the previous values in place
+fn apply_config_file(
+    mut events: EventReader<AssetEvent<GameConfig>>,
+    file: Res<GameConfigFile>,
+    configs: Res<Assets<GameConfig>>,
+    mut config: ResMut<GameConfig>,
+) {
+    for event in events.read() {
+        if let AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } = *event
+            && id == file.0.id()
+            && let Some(new_config) = configs.get(id)
+        {
+            *config = new_config.clone();
+            info!("Applied game config from {GAME_CONFIG_PATH}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shipped_config_parses() {
+        let config: GameConfig =
+            ron::from_str(include_str!("../assets/config/game.config.ron")).unwrap();
+        assert_eq!(config.player.max_health, 100.0);
+        assert_eq!(config.enemy.movement.ground_friction, 2500.0);
+        assert_eq!(config.rules.pause_grace_period, 0.4);
+    }
+
+    #[test]
+    fn missing_settings_keep_their_default() {
+        let config: GameConfig = ron::from_str("(player: (speed: 300.0))").unwrap();
+        assert_eq!(config.player.speed, 300.0);
+        assert_eq!(config.player.jump_force, 500.0);
+        assert_eq!(config.physics.gravity, 980.0);
+    }
+}
@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+
+use crate::game::{GameState, InGame};
+use crate::hitbox::DamageEvent;
+use crate::layers::Layer;
+use crate::player::Player;
+
+// Damage number constants
+const DAMAGE_NUMBER_LIFETIME: f32 = 0.8;
+const DAMAGE_NUMBER_RISE_SPEED: f32 = 70.0;
+const DAMAGE_NUMBER_OFFSET_Y: f32 = 40.0; // Starts above the center of whoever was hit
+const DAMAGE_NUMBER_SPREAD_X: f32 = 12.0; // Random sideways jitter so repeated hits don't stack
+const DAMAGE_NUMBER_FONT_SIZE: f32 = 22.0;
+const DAMAGE_NUMBER_ENEMY_COLOR: Color = Color::srgb(1.0, 0.95, 0.8);
+const DAMAGE_NUMBER_PLAYER_COLOR: Color = Color::srgb(1.0, 0.3, 0.25);
+
+// World-space number that drifts up and fades out
+#[derive(Component)]
+struct DamageNumber {
+    timer: Timer,
+}
+
+pub struct DamageNumbersPlugin;
+
+impl Plugin for DamageNumbersPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (spawn_damage_numbers, animate_damage_numbers).run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+fn spawn_damage_numbers(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut damage_events: EventReader<DamageEvent>,
+    players: Query<(), With<Player>>,
+) {
+    for event in damage_events.read() {
+        if event.amount <= 0.0 {
+            continue;
+        }
+
+        let color = if players.contains(event.target) {
+            DAMAGE_NUMBER_PLAYER_COLOR
+        } else {
+            DAMAGE_NUMBER_ENEMY_COLOR
+        };
+        let jitter = (rand::random::<f32>() * 2.0 - 1.0) * DAMAGE_NUMBER_SPREAD_X;
+
+        commands.spawn((
+            DamageNumber {
+                timer: Timer::from_seconds(DAMAGE_NUMBER_LIFETIME, TimerMode::Once),
+            },
+            StateScoped(InGame),
+            Text2d::new(format!("{}", event.amount.round())),
+            TextFont {
+                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                font_size: DAMAGE_NUMBER_FONT_SIZE,
+                ..default()
+            },
+            TextColor(color),
+            Transform::from_xyz(
+                event.position.x + jitter,
+                event.position.y + DAMAGE_NUMBER_OFFSET_Y,
+                Layer::Overlay.z(),
+            ),
+        ));
+    }
+}
+
+fn animate_damage_numbers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut numbers: Query<(Entity, &mut DamageNumber, &mut Transform, &mut TextColor)>,
+) {
+    for (entity, mut number, mut transform, mut color) in &mut numbers {
+        number.timer.tick(time.delta());
+        if number.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        transform.translation.y += DAMAGE_NUMBER_RISE_SPEED * time.delta_secs();
+        color.0.set_alpha(1.0 - number.timer.fraction());
+    }
+}
@@ -0,0 +1,398 @@
+use bevy::prelude::*;
+
+use crate::animations::AnimationController;
+use crate::enemy::Enemy;
+use crate::game::{GameState, InGame};
+use crate::hitbox::{AttackHitbox, CollisionHitbox};
+use crate::layers::UiLayer;
+use crate::level::RoomRegistry;
+use crate::paralax_background::ParallaxMonitor;
+use crate::physics::{BodyCollider, Physics};
+use crate::player::Player;
+
+// Warp menu constants
+const WARP_MENU_KEY: KeyCode = KeyCode::F9;
+const WARP_CHEAT_CODE: [KeyCode; 4] = [KeyCode::KeyW, KeyCode::KeyA, KeyCode::KeyR, KeyCode::KeyP];
+const WARP_BUTTON_WIDTH: f32 = 240.0;
+const WARP_BUTTON_HEIGHT: f32 = 32.0;
+const WARP_DROP_HEIGHT: f32 = 200.0; // Land on the target instead of appearing inside the ground
+
+const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
+const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
+const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
+
+// Hitbox overlay constants
+const HITBOX_DEBUG_KEY: KeyCode = KeyCode::F3;
+const BODY_HITBOX_COLOR: Color = Color::srgb(0.2, 0.6, 1.0);
+const ATTACK_HITBOX_COLOR: Color = Color::srgb(1.0, 0.2, 0.2);
+const BODY_COLLIDER_COLOR: Color = Color::srgb(0.3, 1.0, 0.4);
+
+// Stats overlay constants
+const STATS_OVERLAY_KEY: KeyCode = KeyCode::F1;
+const STATS_OVERLAY_TOP: f32 = 110.0; // Under the HUD
+const STATS_FONT_SIZE: f32 = 14.0;
+
+// Developer options; the warp menu is always available in debug builds
+#[derive(Resource)]
+pub struct DebugSettings {
+    pub warp_menu_unlocked: bool,
+    pub show_hitboxes: bool, // Outlines every hitbox and collider, debug builds only
+    pub show_stats: bool,
+}
+
+impl Default for DebugSettings {
+    fn default() -> Self {
+        Self {
+            warp_menu_unlocked: cfg!(debug_assertions),
+            show_hitboxes: false,
+            show_stats: false,
+        }
+    }
+}
+
+// How much of the warp cheat code has been typed on the main menu
+#[derive(Resource, Default)]
+struct CheatCodeProgress {
+    matched: usize,
+}
+
+// Root node of the warp menu
+#[derive(Component)]
+struct WarpMenu;
+
+// Text of the stats overlay
+#[derive(Component)]
+struct StatsOverlay;
+
+// Button that sends the player to a bench or room
+#[derive(Component)]
+struct WarpButton {
+    target: Vec2,
+}
+
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugSettings>()
+            .init_resource::<CheatCodeProgress>()
+            .add_systems(
+                Update,
+                (
+                    track_cheat_code.run_if(in_state(GameState::Menu)),
+                    (toggle_warp_menu, handle_warp_buttons).run_if(in_state(GameState::Playing)),
+                    (toggle_stats_overlay, update_stats_overlay)
+                        .chain()
+                        .run_if(in_state(GameState::Playing)),
+                ),
+            )
+            .add_systems(OnEnter(InGame), spawn_stats_overlay)
+            .add_systems(OnExit(GameState::Playing), close_warp_menu);
+
+        // Release builds don't ship the hitbox overlay at all
+        if cfg!(debug_assertions) {
+            app.add_systems(
+                Update,
+                (
+                    toggle_hitbox_overlay,
+                    draw_hitbox_overlay
+                        .run_if(|settings: Res<DebugSettings>| settings.show_hitboxes),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+        }
+    }
+}
+
+// Typing the cheat code on the main menu unlocks the warp menu in release builds
+fn track_cheat_code(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut progress: ResMut<CheatCodeProgress>,
+    mut debug_settings: ResMut<DebugSettings>,
+) {
+    for key in keyboard.get_just_pressed() {
+        if *key == WARP_CHEAT_CODE[progress.matched] {
+            progress.matched += 1;
+        } else {
+            progress.matched = usize::from(*key == WARP_CHEAT_CODE[0]);
+        }
+
+        if progress.matched == WARP_CHEAT_CODE.len() {
+            progress.matched = 0;
+            debug_settings.warp_menu_unlocked = true;
+            info!("Warp menu unlocked, press F9 while playing");
+        }
+    }
+}
+
+fn toggle_warp_menu(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    debug_settings: Res<DebugSettings>,
+    registry: Res<RoomRegistry>,
+    asset_server: Res<AssetServer>,
+    warp_menus: Query<Entity, With<WarpMenu>>,
+) {
+    if !debug_settings.warp_menu_unlocked || !keyboard.just_pressed(WARP_MENU_KEY) {
+        return;
+    }
+
+    if !warp_menus.is_empty() {
+        for entity in &warp_menus {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+
+    // Every bench first, then every room entry point
+    let mut targets = Vec::new();
+    for room in &registry.rooms {
+        for bench in &room.benches {
+            targets.push((format!("Bench: {}", bench.name), bench.position));
+        }
+    }
+    for room in &registry.rooms {
+        targets.push((format!("Room: {}", room.name), room.entry_point()));
+    }
+
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(10.0),
+                top: Val::Px(10.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+            WarpMenu,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Warp to"),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+
+            for (label, target) in targets {
+                parent
+                    .spawn((
+                        Button,
+                        Node {
+                            width: Val::Px(WARP_BUTTON_WIDTH),
+                            height: Val::Px(WARP_BUTTON_HEIGHT),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BackgroundColor(NORMAL_BUTTON),
+                        WarpButton { target },
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            Text::new(label),
+                            TextFont {
+                                font: font.clone(),
+                                font_size: 14.0,
+                                ..default()
+                            },
+                            TextColor(Color::WHITE),
+                        ));
+                    });
+            }
+        });
+}
+
+#[allow(clippy::type_complexity)]
+fn handle_warp_buttons(
+    mut commands: Commands,
+    mut interaction_query: Query<
+        (&Interaction, &WarpButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    mut player_query: Query<(&mut Transform, &mut Physics), With<Player>>,
+    mut camera_query: Query<&mut Transform, (With<Camera2d>, Without<Player>)>,
+    warp_menus: Query<Entity, With<WarpMenu>>,
+) {
+    for (interaction, warp_button, mut color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = PRESSED_BUTTON.into();
+
+                if let Ok((mut transform, mut physics)) = player_query.get_single_mut() {
+                    transform.translation.x = warp_button.target.x;
+                    transform.translation.y = warp_button.target.y + WARP_DROP_HEIGHT;
+                    physics.velocity = Vec2::ZERO;
+                }
+                if let Ok(mut camera_transform) = camera_query.get_single_mut() {
+                    camera_transform.translation.x = warp_button.target.x;
+                }
+
+                for entity in &warp_menus {
+                    commands.entity(entity).despawn_recursive();
+                }
+            }
+            Interaction::Hovered => *color = HOVERED_BUTTON.into(),
+            Interaction::None => *color = NORMAL_BUTTON.into(),
+        }
+    }
+}
+
+fn close_warp_menu(mut commands: Commands, warp_menus: Query<Entity, With<WarpMenu>>) {
+    for entity in &warp_menus {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn toggle_hitbox_overlay(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut debug_settings: ResMut<DebugSettings>,
+) {
+    if keyboard.just_pressed(HITBOX_DEBUG_KEY) {
+        debug_settings.show_hitboxes = !debug_settings.show_hitboxes;
+    }
+}
+
+// Drawn with the same world positions and sizes the collision checks use
+fn draw_hitbox_overlay(
+    mut gizmos: Gizmos,
+    body_hitboxes: Query<(&CollisionHitbox, &GlobalTransform)>,
+    attack_hitboxes: Query<(&AttackHitbox, &GlobalTransform)>,
+    colliders: Query<(&BodyCollider, &GlobalTransform)>,
+) {
+    for (hitbox, transform) in &body_hitboxes {
+        if hitbox.active {
+            let position = transform.translation().truncate();
+            gizmos.rect_2d(
+                Isometry2d::from_translation(position),
+                hitbox.size,
+                BODY_HITBOX_COLOR,
+            );
+        }
+    }
+    for (hitbox, transform) in &attack_hitboxes {
+        if hitbox.active {
+            let position = transform.translation().truncate();
+            gizmos.rect_2d(
+                Isometry2d::from_translation(position),
+                hitbox.size,
+                ATTACK_HITBOX_COLOR,
+            );
+        }
+    }
+    for (collider, transform) in &colliders {
+        let position = transform.translation().truncate();
+        gizmos.rect_2d(
+            Isometry2d::from_translation(position),
+            collider.size,
+            BODY_COLLIDER_COLOR,
+        );
+    }
+}
+
+fn spawn_stats_overlay(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    debug_settings: Res<DebugSettings>,
+) {
+    commands.spawn((
+        StatsOverlay,
+        StateScoped(InGame),
+        Text::new(""),
+        TextFont {
+            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+            font_size: STATS_FONT_SIZE,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(10.0),
+            top: Val::Px(STATS_OVERLAY_TOP),
+            padding: UiRect::all(Val::Px(8.0)),
+            display: if debug_settings.show_stats {
+                Display::Flex
+            } else {
+                Display::None
+            },
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+        GlobalZIndex(UiLayer::Hud.z_index()),
+    ));
+}
+
+fn toggle_stats_overlay(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut debug_settings: ResMut<DebugSettings>,
+    mut overlays: Query<&mut Node, With<StatsOverlay>>,
+) {
+    if !keyboard.just_pressed(STATS_OVERLAY_KEY) {
+        return;
+    }
+
+    debug_settings.show_stats = !debug_settings.show_stats;
+    for mut node in &mut overlays {
+        node.display = if debug_settings.show_stats {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+}
+
+// Frame timing and sprite counts come from the parallax monitor's once a second sample,
+// the rest is read live
+#[allow(clippy::type_complexity)]
+fn update_stats_overlay(
+    debug_settings: Res<DebugSettings>,
+    monitor: Res<ParallaxMonitor>,
+    entities: Query<Entity>,
+    enemies: Query<&Enemy>,
+    players: Query<(&Transform, &Physics, &AnimationController), With<Player>>,
+    cameras: Query<&Transform, (With<Camera2d>, Without<Player>)>,
+    mut overlays: Query<&mut Text, With<StatsOverlay>>,
+) {
+    if !debug_settings.show_stats {
+        return;
+    }
+
+    let mut stats = format!(
+        "FPS: {:.0} ({:.1} ms)\nEntities: {}\nEnemies alive: {}\nParallax layers: {}\nVisible sprites: {}",
+        monitor.fps,
+        monitor.frame_time,
+        entities.iter().count(),
+        enemies.iter().filter(|enemy| !enemy.is_dead).count(),
+        monitor.active_layers,
+        monitor.visible_sprites,
+    );
+    if let Ok((transform, physics, animation_controller)) = players.get_single() {
+        stats.push_str(&format!(
+            "\nPlayer: ({:.0}, {:.0})\nVelocity: ({:.0}, {:.0})\nState: {:?}{}",
+            transform.translation.x,
+            transform.translation.y,
+            physics.velocity.x,
+            physics.velocity.y,
+            animation_controller.get_current_state(),
+            if physics.on_ground { ", grounded" } else { "" },
+        ));
+    }
+    if let Ok(camera) = cameras.get_single() {
+        stats.push_str(&format!(
+            "\nCamera: ({:.0}, {:.0})",
+            camera.translation.x, camera.translation.y
+        ));
+    }
+
+    for mut text in &mut overlays {
+        text.0.clone_from(&stats);
+    }
+}
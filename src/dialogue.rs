@@ -0,0 +1,528 @@
+use bevy::prelude::*;
+
+use crate::game::{GameState, InGame};
+use crate::input::{ActionState, InputAction, KeyBindings};
+use crate::layers::{Layer, UiLayer};
+use crate::level::{NPC_HEIGHT, RoomRegistry};
+use crate::player;
+use crate::triggers::TriggerZone;
+
+// NPC constants
+const NPC_SIZE: Vec2 = Vec2::new(36.0, NPC_HEIGHT);
+const NPC_COLOR: Color = Color::srgb(0.55, 0.5, 0.6);
+const NPC_REACH: f32 = 60.0; // How far to either side the player can talk from
+const NPC_PROMPT_FONT_SIZE: f32 = 18.0;
+const NPC_PROMPT_OFFSET_Y: f32 = 24.0; // Above the NPC's head
+const NPC_PROMPT_COLOR: Color = Color::srgb(0.95, 0.9, 0.75);
+
+// Dialogue box constants
+const DIALOGUE_TYPE_SPEED: f32 = 40.0; // Characters per second
+const DIALOGUE_BOX_WIDTH: f32 = 70.0; // Percent of the screen width
+const DIALOGUE_BOX_BOTTOM: f32 = 40.0;
+const DIALOGUE_BOX_COLOR: Color = Color::srgba(0.02, 0.02, 0.04, 0.9);
+const DIALOGUE_BORDER_COLOR: Color = Color::srgb(0.85, 0.82, 0.75);
+const DIALOGUE_NAME_COLOR: Color = Color::srgb(0.95, 0.8, 0.35);
+const DIALOGUE_CHOICE_COLOR: Color = Color::srgb(0.6, 0.6, 0.6);
+const DIALOGUE_SELECTED_COLOR: Color = Color::WHITE;
+
+// Answer offered at the end of a page
+#[derive(Clone)]
+pub struct DialogueChoice {
+    pub label: String,
+    pub next: Option<usize>, // Page it leads to; none ends the conversation
+    pub outcome: Option<String>, // Passed on when the conversation ends, e.g. to open a shop
+}
+
+// One box of text, with the answers to pick from once it's read
+#[derive(Clone)]
+pub struct DialoguePage {
+    pub text: String,
+    pub choices: Vec<DialogueChoice>,
+    pub next: Option<usize>, // Page after this one when it has no choices
+}
+
+#[derive(Clone)]
+pub struct Conversation {
+    pub id: String,
+    pub pages: Vec<DialoguePage>,
+}
+
+impl Conversation {
+    // Conversations known to the game, looked up by the id level data gives an NPC
+    pub fn get(id: &str) -> Option<Self> {
+        let pages = match id {
+            "wanderer" => vec![
+                page(
+                    "Ah, another traveller. Few come down this road anymore.",
+                    Some(1),
+                ),
+                page(
+                    "The bench behind you is safe to rest on. Mind the cliffs to the east.",
+                    Some(2),
+                ),
+                DialoguePage {
+                    text: "Is there something you wanted to ask?".to_string(),
+                    choices: vec![
+                        choice("What's out east?", Some(3), Some("asked_about_cliffs")),
+                        choice("Nothing. Farewell.", None, None),
+                    ],
+                    next: None,
+                },
+                page(
+                    "A warden guards the cliffs. It has turned back stronger travellers than you.",
+                    None,
+                ),
+            ],
+            _ => return None,
+        };
+        Some(Self {
+            id: id.to_string(),
+            pages,
+        })
+    }
+}
+
+fn page(text: &str, next: Option<usize>) -> DialoguePage {
+    DialoguePage {
+        text: text.to_string(),
+        choices: Vec::new(),
+        next,
+    }
+}
+
+fn choice(label: &str, next: Option<usize>, outcome: Option<&str>) -> DialogueChoice {
+    DialogueChoice {
+        label: label.to_string(),
+        next,
+        outcome: outcome.map(str::to_string),
+    }
+}
+
+// Character placed by the level data that opens a conversation when talked to
+#[derive(Component)]
+pub struct Npc {
+    pub name: String,
+    pub conversation: String,
+}
+
+// "[key] Talk" shown over an NPC while the player is close enough
+#[derive(Component)]
+struct NpcPrompt;
+
+// Conversation on screen, typed out a character at a time
+struct DialogueState {
+    npc: Entity,
+    name: String,
+    conversation: Conversation,
+    page: usize,
+    typed: f32, // Characters of the page shown so far
+    selected: usize,
+    outcome: Option<String>,
+}
+
+impl DialogueState {
+    fn page(&self) -> &DialoguePage {
+        &self.conversation.pages[self.page]
+    }
+
+    fn fully_typed(&self) -> bool {
+        self.typed as usize >= self.page().text.chars().count()
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ActiveDialogue {
+    state: Option<DialogueState>,
+}
+
+// Run condition for player controls, which stop while a conversation is open
+pub fn dialogue_closed(dialogue: Res<ActiveDialogue>) -> bool {
+    dialogue.state.is_none()
+}
+
+// Sent when a conversation is finished, with the outcome of the last answer picked
+#[derive(Event)]
+pub struct ConversationEndedEvent {
+    pub npc: Entity,
+    pub conversation: String,
+    pub outcome: Option<String>,
+}
+
+#[derive(Component)]
+struct DialogueBox;
+
+#[derive(Component)]
+struct DialogueName;
+
+#[derive(Component)]
+struct DialogueText;
+
+#[derive(Component)]
+struct DialogueChoices;
+
+#[derive(Component)]
+struct DialogueChoiceText(usize);
+
+pub struct DialoguePlugin;
+
+impl Plugin for DialoguePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActiveDialogue>()
+            .add_event::<ConversationEndedEvent>()
+            .add_systems(OnEnter(InGame), (spawn_npcs, spawn_dialogue_box))
+            .add_systems(OnExit(InGame), end_dialogue)
+            .add_systems(
+                Update,
+                (
+                    start_conversations,
+                    advance_dialogue.before(player::process_player_input),
+                    update_dialogue_box,
+                    update_npc_prompts,
+                    label_npc_prompts.run_if(resource_changed::<KeyBindings>),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn talk_prompt(bindings: &KeyBindings) -> String {
+    format!("{} Talk", bindings.prompt(InputAction::Interact))
+}
+
+fn spawn_npcs(
+    mut commands: Commands,
+    registry: Res<RoomRegistry>,
+    bindings: Res<KeyBindings>,
+    asset_server: Res<AssetServer>,
+) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+
+    for npc in registry.rooms.iter().flat_map(|room| &room.npcs) {
+        if Conversation::get(&npc.conversation).is_none() {
+            warn!("{} has unknown conversation {}", npc.name, npc.conversation);
+            continue;
+        }
+
+        commands
+            .spawn((
+                Npc {
+                    name: npc.name.clone(),
+                    conversation: npc.conversation.clone(),
+                },
+                StateScoped(InGame),
+                Sprite {
+                    color: NPC_COLOR,
+                    custom_size: Some(NPC_SIZE),
+                    ..default()
+                },
+                Transform::from_translation(npc.position.extend(Layer::Props.z())),
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    TriggerZone::new(Vec2::new(NPC_SIZE.x + NPC_REACH * 2.0, NPC_SIZE.y)),
+                    Transform::default(),
+                ));
+                parent.spawn((
+                    NpcPrompt,
+                    Text2d::new(talk_prompt(&bindings)),
+                    TextFont {
+                        font: font.clone(),
+                        font_size: NPC_PROMPT_FONT_SIZE,
+                        ..default()
+                    },
+                    TextColor(NPC_PROMPT_COLOR),
+                    Transform::from_xyz(0.0, NPC_SIZE.y / 2.0 + NPC_PROMPT_OFFSET_Y, 1.0),
+                    Visibility::Hidden,
+                ));
+            });
+    }
+}
+
+fn spawn_dialogue_box(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                bottom: Val::Px(DIALOGUE_BOX_BOTTOM),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            GlobalZIndex(UiLayer::Dialogue.z_index()),
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    DialogueBox,
+                    Node {
+                        width: Val::Percent(DIALOGUE_BOX_WIDTH),
+                        min_height: Val::Px(140.0),
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(10.0),
+                        padding: UiRect::all(Val::Px(20.0)),
+                        border: UiRect::all(Val::Px(2.0)),
+                        ..default()
+                    },
+                    BorderColor(DIALOGUE_BORDER_COLOR),
+                    BorderRadius::all(Val::Px(8.0)),
+                    BackgroundColor(DIALOGUE_BOX_COLOR),
+                    Visibility::Hidden,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        DialogueName,
+                        Text::new(""),
+                        TextFont {
+                            font: font.clone(),
+                            font_size: 22.0,
+                            ..default()
+                        },
+                        TextColor(DIALOGUE_NAME_COLOR),
+                    ));
+                    parent.spawn((
+                        DialogueText,
+                        Text::new(""),
+                        TextFont {
+                            font: font.clone(),
+                            font_size: 20.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                    ));
+                    parent.spawn((
+                        DialogueChoices,
+                        Node {
+                            column_gap: Val::Px(30.0),
+                            ..default()
+                        },
+                    ));
+                });
+        });
+}
+
+// Interacting next to an NPC opens its conversation
+fn start_conversations(
+    actions: Res<ActionState>,
+    mut dialogue: ResMut<ActiveDialogue>,
+    npcs: Query<(Entity, &Npc, &Children)>,
+    zones: Query<&TriggerZone>,
+) {
+    if dialogue.state.is_some() || !actions.just_pressed(InputAction::Interact) {
+        return;
+    }
+
+    let near = npcs.iter().find(|(_, _, children)| {
+        children
+            .iter()
+            .filter_map(|&child| zones.get(child).ok())
+            .any(|zone| zone.player_inside)
+    });
+    let Some((entity, npc, _)) = near else {
+        return;
+    };
+    let Some(conversation) = Conversation::get(&npc.conversation) else {
+        return;
+    };
+
+    dialogue.state = Some(DialogueState {
+        npc: entity,
+        name: npc.name.clone(),
+        conversation,
+        page: 0,
+        typed: 0.0,
+        selected: 0,
+        outcome: None,
+    });
+}
+
+// Jump finishes the line being typed, then turns the page or picks the answer
+// highlighted with left and right
+fn advance_dialogue(
+    time: Res<Time>,
+    mut actions: ResMut<ActionState>,
+    mut dialogue: ResMut<ActiveDialogue>,
+    mut ended_events: EventWriter<ConversationEndedEvent>,
+) {
+    let Some(state) = dialogue.state.as_mut() else {
+        return;
+    };
+
+    // The press that closes the box mustn't also make the player jump
+    let confirm = actions.just_pressed(InputAction::Jump);
+    actions.consume(InputAction::Jump);
+
+    if !state.fully_typed() {
+        state.typed += DIALOGUE_TYPE_SPEED * time.delta_secs();
+        if confirm {
+            state.typed = state.page().text.chars().count() as f32;
+        }
+        return;
+    }
+
+    let choices = state.page().choices.len();
+    if choices > 0 {
+        if actions.just_pressed(InputAction::MoveRight) {
+            state.selected = (state.selected + 1) % choices;
+        } else if actions.just_pressed(InputAction::MoveLeft) {
+            state.selected = (state.selected + choices - 1) % choices;
+        }
+    }
+    if !confirm {
+        return;
+    }
+
+    let (next, outcome) = match state.page().choices.get(state.selected) {
+        Some(choice) => (choice.next, choice.outcome.clone()),
+        None => (state.page().next, None),
+    };
+    if outcome.is_some() {
+        state.outcome = outcome;
+    }
+    match next.filter(|&page| page < state.conversation.pages.len()) {
+        Some(page) => {
+            state.page = page;
+            state.typed = 0.0;
+            state.selected = 0;
+        }
+        None => {
+            if let Some(state) = dialogue.state.take() {
+                ended_events.send(ConversationEndedEvent {
+                    npc: state.npc,
+                    conversation: state.conversation.id,
+                    outcome: state.outcome,
+                });
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_dialogue_box(
+    mut commands: Commands,
+    dialogue: Res<ActiveDialogue>,
+    asset_server: Res<AssetServer>,
+    mut boxes: Query<&mut Visibility, With<DialogueBox>>,
+    mut texts: ParamSet<(
+        Query<&mut Text, With<DialogueName>>,
+        Query<&mut Text, With<DialogueText>>,
+        Query<(&DialogueChoiceText, &mut Text, &mut TextColor)>,
+    )>,
+    choice_rows: Query<(Entity, Option<&Children>), With<DialogueChoices>>,
+) {
+    if !dialogue.is_changed() {
+        return;
+    }
+
+    for mut visibility in &mut boxes {
+        *visibility = if dialogue.state.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+    let Some(state) = dialogue.state.as_ref() else {
+        return;
+    };
+
+    for mut text in &mut texts.p0() {
+        if text.0 != state.name {
+            text.0 = state.name.clone();
+        }
+    }
+    let typed: String = state
+        .page()
+        .text
+        .chars()
+        .take(state.typed as usize)
+        .collect();
+    for mut text in &mut texts.p1() {
+        if text.0 != typed {
+            text.0 = typed.clone();
+        }
+    }
+
+    // Answers only show up once the page is read
+    let choices: &[DialogueChoice] = if state.fully_typed() {
+        &state.page().choices
+    } else {
+        &[]
+    };
+    for (row, children) in &choice_rows {
+        if children.map_or(0, |children| children.len()) != choices.len() {
+            commands
+                .entity(row)
+                .despawn_descendants()
+                .with_children(|parent| {
+                    for index in 0..choices.len() {
+                        parent.spawn((
+                            DialogueChoiceText(index),
+                            Text::new(""),
+                            TextFont {
+                                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                font_size: 20.0,
+                                ..default()
+                            },
+                            TextColor(DIALOGUE_CHOICE_COLOR),
+                        ));
+                    }
+                });
+        }
+    }
+    for (choice_text, mut text, mut color) in &mut texts.p2() {
+        let Some(choice) = choices.get(choice_text.0) else {
+            continue;
+        };
+        let selected = choice_text.0 == state.selected;
+        let label = if selected {
+            format!("> {}", choice.label)
+        } else {
+            choice.label.clone()
+        };
+        if text.0 != label {
+            text.0 = label;
+        }
+        color.0 = if selected {
+            DIALOGUE_SELECTED_COLOR
+        } else {
+            DIALOGUE_CHOICE_COLOR
+        };
+    }
+}
+
+fn update_npc_prompts(
+    dialogue: Res<ActiveDialogue>,
+    npcs: Query<&Children, With<Npc>>,
+    zones: Query<&TriggerZone>,
+    mut prompts: Query<&mut Visibility, With<NpcPrompt>>,
+) {
+    for children in &npcs {
+        let near = children
+            .iter()
+            .filter_map(|&child| zones.get(child).ok())
+            .any(|zone| zone.player_inside);
+        for &child in children {
+            if let Ok(mut visibility) = prompts.get_mut(child) {
+                visibility.set_if_neq(if near && dialogue.state.is_none() {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                });
+            }
+        }
+    }
+}
+
+// Rebinding the key mid-run relabels the prompts
+fn label_npc_prompts(bindings: Res<KeyBindings>, mut prompts: Query<&mut Text2d, With<NpcPrompt>>) {
+    for mut text in &mut prompts {
+        text.0 = talk_prompt(&bindings);
+    }
+}
+
+fn end_dialogue(mut dialogue: ResMut<ActiveDialogue>) {
+    dialogue.state = None;
+}
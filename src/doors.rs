@@ -0,0 +1,344 @@
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+use crate::enemy::SightBlocker;
+use crate::game::{GameState, InGame};
+use crate::hitbox::{self, CollisionHitbox, Faction, HitEvent};
+use crate::layers::Layer;
+use crate::level::{DoorLock, GROUND_TOP_Y, KeyItemDefinition, RoomRegistry};
+use crate::notifications::NotificationEvent;
+use crate::physics::Solid;
+use crate::triggers::{self, TriggerEnteredEvent, TriggerZone};
+
+// Door constants
+const DOOR_SIZE: Vec2 = Vec2::new(40.0, 220.0); // Too tall to jump over
+const DOOR_Y: f32 = GROUND_TOP_Y + DOOR_SIZE.y / 2.0;
+const DOOR_COLOR: Color = Color::srgb(0.3, 0.3, 0.34);
+const LOCKED_DOOR_COLOR: Color = Color::srgb(0.4, 0.32, 0.18); // Keyholes are brass
+const DOOR_REACH: f32 = 50.0; // How close the player has to walk to unlock it
+const DOOR_OPEN_TIME: f32 = 0.9; // Sinks into the ground this long
+const DOOR_SHAKE: f32 = 1.5;
+const DOOR_SHAKE_SPEED: f32 = 90.0;
+
+// Switch constants
+const SWITCH_SIZE: Vec2 = Vec2::new(14.0, 44.0);
+const SWITCH_COLOR: Color = Color::srgb(0.75, 0.6, 0.3);
+const SWITCH_PULLED_COLOR: Color = Color::srgb(0.35, 0.3, 0.22);
+const SWITCH_PULLED_TILT: f32 = 0.6; // Radians the lever leans once struck
+
+// Key item constants
+const KEY_ITEM_SIZE: Vec2 = Vec2::new(12.0, 24.0);
+const KEY_ITEM_COLOR: Color = Color::srgb(0.95, 0.8, 0.35);
+const KEY_ITEM_PICKUP_SIZE: Vec2 = Vec2::new(40.0, 80.0);
+const KEY_ITEM_BOB: f32 = 5.0;
+const KEY_ITEM_BOB_SPEED: f32 = 2.5;
+
+// Door that blocks the way until the player unlocks it
+#[derive(Component)]
+pub struct Door {
+    pub id: String,
+    pub lock: DoorLock,
+    home_x: f32,
+}
+
+// Door sinking out of the way, removed once the timer runs out
+#[derive(Component)]
+struct DoorOpening(Timer);
+
+// Lever that opens a door when struck
+#[derive(Component)]
+pub struct DoorSwitch {
+    pub door: String,
+}
+
+// Key lying in the level, picked up by walking into it
+#[derive(Component)]
+pub struct KeyItem {
+    pub id: String,
+    pub name: String,
+    home: Vec2,
+}
+
+// Doors opened and keys picked up, saved with the game at benches
+#[derive(Resource, Default, Clone)]
+pub struct DoorProgress {
+    pub opened: HashSet<String>,
+    pub keys: HashSet<String>,
+}
+
+impl DoorProgress {
+    pub fn is_open(&self, id: &str) -> bool {
+        self.opened.contains(id)
+    }
+
+    pub fn has_key(&self, id: &str) -> bool {
+        self.keys.contains(id)
+    }
+}
+
+pub struct DoorPlugin;
+
+impl Plugin for DoorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DoorProgress>()
+            .add_systems(OnEnter(InGame), sync_doors)
+            .add_systems(
+                Update,
+                sync_doors.run_if(in_state(InGame).and(resource_changed::<DoorProgress>)),
+            )
+            .add_systems(
+                Update,
+                (
+                    pick_up_key_items.after(triggers::detect_trigger_overlaps),
+                    unlock_doors,
+                    strike_switches.after(hitbox::detect_hits),
+                    open_doors,
+                    bob_key_items,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+// Spawns every door still shut and key not picked up, and starts opening the doors that
+// were unlocked since, by the player or by an imported save
+fn sync_doors(
+    mut commands: Commands,
+    registry: Res<RoomRegistry>,
+    progress: Res<DoorProgress>,
+    doors: Query<(Entity, &Door, Has<DoorOpening>)>,
+    mut switches: Query<(&DoorSwitch, &mut Sprite, &mut Transform)>,
+    key_items: Query<(Entity, &KeyItem)>,
+) {
+    for (entity, door, opening) in &doors {
+        // The way is clear from the moment it starts sinking
+        if progress.is_open(&door.id) && !opening {
+            commands
+                .entity(entity)
+                .remove::<(Solid, SightBlocker)>()
+                .insert(DoorOpening(Timer::from_seconds(
+                    DOOR_OPEN_TIME,
+                    TimerMode::Once,
+                )));
+        }
+    }
+
+    for (switch, mut sprite, mut transform) in &mut switches {
+        let (color, rotation) = switch_look(progress.is_open(&switch.door));
+        sprite.color = color;
+        transform.rotation = rotation;
+    }
+
+    for (entity, key_item) in &key_items {
+        if progress.has_key(&key_item.id) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    for room in &registry.rooms {
+        for definition in &room.doors {
+            let open = progress.is_open(&definition.id);
+            // Levers stay in the level once pulled, doors don't
+            if let DoorLock::Switch { x } = definition.lock
+                && !switches
+                    .iter()
+                    .any(|(switch, ..)| switch.door == definition.id)
+            {
+                spawn_switch(&mut commands, &definition.id, x, open);
+            }
+
+            let spawned = doors.iter().any(|(_, door, _)| door.id == definition.id);
+            if !spawned && !open {
+                spawn_door(
+                    &mut commands,
+                    &definition.id,
+                    definition.x,
+                    &definition.lock,
+                );
+            }
+        }
+
+        for definition in &room.key_items {
+            let spawned = key_items.iter().any(|(_, key)| key.id == definition.id);
+            if !spawned && !progress.has_key(&definition.id) {
+                spawn_key_item(&mut commands, definition);
+            }
+        }
+    }
+}
+
+fn spawn_door(commands: &mut Commands, id: &str, x: f32, lock: &DoorLock) {
+    commands
+        .spawn((
+            Door {
+                id: id.to_string(),
+                lock: lock.clone(),
+                home_x: x,
+            },
+            StateScoped(InGame),
+            Solid { size: DOOR_SIZE },
+            SightBlocker { size: DOOR_SIZE },
+            Sprite {
+                color: match lock {
+                    DoorLock::Key(_) => LOCKED_DOOR_COLOR,
+                    DoorLock::Switch { .. } => DOOR_COLOR,
+                },
+                custom_size: Some(DOOR_SIZE),
+                ..default()
+            },
+            // Behind the ground strip, so it disappears into it while opening
+            Transform::from_xyz(x, DOOR_Y, Layer::Terrain.z()),
+        ))
+        .with_children(|parent| {
+            // Covers both sides so the key works from either direction
+            parent.spawn((
+                TriggerZone::new(Vec2::new(DOOR_SIZE.x + DOOR_REACH * 2.0, DOOR_SIZE.y)),
+                Transform::default(),
+            ));
+        });
+}
+
+// The lever stands on the ground and stays there, leaning over once it is struck
+fn spawn_switch(commands: &mut Commands, door: &str, x: f32, pulled: bool) {
+    let (color, rotation) = switch_look(pulled);
+    commands
+        .spawn((
+            DoorSwitch {
+                door: door.to_string(),
+            },
+            StateScoped(InGame),
+            // Neither side, so the player's attacks land on it
+            Faction::Neutral,
+            Sprite {
+                color,
+                custom_size: Some(SWITCH_SIZE),
+                ..default()
+            },
+            Transform::from_xyz(x, GROUND_TOP_Y + SWITCH_SIZE.y / 2.0, Layer::Props.z())
+                .with_rotation(rotation),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                CollisionHitbox {
+                    active: true,
+                    size: SWITCH_SIZE,
+                },
+                Transform::default(),
+            ));
+        });
+}
+
+fn switch_look(pulled: bool) -> (Color, Quat) {
+    if pulled {
+        (
+            SWITCH_PULLED_COLOR,
+            Quat::from_rotation_z(-SWITCH_PULLED_TILT),
+        )
+    } else {
+        (SWITCH_COLOR, Quat::IDENTITY)
+    }
+}
+
+fn spawn_key_item(commands: &mut Commands, definition: &KeyItemDefinition) {
+    commands.spawn((
+        KeyItem {
+            id: definition.id.clone(),
+            name: definition.name.clone(),
+            home: definition.position,
+        },
+        StateScoped(InGame),
+        TriggerZone::new(KEY_ITEM_PICKUP_SIZE),
+        Sprite {
+            color: KEY_ITEM_COLOR,
+            custom_size: Some(KEY_ITEM_SIZE),
+            ..default()
+        },
+        Transform::from_translation(definition.position.extend(Layer::Pickups.z())),
+    ));
+}
+
+fn pick_up_key_items(
+    mut trigger_events: EventReader<TriggerEnteredEvent>,
+    mut progress: ResMut<DoorProgress>,
+    key_items: Query<&KeyItem>,
+    mut notifications: EventWriter<NotificationEvent>,
+) {
+    for event in trigger_events.read() {
+        let Ok(key_item) = key_items.get(event.trigger) else {
+            continue;
+        };
+        if progress.keys.insert(key_item.id.clone()) {
+            info!("Picked up {}", key_item.name);
+            notifications.send(NotificationEvent::new(format!(
+                "{} acquired",
+                key_item.name
+            )));
+        }
+    }
+}
+
+// A key door opens as soon as the player is next to it with its key
+fn unlock_doors(
+    mut progress: ResMut<DoorProgress>,
+    doors: Query<(&Door, &Children), Without<DoorOpening>>,
+    zones: Query<&TriggerZone>,
+) {
+    for (door, children) in &doors {
+        let DoorLock::Key(key) = &door.lock else {
+            continue;
+        };
+        let player_near = children
+            .iter()
+            .filter_map(|&child| zones.get(child).ok())
+            .any(|zone| zone.player_inside);
+        if player_near && progress.has_key(key) && !progress.is_open(&door.id) {
+            progress.opened.insert(door.id.clone());
+        }
+    }
+}
+
+// Only the player's side pulls levers; enemies missing the player can't open doors
+fn strike_switches(
+    mut hit_events: EventReader<HitEvent>,
+    mut progress: ResMut<DoorProgress>,
+    switches: Query<&DoorSwitch>,
+    factions: Query<&Faction>,
+) {
+    for event in hit_events.read() {
+        let Ok(switch) = switches.get(event.target) else {
+            continue;
+        };
+        if factions.get(event.attacker) != Ok(&Faction::Player) || progress.is_open(&switch.door) {
+            continue;
+        }
+
+        progress.opened.insert(switch.door.clone());
+    }
+}
+
+// The door rumbles as it sinks, and is gone once it is all the way down
+fn open_doors(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut doors: Query<(Entity, &Door, &mut DoorOpening, &mut Transform)>,
+) {
+    for (entity, door, mut opening, mut transform) in &mut doors {
+        opening.0.tick(time.delta());
+        if opening.0.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let shake = (opening.0.elapsed_secs() * DOOR_SHAKE_SPEED).sin() * DOOR_SHAKE;
+        transform.translation.x = door.home_x + shake;
+        transform.translation.y = DOOR_Y - DOOR_SIZE.y * opening.0.fraction();
+    }
+}
+
+fn bob_key_items(time: Res<Time>, mut key_items: Query<(&KeyItem, &mut Transform)>) {
+    for (key_item, mut transform) in &mut key_items {
+        transform.translation.y =
+            key_item.home.y + (time.elapsed_secs() * KEY_ITEM_BOB_SPEED).sin() * KEY_ITEM_BOB;
+    }
+}
@@ -0,0 +1,454 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::ambient::Ambient;
+use crate::enemy::{Enemy, EnemyKind, EnemySpawnPoint, RespawnRule, SpawnArea};
+use crate::game::{self, GameState, InGame};
+use crate::ground::{self, GroundArt};
+use crate::intro;
+use crate::level::{
+    self, ENEMY_SPAWN_Y, GROUND_TOP_Y, HazardDefinition, HazardKind, PLATFORM_HEIGHT, PitZone,
+    RoomDefinition, RoomRegistry, SurfaceZone,
+};
+use crate::physics::SurfaceMaterial;
+use crate::player::{self, Player};
+
+// Endless run constants
+pub const ENDLESS_ROAD_ID: &str = "endless_road";
+const ENDLESS_ROAD_NAME: &str = "The Endless Road";
+const ENDLESS_ROAD_LENGTH: f32 = 1_000_000.0; // Far more than a run ever covers
+const ENDLESS_START_OFFSET: f32 = 300.0; // Player starts this far into the road
+const ENDLESS_SAFE_CHUNKS: usize = 2; // Plain ground at the start while the player gets going
+const ENDLESS_LOOKAHEAD: f32 = 1600.0; // Chunks are laid out this far ahead of the player
+const ENDLESS_TRAIL: f32 = 1200.0; // and removed once they are this far behind
+const ENDLESS_DIFFICULTY_DISTANCE: f32 = 3000.0; // Distance covered per difficulty step
+const ENDLESS_ELITE_CHANCE_PER_STEP: f32 = 0.08;
+const ENDLESS_ENEMY_ACTIVATION_DISTANCE: f32 = 700.0;
+const CHUNK_WIDTH: f32 = 600.0;
+const SPIKES_HEIGHT: f32 = 24.0;
+const ACID_DEPTH: f32 = 30.0;
+
+// What a run is played as, picked from the main menu
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RunMode {
+    #[default]
+    Story,
+    Endless, // Generated chunks one after another, harder the further the player gets
+}
+
+// Piece of a pre-authored chunk, x measured from the chunk's left edge
+#[derive(Clone, Copy)]
+enum ChunkPiece {
+    Pit { min_x: f32, width: f32 },
+    Ledge { x: f32, y: f32, width: f32 }, // Centered on x
+    Spikes { min_x: f32, width: f32 },
+    Acid { min_x: f32, width: f32 },
+    Ice { min_x: f32, width: f32 },
+    Enemy { x: f32, kind: EnemyKind },
+}
+
+// Stretch of road as authored, picked once the run is at least this difficult
+struct ChunkTemplate {
+    difficulty: u32,
+    pieces: &'static [ChunkPiece],
+}
+
+const CHUNK_TEMPLATES: &[ChunkTemplate] = &[
+    ChunkTemplate {
+        difficulty: 0,
+        pieces: &[],
+    },
+    ChunkTemplate {
+        difficulty: 0,
+        pieces: &[ChunkPiece::Ledge {
+            x: 300.0,
+            y: -200.0,
+            width: 200.0,
+        }],
+    },
+    ChunkTemplate {
+        difficulty: 0,
+        pieces: &[ChunkPiece::Pit {
+            min_x: 230.0,
+            width: 140.0,
+        }],
+    },
+    ChunkTemplate {
+        difficulty: 1,
+        pieces: &[ChunkPiece::Spikes {
+            min_x: 220.0,
+            width: 160.0,
+        }],
+    },
+    ChunkTemplate {
+        difficulty: 1,
+        pieces: &[ChunkPiece::Enemy {
+            x: 400.0,
+            kind: EnemyKind::Skeleton,
+        }],
+    },
+    ChunkTemplate {
+        difficulty: 1,
+        pieces: &[
+            ChunkPiece::Pit {
+                min_x: 180.0,
+                width: 200.0,
+            },
+            ChunkPiece::Ledge {
+                x: 450.0,
+                y: -190.0,
+                width: 160.0,
+            },
+        ],
+    },
+    ChunkTemplate {
+        difficulty: 1,
+        pieces: &[ChunkPiece::Ice {
+            min_x: 0.0,
+            width: CHUNK_WIDTH,
+        }],
+    },
+    ChunkTemplate {
+        difficulty: 2,
+        pieces: &[
+            ChunkPiece::Spikes {
+                min_x: 150.0,
+                width: 120.0,
+            },
+            ChunkPiece::Enemy {
+                x: 450.0,
+                kind: EnemyKind::ChargingSkeleton,
+            },
+        ],
+    },
+    ChunkTemplate {
+        difficulty: 2,
+        pieces: &[
+            ChunkPiece::Acid {
+                min_x: 180.0,
+                width: 240.0,
+            },
+            ChunkPiece::Ledge {
+                x: 300.0,
+                y: -170.0,
+                width: 140.0,
+            },
+        ],
+    },
+    ChunkTemplate {
+        difficulty: 2,
+        pieces: &[ChunkPiece::Pit {
+            min_x: 190.0,
+            width: 220.0,
+        }],
+    },
+    ChunkTemplate {
+        difficulty: 3,
+        pieces: &[
+            ChunkPiece::Pit {
+                min_x: 120.0,
+                width: 180.0,
+            },
+            ChunkPiece::Enemy {
+                x: 480.0,
+                kind: EnemyKind::ShieldedSkeleton,
+            },
+        ],
+    },
+    ChunkTemplate {
+        difficulty: 3,
+        pieces: &[
+            ChunkPiece::Spikes {
+                min_x: 60.0,
+                width: 480.0,
+            },
+            ChunkPiece::Ledge {
+                x: 190.0,
+                y: -200.0,
+                width: 140.0,
+            },
+            ChunkPiece::Ledge {
+                x: 410.0,
+                y: -180.0,
+                width: 140.0,
+            },
+        ],
+    },
+];
+
+// Chunk laid out on the road, with what was spawned for it
+struct PlacedChunk {
+    max_x: f32,
+    entities: Vec<Entity>,
+}
+
+// Progress of the current endless run
+#[derive(Resource)]
+struct EndlessRun {
+    rng: StdRng,
+    start_x: f32,
+    next_x: f32, // Left edge of the next chunk to lay out
+    chunks_placed: usize,
+    chunks: VecDeque<PlacedChunk>,
+}
+
+impl Default for EndlessRun {
+    fn default() -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(0),
+            start_x: 0.0,
+            next_x: 0.0,
+            chunks_placed: 0,
+            chunks: VecDeque::new(),
+        }
+    }
+}
+
+impl EndlessRun {
+    // Steps up every so often along the road
+    fn difficulty(&self, x: f32) -> u32 {
+        ((x - self.start_x).max(0.0) / ENDLESS_DIFFICULTY_DISTANCE) as u32
+    }
+}
+
+pub struct EndlessRunPlugin;
+
+impl Plugin for EndlessRunPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunMode>()
+            .init_resource::<EndlessRun>()
+            .add_systems(
+                OnEnter(InGame),
+                start_endless_run
+                    .after(game::reset_camera)
+                    .after(player::setup_player)
+                    .before(intro::start_intro),
+            )
+            .add_systems(
+                Update,
+                extend_endless_road
+                    .run_if(in_state(GameState::Playing).and(resource_equals(RunMode::Endless))),
+            );
+    }
+}
+
+// Empty road past the end of the world; its ground, pits and surfaces are filled in as
+// the run goes
+pub fn road_room(min_x: f32) -> RoomDefinition {
+    RoomDefinition::strip(
+        ENDLESS_ROAD_ID,
+        ENDLESS_ROAD_NAME,
+        Some(ENDLESS_ROAD_NAME),
+        min_x,
+        min_x + ENDLESS_ROAD_LENGTH,
+    )
+    .with_own_ground()
+    // Long runs see the day go by
+    .with_ambient(Ambient::DayNight)
+}
+
+// Clears what the last run left on the road, then puts the player at its start when the
+// run is an endless one
+fn start_endless_run(
+    mode: Res<RunMode>,
+    mut run: ResMut<EndlessRun>,
+    mut registry: ResMut<RoomRegistry>,
+    mut players: Query<&mut Transform, With<Player>>,
+    mut cameras: Query<&mut Transform, (With<Camera2d>, Without<Player>)>,
+) {
+    let Some(road) = registry
+        .rooms
+        .iter_mut()
+        .find(|room| room.id == ENDLESS_ROAD_ID)
+    else {
+        return;
+    };
+    road.pits.clear();
+    road.surfaces.clear();
+    let start_x = road.bounds.min.x;
+
+    let seed = rand::random();
+    *run = EndlessRun {
+        rng: StdRng::seed_from_u64(seed),
+        start_x,
+        next_x: start_x,
+        chunks_placed: 0,
+        chunks: VecDeque::new(),
+    };
+
+    if *mode != RunMode::Endless {
+        return;
+    }
+    info!("Endless run seed: {seed}");
+
+    let player_x = start_x + ENDLESS_START_OFFSET;
+    for mut transform in &mut players {
+        transform.translation.x = player_x;
+    }
+    for mut transform in &mut cameras {
+        transform.translation.x = player_x;
+    }
+}
+
+// Lays chunks out ahead of the player and removes the ones left far enough behind
+fn extend_endless_road(
+    mut commands: Commands,
+    mut run: ResMut<EndlessRun>,
+    mut registry: ResMut<RoomRegistry>,
+    ground_art: Res<GroundArt>,
+    players: Query<&Transform, With<Player>>,
+    spawn_points: Query<&EnemySpawnPoint>,
+    enemies: Query<(), With<Enemy>>,
+) {
+    let Ok(player_transform) = players.get_single() else {
+        return;
+    };
+    let player_x = player_transform.translation.x;
+    let Some(road) = registry
+        .rooms
+        .iter_mut()
+        .find(|room| room.id == ENDLESS_ROAD_ID)
+    else {
+        return;
+    };
+
+    while run.next_x < player_x + ENDLESS_LOOKAHEAD {
+        let difficulty = run.difficulty(run.next_x);
+        let template = if run.chunks_placed < ENDLESS_SAFE_CHUNKS {
+            &CHUNK_TEMPLATES[0]
+        } else {
+            pick_template(&mut run.rng, difficulty)
+        };
+        let min_x = run.next_x;
+        let entities = place_chunk(
+            &mut commands,
+            &ground_art,
+            road,
+            template,
+            min_x,
+            difficulty,
+        );
+        run.chunks.push_back(PlacedChunk {
+            max_x: min_x + CHUNK_WIDTH,
+            entities,
+        });
+        run.next_x += CHUNK_WIDTH;
+        run.chunks_placed += 1;
+    }
+
+    while let Some(chunk) = run.chunks.front() {
+        if chunk.max_x > player_x - ENDLESS_TRAIL {
+            break;
+        }
+        let chunk_end = chunk.max_x;
+        for &entity in &chunk.entities {
+            // Enemies from the chunk go with it, unless they were already killed
+            if let Ok(spawn_point) = spawn_points.get(entity)
+                && let Some(enemy) = spawn_point.spawned_enemy
+                && enemies.contains(enemy)
+            {
+                commands.entity(enemy).despawn_recursive();
+            }
+            commands.entity(entity).despawn_recursive();
+        }
+        road.pits.retain(|pit| pit.max_x > chunk_end);
+        road.surfaces.retain(|surface| surface.max_x > chunk_end);
+        run.chunks.pop_front();
+    }
+}
+
+// Mostly chunks from the run's current difficulty, with easier ones mixed in
+fn pick_template(rng: &mut StdRng, difficulty: u32) -> &'static ChunkTemplate {
+    let hardest = CHUNK_TEMPLATES
+        .iter()
+        .map(|template| template.difficulty)
+        .max()
+        .unwrap_or(0)
+        .min(difficulty);
+    let candidates: Vec<&ChunkTemplate> = CHUNK_TEMPLATES
+        .iter()
+        .filter(|template| template.difficulty + 1 >= hardest && template.difficulty <= hardest)
+        .collect();
+    candidates[rng.gen_range(0..candidates.len())]
+}
+
+fn place_chunk(
+    commands: &mut Commands,
+    ground_art: &GroundArt,
+    road: &mut RoomDefinition,
+    template: &ChunkTemplate,
+    min_x: f32,
+    difficulty: u32,
+) -> Vec<Entity> {
+    let mut entities = Vec::new();
+    for piece in template.pieces {
+        match *piece {
+            ChunkPiece::Pit { min_x: x, width } => road.pits.push(PitZone {
+                min_x: min_x + x,
+                max_x: min_x + x + width,
+            }),
+            ChunkPiece::Ice { min_x: x, width } => road.surfaces.push(SurfaceZone {
+                min_x: min_x + x,
+                max_x: min_x + x + width,
+                material: SurfaceMaterial::Ice,
+            }),
+            ChunkPiece::Ledge { x, y, width } => entities.push(level::spawn_terrain(
+                commands,
+                Vec2::new(min_x + x, y),
+                Vec2::new(width, PLATFORM_HEIGHT),
+            )),
+            ChunkPiece::Spikes { min_x: x, width } => entities.push(level::spawn_hazard(
+                commands,
+                &HazardDefinition {
+                    area: Rect::new(
+                        min_x + x,
+                        GROUND_TOP_Y,
+                        min_x + x + width,
+                        GROUND_TOP_Y + SPIKES_HEIGHT,
+                    ),
+                    kind: HazardKind::Spikes,
+                },
+            )),
+            ChunkPiece::Acid { min_x: x, width } => entities.push(level::spawn_hazard(
+                commands,
+                &HazardDefinition {
+                    area: Rect::new(
+                        min_x + x,
+                        GROUND_TOP_Y,
+                        min_x + x + width,
+                        GROUND_TOP_Y + ACID_DEPTH,
+                    ),
+                    kind: HazardKind::Acid,
+                },
+            )),
+            ChunkPiece::Enemy { x, kind } => entities.push(
+                commands
+                    .spawn((
+                        EnemySpawnPoint::new(
+                            kind,
+                            RespawnRule::Never,
+                            ENDLESS_ENEMY_ACTIVATION_DISTANCE,
+                            difficulty as f32 * ENDLESS_ELITE_CHANCE_PER_STEP,
+                            SpawnArea::AT_SPAWNER,
+                        ),
+                        StateScoped(InGame),
+                        Transform::from_xyz(min_x + x, ENEMY_SPAWN_Y, 0.0),
+                    ))
+                    .id(),
+            ),
+        }
+    }
+
+    let max_x = min_x + CHUNK_WIDTH;
+    for (span_min_x, span_max_x, material) in
+        level::strip_ground_spans(min_x, max_x, &road.pits, &road.surfaces)
+    {
+        entities.push(ground::spawn_ground_span(
+            commands, ground_art, span_min_x, span_max_x, material,
+        ));
+    }
+    entities
+}
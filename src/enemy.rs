@@ -1,77 +1,186 @@
+use crate::animation_manifest::{AnimationSet, AttackDefinitions, CharacterAssetCache};
 use crate::animations::{
-    AnimationController, AnimationData, CharacterAnimations, CharacterState, CurrentAnimation,
+    self, AnimationController, AnimationFinishedEvent, CharacterDimensions, CharacterState,
+    CurrentAnimation, Facing, FacingOffset,
 };
-use crate::game::GameState;
-use crate::ground::ground_collision;
-use crate::physics::Physics;
+use crate::audio::{SfxEvent, SoundEffect};
+use crate::camera_shake::CameraShake;
+use crate::config::{EnemyConfig, GameConfig};
+use crate::game::{GameState, InGame};
+use crate::hazards::DEATH_BARRIER_Y;
+use crate::hitbox::{
+    self, AttackHitbox, AttackSet, AttackWindow, CollisionHitbox, DamageEvent, DeathEvent, Faction,
+    HitEvent, HitReaction, Knockback, KnockbackEvent, ManagedHitbox,
+};
+use crate::layers::Layer;
+use crate::level::{GROUND_TOP_Y, RoomRegistry};
+use crate::physics::{self, BodyCollider, Physics, Solid, SurfaceContact};
+use crate::pickups::LootTable;
 use crate::player::Player;
 use crate::resolution;
+use crate::status_effects::{StatusEffectKind, StatusEffects};
 use crate::utils;
 use bevy::prelude::*;
 use bevy::sprite::Anchor;
+use bevy::utils::HashSet;
+use rand::Rng;
+use std::time::Duration;
 
 // Constants
-const ENEMY_INITIAL_HEALTH: f32 = 200.0;
-const ENEMY_MAX_HEALTH: f32 = 50.0;
-const ENEMY_ATTACK: f32 = 10.0;
-const ENEMY_DEFENSE: f32 = 5.0;
-const ENEMY_SPEED: f32 = 150.0;
-const ENEMY_ATTACK_RANGE: f32 = 146.0;
-const ENEMY_DETECTION_RANGE: f32 = 400.0;
+const ENEMY_EYE_HEIGHT: f32 = 30.0; // Sight line starts above the feet so low ledges don't blind it
+const ENEMY_SEARCH_DURATION: f32 = 3.0; // How long to look around the last known position
+const ENEMY_SEARCH_REACHED_DISTANCE: f32 = 12.0;
+const ENEMY_SEARCH_SPEED_FACTOR: f32 = 0.6; // Walk instead of run while searching
+const ENEMY_PATROL_RANGE: f32 = 150.0; // Distance walked to each side of the spawn point
+const ENEMY_PATROL_SPEED_FACTOR: f32 = 0.4;
+const ENEMY_PATROL_PAUSE: f32 = 1.5; // Time spent looking around at each end of the patrol
+const ENEMY_SPAWN_ATTEMPTS: usize = 8; // Candidate positions tried per frame before waiting
+const ENEMY_SPAWN_MIN_PLAYER_DISTANCE: f32 = 300.0;
+const ENEMY_SPAWN_SCREEN_MARGIN: f32 = 64.0; // Spawn this far past the edge of the view
+const ENEMY_LEASH_INVULNERABILITY: f32 = 1.5; // Can't be hit right after disengaging
+const ENEMY_LEASH_REGEN: f32 = 40.0; // Health per second while walking back
+const ENEMY_RETURN_SPEED_FACTOR: f32 = 0.8;
 const ENEMY_COLLISION_SIZE: Vec2 = Vec2::new(32.0, 32.0);
 const ENEMY_ATTACK_HITBOX_SIZE: Vec2 = Vec2::new(73.0, 30.0);
-const ENEMY_CHARGE_ATTACK_HITBOX_SIZE: Vec2 = Vec2::new(78.0, 30.0);
-const ENEMY_ATTACK_HITBOX_DURATION: f32 = 0.1;
+const ENEMY_ATTACK_HITBOX_DURATION: f32 = 0.05;
 const ENEMY_ATTACK_HITBOX_OFFSET: f32 = 0.6;
 const ENEMY_DEATH_TIMER: f32 = 3.0;
-const ENEMY_HURT_TIMER: f32 = 0.3;
-const ENEMY_DESIRED_COUNT: usize = 1;
-const ENEMY_SPAWN_OFFSET_X: f32 = 450.0; // Increased for better visibility from camera
-const ENEMY_SPAWN_OFFSET_Y: f32 = 90.0;
 const ENEMY_SCALE_FACTOR: f32 = 2.0;
 const ENEMY_FEET_OFFSET: f32 = 0.5;
-
-// Animation Constants
-const ENEMY_IDLE_FRAMES: usize = 8;
-const ENEMY_ATTACK_FRAMES: usize = 23;
-const ENEMY_MOVE_FRAMES: usize = 10;
-const ENEMY_HURT_FRAMES: usize = 3;
-const ENEMY_DIE_FRAMES: usize = 24;
-
-const ENEMY_IDLE_FPS: f32 = 14.0;
-const ENEMY_ATTACK_FPS: f32 = 14.0;
-const ENEMY_MOVE_FPS: f32 = 14.0;
-const ENEMY_HURT_FPS: f32 = 10.0;
-const ENEMY_DIE_FPS: f32 = 14.0;
+const ENEMY_WEIGHT: f32 = 1.0;
+const ENEMY_LAUNCH_MAX_WEIGHT: f32 = 1.5; // Heavier enemies can't be launched
+const ENEMY_LAUNCH_KNOCKBACK: f32 = 80.0;
+const ENEMY_AIR_HIT_LIFT: f32 = 260.0;
+const ENEMY_AIR_HIT_KNOCKBACK: f32 = 60.0;
+const ENEMY_JUGGLE_KNOCKBACK_DECAY: f32 = 1.5; // Keeps drifting while in the air
+// Shielded variant: slower and too heavy to launch, blocks hits from the front
+const SHIELDED_ENEMY_HEALTH: f32 = 260.0;
+const SHIELDED_ENEMY_SPEED: f32 = 110.0;
+const SHIELDED_ENEMY_WEIGHT: f32 = 2.0;
+const SHIELD_SIZE: Vec2 = Vec2::new(6.0, 30.0); // Local units, scaled with the enemy
+const SHIELD_OFFSET_X: f32 = 14.0; // In front of the skeleton
+const SHIELD_COLOR: Color = Color::srgb(0.55, 0.6, 0.68);
+const SHIELD_BLOCK_COOLDOWN: f32 = 0.25; // One spark and clank per swing, not per frame
+const SHIELD_SPARK_SIZE: Vec2 = Vec2::new(14.0, 14.0);
+const SHIELD_SPARK_COLOR: Color = Color::srgb(1.0, 0.95, 0.6);
+const SHIELD_SPARK_DURATION: f32 = 0.12;
+// Charging variant: telegraphs, then rushes horizontally and gets stunned by walls
+const CHARGING_ENEMY_HEALTH: f32 = 220.0;
+const CHARGING_ENEMY_SPEED: f32 = 130.0;
+const CHARGING_ENEMY_WEIGHT: f32 = 1.8;
+const CHARGE_TRIGGER_RANGE: f32 = 380.0;
+const CHARGE_MAX_HEIGHT_DIFFERENCE: f32 = 80.0; // Only charge at a player on roughly the same level
+const CHARGE_TELEGRAPH_DURATION: f32 = 0.6;
+const CHARGE_DURATION: f32 = 1.2;
+const CHARGE_SPEED: f32 = 650.0;
+const CHARGE_DAMAGE: f32 = 20.0;
+const CHARGE_COOLDOWN: f32 = 2.5;
+const CHARGE_STUN_DURATION: f32 = 1.6;
+const CHARGE_WALL_PROBE: f32 = 6.0; // How far ahead of the body a wall stops the charge
+const CHARGE_WALL_BOUNCE: Vec2 = Vec2::new(180.0, 220.0);
+const CHARGE_WALL_SLAM_TRAUMA: f32 = 0.35; // Camera shake when a charge slams into a wall
+const CHARGE_TELEGRAPH_COLOR: Color = Color::srgb(1.0, 0.45, 0.3);
+const CHARGE_TELEGRAPH_FLASH_SPEED: f32 = 25.0;
+const CHARGE_STUN_COLOR: Color = Color::srgb(0.6, 0.65, 0.85);
+const ENEMY_SEPARATION_SPEED: f32 = 120.0; // Push apart speed when two enemies fully overlap
+const ENEMY_BODY_SIZE: Vec2 = Vec2::new(30.0, 64.0); // Local units; bottom matches the feet offset
+// Elite variant: bigger, tinted, tougher and with better drops
+const ELITE_SCALE: f32 = 1.3;
+const ELITE_HEALTH_MULTIPLIER: f32 = 2.0;
+const ELITE_DAMAGE_MULTIPLIER: f32 = 1.5;
+const ELITE_LOOT_MULTIPLIER: f32 = 2.5;
+const ELITE_TINT: Color = Color::srgb(1.0, 0.55, 0.5);
+const ENEMY_LOOT: LootTable = LootTable {
+    currency_drops: (3, 6),
+    currency_value: 1.0,
+    soul_drops: 2,
+    soul_value: 5.5,
+    health_chance: 0.1,
+    health_value: 20.0,
+    barrier_chance: 0.05,
+};
 
 // Enemy component
 #[derive(Component)]
 pub struct Enemy {
+    pub kind: EnemyKind,
+    pub variant: EnemyVariant,
+    pub tint: Color, // Base sprite color, restored after flashes
     pub health: f32,
     pub max_health: f32,
     pub attack: f32,
+    pub contact_damage: f32,
     pub defense: f32,
     pub speed: f32,
     pub attack_range: f32,
     pub detection_range: f32,
-    pub facing_right: bool,
     pub is_dead: bool,
     pub death_timer: Timer,
     pub hurt_timer: Timer,
+    pub weight: f32,
+    pub juggled: bool, // Launched into the air and not landed yet
+    pub loot: LootTable,
+}
+
+// Sent the frame an enemy is killed by damage
+#[derive(Event)]
+pub struct EnemyDefeatedEvent {
+    pub entity: Entity,
+    pub position: Vec2,
+    pub loot: LootTable,
 }
 
-// Attack hitbox component
+// What the enemy is currently doing about the player
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnemyAiState {
+    Patrolling,
+    Chasing,
+    Searching,
+    Returning,
+    Telegraphing,
+    Charging,
+    Stunned,
+}
+
+// Where the player was last seen before breaking line of sight
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LastKnownPlayerPos(pub Vec2);
+
+// Perception memory: chase while the player is visible, search where they were last seen
+// until the de-aggro timer runs out, then go back to patrolling around home.
+// Straying too far from home breaks off the chase altogether
 #[derive(Component)]
-pub struct AttackHitbox {
-    pub damage: f32,
-    pub active: bool,
-    pub size: Vec2,
-    pub timer: Timer,
+pub struct EnemyAi {
+    pub state: EnemyAiState,
+    pub last_known_player_pos: Option<LastKnownPlayerPos>,
+    pub search_timer: Timer,
+    pub home_x: f32,
+    pub patrol_direction: f32,
+    pub patrol_pause: Timer,
+    pub leash_invulnerability: Timer,
+}
+
+impl EnemyAi {
+    pub fn new(home_x: f32) -> Self {
+        Self {
+            state: EnemyAiState::Patrolling,
+            last_known_player_pos: None,
+            search_timer: Timer::from_seconds(ENEMY_SEARCH_DURATION, TimerMode::Once),
+            home_x,
+            patrol_direction: 1.0,
+            patrol_pause: Timer::from_seconds(ENEMY_PATROL_PAUSE, TimerMode::Once),
+            leash_invulnerability: Timer::from_seconds(0.0, TimerMode::Once),
+        }
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        !self.leash_invulnerability.finished()
+    }
 }
 
+// Blocks enemy line of sight (walls, pillars, blocks)
 #[derive(Component)]
-pub struct CollisionHitbox {
-    pub active: bool,
+pub struct SightBlocker {
     pub size: Vec2,
 }
 
@@ -80,193 +189,211 @@ struct PlayerPosition {
     position: Vec3,
 }
 
-#[derive(Resource)]
-pub struct EnemyCounter {
-    pub current_count: usize,
-    pub desired_count: usize,
-    pub initial_spawn_done: bool, // Track if initial spawn has been done
+// Kind of enemy a spawn point produces
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnemyKind {
+    Skeleton,
+    ShieldedSkeleton,
+    ChargingSkeleton,
 }
 
-impl Default for EnemyCounter {
+// Wind-up, rush and stun timings for enemies that charge at the player
+#[derive(Component)]
+pub struct ChargeAttack {
+    pub telegraph: Timer,
+    pub duration: Timer,
+    pub stun: Timer,
+    pub cooldown: Timer,
+    pub direction: f32,
+    pub hitbox: Option<Entity>,
+}
+
+impl Default for ChargeAttack {
     fn default() -> Self {
+        let mut cooldown = Timer::from_seconds(CHARGE_COOLDOWN, TimerMode::Once);
+        cooldown.tick(cooldown.duration()); // Ready as soon as the player shows up
+
         Self {
-            current_count: 0,
-            desired_count: ENEMY_DESIRED_COUNT,
-            initial_spawn_done: false,
+            telegraph: Timer::from_seconds(CHARGE_TELEGRAPH_DURATION, TimerMode::Once),
+            duration: Timer::from_seconds(CHARGE_DURATION, TimerMode::Once),
+            stun: Timer::from_seconds(CHARGE_STUN_DURATION, TimerMode::Once),
+            cooldown,
+            direction: 1.0,
+            hitbox: None,
         }
     }
 }
 
-pub struct EnemyPlugin;
+// Negates hits coming from the side the enemy is facing
+#[derive(Component)]
+pub struct EnemyShield {
+    pub block_cooldown: Timer,
+}
 
-impl Plugin for EnemyPlugin {
-    fn build(&self, app: &mut App) {
-        app.init_resource::<PlayerPosition>()
-            .init_resource::<EnemyCounter>()
-            // Remove the startup system and handle initial spawning in first update
-            .add_systems(
-                Update,
-                (
-                    initial_enemy_spawn, // Add a new system for initial spawn
-                    update_player_position,
-                    update_enemy_movement,
-                    update_enemy_animations,
-                    handle_damage,
-                    check_death,
-                    cleanup_dead_enemies,
-                    respawn_enemies,
-                    update_enemy_states,
-                    update_attack_hitbox,
-                )
-                    .after(ground_collision)
-                    .run_if(in_state(GameState::Playing)),
-            );
-    }
+// Short flash where a blocked hit landed
+#[derive(Component)]
+struct ShieldSpark {
+    timer: Timer,
 }
 
-// New system for initial enemy spawn that runs only once when camera is available
-fn initial_enemy_spawn(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-    resolution: Res<resolution::Resolution>,
-    windows: Query<&Window>,
-    mut enemy_counter: ResMut<EnemyCounter>,
-    // mut meshes: ResMut<Assets<Mesh>>,
-    // mut materials: ResMut<Assets<ColorMaterial>>,
-    camera_query: Query<&Transform, With<Camera2d>>,
-) {
-    // Only run this system if we haven't spawned initial enemies yet
-    if enemy_counter.initial_spawn_done {
-        return;
-    }
+// Stat tier rolled when an enemy spawns
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum EnemyVariant {
+    #[default]
+    Normal,
+    Elite,
+}
 
-    // Check if camera is available
-    if camera_query.is_empty() {
-        return; // No camera yet, try again next frame
-    }
+// What a spawn point does once the enemy it spawned is gone
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RespawnRule {
+    Never,
+    AfterDelay(f32),
+}
 
-    // Camera is available, spawn initial enemies
-    for _ in 0..enemy_counter.desired_count {
-        spawn_enemy(
-            &mut commands,
-            &asset_server,
-            &camera_query,
-            &mut texture_atlas_layouts,
-            &resolution,
-            &windows,
-            // &mut meshes,
-            // &mut materials,
-        );
-        enemy_counter.current_count += 1;
-    }
+// What a spawn area is measured from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpawnAnchor {
+    Spawner,
+    Camera,
+}
 
-    // Mark initial spawn as complete
-    enemy_counter.initial_spawn_done = true;
+// Which side of the anchor enemies may appear on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpawnSide {
+    Either,
+    Left,
+    Right,
 }
 
-fn update_attack_hitbox(
-    mut commands: Commands,
-    time: Res<Time>,
-    mut query: Query<(
-        Entity,
-        &AnimationController,
-        &Transform,
-        &Enemy,
-        &CurrentAnimation,
-    )>,
-    mut hitbox_query: Query<(Entity, &Parent, &mut AttackHitbox), Without<Enemy>>,
-    // mut meshes: ResMut<Assets<Mesh>>,
-    // mut materials: ResMut<Assets<ColorMaterial>>,
-) {
-    // Update timers and remove expired hitboxes
-    for (hitbox_entity, _parent, mut hitbox) in &mut hitbox_query {
-        hitbox.timer.tick(time.delta());
+// Horizontal band around the anchor where a spawn point may place its enemy
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpawnArea {
+    pub anchor: SpawnAnchor,
+    pub side: SpawnSide,
+    pub min_offset: f32,
+    pub max_offset: f32,
+}
+
+impl SpawnArea {
+    // Exactly on the spawn point, as placed in the level
+    pub const AT_SPAWNER: SpawnArea = SpawnArea {
+        anchor: SpawnAnchor::Spawner,
+        side: SpawnSide::Either,
+        min_offset: 0.0,
+        max_offset: 0.0,
+    };
 
-        if hitbox.timer.finished() {
-            hitbox.active = false;
-            commands.entity(hitbox_entity).despawn();
+    pub fn around_camera(side: SpawnSide, min_offset: f32, max_offset: f32) -> Self {
+        Self {
+            anchor: SpawnAnchor::Camera,
+            side,
+            min_offset,
+            max_offset,
         }
     }
 
-    for (entity, animation_controller, _transform, player, current_animation) in &mut query {
-        let current_state = animation_controller.get_current_state();
+    fn random_x(&self, anchor_x: f32, rng: &mut impl Rng) -> f32 {
+        let direction = match self.side {
+            SpawnSide::Left => -1.0,
+            SpawnSide::Right => 1.0,
+            SpawnSide::Either if rng.gen_bool(0.5) => -1.0,
+            SpawnSide::Either => 1.0,
+        };
+        let offset = if self.max_offset > self.min_offset {
+            rng.gen_range(self.min_offset..=self.max_offset)
+        } else {
+            self.min_offset
+        };
 
-        let is_attacking = matches!(
-            current_state,
-            CharacterState::Attacking | CharacterState::ChargeAttacking
-        );
+        anchor_x + direction * offset
+    }
+}
 
-        // Check if an active hitbox already exists
-        let has_active_hitbox = hitbox_query
-            .iter()
-            .any(|(_, parent, hitbox)| parent.get() == entity && hitbox.active);
+// Level-placed spawner that keeps at most one enemy alive at a time
+#[derive(Component)]
+pub struct EnemySpawnPoint {
+    pub kind: EnemyKind,
+    pub respawn: RespawnRule,
+    pub activation_distance: f32,
+    pub elite_chance: f32,
+    pub area: SpawnArea,
+    pub spawned_enemy: Option<Entity>,
+    pub respawn_timer: Option<Timer>,
+    pub exhausted: bool,
+}
 
-        // Remove old hitboxes if no longer attacking
-        if !is_attacking {
-            for (hitbox_entity, parent, _) in hitbox_query.iter() {
-                if parent.get() == entity {
-                    commands.entity(hitbox_entity).despawn();
-                }
-            }
-            continue;
+impl EnemySpawnPoint {
+    pub fn new(
+        kind: EnemyKind,
+        respawn: RespawnRule,
+        activation_distance: f32,
+        elite_chance: f32,
+        area: SpawnArea,
+    ) -> Self {
+        Self {
+            kind,
+            respawn,
+            activation_distance,
+            elite_chance,
+            area,
+            spawned_enemy: None,
+            respawn_timer: None,
+            exhausted: false,
         }
+    }
+}
 
-        // Only create new hitbox if none active and it's the start of the attack
-        if is_attacking && !has_active_hitbox {
-            let should_create_hitbox = match current_animation.current_frame {
-                4 => true,      // First attack
-                13..16 => true, // Second attack (charged)
-                _ => false,
-            };
-
-            if should_create_hitbox {
-                let damage = if current_state == CharacterState::Attacking {
-                    player.attack
-                } else {
-                    player.attack * 2.0
-                };
+pub struct EnemyPlugin;
 
-                let hitbox_size = if current_state == CharacterState::Attacking {
-                    ENEMY_ATTACK_HITBOX_SIZE
-                } else {
-                    ENEMY_CHARGE_ATTACK_HITBOX_SIZE
-                };
-                let offset_x = hitbox_size.x * ENEMY_ATTACK_HITBOX_OFFSET;
-
-                // Create child entity for hitbox
-                commands.entity(entity).with_children(|parent| {
-                    parent.spawn((
-                        AttackHitbox {
-                            damage,
-                            active: true,
-                            size: hitbox_size,
-                            timer: Timer::from_seconds(
-                                ENEMY_ATTACK_HITBOX_DURATION,
-                                TimerMode::Once,
-                            ),
-                        },
-                        Transform::from_translation(Vec3::new(-offset_x, 0., 0.)),
-                        // Mesh2d(meshes.add(Rectangle::from_size(hitbox_size))),
-                        // MeshMaterial2d(materials.add(Color::Srgba(Srgba {
-                        //     red: 200.,
-                        //     green: 200.,
-                        //     blue: 0.,
-                        //     alpha: 0.1,
-                        // }))),
-                    ));
-                });
-            }
-        }
+impl Plugin for EnemyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerPosition>()
+            .add_event::<EnemyDefeatedEvent>()
+            .add_systems(
+                Update,
+                (
+                    update_player_position,
+                    update_enemy_spawn_points.after(update_player_position),
+                    update_enemy_movement.before(physics::apply_surface_traction),
+                    update_enemy_animations,
+                    finish_enemy_attacks.after(animations::animate_current_state),
+                    apply_hits_to_enemies
+                        .after(hitbox::detect_hits)
+                        .before(hitbox::apply_knockback),
+                    take_enemy_damage.after(apply_hits_to_enemies),
+                    check_death.after(take_enemy_damage),
+                    cleanup_dead_enemies,
+                    update_enemy_states,
+                    update_shields,
+                    update_enemy_charges
+                        .after(update_enemy_movement)
+                        .before(physics::apply_surface_traction),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                apply_enemy_attack_definitions.run_if(resource_changed::<AttackDefinitions>),
+            );
     }
 }
 
 fn update_enemy_states(
     time: Res<Time>,
-    mut enemies: Query<(&mut Enemy, &mut AnimationController)>,
+    mut enemies: Query<(&mut Enemy, &mut AnimationController, &Physics)>,
 ) {
-    for (mut enemy, mut animation_controller) in &mut enemies {
-        if animation_controller.get_current_state() == CharacterState::Hurt {
+    for (mut enemy, mut animation_controller, physics) in &mut enemies {
+        if animation_controller.get_current_state().is_hurt() {
+            // Juggled enemies stay hurt until they touch the ground again
+            if enemy.juggled {
+                if physics.on_ground && physics.velocity.y <= 0.0 {
+                    enemy.juggled = false;
+                    enemy.hurt_timer.reset();
+                }
+                continue;
+            }
+
             enemy.hurt_timer.tick(time.delta());
 
             if enemy.hurt_timer.finished() {
@@ -291,30 +418,105 @@ fn update_player_position(
 }
 
 fn can_enemy_move(state: &CharacterState) -> bool {
-    match state {
-        CharacterState::Attacking | CharacterState::ChargeAttacking | CharacterState::Hurt => false,
-        _ => true,
+    !matches!(
+        state,
+        CharacterState::Attacking | CharacterState::ChargeAttacking
+    ) && !state.is_hurt()
+}
+
+fn has_line_of_sight(
+    from: Vec2,
+    to: Vec2,
+    blockers: &Query<(&GlobalTransform, &SightBlocker)>,
+) -> bool {
+    utils::line_of_sight(
+        from,
+        to,
+        blockers
+            .iter()
+            .map(|(transform, blocker)| (transform.translation().truncate(), blocker.size)),
+    )
+}
+
+fn face_towards(facing: &mut Facing, transform: &Transform, target_x: f32) {
+    *facing = Facing::towards(transform.translation.x, target_x);
+}
+
+// Soft push away from overlapping enemy hitboxes, stronger the deeper the overlap
+fn separation_velocity(
+    entity: Entity,
+    position: Vec2,
+    size: Vec2,
+    others: &[(Entity, Vec2, Vec2)],
+) -> f32 {
+    let mut velocity = 0.0;
+
+    for &(other, other_position, other_size) in others {
+        if other == entity {
+            continue;
+        }
+
+        let delta = position - other_position;
+        let combined = (size + other_size) / 2.0;
+        if delta.x.abs() >= combined.x || delta.y.abs() >= combined.y {
+            continue;
+        }
+
+        // Perfectly stacked enemies split by entity order so they don't both move the same way
+        let direction = if delta.x != 0.0 {
+            delta.x.signum()
+        } else if entity.index() < other.index() {
+            -1.0
+        } else {
+            1.0
+        };
+        let overlap = 1.0 - delta.x.abs() / combined.x;
+        velocity += direction * overlap * ENEMY_SEPARATION_SPEED;
     }
+
+    velocity.clamp(-ENEMY_SEPARATION_SPEED, ENEMY_SEPARATION_SPEED)
 }
 
+#[allow(clippy::type_complexity)]
 fn update_enemy_movement(
+    time: Res<Time>,
+    config: Res<GameConfig>,
     mut query: Query<(
         Entity,
         &mut Enemy,
-        &mut Transform,
+        &mut EnemyAi,
+        &Transform,
+        &mut Facing,
         &mut Physics,
         &mut AnimationController,
-        &mut CharacterAnimations,
+        Option<&StatusEffects>,
     )>,
+    enemy_hitboxes: Query<(&CollisionHitbox, &GlobalTransform, &Parent)>,
+    blockers: Query<(&GlobalTransform, &SightBlocker)>,
     player_position: Res<PlayerPosition>,
 ) {
+    // Hitboxes of every living enemy, used to keep them from stacking
+    let hitboxes: Vec<(Entity, Vec2, Vec2)> = enemy_hitboxes
+        .iter()
+        .filter_map(|(hitbox, transform, parent)| {
+            let (_, enemy, ..) = query.get(parent.get()).ok()?;
+            (hitbox.active && !enemy.is_dead).then_some((
+                parent.get(),
+                transform.translation().truncate(),
+                hitbox.size,
+            ))
+        })
+        .collect();
+
     for (
-        _entity,
+        entity,
         mut enemy,
-        mut transform,
+        mut ai,
+        transform,
+        mut facing,
         mut physics,
         mut animation_controller,
-        mut _animations,
+        status_effects,
     ) in &mut query
     {
         if enemy.is_dead || animation_controller.get_current_state() == CharacterState::Dead {
@@ -322,49 +524,302 @@ fn update_enemy_movement(
             continue;
         }
 
+        ai.leash_invulnerability.tick(time.delta());
+        let speed = enemy.speed * status_effects.map_or(1.0, StatusEffects::speed_factor);
+
+        // Let gravity and the launch impulse play out while airborne
+        if enemy.juggled {
+            continue;
+        }
+
+        // Charges are driven by update_enemy_charges until they end
+        if matches!(
+            ai.state,
+            EnemyAiState::Telegraphing | EnemyAiState::Charging | EnemyAiState::Stunned
+        ) {
+            continue;
+        }
+
         let enemy_pos = transform.translation.truncate();
         let player_pos = player_position.position.truncate();
         let distance = utils::distance_between_points(enemy_pos, player_pos);
         let current_state = animation_controller.get_current_state();
 
-        // If player is within detection range
-        if distance < enemy.detection_range {
-            // Determine direction enemy should face
-            let old_facing = enemy.facing_right;
-            enemy.facing_right = player_position.position.x > transform.translation.x;
-
-            // Only update scale if direction changed
-            if old_facing != enemy.facing_right {
-                let scale_magnitude = transform.scale.x.abs();
-                transform.scale.x = if enemy.facing_right {
-                    -scale_magnitude
+        // The player is seen when in range and nothing blocks the sight line
+        let eye_pos = enemy_pos + Vec2::Y * ENEMY_EYE_HEIGHT;
+        let sees_player =
+            distance < enemy.detection_range && has_line_of_sight(eye_pos, player_pos, &blockers);
+
+        if matches!(ai.state, EnemyAiState::Chasing | EnemyAiState::Searching)
+            && (enemy_pos.x - ai.home_x).abs() > config.enemy.leash_radius
+        {
+            // Too far from home: disengage and walk back
+            ai.state = EnemyAiState::Returning;
+            ai.last_known_player_pos = None;
+            ai.leash_invulnerability =
+                Timer::from_seconds(ENEMY_LEASH_INVULNERABILITY, TimerMode::Once);
+        } else if ai.state == EnemyAiState::Returning {
+            // Ignores the player until it is back home
+        } else if sees_player {
+            ai.state = EnemyAiState::Chasing;
+            ai.last_known_player_pos = Some(LastKnownPlayerPos(player_pos));
+        } else if ai.state == EnemyAiState::Chasing {
+            // Lost the player, go check where they were last seen
+            ai.state = EnemyAiState::Searching;
+            ai.search_timer.reset();
+        }
+
+        match ai.state {
+            EnemyAiState::Chasing => {
+                face_towards(&mut facing, transform, player_pos.x);
+
+                // If within attack range
+                if distance < enemy.attack_range {
+                    // Stop movement and attack
+                    physics.velocity.x = 0.0;
+                    if can_enemy_move(&current_state) {
+                        animation_controller.change_state(CharacterState::Attacking);
+                    }
+                } else if can_enemy_move(&current_state) {
+                    // Move toward player only if able to move
+                    let direction = utils::direction_vector(enemy_pos, player_pos);
+                    physics.velocity.x = direction.x * speed;
+                    animation_controller.change_state(CharacterState::Running);
                 } else {
-                    scale_magnitude
-                };
+                    // If unable to move, stop horizontal movement
+                    physics.velocity.x = 0.0;
+                }
             }
+            EnemyAiState::Searching => {
+                ai.search_timer.tick(time.delta());
+                if ai.search_timer.finished() {
+                    // De-aggro: forget about the player and resume the patrol
+                    ai.state = EnemyAiState::Patrolling;
+                    ai.last_known_player_pos = None;
+                    ai.patrol_pause.reset();
+                }
 
-            // If within attack range
-            if distance < enemy.attack_range {
-                // Stop movement and attack
-                physics.velocity.x = 0.0;
-                if can_enemy_move(&current_state) {
-                    animation_controller.change_state(CharacterState::Attacking);
+                let target_x = ai
+                    .last_known_player_pos
+                    .map(|LastKnownPlayerPos(position)| position.x)
+                    .unwrap_or(enemy_pos.x);
+                let offset = target_x - enemy_pos.x;
+
+                if offset.abs() > ENEMY_SEARCH_REACHED_DISTANCE && can_enemy_move(&current_state) {
+                    face_towards(&mut facing, transform, target_x);
+                    physics.velocity.x = offset.signum() * speed * ENEMY_SEARCH_SPEED_FACTOR;
+                    animation_controller.change_state(CharacterState::Running);
+                } else {
+                    // Reached the spot (or can't move), wait there
+                    physics.velocity.x = 0.0;
+                    if can_enemy_move(&current_state) {
+                        animation_controller.change_state(CharacterState::Idle);
+                    }
                 }
-            } else if can_enemy_move(&current_state) {
-                // Move toward player only if able to move
-                let direction = utils::direction_vector(enemy_pos, player_pos);
-                physics.velocity.x = direction.x * enemy.speed;
-                animation_controller.change_state(CharacterState::Running);
-            } else {
-                // If unable to move, stop horizontal movement
+            }
+            EnemyAiState::Returning => {
+                enemy.health =
+                    (enemy.health + ENEMY_LEASH_REGEN * time.delta_secs()).min(enemy.max_health);
+
+                let offset = ai.home_x - enemy_pos.x;
+                if offset.abs() <= ENEMY_SEARCH_REACHED_DISTANCE {
+                    ai.state = EnemyAiState::Patrolling;
+                    ai.patrol_pause.reset();
+                } else if can_enemy_move(&current_state) {
+                    face_towards(&mut facing, transform, ai.home_x);
+                    physics.velocity.x = offset.signum() * speed * ENEMY_RETURN_SPEED_FACTOR;
+                    animation_controller.change_state(CharacterState::Running);
+                } else {
+                    physics.velocity.x = 0.0;
+                }
+            }
+            EnemyAiState::Telegraphing | EnemyAiState::Charging | EnemyAiState::Stunned => {}
+            EnemyAiState::Patrolling => {
+                let target_x = ai.home_x + ai.patrol_direction * ENEMY_PATROL_RANGE;
+                let offset = target_x - enemy_pos.x;
+
+                if !can_enemy_move(&current_state) {
+                    physics.velocity.x = 0.0;
+                } else if offset.abs() > ENEMY_SEARCH_REACHED_DISTANCE {
+                    face_towards(&mut facing, transform, target_x);
+                    physics.velocity.x = offset.signum() * speed * ENEMY_PATROL_SPEED_FACTOR;
+                    animation_controller.change_state(CharacterState::Running);
+                } else {
+                    // Wait at the end of the patrol before turning around
+                    physics.velocity.x = 0.0;
+                    animation_controller.change_state(CharacterState::Idle);
+
+                    ai.patrol_pause.tick(time.delta());
+                    if ai.patrol_pause.finished() {
+                        ai.patrol_direction = -ai.patrol_direction;
+                        ai.patrol_pause.reset();
+                    }
+                }
+            }
+        }
+
+        // Fan out instead of stacking on the same spot
+        if can_enemy_move(&current_state)
+            && let Some(&(_, hitbox_pos, hitbox_size)) =
+                hitboxes.iter().find(|(owner, _, _)| *owner == entity)
+        {
+            physics.velocity.x += separation_velocity(entity, hitbox_pos, hitbox_size, &hitboxes);
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_enemy_charges(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut enemies: Query<
+        (
+            Entity,
+            &Enemy,
+            &mut EnemyAi,
+            &mut ChargeAttack,
+            &Transform,
+            &mut Facing,
+            &mut Physics,
+            &mut Sprite,
+            &BodyCollider,
+        ),
+        Without<Solid>,
+    >,
+    solids: Query<(&Transform, &Solid), Without<Enemy>>,
+    player_position: Res<PlayerPosition>,
+    mut camera_shake: ResMut<CameraShake>,
+) {
+    for (entity, enemy, mut ai, mut charge, transform, mut facing, mut physics, mut sprite, body) in
+        &mut enemies
+    {
+        if enemy.is_dead {
+            continue;
+        }
+
+        charge.cooldown.tick(time.delta());
+        let enemy_pos = transform.translation.truncate();
+        let player_pos = player_position.position.truncate();
+
+        match ai.state {
+            EnemyAiState::Chasing => {
+                let offset = player_pos - enemy_pos;
+                if charge.cooldown.finished()
+                    && !enemy.juggled
+                    && offset.x.abs() < CHARGE_TRIGGER_RANGE
+                    && offset.x.abs() > enemy.attack_range
+                    && offset.y.abs() < CHARGE_MAX_HEIGHT_DIFFERENCE
+                {
+                    // Lock the direction now so the player can read and dodge the charge
+                    ai.state = EnemyAiState::Telegraphing;
+                    charge.direction = offset.x.signum();
+                    charge.telegraph.reset();
+                    face_towards(&mut facing, transform, player_pos.x);
+                    physics.velocity.x = 0.0;
+                }
+            }
+            EnemyAiState::Telegraphing => {
                 physics.velocity.x = 0.0;
+                charge.telegraph.tick(time.delta());
+
+                // Flash between the normal color and the warning color
+                let flash = (charge.telegraph.elapsed_secs() * CHARGE_TELEGRAPH_FLASH_SPEED)
+                    .sin()
+                    .abs();
+                sprite.color = enemy.tint.mix(&CHARGE_TELEGRAPH_COLOR, flash);
+
+                if charge.telegraph.finished() {
+                    ai.state = EnemyAiState::Charging;
+                    charge.duration.reset();
+                    sprite.color = CHARGE_TELEGRAPH_COLOR;
+
+                    let hitbox = commands
+                        .spawn((
+                            // Hurts the player for the whole charge, removed when it ends
+                            ManagedHitbox,
+                            AttackHitbox {
+                                damage: CHARGE_DAMAGE,
+                                active: true,
+                                size: body.size,
+                                timer: Timer::from_seconds(CHARGE_DURATION, TimerMode::Once),
+                                launch: 0.0,
+                                guard_break: false,
+                                reaction: None,
+                                status: None,
+                            },
+                            Transform::default(),
+                        ))
+                        .id();
+                    commands.entity(entity).add_child(hitbox);
+                    charge.hitbox = Some(hitbox);
+                }
             }
-        } else {
-            // If player is outside detection range, stay still
-            physics.velocity.x = 0.0;
-            if can_enemy_move(&current_state) {
-                animation_controller.change_state(CharacterState::Idle);
+            EnemyAiState::Charging => {
+                physics.velocity.x = charge.direction * CHARGE_SPEED;
+                charge.duration.tick(time.delta());
+
+                // Probe just ahead of the body for a wall
+                let hit_wall = utils::raycast_rects(
+                    enemy_pos,
+                    Vec2::X * charge.direction,
+                    body.size.x / 2.0 + CHARGE_WALL_PROBE,
+                    solids
+                        .iter()
+                        .map(|(transform, solid)| (transform.translation.truncate(), solid.size)),
+                )
+                .is_some();
+
+                if hit_wall || charge.duration.finished() {
+                    if let Some(hitbox) = charge.hitbox.take() {
+                        commands.entity(hitbox).despawn_recursive();
+                    }
+                    charge.cooldown.reset();
+
+                    if hit_wall {
+                        // Bounce off and sit dazed, wide open for punishment
+                        ai.state = EnemyAiState::Stunned;
+                        charge.stun.reset();
+                        physics.velocity = Vec2::new(
+                            -charge.direction * CHARGE_WALL_BOUNCE.x,
+                            CHARGE_WALL_BOUNCE.y,
+                        );
+                        physics.on_ground = false;
+                        sprite.color = CHARGE_STUN_COLOR;
+                        camera_shake.add_trauma(CHARGE_WALL_SLAM_TRAUMA);
+                    } else {
+                        ai.state = EnemyAiState::Chasing;
+                        physics.velocity.x = 0.0;
+                        sprite.color = enemy.tint;
+                    }
+                }
+            }
+            EnemyAiState::Stunned => {
+                if physics.on_ground {
+                    physics.velocity.x = 0.0;
+                }
+
+                charge.stun.tick(time.delta());
+                if charge.stun.finished() {
+                    ai.state = EnemyAiState::Chasing;
+                    sprite.color = enemy.tint;
+                }
             }
+            EnemyAiState::Patrolling | EnemyAiState::Searching | EnemyAiState::Returning => {}
+        }
+    }
+}
+
+// A swing ends with its animation; movement picks the next state from there
+fn finish_enemy_attacks(
+    mut finished_events: EventReader<AnimationFinishedEvent>,
+    mut enemies: Query<&mut AnimationController, With<Enemy>>,
+) {
+    for event in finished_events.read() {
+        if event.state == CharacterState::Attacking
+            && let Ok(mut animation_controller) = enemies.get_mut(event.entity)
+            && animation_controller.get_current_state() == event.state
+        {
+            animation_controller.change_state(CharacterState::Idle);
         }
     }
 }
@@ -376,12 +831,12 @@ fn update_enemy_animations(
         let current_state = animation_controller.get_current_state();
 
         if enemy.is_dead {
-            transform.translation.y = transform.translation.y - 5.0;
+            transform.translation.y -= 5.0;
             continue;
         }
 
         // Don't change animations if attacking or hurt
-        if current_state == CharacterState::Attacking || current_state == CharacterState::Hurt {
+        if current_state == CharacterState::Attacking || current_state.is_hurt() {
             continue;
         }
 
@@ -396,308 +851,467 @@ fn update_enemy_animations(
             }
         }
         // If on ground and moving, use run animation
-        else if physics.on_ground {
-            if current_state != CharacterState::Running {
-                animation_controller.change_state(CharacterState::Running);
-            }
+        else if physics.on_ground && current_state != CharacterState::Running {
+            animation_controller.change_state(CharacterState::Running);
         }
     }
 }
 
-fn handle_damage(
-    mut enemies: Query<(
-        &mut Enemy,
-        &mut AnimationController,
-        &Children,
-        &mut Transform,
-        &mut Physics,
-    )>,
-    enemy_hitboxes: Query<(&CollisionHitbox, &GlobalTransform)>,
-    attack_hitboxes: Query<(&AttackHitbox, &GlobalTransform, &Parent)>,
-    player_query: Query<Entity, With<Player>>,
+// A hit comes from the front when the attacker is on the side the enemy is facing
+fn is_hit_from_front(facing: Facing, enemy_x: f32, attacker_x: f32) -> bool {
+    match facing {
+        Facing::Right => attacker_x > enemy_x,
+        Facing::Left => attacker_x < enemy_x,
+    }
+}
+
+// Only the first hit of each frame lands, blocked or not
+fn apply_hits_to_enemies(
+    mut commands: Commands,
+    mut hit_events: EventReader<HitEvent>,
+    mut enemies: Query<(&mut Enemy, Option<&mut EnemyShield>, &EnemyAi, &Facing)>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut knockback_events: EventWriter<KnockbackEvent>,
+    mut sfx_events: EventWriter<SfxEvent>,
 ) {
-    for (mut enemy, mut animation_controller, children, mut _transform, mut physics) in &mut enemies
-    {
-        if enemy.is_dead {
+    let mut hit_this_frame = HashSet::new();
+
+    for event in hit_events.read() {
+        let Ok((mut enemy, mut shield, ai, facing)) = enemies.get_mut(event.target) else {
+            continue;
+        };
+
+        // Leashed enemies shrug off hits for a moment after disengaging
+        if enemy.is_dead || ai.is_invulnerable() || !hit_this_frame.insert(event.target) {
             continue;
         }
 
-        // Find enemy hitbox
-        let mut enemy_hitbox_data = None;
-        for &child in children.iter() {
-            if let Ok((hitbox, transform)) = enemy_hitboxes.get(child) {
-                if hitbox.active {
-                    enemy_hitbox_data = Some((hitbox.size, transform.translation().truncate()));
-                    break;
-                }
+        let enemy_pos = event.target_pos;
+
+        // Shields negate frontal hits unless the attack breaks guard
+        if let Some(shield) = shield.as_mut()
+            && !event.guard_break
+            && is_hit_from_front(*facing, enemy_pos.x, event.attacker_pos.x)
+        {
+            if shield.block_cooldown.finished() {
+                shield.block_cooldown.reset();
+                let side = facing.sign();
+                let spark_pos = Vec2::new(
+                    enemy_pos.x + side * event.target_size.x / 2.0,
+                    event.attack_pos.y,
+                );
+                spawn_shield_spark(&mut commands, spark_pos);
+                sfx_events.send(SfxEvent {
+                    effect: SoundEffect::ShieldBlock,
+                    position: Some(spark_pos),
+                });
             }
+            continue;
+        }
+
+        let damage = event.damage - enemy.defense;
+        if damage <= 0.0 {
+            continue;
         }
 
-        let (enemy_size, enemy_pos) = match enemy_hitbox_data {
-            Some(data) => data,
-            None => continue,
+        // Pushed away from the attack, apply_knockback scales it by the enemy weight
+        let direction = if event.attack_pos.x > enemy_pos.x {
+            -1.0
+        } else {
+            1.0
+        };
+        let launched = event.launch > 0.0 && enemy.weight <= ENEMY_LAUNCH_MAX_WEIGHT;
+        let knockback = if launched {
+            // Launcher hit: send light enemies up so they can be juggled
+            enemy.juggled = true;
+            Knockback {
+                strength: ENEMY_LAUNCH_KNOCKBACK,
+                lift: event.launch,
+                decay: ENEMY_JUGGLE_KNOCKBACK_DECAY,
+            }
+        } else if enemy.juggled {
+            // Air hit: small lift that keeps the juggle going
+            Knockback {
+                strength: ENEMY_AIR_HIT_KNOCKBACK,
+                lift: ENEMY_AIR_HIT_LIFT,
+                decay: ENEMY_JUGGLE_KNOCKBACK_DECAY,
+            }
+        } else {
+            event.knockback
         };
 
-        // Get player entity
-        if let Ok(player_entity) = player_query.get_single() {
-            for (attack_hitbox, attack_transform, parent) in &attack_hitboxes {
-                if !attack_hitbox.active || parent.get() != player_entity {
-                    continue;
-                }
+        // Enemies too heavy to launch are staggered instead
+        let reaction = if launched || enemy.juggled {
+            HitReaction::Launch
+        } else {
+            match event.reaction {
+                Some(HitReaction::Launch) => HitReaction::Stagger,
+                Some(reaction) => reaction,
+                None if event.launch > 0.0 => HitReaction::Stagger,
+                None => HitReaction::from_damage(damage, enemy.max_health),
+            }
+        };
 
-                let attack_pos = attack_transform.translation().truncate();
+        damage_events.send(DamageEvent {
+            target: event.target,
+            source: Some(event.attacker),
+            amount: damage,
+            position: enemy_pos,
+            reaction: Some(reaction),
+            status: event.status,
+        });
+        if !knockback.is_none() {
+            knockback_events.send(KnockbackEvent {
+                target: event.target,
+                direction,
+                knockback,
+            });
+        }
+    }
+}
 
-                // Use utility function to check collision
-                if utils::check_rect_collision(
-                    enemy_pos,
-                    enemy_size,
-                    attack_pos,
-                    attack_hitbox.size,
-                ) {
-                    let damage = attack_hitbox.damage - enemy.defense;
-                    if damage > 0.0 {
-                        enemy.health -= damage;
-                        animation_controller.change_state(CharacterState::Hurt);
-
-                        // Apply constant physical impulse based on attack direction
-                        let direction = if attack_pos.x > enemy_pos.x {
-                            -1.0
-                        } else {
-                            1.0
-                        };
-                        physics.velocity = Vec2::new(direction * 2150.0, direction * 120.0);
-                        physics.on_ground = false;
-                    }
-                    break; // only one hit per frame
-                }
-            }
+fn take_enemy_damage(
+    config: Res<GameConfig>,
+    mut damage_events: EventReader<DamageEvent>,
+    mut enemies: Query<(&mut Enemy, &mut AnimationController)>,
+) {
+    for event in damage_events.read() {
+        if let Ok((mut enemy, mut animation_controller)) = enemies.get_mut(event.target) {
+            enemy.health -= event.amount;
+            let Some(reaction) = event.reaction else {
+                continue;
+            };
+            animation_controller.change_state(reaction.state());
+
+            // Launched enemies stay hurt until they land, see update_enemy_states
+            let hurt_duration = match reaction {
+                HitReaction::Stagger => config.enemy.stagger_time,
+                HitReaction::Flinch | HitReaction::Launch => config.enemy.hurt_time,
+            };
+            enemy
+                .hurt_timer
+                .set_duration(Duration::from_secs_f32(hurt_duration));
+            enemy.hurt_timer.reset();
         }
     }
 }
 
-fn check_death(
-    mut query: Query<(&mut Enemy, &mut AnimationController, &mut Transform)>,
-    windows: Query<&Window>,
+fn spawn_shield_spark(commands: &mut Commands, position: Vec2) {
+    commands.spawn((
+        ShieldSpark {
+            timer: Timer::from_seconds(SHIELD_SPARK_DURATION, TimerMode::Once),
+        },
+        StateScoped(InGame),
+        Sprite {
+            color: SHIELD_SPARK_COLOR,
+            custom_size: Some(SHIELD_SPARK_SIZE),
+            ..default()
+        },
+        Transform::from_translation(position.extend(Layer::Effects.z()))
+            .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+    ));
+}
+
+// Block cooldowns and fading of the sparks left by blocked hits
+fn update_shields(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut shields: Query<&mut EnemyShield>,
+    mut sparks: Query<(Entity, &mut ShieldSpark, &mut Sprite)>,
 ) {
-    let window = if let Ok(window) = windows.get_single() {
-        window
-    } else {
-        return; // Skip this frame if window is not available
-    };
-    let window_height = window.height();
-    let death_threshold = -window_height * 0.5; // Muerte si cae por debajo de la mitad de la pantalla
+    for mut shield in &mut shields {
+        shield.block_cooldown.tick(time.delta());
+    }
+
+    for (entity, mut spark, mut sprite) in &mut sparks {
+        spark.timer.tick(time.delta());
+        if spark.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+        } else {
+            sprite.color = SHIELD_SPARK_COLOR.with_alpha(1.0 - spark.timer.fraction());
+        }
+    }
+}
 
-    for (mut enemy, mut animation_controller, transform) in &mut query {
+fn check_death(
+    mut query: Query<(
+        Entity,
+        &mut Enemy,
+        &mut AnimationController,
+        &Transform,
+        &mut Physics,
+    )>,
+    mut defeated_events: EventWriter<EnemyDefeatedEvent>,
+    mut death_events: EventWriter<DeathEvent>,
+) {
+    for (entity, mut enemy, mut animation_controller, transform, mut physics) in &mut query {
         // Verificar si el enemigo está muerto por salud
         if enemy.health <= 0.0 && !enemy.is_dead {
             enemy.is_dead = true;
             animation_controller.change_state(CharacterState::Dead);
             enemy.death_timer = Timer::from_seconds(ENEMY_DEATH_TIMER, TimerMode::Once);
+            defeated_events.send(EnemyDefeatedEvent {
+                entity,
+                position: transform.translation.truncate(),
+                loot: enemy.loot,
+            });
+            death_events.send(DeathEvent {
+                entity,
+                position: transform.translation.truncate(),
+            });
         }
 
-        // Verificar si el enemigo está fuera de los límites
-        if transform.translation.x < -1000.0 || transform.translation.y < death_threshold {
-            if !enemy.is_dead {
-                enemy.is_dead = true;
-                animation_controller.change_state(CharacterState::Dead);
-                enemy.death_timer = Timer::from_seconds(ENEMY_DEATH_TIMER, TimerMode::Once);
-            }
+        // Fell into a pit: dies like any other death, just without dropping loot out of
+        // reach, and stays where it is until it is cleaned up
+        if transform.translation.y < DEATH_BARRIER_Y && !enemy.is_dead {
+            physics.velocity = Vec2::ZERO;
+            physics.gravity_scale = 0.0;
+            enemy.is_dead = true;
+            animation_controller.change_state(CharacterState::Dead);
+            enemy.death_timer = Timer::from_seconds(ENEMY_DEATH_TIMER, TimerMode::Once);
+            death_events.send(DeathEvent {
+                entity,
+                position: transform.translation.truncate(),
+            });
         }
     }
 }
 
-fn respawn_enemies(
+// Spawn an enemy from each active spawn point once the player gets close enough
+#[allow(clippy::too_many_arguments)]
+fn update_enemy_spawn_points(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-    resolution: Res<resolution::Resolution>,
-    windows: Query<&Window>,
-    mut enemy_counter: ResMut<EnemyCounter>,
-    // mut meshes: ResMut<Assets<Mesh>>,
-    // mut materials: ResMut<Assets<ColorMaterial>>,
-    camera_query: Query<&Transform, With<Camera2d>>,
+    character_assets: Res<CharacterAssetCache>,
+    time: Res<Time>,
+    player_position: Res<PlayerPosition>,
+    registry: Res<RoomRegistry>,
+    mut spawn_points: Query<(&mut EnemySpawnPoint, &Transform)>,
+    enemies: Query<(), With<Enemy>>,
+    cameras: Query<&Transform, With<Camera2d>>,
+    solids: Query<(&Transform, &Solid)>,
+    config: Res<GameConfig>,
+    attack_definitions: Res<AttackDefinitions>,
 ) {
-    // Skip if camera isn't available
-    if camera_query.is_empty() {
+    let mut rng = rand::thread_rng();
+    let Ok(camera_transform) = cameras.get_single() else {
         return;
-    }
+    };
+    let camera_x = camera_transform.translation.x;
 
-    // If we have fewer enemies than desired, create new ones
-    if enemy_counter.current_count < enemy_counter.desired_count {
-        let to_spawn = enemy_counter.desired_count - enemy_counter.current_count;
-
-        for _ in 0..to_spawn {
-            spawn_enemy(
-                &mut commands,
-                &asset_server,
-                &camera_query,
-                &mut texture_atlas_layouts,
-                &resolution,
-                &windows,
-                // &mut meshes,
-                // &mut materials,
-            );
-            enemy_counter.current_count += 1;
+    for (mut spawn_point, transform) in &mut spawn_points {
+        // Check whether the enemy from this spawn point is still around
+        if let Some(enemy_entity) = spawn_point.spawned_enemy {
+            if enemies.contains(enemy_entity) {
+                continue;
+            }
+
+            spawn_point.spawned_enemy = None;
+            match spawn_point.respawn {
+                RespawnRule::Never => spawn_point.exhausted = true,
+                RespawnRule::AfterDelay(seconds) => {
+                    spawn_point.respawn_timer = Some(Timer::from_seconds(seconds, TimerMode::Once));
+                }
+            }
         }
+
+        if spawn_point.exhausted {
+            continue;
+        }
+
+        if let Some(timer) = &mut spawn_point.respawn_timer {
+            timer.tick(time.delta());
+            if !timer.finished() {
+                continue;
+            }
+            spawn_point.respawn_timer = None;
+        }
+
+        let spawner_position = transform.translation.truncate();
+        if (player_position.position.x - spawner_position.x).abs() > spawn_point.activation_distance
+        {
+            continue;
+        }
+
+        // Try a few spots in the spawn area; if none is valid, try again next frame
+        let anchor_x = match spawn_point.area.anchor {
+            SpawnAnchor::Spawner => spawner_position.x,
+            SpawnAnchor::Camera => camera_x,
+        };
+        let Some(spawn_position) = (0..ENEMY_SPAWN_ATTEMPTS)
+            .map(|_| {
+                Vec2::new(
+                    spawn_point.area.random_x(anchor_x, &mut rng),
+                    spawner_position.y,
+                )
+            })
+            .find(|&position| {
+                is_valid_spawn_position(
+                    position,
+                    player_position.position.x,
+                    camera_x,
+                    &registry,
+                    &solids,
+                )
+            })
+        else {
+            continue;
+        };
+
+        let facing = Facing::towards(spawn_position.x, player_position.position.x);
+        let variant = if rng.gen_bool(spawn_point.elite_chance.clamp(0.0, 1.0) as f64) {
+            EnemyVariant::Elite
+        } else {
+            EnemyVariant::Normal
+        };
+        let enemy_entity = spawn_enemy(
+            &mut commands,
+            &character_assets,
+            spawn_point.kind,
+            variant,
+            spawn_position,
+            facing,
+            &config.enemy,
+            &attack_definitions,
+        );
+        spawn_point.spawned_enemy = Some(enemy_entity);
     }
 }
 
+// Enemies never pop in on screen, right next to the player, over a pit or inside terrain
+fn is_valid_spawn_position(
+    position: Vec2,
+    player_x: f32,
+    camera_x: f32,
+    registry: &RoomRegistry,
+    solids: &Query<(&Transform, &Solid)>,
+) -> bool {
+    let half_width = ENEMY_BODY_SIZE.x * ENEMY_SCALE_FACTOR / 2.0;
+    let visible_half_width = resolution::SCREEN_WIDTH / 2.0 + ENEMY_SPAWN_SCREEN_MARGIN;
+
+    if (position.x - player_x).abs() < ENEMY_SPAWN_MIN_PLAYER_DISTANCE
+        || (position.x - camera_x).abs() < visible_half_width
+        || registry.has_pit_between(position.x - half_width, position.x + half_width)
+    {
+        return false;
+    }
+
+    // The whole drop down to the ground has to be clear
+    let drop_height = position.y - GROUND_TOP_Y;
+    let drop_center = Vec2::new(position.x, GROUND_TOP_Y + drop_height / 2.0);
+    let drop_size = Vec2::new(half_width * 2.0, drop_height);
+    !solids.iter().any(|(transform, solid)| {
+        utils::check_rect_collision(
+            drop_center,
+            drop_size,
+            transform.translation.truncate(),
+            solid.size,
+        )
+    })
+}
+
 fn cleanup_dead_enemies(
     mut commands: Commands,
     mut query: Query<(Entity, &mut Enemy)>,
     time: Res<Time>,
-    mut enemy_counter: ResMut<EnemyCounter>,
 ) {
     for (entity, mut enemy) in &mut query {
         if enemy.is_dead {
             enemy.death_timer.tick(time.delta());
             if enemy.death_timer.finished() {
                 commands.entity(entity).despawn_recursive();
-                enemy_counter.current_count -= 1;
             }
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn spawn_enemy(
     commands: &mut Commands,
-    asset_server: &AssetServer,
-    camera_query: &Query<&Transform, With<Camera2d>>,
-    texture_atlas_layouts: &mut Assets<TextureAtlasLayout>,
-    resolution: &resolution::Resolution,
-    windows: &Query<&Window>,
-    // meshes: &mut ResMut<Assets<Mesh>>,
-    // materials: &mut ResMut<Assets<ColorMaterial>>,
-) {
-    let window = windows.single();
-    let window_height = window.height();
-    let ground_height = -window_height * 0.3;
-
-    // Get camera position safely
-    let camera_transform = if let Ok(transform) = camera_query.get_single() {
-        transform
-    } else {
-        // Fallback if camera not found
-        return;
+    character_assets: &CharacterAssetCache,
+    kind: EnemyKind,
+    variant: EnemyVariant,
+    position: Vec2,
+    facing: Facing,
+    config: &EnemyConfig,
+    attack_definitions: &AttackDefinitions,
+) -> Entity {
+    let scale = match variant {
+        EnemyVariant::Normal => ENEMY_SCALE_FACTOR,
+        EnemyVariant::Elite => ENEMY_SCALE_FACTOR * ELITE_SCALE,
     };
 
-    // Randomize spawn side (left or right of camera)
-    let spawn_side = if rand::random::<bool>() { 1.0 } else { -1.0 };
-
-    // Calculate spawn position relative to camera
-    let spawn_x = camera_transform.translation.x + (ENEMY_SPAWN_OFFSET_X);
-    let enemy_y = ground_height + ENEMY_SPAWN_OFFSET_Y * resolution.pixel_ratio;
-
-    let idle_texture = asset_server.load("enemy/skeleton/skeletonIdle-Sheet64x64.png");
-    let attack_texture = asset_server.load("enemy/skeleton/skeletonAttack-cropped.png");
-    let move_texture = asset_server.load("enemy/skeleton/skeletonMove-Sheet64x64.png");
-    let hurt_texture = asset_server.load("enemy/skeleton/skeletonHurt-Sheet64x64.png");
-    let die_texture = asset_server.load("enemy/skeleton/skeletonDie-Sheet118x64_all.png");
-
-    // Create atlas layouts
-    let idle_layout = TextureAtlasLayout::from_grid(UVec2::splat(64), 8, 1, None, None);
-    let attack_layout =
-        TextureAtlasLayout::from_grid(UVec2::new(146, 64), 5, 5, Some(UVec2::new(0, 0)), None);
-    let move_layout = TextureAtlasLayout::from_grid(UVec2::splat(64), 10, 1, None, None);
-    let hurt_layout = TextureAtlasLayout::from_grid(UVec2::splat(64), 3, 1, None, None);
-    let die_layout = TextureAtlasLayout::from_grid(UVec2::new(118, 64), 5, 5, None, None);
-
-    let idle_atlas_layout = texture_atlas_layouts.add(idle_layout);
-    let attack_atlas_layout = texture_atlas_layouts.add(attack_layout);
-    let move_atlas_layout = texture_atlas_layouts.add(move_layout);
-    let hurt_atlas_layout = texture_atlas_layouts.add(hurt_layout);
-    let die_atlas_layout = texture_atlas_layouts.add(die_layout);
-
-    // Create animation data
-    let animations = CharacterAnimations {
-        animations: vec![
-            AnimationData {
-                state: CharacterState::Idle,
-                texture: idle_texture.clone(),
-                atlas_layout: idle_atlas_layout.clone(),
-                frames: ENEMY_IDLE_FRAMES,
-                fps: ENEMY_IDLE_FPS,
-                looping: true,
-                ping_pong: false,
-            },
-            AnimationData {
-                state: CharacterState::Attacking,
-                texture: attack_texture.clone(),
-                atlas_layout: attack_atlas_layout.clone(),
-                frames: ENEMY_ATTACK_FRAMES,
-                fps: ENEMY_ATTACK_FPS,
-                looping: false,
-                ping_pong: false,
-            },
-            AnimationData {
-                state: CharacterState::Running,
-                texture: move_texture.clone(),
-                atlas_layout: move_atlas_layout.clone(),
-                frames: ENEMY_MOVE_FRAMES,
-                fps: ENEMY_MOVE_FPS,
-                looping: true,
-                ping_pong: false,
-            },
-            AnimationData {
-                state: CharacterState::Hurt,
-                texture: hurt_texture.clone(),
-                atlas_layout: hurt_atlas_layout.clone(),
-                frames: ENEMY_HURT_FRAMES,
-                fps: ENEMY_HURT_FPS,
-                looping: false,
-                ping_pong: false,
-            },
-            AnimationData {
-                state: CharacterState::Dead,
-                texture: die_texture.clone(),
-                atlas_layout: die_atlas_layout.clone(),
-                frames: ENEMY_DIE_FRAMES,
-                fps: ENEMY_DIE_FPS,
-                looping: false,
-                ping_pong: false,
-            },
-        ],
+    // Stats that change with the kind of enemy
+    let (health, max_health, speed, weight) = match kind {
+        EnemyKind::Skeleton => (
+            config.max_health,
+            config.max_health,
+            config.speed,
+            ENEMY_WEIGHT,
+        ),
+        EnemyKind::ShieldedSkeleton => (
+            SHIELDED_ENEMY_HEALTH,
+            SHIELDED_ENEMY_HEALTH,
+            SHIELDED_ENEMY_SPEED,
+            SHIELDED_ENEMY_WEIGHT,
+        ),
+        EnemyKind::ChargingSkeleton => (
+            CHARGING_ENEMY_HEALTH,
+            CHARGING_ENEMY_HEALTH,
+            CHARGING_ENEMY_SPEED,
+            CHARGING_ENEMY_WEIGHT,
+        ),
     };
 
-    // Initial animation (idle)
-    let initial_animation = CurrentAnimation {
-        current_frame: 0,
-        timer: Timer::from_seconds(0.1, TimerMode::Repeating),
-        total_frames: ENEMY_IDLE_FRAMES,
-        looping: true,
-        reverse_direction: false,
-    };
+    let status = variant_status_effect(kind, variant);
 
-    // Set facing direction based on spawn side
-    let facing_right = spawn_side < 0.0;
-    let scale_x = if facing_right {
-        -ENEMY_SCALE_FACTOR
-    } else {
-        ENEMY_SCALE_FACTOR
+    // Elites multiply the base stats and drops
+    let (health, max_health, damage_multiplier, loot, tint) = match variant {
+        EnemyVariant::Normal => (health, max_health, 1.0, ENEMY_LOOT, Color::WHITE),
+        EnemyVariant::Elite => (
+            health * ELITE_HEALTH_MULTIPLIER,
+            max_health * ELITE_HEALTH_MULTIPLIER,
+            ELITE_DAMAGE_MULTIPLIER,
+            ENEMY_LOOT.multiplied(ELITE_LOOT_MULTIPLIER),
+            ELITE_TINT,
+        ),
     };
 
     // Create enemy entity with uniform scale
-    commands
+    let enemy_entity = commands
         .spawn((
-            Sprite::from_atlas_image(
-                idle_texture,
-                TextureAtlas {
-                    layout: idle_atlas_layout,
-                    index: 0,
-                },
-            ),
+            // The image is set once the animation manifest has loaded
+            Sprite {
+                color: tint,
+                ..default()
+            },
             Enemy {
-                health: ENEMY_INITIAL_HEALTH,
-                max_health: ENEMY_MAX_HEALTH,
-                attack: ENEMY_ATTACK,
-                defense: ENEMY_DEFENSE,
-                speed: ENEMY_SPEED,
-                attack_range: ENEMY_ATTACK_RANGE,
-                detection_range: ENEMY_DETECTION_RANGE,
-                facing_right,
+                kind,
+                variant,
+                tint,
+                health,
+                max_health,
+                attack: config.attack * damage_multiplier,
+                contact_damage: config.contact_damage * damage_multiplier,
+                defense: config.defense,
+                speed,
+                attack_range: config.attack_range,
+                detection_range: config.detection_range,
                 is_dead: false,
                 death_timer: Timer::from_seconds(ENEMY_DEATH_TIMER, TimerMode::Once),
-                hurt_timer: Timer::from_seconds(ENEMY_HURT_TIMER, TimerMode::Once),
+                hurt_timer: Timer::from_seconds(config.hurt_time, TimerMode::Once),
+                weight,
+                juggled: false,
+                loot,
+            },
+            EnemyAi::new(position.x),
+            BodyCollider {
+                size: ENEMY_BODY_SIZE * scale,
+            },
+            (SurfaceContact::default(), config.movement),
+            StateScoped(InGame),
+            CharacterDimensions {
+                height: ENEMY_BODY_SIZE.y,
+                feet_offset: ENEMY_BODY_SIZE.y / 2.0,
             },
             Physics {
                 velocity: Vec2::ZERO,
@@ -705,32 +1319,118 @@ fn spawn_enemy(
                 on_ground: true,
                 gravity_scale: 1.0,
             },
-            Transform::from_xyz(spawn_x, enemy_y, 5.0).with_scale(Vec3::new(
-                scale_x,
-                ENEMY_SCALE_FACTOR,
-                1.0,
-            )),
+            Transform::from_translation(position.extend(Layer::Enemies.z()))
+                .with_scale(Vec3::new(scale, scale, 1.0)),
             Anchor::Center,
+            facing,
             AnimationController::default(),
-            animations,
-            initial_animation,
+            AnimationSet(character_assets.skeleton.clone()),
+            CurrentAnimation::default(),
+            enemy_attacks(
+                config.attack * damage_multiplier,
+                status,
+                attack_definitions,
+            ),
         ))
         .with_children(|parent| {
             parent.spawn((
                 CollisionHitbox {
                     active: true,
-                    size: ENEMY_COLLISION_SIZE * ENEMY_SCALE_FACTOR,
+                    size: ENEMY_COLLISION_SIZE * scale,
                 },
-                // Mesh2d(meshes.add(Rectangle::from_size(ENEMY_COLLISION_SIZE))),
-                // MeshMaterial2d(materials.add(Color::Srgba(Srgba {
-                //     red: 0.,
-                //     green: 0.,
-                //     blue: 255.,
-                //     alpha: 0.1,
-                // }))),
                 Transform::from_scale(Vec3::new(ENEMY_SCALE_FACTOR, ENEMY_SCALE_FACTOR, 1.0))
                     .with_translation(Vec3::new(0.0, -ENEMY_FEET_OFFSET * 0.5, 0.0)),
                 Anchor::Center,
             ));
-        });
+        })
+        .id();
+
+    if kind == EnemyKind::ShieldedSkeleton {
+        commands
+            .entity(enemy_entity)
+            .insert(EnemyShield {
+                block_cooldown: Timer::from_seconds(SHIELD_BLOCK_COOLDOWN, TimerMode::Once),
+            })
+            .with_children(|parent| {
+                parent.spawn((
+                    Sprite {
+                        color: SHIELD_COLOR,
+                        custom_size: Some(SHIELD_SIZE),
+                        ..default()
+                    },
+                    FacingOffset(Vec2::new(SHIELD_OFFSET_X, 0.0)),
+                    Transform::from_xyz(SHIELD_OFFSET_X * facing.sign(), 0.0, 0.5),
+                ));
+            });
+    }
+
+    commands
+        .entity(enemy_entity)
+        .insert((StatusEffects::new(tint), Faction::Enemy));
+    if kind == EnemyKind::ChargingSkeleton {
+        commands
+            .entity(enemy_entity)
+            .insert(ChargeAttack::default());
+    }
+
+    enemy_entity
+}
+
+// Elites poison, shielded skeletons hit hard enough to slow
+fn variant_status_effect(kind: EnemyKind, variant: EnemyVariant) -> Option<StatusEffectKind> {
+    match (kind, variant) {
+        (_, EnemyVariant::Elite) => Some(StatusEffectKind::Poison),
+        (EnemyKind::ShieldedSkeleton, _) => Some(StatusEffectKind::Slow),
+        _ => None,
+    }
+}
+
+// Edited attacks reach enemies already in the level, keeping their damage
+fn apply_enemy_attack_definitions(
+    attack_definitions: Res<AttackDefinitions>,
+    mut enemies: Query<(&Enemy, &mut AttackSet)>,
+) {
+    for (enemy, mut attack_set) in &mut enemies {
+        *attack_set = enemy_attacks(
+            attack_set.damage,
+            variant_status_effect(enemy.kind, enemy.variant),
+            &attack_definitions,
+        );
+    }
+}
+
+// Attacks from the animation manifest if it lists them, otherwise the built-in ones.
+// Variant effects (elite poison, shield slow) go on windows that don't set their own
+fn enemy_attacks(
+    damage: f32,
+    status: Option<StatusEffectKind>,
+    definitions: &AttackDefinitions,
+) -> AttackSet {
+    let mut windows = definitions
+        .windows("skeleton")
+        .unwrap_or_else(default_enemy_attacks);
+    for window in &mut windows {
+        window.status = window.status.or(status);
+    }
+
+    AttackSet { damage, windows }
+}
+
+// The attack strip has two swings, each with its own hitbox
+fn default_enemy_attacks() -> Vec<AttackWindow> {
+    let swing = |frames| AttackWindow {
+        state: CharacterState::Attacking,
+        frames,
+        damage_multiplier: 1.0,
+        size: ENEMY_ATTACK_HITBOX_SIZE,
+        offset: Vec2::new(ENEMY_ATTACK_HITBOX_SIZE.x * ENEMY_ATTACK_HITBOX_OFFSET, 0.0),
+        duration: ENEMY_ATTACK_HITBOX_DURATION,
+        knockback: Knockback::default(),
+        launch: 0.0,
+        guard_break: false,
+        reaction: None,
+        status: None,
+    };
+
+    vec![swing(4..5), swing(13..16)]
 }
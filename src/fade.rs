@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+
+use crate::layers::UiLayer;
+
+// Full-screen black overlay that other systems fade in and out
+#[derive(Resource)]
+pub struct ScreenFade {
+    alpha: f32,
+    target: f32,
+    speed: f32, // Alpha change per second
+}
+
+impl Default for ScreenFade {
+    fn default() -> Self {
+        Self {
+            alpha: 0.0,
+            target: 0.0,
+            speed: 1.0,
+        }
+    }
+}
+
+impl ScreenFade {
+    pub fn fade_out(&mut self, duration: f32) {
+        self.fade_to(1.0, duration);
+    }
+
+    pub fn fade_in(&mut self, duration: f32) {
+        self.fade_to(0.0, duration);
+    }
+
+    fn fade_to(&mut self, target: f32, duration: f32) {
+        self.target = target;
+        if duration > 0.0 {
+            self.speed = (target - self.alpha).abs() / duration;
+        } else {
+            self.alpha = target;
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.alpha == self.target
+    }
+}
+
+// UI node that renders the fade
+#[derive(Component)]
+struct FadeOverlay;
+
+pub struct FadePlugin;
+
+impl Plugin for FadePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScreenFade>()
+            .add_systems(Startup, setup_fade_overlay)
+            .add_systems(Update, update_screen_fade);
+    }
+}
+
+fn setup_fade_overlay(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        BackgroundColor(Color::BLACK.with_alpha(0.0)),
+        GlobalZIndex(UiLayer::ScreenFade.z_index()),
+        FadeOverlay,
+    ));
+}
+
+fn update_screen_fade(
+    time: Res<Time>,
+    mut fade: ResMut<ScreenFade>,
+    mut overlays: Query<&mut BackgroundColor, With<FadeOverlay>>,
+) {
+    if fade.alpha != fade.target {
+        let max_change = fade.speed * time.delta_secs();
+        let change = (fade.target - fade.alpha).clamp(-max_change, max_change);
+        fade.alpha += change;
+    }
+
+    if !fade.is_changed() {
+        return;
+    }
+
+    for mut background in &mut overlays {
+        background.0 = Color::BLACK.with_alpha(fade.alpha);
+    }
+}
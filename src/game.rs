@@ -1,22 +1,92 @@
 use bevy::prelude::*;
 
+use crate::aggro_indicators;
+use crate::ambient;
+use crate::animation_manifest;
 use crate::animations;
+use crate::aseprite;
+use crate::atlas;
+use crate::audio;
+#[cfg(feature = "avian")]
+use crate::avian_backend;
+use crate::barrier;
+use crate::boss;
+use crate::breakable;
+use crate::camera_director;
+use crate::camera_shake;
+use crate::camera_zoom;
+use crate::cleanup;
+use crate::companion;
+use crate::config;
+use crate::damage_numbers;
+use crate::debug;
+use crate::dialogue;
+use crate::doors;
+use crate::endless;
 use crate::enemy;
+use crate::fade;
+use crate::game_over;
 use crate::ground;
+use crate::hazards;
+use crate::health_bar;
+use crate::hitbox;
+use crate::hud;
+use crate::hurt_flash;
+use crate::input;
+use crate::input_display;
+use crate::intro;
+use crate::inventory;
+use crate::ledge_grab;
+use crate::level;
+use crate::map;
 use crate::menu;
+use crate::menu_navigation;
+use crate::music;
+use crate::notifications;
 use crate::paralax_background;
 use crate::pause;
 use crate::physics;
+use crate::pickups;
 use crate::player;
+use crate::pushable;
 use crate::resolution;
+use crate::save;
+use crate::save_slots;
+use crate::set_pieces;
+use crate::settings;
+use crate::settings_menu;
+use crate::spatial;
+use crate::status_effects;
+use crate::toll_gate;
+use crate::triggers;
 
 // Game state enum to control the flow of the game
 #[derive(States, Debug, Clone, Eq, PartialEq, Hash, Default)]
 pub enum GameState {
     #[default]
+    Loading, // Waits for the character assets and the level file before showing the menu
     Menu,
     Playing,
     Paused,
+    GameOver,   // The player died; the run stays on screen behind the retry prompt
+    Restarting, // Passed through for a frame so a retry ends the run and starts a new one
+}
+
+// A run in progress, playing, paused or lost. Gameplay entities are spawned when it starts and
+// despawned when it ends
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct InGame;
+
+impl ComputedStates for InGame {
+    type SourceStates = GameState;
+
+    fn compute(state: GameState) -> Option<Self> {
+        matches!(
+            state,
+            GameState::Playing | GameState::Paused | GameState::GameOver
+        )
+        .then_some(InGame)
+    }
 }
 
 pub struct GamePlugin;
@@ -24,24 +94,91 @@ pub struct GamePlugin;
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app.init_state::<GameState>()
+            .add_computed_state::<InGame>()
+            // Gameplay entities are scoped to InGame, so pausing keeps them and going back to
+            // the menu clears them
+            .enable_state_scoped_entities::<GameState>()
+            .enable_state_scoped_entities::<InGame>()
             .add_plugins((
                 menu::MenuPlugin,
+                menu_navigation::MenuNavigationPlugin,
                 resolution::ResolutionPlugin,
                 paralax_background::ParallaxPlugin,
                 pause::PausePlugin,
+                game_over::GameOverPlugin,
+                settings::SettingsPlugin,
+                settings_menu::SettingsMenuPlugin,
+                debug::DebugPlugin,
+                fade::FadePlugin,
+                atlas::AtlasPackingPlugin,
+                input::InputPlugin,
+                input_display::InputDisplayPlugin,
+                config::GameConfigPlugin,
+                intro::IntroPlugin,
             ))
             .add_plugins((
                 physics::GravityPlugin,
+                hitbox::HitboxPlugin,
+                spatial::SpatialHashPlugin,
                 animations::AnimationPlugin,
+                animation_manifest::AnimationManifestPlugin,
+                aseprite::AsepritePlugin,
                 player::PlayerPlugin,
+                ledge_grab::LedgeGrabPlugin,
                 ground::GroundPlugin,
                 enemy::EnemyPlugin,
+                companion::CompanionPlugin,
+            ))
+            .add_plugins((
+                triggers::TriggerPlugin,
+                level::LevelPlugin,
+                notifications::NotificationsPlugin,
+                health_bar::HealthBarPlugin,
+                pickups::PickupPlugin,
+                pushable::PushableBlockPlugin,
+                audio::SoundPlugin,
+                hazards::HazardPlugin,
+                toll_gate::TollGatePlugin,
+                damage_numbers::DamageNumbersPlugin,
+                barrier::BarrierPlugin,
+                status_effects::StatusEffectsPlugin,
+                aggro_indicators::AggroIndicatorsPlugin,
+                hurt_flash::HurtFlashPlugin,
             ))
+            .add_plugins((
+                cleanup::CleanupPlugin,
+                set_pieces::SetPiecePlugin,
+                breakable::BreakablePlugin,
+                doors::DoorPlugin,
+                endless::EndlessRunPlugin,
+                camera_shake::CameraShakePlugin,
+                camera_director::CameraDirectorPlugin,
+                camera_zoom::CameraZoomPlugin,
+                ambient::AmbientPlugin,
+                hud::HudPlugin,
+                boss::BossPlugin,
+                map::MapPlugin,
+                inventory::InventoryPlugin,
+                dialogue::DialoguePlugin,
+                music::MusicPlugin,
+            ))
+            .add_plugins((save::SavePlugin, save_slots::SaveSlotsPlugin))
             .add_systems(Startup, setup_camera)
-        .add_systems(Update, paralax_background::monitor_performance);
+            .add_systems(OnEnter(InGame), reset_camera)
+            .add_systems(Update, paralax_background::monitor_performance);
+
+        #[cfg(feature = "avian")]
+        app.add_plugins(avian_backend::AvianBackendPlugin);
     }
 }
 
 fn setup_camera(mut commands: Commands) {
-    commands.spawn(Camera2d { ..default() });
+    commands.spawn(Camera2d);
+}
+
+// Every run starts with the camera where the first one did, wherever the last one left it
+pub fn reset_camera(mut cameras: Query<&mut Transform, With<Camera2d>>) {
+    for mut transform in &mut cameras {
+        *transform = Transform::default();
+    }
 }
@@ -0,0 +1,285 @@
+use bevy::prelude::*;
+
+use crate::game::{self, GameState, InGame};
+use crate::hitbox::DeathEvent;
+use crate::intro;
+use crate::layers::UiLayer;
+use crate::level::Bench;
+use crate::physics::Physics;
+use crate::player::{self, Player};
+
+// Game over constants
+const GAME_OVER_DELAY: f32 = 1.6; // Lets the death animation play out before the screen comes up
+const CHECKPOINT_RANGE: f32 = 60.0; // How close to a bench the player has to stand to save there
+const GAME_OVER_OVERLAY_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.75);
+const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
+const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
+
+// Deaths since the game was started from the main menu
+#[derive(Resource, Default)]
+pub struct DeathCount(pub u32);
+
+// Last bench the player stood at; a retry starts there instead of at the start of the game,
+// and so does continuing from the main menu
+#[derive(Resource, Default)]
+pub struct Checkpoint {
+    pub position: Option<Vec2>,
+}
+
+// Sent each time the player arrives at a bench, which becomes the checkpoint
+#[derive(Event)]
+pub struct CheckpointReachedEvent {
+    pub bench: String,
+    pub room_id: String,
+    pub position: Vec2,
+}
+
+// Counts down from the player's death to the game over screen
+#[derive(Resource, Default)]
+pub struct PlayerDeath {
+    timer: Option<Timer>,
+}
+
+// Run condition for player controls, which stop once the player dies
+pub fn player_alive(death: Res<PlayerDeath>) -> bool {
+    death.timer.is_none()
+}
+
+#[derive(Component)]
+struct RetryButton;
+
+#[derive(Component)]
+struct QuitButton;
+
+pub struct GameOverPlugin;
+
+impl Plugin for GameOverPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DeathCount>()
+            .init_resource::<Checkpoint>()
+            .add_event::<CheckpointReachedEvent>()
+            .init_resource::<PlayerDeath>()
+            .add_systems(OnEnter(GameState::Menu), reset_progress)
+            .add_systems(
+                OnEnter(InGame),
+                respawn_at_checkpoint
+                    .after(player::setup_player)
+                    .after(game::reset_camera)
+                    .before(intro::start_intro),
+            )
+            .add_systems(OnExit(InGame), clear_player_death)
+            .add_systems(
+                Update,
+                (record_checkpoints, detect_player_death, update_player_death)
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnEnter(GameState::GameOver), setup_game_over_screen)
+            .add_systems(
+                Update,
+                handle_game_over_buttons.run_if(in_state(GameState::GameOver)),
+            )
+            .add_systems(OnEnter(GameState::Restarting), restart_run);
+    }
+}
+
+// Back on the main menu the death count starts over
+fn reset_progress(mut death_count: ResMut<DeathCount>) {
+    death_count.0 = 0;
+}
+
+fn respawn_at_checkpoint(
+    checkpoint: Res<Checkpoint>,
+    mut players: Query<&mut Transform, With<Player>>,
+    mut cameras: Query<&mut Transform, (With<Camera2d>, Without<Player>)>,
+) {
+    let Some(position) = checkpoint.position else {
+        return;
+    };
+
+    for mut transform in &mut players {
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+    // The intro pans in to wherever the camera starts
+    for mut transform in &mut cameras {
+        transform.translation.x = position.x;
+    }
+}
+
+fn record_checkpoints(
+    mut checkpoint: ResMut<Checkpoint>,
+    mut current_bench: Local<Option<Entity>>,
+    players: Query<(&Transform, &Physics), With<Player>>,
+    benches: Query<(Entity, &Transform, &Bench)>,
+    mut checkpoint_events: EventWriter<CheckpointReachedEvent>,
+) {
+    let Ok((player_transform, physics)) = players.get_single() else {
+        return;
+    };
+
+    // Reached once on arriving at a bench; walking away and coming back reaches it again
+    let player_position = player_transform.translation.truncate();
+    let Some((entity, bench_transform, bench)) = benches
+        .iter()
+        .find(|(_, bench, _)| (bench.translation.x - player_position.x).abs() < CHECKPOINT_RANGE)
+    else {
+        *current_bench = None;
+        return;
+    };
+    if !physics.on_ground || *current_bench == Some(entity) {
+        return;
+    }
+
+    *current_bench = Some(entity);
+    let position = Vec2::new(bench_transform.translation.x, player_position.y.round());
+    checkpoint.position = Some(position);
+    checkpoint_events.send(CheckpointReachedEvent {
+        bench: bench.name.clone(),
+        room_id: bench.room_id.clone(),
+        position,
+    });
+}
+
+fn detect_player_death(
+    mut death_events: EventReader<DeathEvent>,
+    mut death: ResMut<PlayerDeath>,
+    mut death_count: ResMut<DeathCount>,
+    players: Query<(), With<Player>>,
+) {
+    for event in death_events.read() {
+        if players.contains(event.entity) && death.timer.is_none() {
+            death.timer = Some(Timer::from_seconds(GAME_OVER_DELAY, TimerMode::Once));
+            death_count.0 += 1;
+        }
+    }
+}
+
+fn update_player_death(
+    time: Res<Time>,
+    mut death: ResMut<PlayerDeath>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(timer) = death.timer.as_mut() else {
+        return;
+    };
+
+    timer.tick(time.delta());
+    if timer.finished() {
+        next_state.set(GameState::GameOver);
+    }
+}
+
+fn clear_player_death(mut death: ResMut<PlayerDeath>) {
+    death.timer = None;
+}
+
+fn setup_game_over_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    death_count: Res<DeathCount>,
+) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(24.0),
+                ..default()
+            },
+            BackgroundColor(GAME_OVER_OVERLAY_COLOR),
+            GlobalZIndex(UiLayer::Overlay.z_index()),
+            StateScoped(GameState::GameOver),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("GAME OVER"),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 48.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+            parent.spawn((
+                Text::new(format!("Deaths: {}", death_count.0)),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.7, 0.7, 0.7)),
+            ));
+
+            spawn_game_over_button(parent, &font, "Retry", RetryButton);
+            spawn_game_over_button(parent, &font, "Main Menu", QuitButton);
+        });
+}
+
+fn spawn_game_over_button(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    label: &str,
+    marker: impl Component,
+) {
+    parent
+        .spawn((
+            Button,
+            marker,
+            Node {
+                width: Val::Px(180.0),
+                height: Val::Px(65.0),
+                border: UiRect::all(Val::Px(5.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BorderColor(Color::BLACK),
+            BorderRadius::MAX,
+            BackgroundColor(NORMAL_BUTTON),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(label),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
+}
+
+// Both ways out end the run, so everything spawned for it is cleaned up on the way
+#[allow(clippy::type_complexity)]
+fn handle_game_over_buttons(
+    mut next_state: ResMut<NextState<GameState>>,
+    mut interaction_query: Query<
+        (
+            &Interaction,
+            &mut BackgroundColor,
+            Has<RetryButton>,
+            Has<QuitButton>,
+        ),
+        Changed<Interaction>,
+    >,
+) {
+    for (interaction, mut color, retry, quit) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed if retry => next_state.set(GameState::Restarting),
+            Interaction::Pressed if quit => next_state.set(GameState::Menu),
+            Interaction::Hovered => *color = HOVERED_BUTTON.into(),
+            _ => *color = NORMAL_BUTTON.into(),
+        }
+    }
+}
+
+fn restart_run(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::Playing);
+}
@@ -1,209 +1,175 @@
-use crate::game::GameState;
-use crate::physics::Physics;
-use crate::resolution::{GROUND_HEIGHT_RATIO, Resolution};
+use crate::animations::CharacterDimensions;
+use crate::config::GameConfig;
+use crate::enemy::SightBlocker;
+use crate::game::InGame;
+use crate::layers::Layer;
+use crate::level::GROUND_TOP_Y;
+use crate::physics::{KinematicCharacter, Physics, SurfaceContact, SurfaceMaterial};
+use crate::resolution::PIXEL_RATIO;
 use bevy::prelude::*;
 
 // Ground Constants
-const GROUND_HEIGHT: f32 = 19.0;
-const PLAYER_FEET_OFFSET: f32 = 25.0;
-const ENEMY_FEET_OFFSET: f32 = 32.0;
-const GROUND_REPEAT: i32 = 28;
+const GROUND_TEXTURE: &str = "world/levels/1/ground/ground-230x19.png";
 const GROUND_SCALE_FACTOR: f32 = 1.8;
 const GROUND_TILE_SIZE: UVec2 = UVec2::new(19, 19);
 const GROUND_TILE_COLUMNS: u32 = 19;
 const GROUND_TILE_ROWS: u32 = 1;
 const GROUND_DEFAULT_TILE_INDEX: usize = 3;
-const GROUND_COLLISION_TOLERANCE: f32 = 10.0;
-const GROUND_COLLISION_RANGE: f32 = 15.0;
+const GROUND_THICKNESS: f32 = 19.0 * GROUND_SCALE_FACTOR * PIXEL_RATIO; // One tile
+
+// Ground tile tints per surface material
+pub const ICE_TINT: Color = Color::srgb(0.7, 0.9, 1.0);
+pub const MUD_TINT: Color = Color::srgb(0.6, 0.45, 0.3);
 
 pub struct GroundPlugin;
 
 impl Plugin for GroundPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_ground).add_systems(
-            Update,
-            (
-                update_ground_position,
-                ground_collision,
-                check_characters_out_of_screen,
-            )
-                .run_if(in_state(GameState::Playing)),
-        );
+        app.init_resource::<GroundArt>();
     }
 }
 
-// Component to identify ground sprites
+// Box characters stand on, centered on its transform: a span of the ground strip or a
+// segment placed by the level at any height
 #[derive(Component)]
 pub struct Ground {
-    pub sprite_width: f32,
-    pub original_position: Vec3,
-    pub position_index: i32,
+    pub size: Vec2, // World units, scale already applied
 }
 
-fn setup_ground(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-    resolution: Res<Resolution>,
-    windows: Query<&Window>,
-) {
-    let window = windows.single();
-    let window_height = window.height();
-
-    // Cargar la imagen del tileset
-    let texture_handle = asset_server.load("world/levels/1/ground/ground-230x19.png");
+impl Ground {
+    pub fn top(&self, transform: &Transform) -> f32 {
+        transform.translation.y + self.size.y / 2.0
+    }
+}
 
-    // Usar 6x6 grilla con tiles de 160x160 px
-    let ground_atlas = TextureAtlasLayout::from_grid(
-        GROUND_TILE_SIZE,
-        GROUND_TILE_COLUMNS,
-        GROUND_TILE_ROWS,
-        None,
-        None,
-    );
-    let ground_atlas_layout = texture_atlas_layouts.add(ground_atlas);
+// Tileset the ground strip is drawn with
+#[derive(Resource)]
+pub struct GroundArt {
+    texture: Handle<Image>,
+    atlas_layout: Handle<TextureAtlasLayout>,
+}
 
-    // Escalado y posicionamiento
-    let scale_factor = resolution.pixel_ratio * GROUND_SCALE_FACTOR;
-    let scaled_width = GROUND_TILE_SIZE.x as f32 * scale_factor;
-    let ground_height = -window_height * GROUND_HEIGHT_RATIO;
+impl FromWorld for GroundArt {
+    fn from_world(world: &mut World) -> Self {
+        let texture = world.resource::<AssetServer>().load(GROUND_TEXTURE);
+        let atlas_layout =
+            world
+                .resource_mut::<Assets<TextureAtlasLayout>>()
+                .add(TextureAtlasLayout::from_grid(
+                    GROUND_TILE_SIZE,
+                    GROUND_TILE_COLUMNS,
+                    GROUND_TILE_ROWS,
+                    None,
+                    None,
+                ));
+        Self {
+            texture,
+            atlas_layout,
+        }
+    }
+}
 
-    // Entidad padre
-    let ground_parent = commands
+// Stretch of the ground strip between two x positions, its top at GROUND_TOP_Y. The
+// tiles are stretched a little so a whole number of them covers the span exactly
+pub fn spawn_ground_span(
+    commands: &mut Commands,
+    art: &GroundArt,
+    min_x: f32,
+    max_x: f32,
+    material: SurfaceMaterial,
+) -> Entity {
+    let size = Vec2::new(max_x - min_x, GROUND_THICKNESS);
+    let tiles = (size.x / GROUND_THICKNESS).ceil().max(1.0);
+    let tile_width = size.x / tiles;
+    let tint = match material {
+        SurfaceMaterial::Stone => Color::WHITE,
+        SurfaceMaterial::Ice => ICE_TINT,
+        SurfaceMaterial::Mud => MUD_TINT,
+    };
+
+    commands
         .spawn((
-            Transform::default(),
+            StateScoped(InGame),
+            Ground { size },
+            material,
+            // Enemies can't see through the ground
+            SightBlocker { size },
+            Transform::from_xyz(
+                (min_x + max_x) / 2.0,
+                GROUND_TOP_Y - GROUND_THICKNESS / 2.0,
+                Layer::Ground.z(),
+            ),
             Visibility::default(),
-            InheritedVisibility::default(),
-            ViewVisibility::default(),
         ))
-        .id();
-
-    // Crear los bloques de suelo
-    commands.entity(ground_parent).with_children(|parent| {
-        for i in 0..=GROUND_REPEAT {
-            let x_pos = i as f32 * scaled_width;
-
-            parent.spawn((
-                Sprite::from_atlas_image(
-                    texture_handle.clone(),
-                    TextureAtlas {
-                        layout: ground_atlas_layout.clone(),
-                        index: GROUND_DEFAULT_TILE_INDEX,
+        .with_children(|parent| {
+            for index in 0..tiles as usize {
+                parent.spawn((
+                    Sprite {
+                        image: art.texture.clone(),
+                        texture_atlas: Some(TextureAtlas {
+                            layout: art.atlas_layout.clone(),
+                            index: GROUND_DEFAULT_TILE_INDEX,
+                        }),
+                        color: tint,
+                        custom_size: Some(Vec2::new(tile_width, GROUND_THICKNESS)),
+                        ..default()
                     },
-                ),
-                Transform::from_xyz(x_pos, ground_height, 10.0).with_scale(Vec3::new(
-                    scale_factor,
-                    scale_factor,
-                    1.0,
-                )),
-                Ground {
-                    sprite_width: scaled_width,
-                    original_position: Vec3::new(x_pos, ground_height, 10.0),
-                    position_index: i as i32 - 14,
-                },
-                Visibility::default(),
-                InheritedVisibility::default(),
-                ViewVisibility::default(),
-            ));
-        }
-    });
-}
-
-fn update_ground_position(
-    mut ground_query: Query<(&mut Transform, &mut Ground), Without<Camera2d>>,
-    camera_query: Query<&Transform, With<Camera2d>>,
-    windows: Query<&Window>,
-) {
-    let window = windows.single();
-    let window_width = window.width();
-
-    if let Ok(camera_transform) = camera_query.get_single() {
-        let camera_x = camera_transform.translation.x;
-
-        for (mut transform, mut ground) in ground_query.iter_mut() {
-            // The ground stays fixed to world position (no parallax effect)
-            // But we need to reposition the sprites to create an infinite ground
-
-            // Check if ground piece is off-screen
-            let half_window = window_width / 2.0;
-
-            if transform.translation.x < camera_x - half_window - (ground.sprite_width / 2.0) {
-                // This ground piece is off-screen to the left, move it to the right
-                transform.translation.x += ground.sprite_width * GROUND_REPEAT as f32;
-
-                // Update position index
-                ground.position_index += GROUND_REPEAT;
-
-                // Update original position
-                ground.original_position.x = transform.translation.x;
-            } else if transform.translation.x > camera_x + half_window + (ground.sprite_width / 2.0)
-            {
-                // This ground piece is off-screen to the right, move it to the left
-                transform.translation.x -= ground.sprite_width * GROUND_REPEAT as f32;
-
-                // Update position index
-                ground.position_index -= GROUND_REPEAT;
-
-                // Update original position
-                ground.original_position.x = transform.translation.x;
+                    Transform::from_xyz(
+                        -size.x / 2.0 + tile_width * (index as f32 + 0.5),
+                        0.0,
+                        0.0,
+                    ),
+                ));
             }
-        }
-    }
+        })
+        .id()
 }
 
+#[allow(clippy::type_complexity)]
 pub fn ground_collision(
-    ground_query: Query<(&Transform, &Ground)>,
-    mut characters_query: Query<(Entity, &mut Transform, &mut Physics), Without<Ground>>,
+    config: Res<GameConfig>,
+    ground_query: Query<(&Transform, &Ground, &SurfaceMaterial)>,
+    mut characters_query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Physics,
+            &CharacterDimensions,
+            Option<&mut SurfaceContact>,
+        ),
+        (Without<Ground>, Without<KinematicCharacter>),
+    >,
 ) {
     // Procesar cada entidad (jugador o enemigo) individualmente
-    for (_entity, mut character_transform, mut physics) in characters_query.iter_mut() {
+    for (_entity, mut character_transform, mut physics, dimensions, mut surface_contact) in
+        characters_query.iter_mut()
+    {
         physics.on_ground = false;
-        let character_scale = character_transform.scale.y.abs();
-
-        // Check if this entity is the player based on its Z position
-        // Player is at Z=0, enemies are at Z=5
-        let is_player = character_transform.translation.z == 0.0;
-        // Use the appropriate feet offset based on entity type
-        let feet_offset = if is_player {
-            PLAYER_FEET_OFFSET
-        } else {
-            ENEMY_FEET_OFFSET
-        };
+        let character_scale = character_transform.scale.y;
 
-        // Calculate the feet position using the appropriate offset
+        // Each entity knows where its feet are, players and enemies alike
+        let feet_offset = dimensions.feet_offset;
         let character_feet = character_transform.translation.y - feet_offset * character_scale;
 
-        for (ground_transform, ground) in ground_query.iter() {
-            let ground_scale = ground_transform.scale.y.abs();
-            let ground_top = ground_transform.translation.y + (GROUND_HEIGHT / 2.0) * ground_scale;
+        for (ground_transform, ground, surface) in ground_query.iter() {
+            // Strip spans and raised or floating segments alike, wherever their top is
+            let ground_top = ground.top(ground_transform);
             if physics.velocity.y <= 0.0
-                && character_feet <= ground_top + GROUND_COLLISION_TOLERANCE
-                && character_feet >= ground_top - GROUND_COLLISION_RANGE
+                && character_feet <= ground_top + config.ground.collision_tolerance
+                && character_feet >= ground_top - config.ground.collision_range
                 && (character_transform.translation.x - ground_transform.translation.x).abs()
-                    < ground.sprite_width / 2.0
+                    < ground.size.x / 2.0
             {
                 // Adjust character position based on its feet offset
                 character_transform.translation.y = ground_top + feet_offset * character_scale;
 
                 physics.velocity.y = 0.0;
                 physics.on_ground = true;
+                if let Some(contact) = surface_contact.as_mut() {
+                    contact.surface = *surface;
+                }
                 break;
             }
         }
     }
 }
-
-pub fn check_characters_out_of_screen(
-    mut characters_query: Query<(Entity, &mut Transform), Without<Ground>>,
-    windows: Query<&Window>,
-) {
-    let window = windows.single();
-    let window_height = window.height();
-
-    for (_, mut character_transform) in characters_query.iter_mut() {
-        if character_transform.translation.y < -window_height / 2.0 {
-            // Character is off-screen to the left, move it to the right
-            character_transform.translation.y = window_height / 2.0;
-        }
-    }
-}
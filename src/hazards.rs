@@ -0,0 +1,259 @@
+use bevy::prelude::*;
+
+use crate::animations::{AnimationController, CharacterState};
+use crate::enemy::Enemy;
+use crate::fade::ScreenFade;
+use crate::game::{GameState, InGame};
+use crate::game_over;
+use crate::hitbox::{DamageEvent, HitReaction};
+use crate::level::RoomRegistry;
+use crate::paralax_background::CameraHold;
+use crate::physics::{BodyCollider, Physics};
+use crate::player::Player;
+use crate::utils;
+
+// Hazard respawn constants
+pub const DEATH_BARRIER_Y: f32 = -360.0; // Below the ground top: anything under it fell in a pit
+const SAFE_GROUND_PIT_MARGIN: f32 = 120.0; // Respawn points keep this far away from any pit
+const HAZARD_HOLD_DURATION: f32 = 0.35; // Camera holds on the pit edge before fading
+const HAZARD_FADE_OUT_DURATION: f32 = 0.4;
+const HAZARD_FADE_IN_DURATION: f32 = 0.5;
+const HAZARD_DAMAGE: f32 = 10.0; // One mask
+const HAZARD_TOUCH_HOLD_DURATION: f32 = 0.25; // Frozen on the spikes before fading
+
+// Steps of the fall-into-a-pit flow
+#[derive(Default)]
+enum HazardPhase {
+    #[default]
+    Inactive,
+    Holding(Timer),
+    FadingOut,
+    FadingIn,
+}
+
+// Last safe spot the player stood on and where a hazard respawn is in its flow
+#[derive(Resource, Default)]
+struct HazardRespawn {
+    phase: HazardPhase,
+    safe_position: Vec2,
+    frozen_at: Vec2,
+    respawn_damage: f32, // Pits hurt on the way back, spikes already hurt on touch
+}
+
+// Spikes, acid and anything else that hurts on touch and sends the player back to safe
+// ground, like falling into a pit does
+#[derive(Component)]
+pub struct Hazard {
+    pub size: Vec2,
+}
+
+pub struct HazardPlugin;
+
+impl Plugin for HazardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HazardRespawn>()
+            .add_systems(
+                Update,
+                (
+                    remove_fallen_bodies,
+                    (remember_safe_ground, touch_hazards, update_hazard_respawn)
+                        .chain()
+                        .run_if(game_over::player_alive),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnExit(InGame), abandon_hazard_respawn);
+    }
+}
+
+// Leaving the run halfway through a respawn mustn't leave the screen black over the menu
+fn abandon_hazard_respawn(mut respawn: ResMut<HazardRespawn>, mut fade: ResMut<ScreenFade>) {
+    *respawn = HazardRespawn::default();
+    fade.fade_in(0.0);
+}
+
+// Anything other than the player that falls into a pit is gone for good. Enemies go
+// through their own death instead, so their spawn points and death effects see it
+#[allow(clippy::type_complexity)]
+fn remove_fallen_bodies(
+    mut commands: Commands,
+    bodies: Query<(Entity, &Transform), (With<Physics>, Without<Player>, Without<Enemy>)>,
+) {
+    for (entity, transform) in &bodies {
+        if transform.translation.y < DEATH_BARRIER_Y {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+// Remember solid ground far enough from any pit or hazard to come back to
+fn remember_safe_ground(
+    registry: Res<RoomRegistry>,
+    mut hazard: ResMut<HazardRespawn>,
+    hazards: Query<(&Transform, &Hazard)>,
+    player_query: Query<(&Transform, &Physics, &BodyCollider), With<Player>>,
+) {
+    let Ok((transform, physics, collider)) = player_query.get_single() else {
+        return;
+    };
+    if !matches!(hazard.phase, HazardPhase::Inactive) || !physics.on_ground {
+        return;
+    }
+
+    let player_pos = transform.translation.truncate();
+    let margin = collider.size + Vec2::splat(SAFE_GROUND_PIT_MARGIN * 2.0);
+    let near_hazard = hazards.iter().any(|(hazard_transform, hazard)| {
+        utils::check_rect_collision(
+            player_pos,
+            margin,
+            hazard_transform.translation.truncate(),
+            hazard.size,
+        )
+    });
+    if !near_hazard
+        && !registry.has_pit_between(
+            player_pos.x - SAFE_GROUND_PIT_MARGIN,
+            player_pos.x + SAFE_GROUND_PIT_MARGIN,
+        )
+    {
+        hazard.safe_position = player_pos;
+    }
+}
+
+// Touching a hazard costs a mask right away, then the player is frozen there and
+// brought back to safe ground. i-frames cover the trip back, and a player still flashing
+// from another hit passes over hazards unhurt
+fn touch_hazards(
+    mut hazard: ResMut<HazardRespawn>,
+    hazards: Query<(&Transform, &Hazard)>,
+    mut player_query: Query<(Entity, &mut Player, &Transform, &BodyCollider)>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    let Ok((entity, mut player, transform, collider)) = player_query.get_single_mut() else {
+        return;
+    };
+    if !matches!(hazard.phase, HazardPhase::Inactive) || !player.hurt_timer.finished() {
+        return;
+    }
+
+    let player_pos = transform.translation.truncate();
+    let touching = hazards.iter().any(|(hazard_transform, hazard)| {
+        utils::check_rect_collision(
+            player_pos,
+            collider.size,
+            hazard_transform.translation.truncate(),
+            hazard.size,
+        )
+    });
+    if !touching {
+        return;
+    }
+
+    damage_events.send(DamageEvent {
+        target: entity,
+        source: None,
+        amount: HAZARD_DAMAGE,
+        position: player_pos,
+        reaction: Some(HitReaction::Flinch),
+        status: None,
+    });
+    player.hurt_timer.reset();
+
+    hazard.frozen_at = player_pos;
+    hazard.respawn_damage = 0.0;
+    hazard.phase = HazardPhase::Holding(Timer::from_seconds(
+        HAZARD_TOUCH_HOLD_DURATION,
+        TimerMode::Once,
+    ));
+}
+
+#[allow(clippy::type_complexity)]
+fn update_hazard_respawn(
+    time: Res<Time>,
+    mut hazard: ResMut<HazardRespawn>,
+    mut fade: ResMut<ScreenFade>,
+    mut camera_hold: ResMut<CameraHold>,
+    mut player_query: Query<
+        (
+            Entity,
+            &mut Player,
+            &mut Transform,
+            &mut Physics,
+            &mut AnimationController,
+        ),
+        Without<Camera2d>,
+    >,
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    let Ok((entity, mut player, mut transform, mut physics, mut animation_controller)) =
+        player_query.get_single_mut()
+    else {
+        return;
+    };
+    let player_pos = transform.translation.truncate();
+
+    match &mut hazard.phase {
+        HazardPhase::Inactive => {
+            if player_pos.y < DEATH_BARRIER_Y {
+                hazard.frozen_at = player_pos;
+                hazard.respawn_damage = HAZARD_DAMAGE;
+                hazard.phase = HazardPhase::Holding(Timer::from_seconds(
+                    HAZARD_HOLD_DURATION,
+                    TimerMode::Once,
+                ));
+                camera_hold.active = true;
+            }
+        }
+        HazardPhase::Holding(timer) => {
+            timer.tick(time.delta());
+            if timer.finished() {
+                fade.fade_out(HAZARD_FADE_OUT_DURATION);
+                hazard.phase = HazardPhase::FadingOut;
+            }
+        }
+        HazardPhase::FadingOut => {
+            if fade.is_finished() {
+                // Back on safe ground while the screen is black
+                transform.translation.x = hazard.safe_position.x;
+                transform.translation.y = hazard.safe_position.y;
+                physics.velocity = Vec2::ZERO;
+                // Through the usual damage path, so a fall can be the one that kills
+                if hazard.respawn_damage > 0.0 {
+                    damage_events.send(DamageEvent {
+                        target: entity,
+                        source: None,
+                        amount: hazard.respawn_damage,
+                        position: hazard.safe_position,
+                        reaction: None,
+                        status: None,
+                    });
+                }
+                player.hurt_timer.reset();
+                animation_controller.change_state(CharacterState::Idle);
+
+                if let Ok(mut camera_transform) = camera_query.get_single_mut() {
+                    camera_transform.translation.x = hazard.safe_position.x;
+                }
+                camera_hold.active = false;
+
+                fade.fade_in(HAZARD_FADE_IN_DURATION);
+                hazard.phase = HazardPhase::FadingIn;
+                return;
+            }
+        }
+        HazardPhase::FadingIn => {
+            if fade.is_finished() {
+                hazard.phase = HazardPhase::Inactive;
+            }
+            return;
+        }
+    }
+
+    // Keep the player still below the edge until the respawn happens
+    if !matches!(hazard.phase, HazardPhase::Inactive) {
+        transform.translation.x = hazard.frozen_at.x;
+        transform.translation.y = hazard.frozen_at.y;
+        physics.velocity = Vec2::ZERO;
+    }
+}
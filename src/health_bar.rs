@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+
+use crate::enemy::Enemy;
+use crate::game::GameState;
+
+// Health bar constants (local units, scaled with the enemy)
+const HEALTH_BAR_WIDTH: f32 = 36.0;
+const HEALTH_BAR_HEIGHT: f32 = 3.0;
+const HEALTH_BAR_BORDER: f32 = 1.0;
+const HEALTH_BAR_OFFSET_Y: f32 = 26.0;
+const HEALTH_BAR_Z: f32 = 1.0;
+const HEALTH_BAR_BACKGROUND_COLOR: Color = Color::srgba(0.05, 0.05, 0.05, 0.8);
+const HEALTH_BAR_FILL_COLOR: Color = Color::srgb(0.8, 0.1, 0.1);
+
+// Root of the world-space health bar attached to an enemy
+#[derive(Component)]
+pub struct EnemyHealthBar;
+
+// Foreground part of the bar that shrinks with health
+#[derive(Component)]
+struct HealthBarFill;
+
+// Meshes and materials shared by every health bar
+#[derive(Resource)]
+struct HealthBarAssets {
+    background_mesh: Handle<Mesh>,
+    fill_mesh: Handle<Mesh>,
+    background_material: Handle<ColorMaterial>,
+    fill_material: Handle<ColorMaterial>,
+}
+
+pub struct HealthBarPlugin;
+
+impl Plugin for HealthBarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_health_bar_assets)
+            .add_systems(
+                Update,
+                (attach_enemy_health_bars, update_enemy_health_bars)
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn setup_health_bar_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    commands.insert_resource(HealthBarAssets {
+        background_mesh: meshes.add(Rectangle::new(
+            HEALTH_BAR_WIDTH + HEALTH_BAR_BORDER * 2.0,
+            HEALTH_BAR_HEIGHT + HEALTH_BAR_BORDER * 2.0,
+        )),
+        fill_mesh: meshes.add(Rectangle::new(HEALTH_BAR_WIDTH, HEALTH_BAR_HEIGHT)),
+        background_material: materials.add(HEALTH_BAR_BACKGROUND_COLOR),
+        fill_material: materials.add(HEALTH_BAR_FILL_COLOR),
+    });
+}
+
+fn attach_enemy_health_bars(
+    mut commands: Commands,
+    assets: Res<HealthBarAssets>,
+    new_enemies: Query<Entity, Added<Enemy>>,
+) {
+    for enemy_entity in &new_enemies {
+        commands.entity(enemy_entity).with_children(|parent| {
+            parent
+                .spawn((
+                    EnemyHealthBar,
+                    Mesh2d(assets.background_mesh.clone()),
+                    MeshMaterial2d(assets.background_material.clone()),
+                    Transform::from_xyz(0.0, HEALTH_BAR_OFFSET_Y, HEALTH_BAR_Z),
+                    Visibility::Hidden,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        HealthBarFill,
+                        Mesh2d(assets.fill_mesh.clone()),
+                        MeshMaterial2d(assets.fill_material.clone()),
+                        Transform::from_xyz(0.0, 0.0, 0.1),
+                    ));
+                });
+        });
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_enemy_health_bars(
+    mut commands: Commands,
+    enemies: Query<&Enemy>,
+    mut bars: Query<
+        (Entity, &Parent, &Children, &mut Visibility),
+        (With<EnemyHealthBar>, Without<Enemy>),
+    >,
+    mut fills: Query<
+        &mut Transform,
+        (With<HealthBarFill>, Without<EnemyHealthBar>, Without<Enemy>),
+    >,
+) {
+    for (bar_entity, parent, children, mut visibility) in &mut bars {
+        let Ok(enemy) = enemies.get(parent.get()) else {
+            continue;
+        };
+
+        if enemy.is_dead {
+            commands.entity(bar_entity).despawn_recursive();
+            continue;
+        }
+
+        *visibility = if enemy.health >= enemy.max_health {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+
+        let ratio = (enemy.health / enemy.max_health).clamp(0.0, 1.0);
+        for &child in children.iter() {
+            if let Ok(mut fill_transform) = fills.get_mut(child) {
+                fill_transform.scale.x = ratio;
+                fill_transform.translation.x = -HEALTH_BAR_WIDTH * (1.0 - ratio) / 2.0;
+            }
+        }
+    }
+}
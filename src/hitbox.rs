@@ -0,0 +1,430 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::animations::{
+    AnimationController, CharacterState, CurrentAnimation, Facing, FacingOffset,
+};
+use crate::enemy::Enemy;
+use crate::game::GameState;
+use crate::physics::{self, Physics};
+use crate::spatial::HitboxGrid;
+use crate::status_effects::StatusEffectKind;
+use crate::utils;
+
+// Hit reaction constants
+const HEAVY_HIT_HEALTH_RATIO: f32 = 0.15; // Hits taking this much of max health stagger
+
+// Knockback constants
+pub const DEFAULT_KNOCKBACK_DECAY: f32 = 10.0;
+const KNOCKBACK_STOP_SPEED: f32 = 20.0; // Pushes slower than this are over
+
+// Attack hitbox component
+#[derive(Component)]
+pub struct AttackHitbox {
+    pub damage: f32,
+    pub active: bool,
+    pub size: Vec2,
+    pub timer: Timer,
+    pub launch: f32,       // Upward impulse for launcher attacks, 0 for regular hits
+    pub guard_break: bool, // Goes through shields even from the front
+    pub reaction: Option<HitReaction>, // None picks one from the damage dealt
+    pub status: Option<StatusEffectKind>, // Left on the target when the hit deals damage
+}
+
+// Push an attack gives whoever it hits, divided by the weight of the target
+#[derive(Component, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Knockback {
+    pub strength: f32, // Horizontal speed away from the attacker
+    pub lift: f32,     // Upward speed, whichever side the hit came from
+    pub decay: f32,    // How fast the horizontal push dies out, per second
+}
+
+impl Knockback {
+    pub fn is_none(&self) -> bool {
+        self.strength == 0.0 && self.lift == 0.0
+    }
+}
+
+// Push still being resolved on a character, overrides its horizontal speed until it dies out
+#[derive(Component)]
+pub struct KnockedBack {
+    velocity: f32,
+    decay: f32,
+}
+
+// Side a character fights on; attacks only land on characters of another faction.
+// Neutral things like hazards hurt both sides
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Faction {
+    Player,
+    Enemy,
+    Neutral,
+}
+
+impl Faction {
+    pub fn is_hostile_to(self, other: Faction) -> bool {
+        self != other
+    }
+}
+
+// Body area that attack hitboxes can land on
+#[derive(Component)]
+pub struct CollisionHitbox {
+    pub active: bool,
+    pub size: Vec2,
+}
+
+// Hitbox whose lifetime is handled by the system that spawned it instead of its timer
+#[derive(Component)]
+pub struct ManagedHitbox;
+
+// Hitbox spawned while an attack animation is on one of the given frames, loaded from
+// the attacks of the character's animation manifest when it lists any
+#[derive(Clone, Debug)]
+pub struct AttackWindow {
+    pub state: CharacterState,
+    pub frames: std::ops::Range<usize>,
+    pub damage_multiplier: f32, // Of the attack stat of the character
+    pub size: Vec2,
+    pub offset: Vec2, // Positive x is in front of the attacker, whichever way it faces
+    pub duration: f32,
+    pub knockback: Knockback,
+    pub launch: f32,
+    pub guard_break: bool,
+    pub reaction: Option<HitReaction>,
+    pub status: Option<StatusEffectKind>,
+}
+
+// How hard a character reels from a hit, each with its own hurt animation
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HitReaction {
+    Flinch,
+    Stagger,
+    Launch,
+}
+
+impl HitReaction {
+    // Reaction for attacks that don't force one, based on how much health the hit took
+    pub fn from_damage(damage: f32, max_health: f32) -> Self {
+        if damage >= max_health * HEAVY_HIT_HEALTH_RATIO {
+            HitReaction::Stagger
+        } else {
+            HitReaction::Flinch
+        }
+    }
+
+    // Hurt animation played for the reaction
+    pub fn state(self) -> CharacterState {
+        match self {
+            HitReaction::Flinch => CharacterState::Hurt,
+            HitReaction::Stagger => CharacterState::Staggered,
+            HitReaction::Launch => CharacterState::Launched,
+        }
+    }
+}
+
+// Every attack an animated character can perform
+#[derive(Component, Clone)]
+#[require(SwingProgress)]
+pub struct AttackSet {
+    pub damage: f32, // Base damage the windows multiply
+    pub windows: Vec<AttackWindow>,
+}
+
+// Windows that already put out their hitbox during the current swing, so each one strikes
+// once however long its frames last. Cleared when the attack animation starts over
+#[derive(Component, Default)]
+pub struct SwingProgress {
+    state: Option<CharacterState>,
+    last_frame: usize,
+    spent: Vec<usize>, // Indices into the attack set's windows
+}
+
+// Sent for each attack hitbox overlapping the body of a foe
+#[derive(Event)]
+pub struct HitEvent {
+    pub attacker: Entity,
+    pub target: Entity,
+    pub damage: f32,
+    pub launch: f32,
+    pub guard_break: bool,
+    pub reaction: Option<HitReaction>,
+    pub status: Option<StatusEffectKind>,
+    pub knockback: Knockback,
+    pub attack_pos: Vec2,
+    pub attacker_pos: Vec2,
+    pub target_pos: Vec2, // Center of the body hitbox that was hit
+    pub target_size: Vec2,
+}
+
+// Health taken from a character once defense and shields have been accounted for
+#[derive(Event)]
+pub struct DamageEvent {
+    pub target: Entity,
+    pub source: Option<Entity>,
+    pub amount: f32,
+    pub position: Vec2,
+    pub reaction: Option<HitReaction>, // None for damage that shouldn't interrupt, like burning
+    pub status: Option<StatusEffectKind>,
+}
+
+// Sent once when a character runs out of health
+#[derive(Event)]
+pub struct DeathEvent {
+    pub entity: Entity,
+    pub position: Vec2,
+}
+
+// Push a hit gives the character it lands on
+#[derive(Event)]
+pub struct KnockbackEvent {
+    pub target: Entity,
+    pub direction: f32, // 1 pushes right, -1 left
+    pub knockback: Knockback,
+}
+
+pub struct HitboxPlugin;
+
+impl Plugin for HitboxPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<HitEvent>()
+            .add_event::<DamageEvent>()
+            .add_event::<DeathEvent>()
+            .add_event::<KnockbackEvent>()
+            .add_systems(
+                Update,
+                (tick_attack_hitboxes, spawn_attack_hitboxes, detect_hits)
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                (
+                    apply_knockback.before(physics::apply_surface_traction),
+                    resolve_knockback
+                        .after(apply_knockback)
+                        .after(physics::apply_surface_traction),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn tick_attack_hitboxes(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut hitboxes: Query<(Entity, &mut AttackHitbox), Without<ManagedHitbox>>,
+) {
+    for (entity, mut hitbox) in &mut hitboxes {
+        hitbox.timer.tick(time.delta());
+
+        if hitbox.timer.finished() {
+            hitbox.active = false;
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+// One hitbox per attack window and swing, removed as soon as the attack animation ends
+#[allow(clippy::type_complexity)]
+fn spawn_attack_hitboxes(
+    mut commands: Commands,
+    mut attackers: Query<(
+        Entity,
+        &AnimationController,
+        &CurrentAnimation,
+        &AttackSet,
+        &mut SwingProgress,
+        Option<&Facing>,
+    )>,
+    hitboxes: Query<(Entity, &Parent), (With<AttackHitbox>, Without<ManagedHitbox>)>,
+) {
+    for (entity, animation_controller, current_animation, attack_set, mut progress, facing) in
+        &mut attackers
+    {
+        let current_state = animation_controller.get_current_state();
+        let is_attacking = matches!(
+            current_state,
+            CharacterState::Attacking | CharacterState::ChargeAttacking
+        );
+
+        if !is_attacking {
+            for (hitbox_entity, parent) in &hitboxes {
+                if parent.get() == entity {
+                    commands.entity(hitbox_entity).despawn_recursive();
+                }
+            }
+            progress.state = None;
+            progress.spent.clear();
+            continue;
+        }
+
+        // Another attack, or the same one played again from its first frame
+        let frame = current_animation.current_frame;
+        if progress.state != Some(current_state) || frame < progress.last_frame {
+            progress.spent.clear();
+        }
+        progress.state = Some(current_state);
+        progress.last_frame = frame;
+
+        let Some((index, window)) =
+            attack_set
+                .windows
+                .iter()
+                .enumerate()
+                .find(|(index, window)| {
+                    window.state == current_state
+                        && window.frames.contains(&frame)
+                        && !progress.spent.contains(index)
+                })
+        else {
+            continue;
+        };
+        progress.spent.push(index);
+
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn((
+                AttackHitbox {
+                    damage: attack_set.damage * window.damage_multiplier,
+                    active: true,
+                    size: window.size,
+                    timer: Timer::from_seconds(window.duration, TimerMode::Once),
+                    launch: window.launch,
+                    guard_break: window.guard_break,
+                    reaction: window.reaction,
+                    status: window.status,
+                },
+                window.knockback,
+                FacingOffset(window.offset),
+                Transform::from_translation(
+                    facing
+                        .copied()
+                        .unwrap_or_default()
+                        .mirror(window.offset)
+                        .extend(0.0),
+                ),
+            ));
+        });
+    }
+}
+
+// Where a hitbox is this frame, from its owner's transform. The global one is only
+// propagated after Update, so it lags the fixed step and is still zero on a hitbox
+// spawned this frame
+pub fn hitbox_position(owner: &Transform, hitbox: &Transform) -> Vec2 {
+    owner.transform_point(hitbox.translation).truncate()
+}
+
+// Attacks and bodies whose owner has no faction are left out
+#[allow(clippy::type_complexity)]
+pub fn detect_hits(
+    grid: Res<HitboxGrid>,
+    attack_hitboxes: Query<(&AttackHitbox, Option<&Knockback>, &Transform, &Parent)>,
+    body_hitboxes: Query<(&CollisionHitbox, &Transform, &Parent)>,
+    owners: Query<&Transform>,
+    factions: Query<&Faction>,
+    mut hit_events: EventWriter<HitEvent>,
+) {
+    let is_foe = |a: Entity, b: Entity| match (factions.get(a), factions.get(b)) {
+        (Ok(a), Ok(b)) => a.is_hostile_to(*b),
+        _ => false,
+    };
+
+    for (attack, knockback, attack_transform, attacker) in &attack_hitboxes {
+        if !attack.active {
+            continue;
+        }
+        let Ok(attacker_transform) = owners.get(attacker.get()) else {
+            continue;
+        };
+        let attack_pos = hitbox_position(attacker_transform, attack_transform);
+
+        // Only the bodies around the attack, not every one in the level
+        for candidate in grid.0.query(attack_pos, attack.size) {
+            let Ok((body, body_transform, target)) = body_hitboxes.get(candidate) else {
+                continue;
+            };
+            if !body.active || !is_foe(attacker.get(), target.get()) {
+                continue;
+            }
+            let Ok(target_transform) = owners.get(target.get()) else {
+                continue;
+            };
+
+            let target_pos = hitbox_position(target_transform, body_transform);
+            if !utils::check_rect_collision(target_pos, body.size, attack_pos, attack.size) {
+                continue;
+            }
+
+            let attacker_pos = attacker_transform.translation.truncate();
+            hit_events.send(HitEvent {
+                attacker: attacker.get(),
+                target: target.get(),
+                damage: attack.damage,
+                launch: attack.launch,
+                guard_break: attack.guard_break,
+                reaction: attack.reaction,
+                status: attack.status,
+                knockback: knockback.copied().unwrap_or_default(),
+                attack_pos,
+                attacker_pos,
+                target_pos,
+                target_size: body.size,
+            });
+        }
+    }
+}
+
+// Heavier enemies are pushed less far; the player and companions weigh 1
+pub fn apply_knockback(
+    mut commands: Commands,
+    mut knockback_events: EventReader<KnockbackEvent>,
+    mut bodies: Query<(&mut Physics, Option<&Enemy>)>,
+) {
+    for event in knockback_events.read() {
+        let Ok((mut physics, enemy)) = bodies.get_mut(event.target) else {
+            continue;
+        };
+
+        let weight = enemy.map_or(1.0, |enemy| enemy.weight);
+        let velocity = event.direction * event.knockback.strength / weight;
+        physics.velocity.x = velocity;
+        if event.knockback.lift > 0.0 {
+            physics.velocity.y = event.knockback.lift / weight;
+            physics.on_ground = false;
+        }
+
+        commands.entity(event.target).insert(KnockedBack {
+            velocity,
+            decay: event.knockback.decay,
+        });
+    }
+}
+
+// Runs after traction so the surface doesn't eat the push, and after movement so it wins
+fn resolve_knockback(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut bodies: Query<(Entity, &mut KnockedBack, &mut Physics)>,
+) {
+    for (entity, mut knocked_back, mut physics) in &mut bodies {
+        physics.velocity.x = knocked_back.velocity;
+
+        knocked_back.velocity *= (-knocked_back.decay * time.delta_secs()).exp();
+        if knocked_back.velocity.abs() < KNOCKBACK_STOP_SPEED {
+            commands.entity(entity).remove::<KnockedBack>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hitbox_follows_its_owner_scale_and_position() {
+        let owner = Transform::from_xyz(100.0, -50.0, 5.0).with_scale(Vec3::splat(2.0));
+        let hitbox = Transform::from_xyz(10.0, -4.0, 0.0);
+        assert_eq!(hitbox_position(&owner, &hitbox), Vec2::new(120.0, -58.0));
+    }
+}
@@ -0,0 +1,291 @@
+use bevy::prelude::*;
+
+use crate::game::{GameState, InGame};
+use crate::layers::UiLayer;
+use crate::player::Player;
+
+// HUD constants
+const HUD_MARGIN: f32 = 24.0;
+const HUD_GAP: f32 = 12.0;
+const HEALTH_PER_MASK: f32 = 20.0;
+const MASK_SIZE: Vec2 = Vec2::new(22.0, 28.0);
+const MASK_GAP: f32 = 6.0;
+const MASK_BREAK_TIME: f32 = 0.35; // Flash of a mask that just emptied
+const MASK_FULL_COLOR: Color = Color::srgb(0.95, 0.95, 0.97);
+const MASK_EMPTY_COLOR: Color = Color::srgba(0.2, 0.2, 0.25, 0.7);
+const MASK_BREAK_COLOR: Color = Color::srgb(1.0, 0.35, 0.3);
+const SOUL_VESSEL_SIZE: f32 = 64.0;
+const SOUL_VESSEL_BORDER: f32 = 3.0;
+const SOUL_VESSEL_COLOR: Color = Color::srgba(0.05, 0.05, 0.08, 0.8);
+const SOUL_FILL_COLOR: Color = Color::srgb(0.85, 0.9, 1.0);
+const CURRENCY_ICON_SIZE: f32 = 12.0;
+const CURRENCY_COLOR: Color = Color::srgb(0.95, 0.8, 0.3);
+const CURRENCY_FONT_SIZE: f32 = 22.0;
+
+// Root of the heads-up display, spawned for each run
+#[derive(Component)]
+struct Hud;
+
+// Row the health masks are laid out in, rebuilt when max health changes
+#[derive(Component)]
+struct HealthMaskRow {
+    masks: usize,
+}
+
+// One mask worth HEALTH_PER_MASK health, drained from the right
+#[derive(Component)]
+struct HealthMask {
+    index: usize,
+    filled: bool,
+    break_timer: Option<Timer>,
+}
+
+// Part of a mask that shrinks as its share of health goes
+#[derive(Component)]
+struct HealthMaskFill {
+    index: usize,
+}
+
+#[derive(Component)]
+struct SoulFill;
+
+#[derive(Component)]
+struct CurrencyText;
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(InGame), spawn_hud).add_systems(
+            Update,
+            (
+                rebuild_health_masks,
+                update_health_masks,
+                update_mask_breaks,
+                update_soul_vessel,
+                update_currency_counter,
+            )
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+fn spawn_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+
+    commands
+        .spawn((
+            Hud,
+            StateScoped(InGame),
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(HUD_MARGIN),
+                top: Val::Px(HUD_MARGIN),
+                column_gap: Val::Px(HUD_GAP),
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            GlobalZIndex(UiLayer::Hud.z_index()),
+        ))
+        .with_children(|parent| {
+            // Soul vessel, filled from the bottom
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(SOUL_VESSEL_SIZE),
+                        height: Val::Px(SOUL_VESSEL_SIZE),
+                        border: UiRect::all(Val::Px(SOUL_VESSEL_BORDER)),
+                        align_items: AlignItems::End,
+                        overflow: Overflow::clip(),
+                        ..default()
+                    },
+                    BorderColor(SOUL_FILL_COLOR),
+                    BorderRadius::MAX,
+                    BackgroundColor(SOUL_VESSEL_COLOR),
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        SoulFill,
+                        Node {
+                            width: Val::Percent(100.0),
+                            height: Val::Percent(0.0),
+                            ..default()
+                        },
+                        BackgroundColor(SOUL_FILL_COLOR),
+                    ));
+                });
+
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(HUD_GAP / 2.0),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    // Masks are added once the player's max health is known
+                    parent.spawn((
+                        HealthMaskRow { masks: 0 },
+                        Node {
+                            column_gap: Val::Px(MASK_GAP),
+                            ..default()
+                        },
+                    ));
+
+                    parent
+                        .spawn(Node {
+                            column_gap: Val::Px(HUD_GAP / 2.0),
+                            align_items: AlignItems::Center,
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            parent.spawn((
+                                Node {
+                                    width: Val::Px(CURRENCY_ICON_SIZE),
+                                    height: Val::Px(CURRENCY_ICON_SIZE),
+                                    ..default()
+                                },
+                                BorderRadius::all(Val::Px(CURRENCY_ICON_SIZE / 2.0)),
+                                BackgroundColor(CURRENCY_COLOR),
+                            ));
+                            parent.spawn((
+                                CurrencyText,
+                                Text::new("0"),
+                                TextFont {
+                                    font,
+                                    font_size: CURRENCY_FONT_SIZE,
+                                    ..default()
+                                },
+                                TextColor(CURRENCY_COLOR),
+                            ));
+                        });
+                });
+        });
+}
+
+// One mask per HEALTH_PER_MASK of max health, so config edits add or remove masks
+fn rebuild_health_masks(
+    mut commands: Commands,
+    players: Query<&Player, Changed<Player>>,
+    mut rows: Query<(Entity, &mut HealthMaskRow)>,
+) {
+    let Ok(player) = players.get_single() else {
+        return;
+    };
+    let masks = (player.max_health / HEALTH_PER_MASK).ceil().max(1.0) as usize;
+
+    for (row_entity, mut row) in &mut rows {
+        if row.masks == masks {
+            continue;
+        }
+        row.masks = masks;
+
+        commands
+            .entity(row_entity)
+            .despawn_descendants()
+            .with_children(|parent| {
+                for index in 0..masks {
+                    parent
+                        .spawn((
+                            HealthMask {
+                                index,
+                                filled: true,
+                                break_timer: None,
+                            },
+                            Node {
+                                width: Val::Px(MASK_SIZE.x),
+                                height: Val::Px(MASK_SIZE.y),
+                                align_items: AlignItems::End,
+                                overflow: Overflow::clip(),
+                                ..default()
+                            },
+                            BorderRadius::bottom(Val::Px(MASK_SIZE.x / 2.0)),
+                            BackgroundColor(MASK_EMPTY_COLOR),
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                HealthMaskFill { index },
+                                Node {
+                                    width: Val::Percent(100.0),
+                                    height: Val::Percent(100.0),
+                                    ..default()
+                                },
+                                BackgroundColor(MASK_FULL_COLOR),
+                            ));
+                        });
+                }
+            });
+    }
+}
+
+fn update_health_masks(
+    players: Query<&Player, Changed<Player>>,
+    mut masks: Query<&mut HealthMask>,
+    mut fills: Query<(&HealthMaskFill, &mut Node)>,
+) {
+    let Ok(player) = players.get_single() else {
+        return;
+    };
+
+    let mask_health = |index: usize| {
+        ((player.health - index as f32 * HEALTH_PER_MASK) / HEALTH_PER_MASK).clamp(0.0, 1.0)
+    };
+
+    for (fill, mut node) in &mut fills {
+        node.height = Val::Percent(mask_health(fill.index) * 100.0);
+    }
+
+    // A mask breaks the moment its last bit of health goes
+    for mut mask in &mut masks {
+        let filled = mask_health(mask.index) > 0.0;
+        if mask.filled && !filled {
+            mask.break_timer = Some(Timer::from_seconds(MASK_BREAK_TIME, TimerMode::Once));
+        }
+        mask.filled = filled;
+    }
+}
+
+fn update_mask_breaks(time: Res<Time>, mut masks: Query<(&mut HealthMask, &mut BackgroundColor)>) {
+    for (mut mask, mut color) in &mut masks {
+        let Some(timer) = mask.break_timer.as_mut() else {
+            continue;
+        };
+        timer.tick(time.delta());
+
+        color.0 = MASK_BREAK_COLOR.mix(&MASK_EMPTY_COLOR, timer.fraction());
+        if timer.finished() {
+            mask.break_timer = None;
+        }
+    }
+}
+
+fn update_soul_vessel(
+    players: Query<&Player, Changed<Player>>,
+    mut fills: Query<&mut Node, With<SoulFill>>,
+) {
+    let Ok(player) = players.get_single() else {
+        return;
+    };
+    let fraction = if player.max_soul > 0.0 {
+        (player.soul / player.max_soul).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    for mut node in &mut fills {
+        node.height = Val::Percent(fraction * 100.0);
+    }
+}
+
+fn update_currency_counter(
+    players: Query<&Player, Changed<Player>>,
+    mut texts: Query<&mut Text, With<CurrencyText>>,
+) {
+    let Ok(player) = players.get_single() else {
+        return;
+    };
+
+    for mut text in &mut texts {
+        text.0 = player.currency.to_string();
+    }
+}
@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+
+use crate::game::GameState;
+use crate::hitbox::DamageEvent;
+
+// Hurt flash constants
+const HURT_FLASH_DURATION: f32 = 0.1;
+// Above 1 so the sprite texture washes out to white instead of keeping its colors
+const HURT_FLASH_COLOR: Color = Color::linear_rgb(6.0, 6.0, 6.0);
+
+// Sprite drawn white for a moment after a hit, then given back its own color
+#[derive(Component)]
+struct HurtFlash {
+    timer: Timer,
+    original_color: Color,
+}
+
+pub struct HurtFlashPlugin;
+
+impl Plugin for HurtFlashPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (start_hurt_flashes, update_hurt_flashes)
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+// Damage over time doesn't flash, only hits with a reaction do
+fn start_hurt_flashes(
+    mut commands: Commands,
+    mut damage_events: EventReader<DamageEvent>,
+    mut targets: Query<(&mut Sprite, Option<&mut HurtFlash>)>,
+) {
+    for event in damage_events.read() {
+        if event.reaction.is_none() {
+            continue;
+        }
+        let Ok((mut sprite, flash)) = targets.get_mut(event.target) else {
+            continue;
+        };
+
+        // A hit during a flash restarts it, keeping the color from before the first one
+        match flash {
+            Some(mut flash) => flash.timer.reset(),
+            // Second hit this frame, the flash from the first one isn't inserted yet
+            None if sprite.color == HURT_FLASH_COLOR => {}
+            None => {
+                commands.entity(event.target).insert(HurtFlash {
+                    timer: Timer::from_seconds(HURT_FLASH_DURATION, TimerMode::Once),
+                    original_color: sprite.color,
+                });
+            }
+        }
+        sprite.color = HURT_FLASH_COLOR;
+    }
+}
+
+fn update_hurt_flashes(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut flashes: Query<(Entity, &mut HurtFlash, &mut Sprite)>,
+) {
+    for (entity, mut flash, mut sprite) in &mut flashes {
+        flash.timer.tick(time.delta());
+        if flash.timer.finished() {
+            sprite.color = flash.original_color;
+            commands.entity(entity).remove::<HurtFlash>();
+        }
+    }
+}
@@ -0,0 +1,399 @@
+use std::collections::BTreeMap;
+
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+
+// Gameplay actions, independent of the keys that trigger them
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InputAction {
+    MoveLeft,
+    MoveRight,
+    MoveDown, // With jump, drops through one-way platforms
+    Jump,
+    Attack,
+    ChargeAttack,
+    Dash,
+    Summon,
+    Interact, // Talks to NPCs and pays toll gates
+    Pause,    // Also backs out of menus
+    Map,
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 11] = [
+        InputAction::MoveLeft,
+        InputAction::MoveRight,
+        InputAction::MoveDown,
+        InputAction::Jump,
+        InputAction::Attack,
+        InputAction::ChargeAttack,
+        InputAction::Dash,
+        InputAction::Summon,
+        InputAction::Interact,
+        InputAction::Pause,
+        InputAction::Map,
+    ];
+
+    // Keyboard binding of each action until the player picks another
+    pub fn default_key(self) -> KeyCode {
+        match self {
+            InputAction::MoveLeft => KeyCode::ArrowLeft,
+            InputAction::MoveRight => KeyCode::ArrowRight,
+            InputAction::MoveDown => KeyCode::ArrowDown,
+            InputAction::Jump => KeyCode::Space,
+            InputAction::Attack => KeyCode::KeyZ,
+            InputAction::ChargeAttack => KeyCode::KeyV,
+            InputAction::Dash => KeyCode::KeyX,
+            InputAction::Summon => KeyCode::KeyC,
+            InputAction::Interact => KeyCode::ArrowUp,
+            InputAction::Pause => KeyCode::Escape,
+            InputAction::Map => KeyCode::Tab,
+        }
+    }
+
+    // Second key that also works for the action, unless it's been bound to another one
+    fn alternate_key(self) -> Option<KeyCode> {
+        match self {
+            InputAction::Pause => Some(KeyCode::KeyP),
+            _ => None,
+        }
+    }
+
+    // Controller button of each action; the left stick moves as well as the D-pad
+    pub fn gamepad_button(self) -> GamepadButton {
+        match self {
+            InputAction::MoveLeft => GamepadButton::DPadLeft,
+            InputAction::MoveRight => GamepadButton::DPadRight,
+            InputAction::MoveDown => GamepadButton::DPadDown,
+            InputAction::Jump => GamepadButton::South,
+            InputAction::Attack => GamepadButton::West,
+            InputAction::ChargeAttack => GamepadButton::North,
+            InputAction::Dash => GamepadButton::RightTrigger,
+            InputAction::Summon => GamepadButton::East,
+            InputAction::Interact => GamepadButton::DPadUp,
+            InputAction::Pause => GamepadButton::Start,
+            InputAction::Map => GamepadButton::Select,
+        }
+    }
+
+    fn stick_pressed(self, stick: Vec2) -> bool {
+        match self {
+            InputAction::MoveLeft => stick.x < -GAMEPAD_STICK_THRESHOLD,
+            InputAction::MoveRight => stick.x > GAMEPAD_STICK_THRESHOLD,
+            InputAction::MoveDown => stick.y < -GAMEPAD_STICK_THRESHOLD,
+            InputAction::Interact => stick.y > GAMEPAD_STICK_THRESHOLD,
+            _ => false,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            InputAction::MoveLeft => "<",
+            InputAction::MoveRight => ">",
+            InputAction::MoveDown => "v",
+            InputAction::Jump => "Jump",
+            InputAction::Attack => "Attack",
+            InputAction::ChargeAttack => "Charge",
+            InputAction::Dash => "Dash",
+            InputAction::Summon => "Summon",
+            InputAction::Interact => "Interact",
+            InputAction::Pause => "Pause",
+            InputAction::Map => "Map",
+        }
+    }
+
+    // Name used for the action in save files
+    pub fn id(self) -> &'static str {
+        match self {
+            InputAction::MoveLeft => "move_left",
+            InputAction::MoveRight => "move_right",
+            InputAction::MoveDown => "move_down",
+            InputAction::Jump => "jump",
+            InputAction::Attack => "attack",
+            InputAction::ChargeAttack => "charge_attack",
+            InputAction::Dash => "dash",
+            InputAction::Summon => "summon",
+            InputAction::Interact => "interact",
+            InputAction::Pause => "pause",
+            InputAction::Map => "map",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.id() == id)
+    }
+}
+
+const GAMEPAD_STICK_THRESHOLD: f32 = 0.5; // How far the left stick has to lean to count as a press
+const KEY_BINDINGS_SAVE_PATH: &str = "bindings.sav"; // Same form as the bindings in a save
+
+// Keys an action can be bound to, written in save files by their names
+const BINDABLE_KEYS: [KeyCode; 51] = [
+    KeyCode::KeyA,
+    KeyCode::KeyB,
+    KeyCode::KeyC,
+    KeyCode::KeyD,
+    KeyCode::KeyE,
+    KeyCode::KeyF,
+    KeyCode::KeyG,
+    KeyCode::KeyH,
+    KeyCode::KeyI,
+    KeyCode::KeyJ,
+    KeyCode::KeyK,
+    KeyCode::KeyL,
+    KeyCode::KeyM,
+    KeyCode::KeyN,
+    KeyCode::KeyO,
+    KeyCode::KeyP,
+    KeyCode::KeyQ,
+    KeyCode::KeyR,
+    KeyCode::KeyS,
+    KeyCode::KeyT,
+    KeyCode::KeyU,
+    KeyCode::KeyV,
+    KeyCode::KeyW,
+    KeyCode::KeyX,
+    KeyCode::KeyY,
+    KeyCode::KeyZ,
+    KeyCode::Digit0,
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+    KeyCode::ArrowLeft,
+    KeyCode::ArrowRight,
+    KeyCode::ArrowUp,
+    KeyCode::ArrowDown,
+    KeyCode::Space,
+    KeyCode::Enter,
+    KeyCode::Tab,
+    KeyCode::ShiftLeft,
+    KeyCode::ShiftRight,
+    KeyCode::ControlLeft,
+    KeyCode::ControlRight,
+    KeyCode::AltLeft,
+    KeyCode::AltRight,
+    KeyCode::Backspace,
+    KeyCode::Escape,
+];
+
+pub fn key_name(key: KeyCode) -> String {
+    format!("{key:?}")
+}
+
+pub fn is_bindable(key: KeyCode) -> bool {
+    BINDABLE_KEYS.contains(&key)
+}
+
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+    BINDABLE_KEYS.into_iter().find(|&key| key_name(key) == name)
+}
+
+// Key each action is bound to right now. Stored in saves by action id and key name
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "BTreeMap<String, String>", into = "BTreeMap<String, String>")]
+pub struct KeyBindings {
+    keys: HashMap<InputAction, KeyCode>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            keys: InputAction::ALL
+                .into_iter()
+                .map(|action| (action, action.default_key()))
+                .collect(),
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn key(&self, action: InputAction) -> KeyCode {
+        self.keys
+            .get(&action)
+            .copied()
+            .unwrap_or_else(|| action.default_key())
+    }
+
+    // Key of the action as on-screen prompts show it, e.g. "[ArrowUp]"
+    pub fn prompt(&self, action: InputAction) -> String {
+        format!("[{}]", key_name(self.key(action)))
+    }
+
+    // Keys the action answers to: its binding, and its alternate while that's free
+    fn keys(&self, action: InputAction) -> impl Iterator<Item = KeyCode> + '_ {
+        let alternate = action.alternate_key().filter(|&key| {
+            InputAction::ALL
+                .into_iter()
+                .all(|other| self.key(other) != key)
+        });
+        std::iter::once(self.key(action)).chain(alternate)
+    }
+
+    pub fn bind(&mut self, action: InputAction, key: KeyCode) {
+        self.keys.insert(action, key);
+    }
+
+    // Binds the key chosen in the controls screen; an action that had it takes this one's
+    // old key, so no two actions ever share one
+    pub fn rebind(&mut self, action: InputAction, key: KeyCode) {
+        let old_key = self.key(action);
+        if let Some(other) = InputAction::ALL
+            .into_iter()
+            .find(|&other| other != action && self.key(other) == key)
+        {
+            self.bind(other, old_key);
+        }
+        self.bind(action, key);
+    }
+
+    // No file, or one that can't be read, leaves every action on its default key
+    pub fn load() -> Self {
+        let Ok(contents) = std::fs::read_to_string(KEY_BINDINGS_SAVE_PATH) else {
+            return Self::default();
+        };
+        ron::from_str(&contents)
+            .inspect_err(|error| warn!("Could not read key bindings: {error}"))
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let contents = match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(contents) => contents,
+            Err(error) => {
+                warn!("Could not save key bindings: {error}");
+                return;
+            }
+        };
+        if let Err(error) = std::fs::write(KEY_BINDINGS_SAVE_PATH, contents) {
+            warn!("Could not save key bindings: {error}");
+        }
+    }
+}
+
+// Unknown actions or keys are skipped and keep their default
+impl From<BTreeMap<String, String>> for KeyBindings {
+    fn from(names: BTreeMap<String, String>) -> Self {
+        let mut bindings = Self::default();
+        for (action, key) in &names {
+            if let (Some(action), Some(key)) = (InputAction::from_id(action), key_from_name(key)) {
+                bindings.bind(action, key);
+            }
+        }
+        bindings
+    }
+}
+
+impl From<KeyBindings> for BTreeMap<String, String> {
+    fn from(bindings: KeyBindings) -> Self {
+        InputAction::ALL
+            .into_iter()
+            .map(|action| (action.id().to_string(), key_name(bindings.key(action))))
+            .collect()
+    }
+}
+
+// Actions held down this frame, and the ones that went down this frame
+#[derive(Resource, Default)]
+pub struct ActionState {
+    pressed: HashSet<InputAction>,
+    just_pressed: HashSet<InputAction>,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: InputAction) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    // Counts as a press this frame, for other buttons standing in for the action
+    pub fn trigger(&mut self, action: InputAction) {
+        self.just_pressed.insert(action);
+    }
+
+    // Keeps a press handled by one system from also reaching the ones after it
+    pub fn consume(&mut self, action: InputAction) {
+        self.just_pressed.remove(&action);
+    }
+}
+
+pub struct InputPlugin;
+
+impl Plugin for InputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActionState>()
+            .insert_resource(KeyBindings::load())
+            // After Bevy has read this frame's keyboard and controller events
+            .add_systems(PreUpdate, update_action_state.after(InputSystem));
+    }
+}
+
+// Keyboard and every connected controller count at the same time
+pub fn update_action_state(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    gamepads: Query<&Gamepad>,
+    mut actions: ResMut<ActionState>,
+) {
+    let pressed: HashSet<InputAction> = InputAction::ALL
+        .into_iter()
+        .filter(|&action| {
+            keyboard.any_pressed(bindings.keys(action))
+                || gamepads.iter().any(|gamepad| {
+                    gamepad.pressed(action.gamepad_button())
+                        || action.stick_pressed(gamepad.left_stick())
+                })
+        })
+        .collect();
+    // A stick has no press of its own, so anything newly held counts
+    let just_pressed: HashSet<InputAction> = pressed
+        .iter()
+        .copied()
+        .filter(|&action| {
+            !actions.pressed.contains(&action) || keyboard.any_just_pressed(bindings.keys(action))
+        })
+        .collect();
+
+    // Only touch the resource when something changed so readers can use change detection
+    if actions.pressed != pressed || actions.just_pressed != just_pressed {
+        actions.pressed = pressed;
+        actions.just_pressed = just_pressed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bindings_round_trip_through_ron() {
+        let mut bindings = KeyBindings::default();
+        bindings.rebind(InputAction::ALL[0], KeyCode::KeyQ);
+
+        let contents =
+            ron::ser::to_string_pretty(&bindings, ron::ser::PrettyConfig::default()).unwrap();
+        assert_eq!(ron::from_str::<KeyBindings>(&contents).unwrap(), bindings);
+    }
+
+    #[test]
+    fn unknown_actions_and_keys_keep_the_default() {
+        let action = InputAction::ALL[0];
+        let contents = format!(
+            r#"{{"{}": "NotAKey", "not_an_action": "KeyQ"}}"#,
+            action.id()
+        );
+
+        let bindings: KeyBindings = ron::from_str(&contents).unwrap();
+        assert_eq!(bindings, KeyBindings::default());
+    }
+}
@@ -0,0 +1,132 @@
+use bevy::prelude::*;
+
+use crate::game::GameState;
+use crate::input::{ActionState, InputAction};
+use crate::settings::GameSettings;
+
+// Input display constants
+const INPUT_DISPLAY_MARGIN: f32 = 16.0;
+const INPUT_DISPLAY_GAP: f32 = 6.0;
+const INPUT_BUTTON_HEIGHT: f32 = 34.0;
+const INPUT_BUTTON_MIN_WIDTH: f32 = 34.0;
+const INPUT_BUTTON_FONT_SIZE: f32 = 14.0;
+const INPUT_BUTTON_COLOR: Color = Color::srgba(0.1, 0.1, 0.1, 0.6);
+const INPUT_BUTTON_PRESSED_COLOR: Color = Color::srgba(0.85, 0.85, 0.85, 0.9);
+const INPUT_LABEL_COLOR: Color = Color::WHITE;
+const INPUT_LABEL_PRESSED_COLOR: Color = Color::BLACK;
+
+// Root of the pressed-actions overlay
+#[derive(Component)]
+struct InputDisplay;
+
+// One button of the overlay, lit while its action is held
+#[derive(Component)]
+struct InputDisplayButton {
+    action: InputAction,
+}
+
+pub struct InputDisplayPlugin;
+
+impl Plugin for InputDisplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_input_display).add_systems(
+            Update,
+            (
+                toggle_input_display,
+                update_input_display.run_if(resource_changed::<ActionState>),
+            ),
+        );
+    }
+}
+
+fn setup_input_display(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(INPUT_DISPLAY_MARGIN),
+                bottom: Val::Px(INPUT_DISPLAY_MARGIN),
+                column_gap: Val::Px(INPUT_DISPLAY_GAP),
+                display: Display::None,
+                ..default()
+            },
+            InputDisplay,
+        ))
+        .with_children(|parent| {
+            // Pausing and the map aren't part of play, so they're left out
+            for action in InputAction::ALL
+                .into_iter()
+                .filter(|action| !matches!(action, InputAction::Pause | InputAction::Map))
+            {
+                parent
+                    .spawn((
+                        Node {
+                            min_width: Val::Px(INPUT_BUTTON_MIN_WIDTH),
+                            height: Val::Px(INPUT_BUTTON_HEIGHT),
+                            padding: UiRect::horizontal(Val::Px(8.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BorderRadius::all(Val::Px(6.0)),
+                        BackgroundColor(INPUT_BUTTON_COLOR),
+                        InputDisplayButton { action },
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            Text::new(action.label()),
+                            TextFont {
+                                font: font.clone(),
+                                font_size: INPUT_BUTTON_FONT_SIZE,
+                                ..default()
+                            },
+                            TextColor(INPUT_LABEL_COLOR),
+                        ));
+                    });
+            }
+        });
+}
+
+// Only shown in game, and only when turned on in the settings
+fn toggle_input_display(
+    settings: Res<GameSettings>,
+    state: Res<State<GameState>>,
+    mut displays: Query<&mut Node, With<InputDisplay>>,
+) {
+    let display = if settings.input_display && *state.get() == GameState::Playing {
+        Display::Flex
+    } else {
+        Display::None
+    };
+
+    for mut node in &mut displays {
+        if node.display != display {
+            node.display = display;
+        }
+    }
+}
+
+fn update_input_display(
+    actions: Res<ActionState>,
+    mut buttons: Query<(&InputDisplayButton, &mut BackgroundColor, &Children)>,
+    mut labels: Query<&mut TextColor>,
+) {
+    for (button, mut background, children) in &mut buttons {
+        let pressed = actions.pressed(button.action);
+        background.0 = if pressed {
+            INPUT_BUTTON_PRESSED_COLOR
+        } else {
+            INPUT_BUTTON_COLOR
+        };
+
+        if let Ok(mut label) = labels.get_mut(children[0]) {
+            label.0 = if pressed {
+                INPUT_LABEL_PRESSED_COLOR
+            } else {
+                INPUT_LABEL_COLOR
+            };
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use bevy::prelude::*;
+
+use crate::animations::{AnimationController, CharacterState, CurrentAnimation};
+use crate::endless::RunMode;
+use crate::game::{self, GameState, InGame};
+use crate::layers::UiLayer;
+use crate::paralax_background::CameraHold;
+use crate::player::{self, Player};
+use crate::save::ActiveSave;
+
+// Intro constants
+const INTRO_PAN_DISTANCE: f32 = 1400.0; // The camera starts this far ahead of the player
+const INTRO_PAN_DURATION: f32 = 3.5;
+const INTRO_TITLE_DURATION: f32 = 2.5;
+const INTRO_TITLE_FADE_TIME: f32 = 0.6;
+const INTRO_WAKE_UP_DURATION: f32 = 1.4; // A bit longer than the animation, holds the last frame
+const INTRO_TITLE: &str = "Platformer Demo";
+const INTRO_TITLE_FONT_SIZE: f32 = 72.0;
+const INTRO_TITLE_COLOR: Color = Color::srgb(0.9, 0.9, 0.95);
+
+// Steps of the intro, played in order
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum IntroStep {
+    CameraPan,
+    TitleCard,
+    WakeUp,
+}
+
+impl IntroStep {
+    fn duration(self) -> f32 {
+        match self {
+            IntroStep::CameraPan => INTRO_PAN_DURATION,
+            IntroStep::TitleCard => INTRO_TITLE_DURATION,
+            IntroStep::WakeUp => INTRO_WAKE_UP_DURATION,
+        }
+    }
+
+    fn next(self) -> Option<Self> {
+        match self {
+            IntroStep::CameraPan => Some(IntroStep::TitleCard),
+            IntroStep::TitleCard => Some(IntroStep::WakeUp),
+            IntroStep::WakeUp => None,
+        }
+    }
+}
+
+// Scripted sequence played when a new game starts; gameplay input waits for it
+#[derive(Resource, Default)]
+pub struct IntroSequence {
+    step: Option<IntroStep>,
+    timer: Timer,
+    camera_target_x: f32, // Where the camera rests once the pan is over
+}
+
+impl IntroSequence {
+    pub fn is_playing(&self) -> bool {
+        self.step.is_some()
+    }
+
+    fn start_step(&mut self, step: IntroStep) {
+        self.step = Some(step);
+        self.timer = Timer::from_seconds(step.duration(), TimerMode::Once);
+    }
+}
+
+// Run condition for systems that should wait until the intro is over
+pub fn intro_finished(intro: Res<IntroSequence>) -> bool {
+    !intro.is_playing()
+}
+
+// Only a story started from scratch opens with the intro. Continuing from a bench, retrying
+// after a bench and endless runs all drop the player straight in
+fn new_story_game(save: Res<ActiveSave>, run_mode: Res<RunMode>) -> bool {
+    save.data.bench.is_none() && *run_mode == RunMode::Story
+}
+
+// Title shown between the pan and the player waking up
+#[derive(Component)]
+struct IntroTitleCard;
+
+#[derive(Component)]
+struct IntroTitleText;
+
+pub struct IntroPlugin;
+
+impl Plugin for IntroPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<IntroSequence>()
+            .add_systems(
+                OnEnter(InGame),
+                start_intro
+                    .after(game::reset_camera)
+                    .after(player::setup_player)
+                    .run_if(new_story_game),
+            )
+            .add_systems(OnExit(InGame), reset_intro)
+            .add_systems(
+                Update,
+                update_intro.run_if(in_state(GameState::Playing).and(not(intro_finished))),
+            );
+    }
+}
+
+// A run quit mid-intro mustn't leave the next one, which may skip it, waiting on it
+fn reset_intro(mut intro: ResMut<IntroSequence>, mut camera_hold: ResMut<CameraHold>) {
+    if intro.is_playing() {
+        camera_hold.active = false;
+    }
+    *intro = IntroSequence::default();
+}
+
+pub fn start_intro(
+    mut intro: ResMut<IntroSequence>,
+    mut camera_hold: ResMut<CameraHold>,
+    mut cameras: Query<&mut Transform, With<Camera2d>>,
+    mut players: Query<&mut AnimationController, With<Player>>,
+) {
+    let Ok(mut camera_transform) = cameras.get_single_mut() else {
+        return;
+    };
+
+    intro.camera_target_x = camera_transform.translation.x;
+    camera_transform.translation.x += INTRO_PAN_DISTANCE;
+    camera_hold.active = true;
+
+    // Lies on the first frame of the wake up animation until its step comes
+    for mut animation_controller in &mut players {
+        animation_controller.change_state(CharacterState::WakingUp);
+    }
+
+    intro.start_step(IntroStep::CameraPan);
+}
+
+// Any key, click or gamepad button skips straight to the end
+#[allow(clippy::too_many_arguments)]
+fn update_intro(
+    mut commands: Commands,
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    mut intro: ResMut<IntroSequence>,
+    mut camera_hold: ResMut<CameraHold>,
+    mut cameras: Query<&mut Transform, With<Camera2d>>,
+    mut players: Query<(&mut AnimationController, &mut CurrentAnimation), With<Player>>,
+    title_cards: Query<Entity, With<IntroTitleCard>>,
+    mut title_texts: Query<&mut TextColor, With<IntroTitleText>>,
+) {
+    let Some(step) = intro.step else {
+        return;
+    };
+
+    let skipped = keyboard.get_just_pressed().next().is_some()
+        || mouse.get_just_pressed().next().is_some()
+        || gamepads
+            .iter()
+            .any(|gamepad| gamepad.get_just_pressed().next().is_some());
+
+    intro.timer.tick(time.delta());
+    let next_step = if skipped {
+        None
+    } else if intro.timer.finished() {
+        step.next()
+    } else {
+        Some(step)
+    };
+
+    let Ok(mut camera_transform) = cameras.get_single_mut() else {
+        return;
+    };
+
+    match next_step {
+        None => {
+            camera_transform.translation.x = intro.camera_target_x;
+            camera_hold.active = false;
+            for entity in &title_cards {
+                commands.entity(entity).despawn_recursive();
+            }
+            for (mut animation_controller, _) in &mut players {
+                animation_controller.change_state(CharacterState::Idle);
+            }
+            intro.step = None;
+            return;
+        }
+        Some(next_step) if next_step != step => {
+            if step == IntroStep::TitleCard {
+                for entity in &title_cards {
+                    commands.entity(entity).despawn_recursive();
+                }
+            }
+            if next_step == IntroStep::TitleCard {
+                spawn_title_card(&mut commands, &asset_server);
+            }
+            intro.start_step(next_step);
+            return;
+        }
+        Some(_) => {}
+    }
+
+    match step {
+        IntroStep::CameraPan => {
+            // Eases in and out so the pan doesn't start or stop with a jolt
+            let t = intro.timer.fraction();
+            let eased = t * t * (3.0 - 2.0 * t);
+            camera_transform.translation.x =
+                intro.camera_target_x + INTRO_PAN_DISTANCE * (1.0 - eased);
+        }
+        IntroStep::TitleCard => {
+            let elapsed = intro.timer.elapsed_secs();
+            let remaining = intro.timer.remaining_secs();
+            let alpha = (elapsed.min(remaining) / INTRO_TITLE_FADE_TIME).min(1.0);
+            for mut color in &mut title_texts {
+                color.0.set_alpha(alpha);
+            }
+        }
+        IntroStep::WakeUp => {}
+    }
+
+    // Keep the player lying down until the wake up step lets the animation run
+    if step != IntroStep::WakeUp {
+        for (animation_controller, mut current_animation) in &mut players {
+            if animation_controller.get_current_state() == CharacterState::WakingUp {
+                current_animation.current_frame = 0;
+                current_animation.timer.reset();
+            }
+        }
+    }
+}
+
+fn spawn_title_card(commands: &mut Commands, asset_server: &AssetServer) {
+    commands
+        .spawn((
+            IntroTitleCard,
+            StateScoped(InGame),
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            GlobalZIndex(UiLayer::TitleCard.z_index()),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                IntroTitleText,
+                Text::new(INTRO_TITLE),
+                TextFont {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: INTRO_TITLE_FONT_SIZE,
+                    ..default()
+                },
+                TextColor(INTRO_TITLE_COLOR.with_alpha(0.0)),
+            ));
+        });
+}
@@ -0,0 +1,438 @@
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use serde::{Deserialize, Serialize};
+
+use crate::animation_manifest::AttackDefinitions;
+use crate::config::GameConfig;
+use crate::doors::DoorProgress;
+use crate::game::{GameState, InGame};
+use crate::hitbox::AttackSet;
+use crate::input::{ActionState, InputAction};
+use crate::layers::UiLayer;
+use crate::level::{Bench, RoomRegistry};
+use crate::physics::Physics;
+use crate::player::{self, Player};
+
+// Inventory constants
+const CHARM_NOTCHES: u32 = 4;
+const HEART_CHARM_HEALTH: f32 = 20.0; // One more mask
+const SHELL_CHARM_DEFENSE: f32 = 3.0;
+const SPRINT_CHARM_SPEED_UP: f32 = 0.2; // Fraction of the base speed
+const BLOW_CHARM_ATTACK: f32 = 4.0;
+const BENCH_REST_RANGE: f32 = 60.0; // How close to a bench the player has to stand to rest
+const INVENTORY_BACKGROUND: Color = Color::srgba(0.05, 0.05, 0.05, 0.95);
+const INVENTORY_COLUMN_WIDTH: f32 = 320.0;
+const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
+const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
+const EQUIPPED_BUTTON: Color = Color::srgb(0.55, 0.45, 0.2);
+const HEADING_COLOR: Color = Color::srgb(0.95, 0.8, 0.35);
+const DETAIL_COLOR: Color = Color::srgb(0.7, 0.7, 0.7);
+
+// Trinket worn for a bonus, taking up notches while equipped
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CharmKind {
+    UnbreakableHeart,
+    StalwartShell,
+    Sprintmaster,
+    HeavyBlow,
+}
+
+impl CharmKind {
+    pub const ALL: [CharmKind; 4] = [
+        CharmKind::UnbreakableHeart,
+        CharmKind::StalwartShell,
+        CharmKind::Sprintmaster,
+        CharmKind::HeavyBlow,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CharmKind::UnbreakableHeart => "Unbreakable Heart",
+            CharmKind::StalwartShell => "Stalwart Shell",
+            CharmKind::Sprintmaster => "Sprintmaster",
+            CharmKind::HeavyBlow => "Heavy Blow",
+        }
+    }
+
+    pub fn notch_cost(self) -> u32 {
+        match self {
+            CharmKind::UnbreakableHeart => 2,
+            CharmKind::StalwartShell => 2,
+            CharmKind::Sprintmaster => 1,
+            CharmKind::HeavyBlow => 2,
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            CharmKind::UnbreakableHeart => "One more mask",
+            CharmKind::StalwartShell => "Takes the edge off hits",
+            CharmKind::Sprintmaster => "Runs faster",
+            CharmKind::HeavyBlow => "Strikes harder",
+        }
+    }
+}
+
+// Charms carried and worn. There are no charms to find in the world yet, so every
+// charm is carried from the start
+#[derive(Resource)]
+pub struct Inventory {
+    pub charms: Vec<CharmKind>,
+    pub equipped: Vec<CharmKind>,
+    pub notches: u32,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self {
+            charms: CharmKind::ALL.to_vec(),
+            equipped: Vec::new(),
+            notches: CHARM_NOTCHES,
+        }
+    }
+}
+
+impl Inventory {
+    pub fn notches_used(&self) -> u32 {
+        self.equipped.iter().map(|charm| charm.notch_cost()).sum()
+    }
+
+    pub fn is_equipped(&self, charm: CharmKind) -> bool {
+        self.equipped.contains(&charm)
+    }
+
+    // Takes a worn charm off, or puts one on if there are notches free for it
+    pub fn toggle(&mut self, charm: CharmKind) {
+        if self.is_equipped(charm) {
+            self.equipped.retain(|&equipped| equipped != charm);
+        } else if self.charms.contains(&charm)
+            && self.notches_used() + charm.notch_cost() <= self.notches
+        {
+            self.equipped.push(charm);
+        }
+    }
+}
+
+// Inventory screen, opened from the pause menu
+#[derive(Resource, Default)]
+pub struct InventoryScreen {
+    pub open: bool,
+}
+
+// Run condition for the pause menu, which waits while the inventory is open
+pub fn inventory_closed(screen: Res<InventoryScreen>) -> bool {
+    !screen.open
+}
+
+// Root of the screen; charms only come on and off while the player rests at a bench
+#[derive(Component)]
+struct InventoryRoot {
+    resting: bool,
+}
+
+#[derive(Component)]
+struct CharmButton(CharmKind);
+
+#[derive(Component)]
+struct NotchLabel;
+
+#[derive(Component)]
+struct InventoryBackButton;
+
+pub struct InventoryPlugin;
+
+impl Plugin for InventoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Inventory>()
+            .init_resource::<InventoryScreen>()
+            .add_systems(OnExit(GameState::Paused), close_inventory)
+            .add_systems(OnEnter(InGame), apply_charms.after(player::setup_player))
+            .add_systems(
+                Update,
+                apply_charms.after(player::apply_player_config).run_if(
+                    resource_changed::<Inventory>
+                        .or(resource_changed::<GameConfig>)
+                        .or(resource_changed::<AttackDefinitions>),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    sync_inventory_screen.run_if(resource_changed::<InventoryScreen>),
+                    handle_charm_buttons,
+                    handle_inventory_back,
+                    update_charm_buttons,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Paused)),
+            );
+    }
+}
+
+fn close_inventory(mut screen: ResMut<InventoryScreen>) {
+    screen.open = false;
+}
+
+// Worn charms go on top of the configured stats, so a config reload keeps them
+pub fn apply_charms(
+    inventory: Res<Inventory>,
+    config: Res<GameConfig>,
+    mut players: Query<(&mut Player, &mut AttackSet)>,
+) {
+    let bonus = |charm, amount| {
+        if inventory.is_equipped(charm) {
+            amount
+        } else {
+            0.0
+        }
+    };
+
+    for (mut player, mut attack_set) in &mut players {
+        // A full player stays full when the extra mask goes on, as at the start of a run
+        let was_full = player.health >= player.max_health;
+        player.max_health =
+            config.player.max_health + bonus(CharmKind::UnbreakableHeart, HEART_CHARM_HEALTH);
+        player.health = if was_full {
+            player.max_health
+        } else {
+            player.health.min(player.max_health)
+        };
+        player.defense =
+            config.player.defense + bonus(CharmKind::StalwartShell, SHELL_CHARM_DEFENSE);
+        player.speed =
+            config.player.speed * (1.0 + bonus(CharmKind::Sprintmaster, SPRINT_CHARM_SPEED_UP));
+        player.attack = config.player.attack + bonus(CharmKind::HeavyBlow, BLOW_CHARM_ATTACK);
+        attack_set.damage = player.attack;
+    }
+}
+
+// Spawns the screen when it's opened and takes it down when it's closed
+#[allow(clippy::too_many_arguments)]
+fn sync_inventory_screen(
+    mut commands: Commands,
+    screen: Res<InventoryScreen>,
+    inventory: Res<Inventory>,
+    door_progress: Res<DoorProgress>,
+    registry: Res<RoomRegistry>,
+    asset_server: Res<AssetServer>,
+    roots: Query<Entity, With<InventoryRoot>>,
+    players: Query<(&Player, &Transform, &Physics)>,
+    benches: Query<&Transform, With<Bench>>,
+) {
+    for entity in &roots {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !screen.open {
+        return;
+    }
+
+    let player = players.get_single().ok();
+    let resting = player.is_some_and(|(_, transform, physics)| {
+        physics.on_ground
+            && physics.velocity.x.abs() < 0.1
+            && benches.iter().any(|bench| {
+                (bench.translation.x - transform.translation.x).abs() < BENCH_REST_RANGE
+            })
+    });
+    let currency = player.map_or(0, |(player, _, _)| player.currency);
+    let mut key_items: Vec<&str> = registry
+        .rooms
+        .iter()
+        .flat_map(|room| &room.key_items)
+        .filter(|key| door_progress.has_key(&key.id))
+        .map(|key| key.name.as_str())
+        .collect();
+    key_items.sort_unstable();
+
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let text = |label: String, size: f32, color: Color| {
+        (
+            Text::new(label),
+            TextFont {
+                font: font.clone(),
+                font_size: size,
+                ..default()
+            },
+            TextColor(color),
+        )
+    };
+    let column = Node {
+        width: Val::Px(INVENTORY_COLUMN_WIDTH),
+        flex_direction: FlexDirection::Column,
+        row_gap: Val::Px(10.0),
+        ..default()
+    };
+
+    commands
+        .spawn((
+            InventoryRoot { resting },
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(20.0),
+                ..default()
+            },
+            BackgroundColor(INVENTORY_BACKGROUND),
+            // Keeps clicks off the pause menu underneath
+            FocusPolicy::Block,
+            GlobalZIndex(UiLayer::Submenu.z_index()),
+        ))
+        .with_children(|parent| {
+            parent.spawn(text("INVENTORY".to_string(), 32.0, Color::WHITE));
+
+            parent
+                .spawn(Node {
+                    column_gap: Val::Px(40.0),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    // Items on the left
+                    parent.spawn(column.clone()).with_children(|parent| {
+                        parent.spawn(text("Items".to_string(), 24.0, HEADING_COLOR));
+                        parent.spawn(text(format!("Geo: {currency}"), 20.0, Color::WHITE));
+
+                        parent.spawn(text("Key items".to_string(), 24.0, HEADING_COLOR));
+                        if key_items.is_empty() {
+                            parent.spawn(text("None yet".to_string(), 20.0, DETAIL_COLOR));
+                        }
+                        for name in key_items {
+                            parent.spawn(text(name.to_string(), 20.0, Color::WHITE));
+                        }
+                    });
+
+                    // Charms on the right
+                    parent.spawn(column).with_children(|parent| {
+                        parent.spawn(text("Charms".to_string(), 24.0, HEADING_COLOR));
+                        parent.spawn((NotchLabel, text(String::new(), 20.0, Color::WHITE)));
+
+                        for &charm in &inventory.charms {
+                            parent
+                                .spawn((
+                                    Button,
+                                    CharmButton(charm),
+                                    Node {
+                                        flex_direction: FlexDirection::Column,
+                                        padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                                        ..default()
+                                    },
+                                    BorderRadius::all(Val::Px(6.0)),
+                                    BackgroundColor(NORMAL_BUTTON),
+                                ))
+                                .with_children(|parent| {
+                                    parent.spawn(text(
+                                        format!("{} ({})", charm.name(), charm.notch_cost()),
+                                        20.0,
+                                        Color::WHITE,
+                                    ));
+                                    parent.spawn(text(
+                                        charm.description().to_string(),
+                                        16.0,
+                                        DETAIL_COLOR,
+                                    ));
+                                });
+                        }
+
+                        let hint = if resting {
+                            "Resting at a bench: click a charm to wear it or take it off"
+                        } else {
+                            "Rest at a bench to change charms"
+                        };
+                        parent.spawn(text(hint.to_string(), 16.0, DETAIL_COLOR));
+                    });
+                });
+
+            parent
+                .spawn((
+                    Button,
+                    InventoryBackButton,
+                    Node {
+                        width: Val::Px(150.0),
+                        height: Val::Px(50.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BorderRadius::MAX,
+                    BackgroundColor(NORMAL_BUTTON),
+                ))
+                .with_children(|parent| {
+                    parent.spawn(text("Back".to_string(), 24.0, Color::WHITE));
+                });
+        });
+}
+
+fn handle_charm_buttons(
+    mut inventory: ResMut<Inventory>,
+    roots: Query<&InventoryRoot>,
+    interaction_query: Query<(&Interaction, &CharmButton), Changed<Interaction>>,
+) {
+    if !roots.iter().any(|root| root.resting) {
+        return;
+    }
+
+    for (interaction, button) in &interaction_query {
+        if *interaction == Interaction::Pressed {
+            inventory.toggle(button.0);
+        }
+    }
+}
+
+// The pause key backs out of the screen without also closing the pause menu under it
+#[allow(clippy::type_complexity)]
+fn handle_inventory_back(
+    mut screen: ResMut<InventoryScreen>,
+    mut actions: ResMut<ActionState>,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<InventoryBackButton>),
+    >,
+) {
+    if !screen.open {
+        return;
+    }
+
+    for (interaction, mut color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => screen.open = false,
+            Interaction::Hovered => *color = HOVERED_BUTTON.into(),
+            Interaction::None => *color = NORMAL_BUTTON.into(),
+        }
+    }
+
+    if actions.just_pressed(InputAction::Pause) {
+        actions.consume(InputAction::Pause);
+        screen.open = false;
+    }
+}
+
+// Worn charms stand out from the rest, and the notch count follows what's worn
+fn update_charm_buttons(
+    inventory: Res<Inventory>,
+    mut buttons: Query<(&CharmButton, &Interaction, &mut BackgroundColor)>,
+    mut labels: Query<&mut Text, With<NotchLabel>>,
+) {
+    for (button, interaction, mut color) in &mut buttons {
+        color.0 = if inventory.is_equipped(button.0) {
+            EQUIPPED_BUTTON
+        } else if *interaction == Interaction::Hovered {
+            HOVERED_BUTTON
+        } else {
+            NORMAL_BUTTON
+        };
+    }
+
+    let notches = format!(
+        "Notches: {} / {}",
+        inventory.notches_used(),
+        inventory.notches
+    );
+    for mut text in &mut labels {
+        if text.0 != notches {
+            text.0 = notches.clone();
+        }
+    }
+}
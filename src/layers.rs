@@ -0,0 +1,60 @@
+// Draw order of the world, back to front. Everything takes its Z from here when it's
+// spawned, so the order lives in one place instead of numbers scattered across modules
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layer {
+    Background,
+    Parallax, // Farthest parallax layer; nearer ones sit a bit in front of it
+    Props,    // Benches and other scenery the characters walk past
+    Terrain,  // Platforms, blocks and gates
+    Enemies,
+    Player,
+    Pickups,
+    Effects,    // Sparks, shards and other short lived particles
+    Ground,     // In front of the characters so their feet sink into the grass
+    Foreground, // Parallax foliage and fog drifting in front of everything in the world
+    Overlay,    // World space text: damage numbers, aggro indicators
+}
+
+impl Layer {
+    pub const fn z(self) -> f32 {
+        match self {
+            Layer::Background => -100.0,
+            Layer::Parallax => -40.0,
+            Layer::Props => -1.0,
+            Layer::Terrain => 2.0,
+            Layer::Enemies => 4.0,
+            Layer::Player => 5.0,
+            Layer::Pickups => 6.0,
+            Layer::Effects => 7.0,
+            Layer::Ground => 10.0,
+            Layer::Foreground => 14.0,
+            Layer::Overlay => 20.0,
+        }
+    }
+}
+
+// Order of the full screen UI drawn on top of the game
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UiLayer {
+    Hud,       // Health, soul and currency, under anything shown over the game
+    Dialogue,  // Speech box of the character being talked to
+    Letterbox, // Bars of the camera director's shots, under any title over them
+    TitleCard,
+    Overlay,    // Screens over a run in progress, like game over and pause
+    Submenu,    // Screens opened from a menu, like settings and the inventory
+    ScreenFade, // Covers everything, including the title card
+}
+
+impl UiLayer {
+    pub const fn z_index(self) -> i32 {
+        match self {
+            UiLayer::Hud => 700,
+            UiLayer::Dialogue => 750,
+            UiLayer::Letterbox => 800,
+            UiLayer::TitleCard => 900,
+            UiLayer::Overlay => 950,
+            UiLayer::Submenu => 970,
+            UiLayer::ScreenFade => 1000,
+        }
+    }
+}
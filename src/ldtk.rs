@@ -0,0 +1,375 @@
+use std::fmt;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::prelude::*;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::ambient::{Ambient, Palette};
+use crate::enemy::{EnemyKind, RespawnRule};
+use crate::level::{CameraLock, HazardKind, RoomDefinition};
+use crate::physics::SurfaceMaterial;
+
+// Int grid values of the collision layer, as set up in the LDtk project
+const CELL_TERRAIN: i64 = 1;
+const CELL_ONE_WAY: i64 = 2;
+const CELL_PIT: i64 = 3;
+const CELL_ICE: i64 = 4;
+const CELL_MUD: i64 = 5;
+
+// Walls placed without a health field take this much damage to break
+const DEFAULT_BREAKABLE_HEALTH: f32 = 30.0;
+
+// Rooms of an LDtk project, one per level
+#[derive(Asset, TypePath)]
+pub struct LdtkWorld {
+    pub rooms: Vec<RoomDefinition>,
+}
+
+// Level exported from LDtk. Only the parts the game reads: levels sit where the editor's
+// world puts them, its coordinates being the game's with y pointing down. The ground is
+// whatever the collision layer fills in
+#[derive(Deserialize)]
+struct LdtkProject {
+    levels: Vec<LdtkLevel>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LdtkLevel {
+    identifier: String,
+    world_x: f32,
+    world_y: f32,
+    px_wid: f32,
+    px_hei: f32,
+    #[serde(default)]
+    field_instances: Vec<LdtkField>,
+    #[serde(default)]
+    layer_instances: Option<Vec<LdtkLayer>>, // Missing when levels are saved to separate files
+}
+
+#[derive(Deserialize)]
+struct LdtkLayer {
+    #[serde(rename = "__type")]
+    kind: String,
+    #[serde(rename = "__cWid")]
+    columns: usize,
+    #[serde(rename = "__gridSize")]
+    grid_size: f32,
+    #[serde(rename = "intGridCsv", default)]
+    int_grid: Vec<i64>,
+    #[serde(rename = "entityInstances", default)]
+    entities: Vec<LdtkEntity>,
+}
+
+#[derive(Deserialize)]
+struct LdtkEntity {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    #[serde(rename = "__pivot")]
+    pivot: [f32; 2],
+    px: [f32; 2],
+    width: f32,
+    height: f32,
+    #[serde(rename = "fieldInstances", default)]
+    fields: Vec<LdtkField>,
+}
+
+#[derive(Deserialize)]
+struct LdtkField {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    #[serde(rename = "__value")]
+    value: Value,
+}
+
+#[derive(Debug)]
+pub enum LdtkError {
+    Io(std::io::Error),
+    Parse(serde_json::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for LdtkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LdtkError::Io(error) => write!(f, "could not read level file: {error}"),
+            LdtkError::Parse(error) => write!(f, "could not parse level file: {error}"),
+            LdtkError::Invalid(message) => write!(f, "invalid level file: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for LdtkError {}
+
+impl From<std::io::Error> for LdtkError {
+    fn from(error: std::io::Error) -> Self {
+        LdtkError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for LdtkError {
+    fn from(error: serde_json::Error) -> Self {
+        LdtkError::Parse(error)
+    }
+}
+
+#[derive(Default)]
+pub struct LdtkLoader;
+
+impl AssetLoader for LdtkLoader {
+    type Asset = LdtkWorld;
+    type Settings = ();
+    type Error = LdtkError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<LdtkWorld, LdtkError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(LdtkWorld {
+            rooms: parse_rooms(&bytes)?,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ldtk"]
+    }
+}
+
+// Every level of the project becomes a room
+fn parse_rooms(bytes: &[u8]) -> Result<Vec<RoomDefinition>, LdtkError> {
+    let project: LdtkProject = serde_json::from_slice(bytes)?;
+    project.levels.iter().map(build_room).collect()
+}
+
+fn build_room(level: &LdtkLevel) -> Result<RoomDefinition, LdtkError> {
+    let name = string_field(&level.field_instances, "name")
+        .unwrap_or_else(|| level.identifier.replace('_', " "));
+    let area = string_field(&level.field_instances, "area");
+    // LDtk y grows downwards, from the world's origin and from each level's top
+    let to_world = |px: Vec2| Vec2::new(level.world_x + px.x, -(level.world_y + px.y));
+    let mut room = RoomDefinition::new(
+        &level.identifier.to_lowercase(),
+        &name,
+        area.as_deref(),
+        Rect::from_corners(
+            to_world(Vec2::ZERO),
+            to_world(Vec2::new(level.px_wid, level.px_hei)),
+        ),
+    );
+
+    if let Some(backdrop) = string_field(&level.field_instances, "backdrop") {
+        room = room.with_backdrop(&backdrop);
+    }
+    room = match string_field(&level.field_instances, "ambient").as_deref() {
+        Some("Day") | None => room,
+        Some("Dusk") => room.with_ambient(Ambient::Fixed(Palette::DUSK)),
+        Some("Night") => room.with_ambient(Ambient::Fixed(Palette::NIGHT)),
+        Some("Dawn") => room.with_ambient(Ambient::Fixed(Palette::DAWN)),
+        Some("DayNight") => room.with_ambient(Ambient::DayNight),
+        Some(other) => {
+            return Err(LdtkError::Invalid(format!("unknown ambient {other}")));
+        }
+    };
+
+    let Some(layers) = &level.layer_instances else {
+        return Err(LdtkError::Invalid(format!(
+            "level {} has no layers, save it inside the project file",
+            level.identifier
+        )));
+    };
+    for layer in layers {
+        match layer.kind.as_str() {
+            "IntGrid" => room = add_cells(room, layer, &to_world),
+            "Entities" => {
+                for entity in &layer.entities {
+                    room = add_entity(room, entity, &to_world)?;
+                }
+            }
+            _ => {} // Tiles and auto layers are only art
+        }
+    }
+
+    Ok(room)
+}
+
+// Runs of the same value along each row become one box, and the merge pass in the level
+// stacks rows of terrain back into blocks. Ice and mud are ground of that material, pits
+// are left empty
+fn add_cells(
+    mut room: RoomDefinition,
+    layer: &LdtkLayer,
+    to_world: &impl Fn(Vec2) -> Vec2,
+) -> RoomDefinition {
+    if layer.columns == 0 {
+        return room;
+    }
+
+    for (row, cells) in layer.int_grid.chunks(layer.columns).enumerate() {
+        let mut column = 0;
+        while column < cells.len() {
+            let value = cells[column];
+            let start = column;
+            while column < cells.len() && cells[column] == value {
+                column += 1;
+            }
+
+            let top_left = to_world(Vec2::new(
+                start as f32 * layer.grid_size,
+                row as f32 * layer.grid_size,
+            ));
+            let bottom_right = to_world(Vec2::new(
+                column as f32 * layer.grid_size,
+                (row + 1) as f32 * layer.grid_size,
+            ));
+            let (min_x, max_x) = (top_left.x, bottom_right.x);
+            let cells = Rect::new(min_x, bottom_right.y, max_x, top_left.y);
+            room = match value {
+                CELL_TERRAIN => room.with_terrain(cells),
+                CELL_ONE_WAY => room.with_one_way_ledge(min_x, max_x, top_left.y),
+                CELL_PIT => room.with_pit(min_x, max_x),
+                CELL_ICE => room.with_ground(cells, SurfaceMaterial::Ice),
+                CELL_MUD => room.with_ground(cells, SurfaceMaterial::Mud),
+                _ => room,
+            };
+        }
+    }
+
+    room
+}
+
+fn add_entity(
+    room: RoomDefinition,
+    entity: &LdtkEntity,
+    to_world: &impl Fn(Vec2) -> Vec2,
+) -> Result<RoomDefinition, LdtkError> {
+    let size = Vec2::new(entity.width, entity.height);
+    let top_left = Vec2::from(entity.px) - Vec2::from(entity.pivot) * size;
+    let area = Rect::from_corners(to_world(top_left), to_world(top_left + size));
+
+    let room = match entity.identifier.as_str() {
+        "PlayerSpawn" => room.with_player_spawn(area.center()),
+        "Enemy" => {
+            let kind = match string_field(&entity.fields, "kind").as_deref() {
+                Some("Skeleton") | None => EnemyKind::Skeleton,
+                Some("ShieldedSkeleton") => EnemyKind::ShieldedSkeleton,
+                Some("ChargingSkeleton") => EnemyKind::ChargingSkeleton,
+                Some(other) => {
+                    return Err(LdtkError::Invalid(format!("unknown enemy kind {other}")));
+                }
+            };
+            // No respawn delay set means it stays dead
+            let respawn = field(&entity.fields, "respawn_delay")
+                .and_then(Value::as_f64)
+                .map_or(RespawnRule::Never, |delay| {
+                    RespawnRule::AfterDelay(delay as f32)
+                });
+            // Enemies drop from the top of their marker
+            room.with_enemy_at(kind, Vec2::new(area.center().x, area.max.y), respawn)
+        }
+        "Bench" => {
+            let name = string_field(&entity.fields, "name").unwrap_or_else(|| room.name.clone());
+            room.with_bench_at(&name, area.center())
+        }
+        "Pit" => room.with_pit(area.min.x, area.max.x),
+        "Hazard" => {
+            let kind = match string_field(&entity.fields, "kind").as_deref() {
+                Some("Spikes") | None => HazardKind::Spikes,
+                Some("Acid") => HazardKind::Acid,
+                Some(other) => {
+                    return Err(LdtkError::Invalid(format!("unknown hazard kind {other}")));
+                }
+            };
+            room.with_hazard(area, kind)
+        }
+        "Breakable" => {
+            let health = field(&entity.fields, "health")
+                .and_then(Value::as_f64)
+                .unwrap_or(DEFAULT_BREAKABLE_HEALTH as f64);
+            room.with_breakable_wall(area, health as f32)
+        }
+        // Keeps the view inside its own area while the player is in it, or holds it on
+        // the center when marked fixed
+        "CameraLock" => {
+            let fixed = field(&entity.fields, "fixed")
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+            let lock = if fixed {
+                CameraLock::Fixed(area.center())
+            } else {
+                CameraLock::Confine(area)
+            };
+            room.with_camera_lock(area, lock)
+        }
+        "Transition" => {
+            let Some(target) = string_field(&entity.fields, "target") else {
+                return Err(LdtkError::Invalid(format!(
+                    "transition in {} has no target level",
+                    room.id
+                )));
+            };
+            room.with_transition(area, &target.to_lowercase())
+        }
+        other => {
+            warn!("Level {} has an unknown entity {other}", room.id);
+            room
+        }
+    };
+
+    Ok(room)
+}
+
+fn field<'a>(fields: &'a [LdtkField], identifier: &str) -> Option<&'a Value> {
+    fields
+        .iter()
+        .find(|field| field.identifier == identifier)
+        .map(|field| &field.value)
+}
+
+fn string_field(fields: &[LdtkField], identifier: &str) -> Option<String> {
+    field(fields, identifier)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::level::GROUND_TOP_Y;
+
+    const SHIPPED_WORLD: &[u8] = include_bytes!("../assets/world/levels/world.ldtk");
+
+    #[test]
+    fn shipped_level_sits_at_its_world_position() {
+        let rooms = parse_rooms(SHIPPED_WORLD).unwrap();
+        let hall = rooms.iter().find(|room| room.id == "sunken_hall").unwrap();
+        assert_eq!(hall.bounds, Rect::new(16000.0, -343.0, 17920.0, 41.0));
+        assert!(!hall.ground_strip);
+    }
+
+    #[test]
+    fn shipped_level_floor_comes_from_its_cells() {
+        let rooms = parse_rooms(SHIPPED_WORLD).unwrap();
+        let hall = rooms.iter().find(|room| room.id == "sunken_hall").unwrap();
+
+        // The bottom row is the floor, flush with the ground of the rooms next to it
+        assert!(
+            hall.terrain
+                .iter()
+                .any(|rect| rect.min.x == 16000.0 && rect.max.y == GROUND_TOP_Y)
+        );
+        assert_eq!(hall.pits.len(), 1);
+        assert_eq!((hall.pits[0].min_x, hall.pits[0].max_x), (16448.0, 16576.0));
+        assert_eq!(hall.ground_segments.len(), 1);
+        assert_eq!(hall.ground_segments[0].material, SurfaceMaterial::Ice);
+        assert_eq!(hall.ground_segments[0].area.max.y, GROUND_TOP_Y);
+        // and the bench stands on it
+        assert_eq!(hall.benches[0].position.y - 10.0, GROUND_TOP_Y);
+    }
+}
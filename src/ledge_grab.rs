@@ -0,0 +1,195 @@
+use bevy::prelude::*;
+
+use crate::animations::{AnimationController, CharacterState, Facing};
+use crate::camera_director;
+use crate::dialogue;
+use crate::game::GameState;
+use crate::game_over;
+use crate::input::{ActionState, InputAction};
+use crate::intro;
+use crate::map;
+use crate::pause;
+use crate::physics::{self, BodyCollider, OneWayPlatform, Physics, Solid};
+use crate::player::{self, Player};
+
+// Ledge grab constants
+const LEDGE_GRAB_REACH: f32 = 14.0; // Gap between the player's front and the wall
+const LEDGE_GRAB_WINDOW: f32 = 22.0; // How far the head can be from the edge, above or below
+const LEDGE_GRAB_MAX_RISE: f32 = 120.0; // Still rising faster than this, the edge is missed
+const LEDGE_CLIMB_INSET: f32 = 6.0; // How far onto the ledge climbing puts the player
+const LEDGE_REGRAB_DELAY: f32 = 0.3; // After letting go, so the same edge isn't caught again
+
+// Player hanging from the top corner of a solid
+#[derive(Component)]
+pub struct LedgeHang {
+    corner: Vec2,
+    facing: Facing,
+}
+
+// Let go of a ledge a moment ago
+#[derive(Component)]
+struct LedgeRegrabDelay(Timer);
+
+pub struct LedgeGrabPlugin;
+
+impl Plugin for LedgeGrabPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                tick_regrab_delays,
+                grab_ledges,
+                update_ledge_hangs.run_if(
+                    intro::intro_finished
+                        .and(camera_director::director_idle)
+                        .and(not(pause::pause_requested))
+                        .and(game_over::player_alive)
+                        .and(map::map_closed)
+                        .and(dialogue::dialogue_closed),
+                ),
+            )
+                .chain()
+                // The grab sets its state after the usual jump and fall ones
+                .after(player::update_animations)
+                .before(physics::apply_surface_traction)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+// Sensors taken from the body collider: the front edge of the body reaches for a wall,
+// the head has to be level with its top corner, and the body has to fit on top of it
+#[allow(clippy::type_complexity)]
+fn grab_ledges(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    solids: Query<(&Transform, &Solid), (Without<OneWayPlatform>, Without<Player>)>,
+    mut players: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Physics,
+            &mut AnimationController,
+            &Facing,
+            &BodyCollider,
+        ),
+        (With<Player>, Without<LedgeHang>, Without<LedgeRegrabDelay>),
+    >,
+) {
+    let solid_boxes: Vec<(Vec2, Vec2)> = solids
+        .iter()
+        .map(|(transform, solid)| (transform.translation.truncate(), solid.size))
+        .collect();
+
+    for (entity, mut transform, mut physics, mut animation_controller, &facing, collider) in
+        &mut players
+    {
+        let state = animation_controller.get_current_state();
+        let toward = match facing {
+            Facing::Left => InputAction::MoveLeft,
+            Facing::Right => InputAction::MoveRight,
+        };
+        if physics.on_ground
+            || physics.velocity.y > LEDGE_GRAB_MAX_RISE
+            || !matches!(state, CharacterState::Jumping | CharacterState::Falling)
+            || !actions.pressed(toward)
+        {
+            continue;
+        }
+
+        let position = transform.translation.truncate();
+        let half_size = collider.size / 2.0;
+        let front = position.x + facing.sign() * half_size.x;
+        let head = position.y + half_size.y;
+
+        let ledge = solid_boxes.iter().find_map(|&(solid_pos, solid_size)| {
+            let corner = Vec2::new(
+                solid_pos.x - facing.sign() * solid_size.x / 2.0,
+                solid_pos.y + solid_size.y / 2.0,
+            );
+            let gap = (corner.x - front) * facing.sign();
+            let standing = Vec2::new(
+                corner.x + facing.sign() * (half_size.x + LEDGE_CLIMB_INSET),
+                corner.y + half_size.y,
+            );
+            ((-LEDGE_GRAB_REACH..=LEDGE_GRAB_REACH).contains(&gap)
+                && (head - corner.y).abs() <= LEDGE_GRAB_WINDOW
+                && physics::area_is_clear(&solid_boxes, standing, collider.size))
+            .then_some(corner)
+        });
+        let Some(corner) = ledge else {
+            continue;
+        };
+
+        // Hands on the edge, body flush against the wall
+        transform.translation.x = corner.x - facing.sign() * half_size.x;
+        transform.translation.y = corner.y - half_size.y;
+        physics.velocity = Vec2::ZERO;
+        physics.gravity_scale = 0.0;
+        animation_controller.change_state(CharacterState::LedgeHanging);
+        commands.entity(entity).insert(LedgeHang { corner, facing });
+    }
+}
+
+// Jump climbs onto the ledge, down lets go. Getting hit also knocks the player off
+fn update_ledge_hangs(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    mut players: Query<(
+        Entity,
+        &LedgeHang,
+        &mut Transform,
+        &mut Physics,
+        &mut AnimationController,
+        &BodyCollider,
+    )>,
+) {
+    for (entity, hang, mut transform, mut physics, mut animation_controller, collider) in
+        &mut players
+    {
+        // Jumping or falling until the hanging state is applied
+        let knocked_off = !matches!(
+            animation_controller.get_current_state(),
+            CharacterState::LedgeHanging | CharacterState::Jumping | CharacterState::Falling
+        );
+
+        if knocked_off {
+            // The hit's knockback carries the player away from the wall
+        } else if actions.just_pressed(InputAction::Jump) {
+            let half_size = collider.size / 2.0;
+            transform.translation.x =
+                hang.corner.x + hang.facing.sign() * (half_size.x + LEDGE_CLIMB_INSET);
+            transform.translation.y = hang.corner.y + half_size.y;
+            physics.velocity = Vec2::ZERO;
+            animation_controller.change_state(CharacterState::Idle);
+        } else if actions.just_pressed(InputAction::MoveDown) {
+            physics.velocity = Vec2::ZERO;
+            animation_controller.change_state(CharacterState::Falling);
+            commands
+                .entity(entity)
+                .insert(LedgeRegrabDelay(Timer::from_seconds(
+                    LEDGE_REGRAB_DELAY,
+                    TimerMode::Once,
+                )));
+        } else {
+            physics.velocity = Vec2::ZERO;
+            continue;
+        }
+
+        physics.gravity_scale = 1.0;
+        commands.entity(entity).remove::<LedgeHang>();
+    }
+}
+
+fn tick_regrab_delays(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut delays: Query<(Entity, &mut LedgeRegrabDelay)>,
+) {
+    for (entity, mut delay) in &mut delays {
+        delay.0.tick(time.delta());
+        if delay.0.finished() {
+            commands.entity(entity).remove::<LedgeRegrabDelay>();
+        }
+    }
+}
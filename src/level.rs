@@ -0,0 +1,1420 @@
+use bevy::prelude::*;
+
+use crate::ambient::{Ambient, Palette};
+use crate::arena;
+use crate::boss::Boss;
+use crate::endless;
+use crate::enemy::SightBlocker;
+use crate::enemy::{EnemyKind, EnemySpawnPoint, RespawnRule, SpawnArea, SpawnSide};
+use crate::game::{GameState, InGame};
+use crate::ground::{self, Ground, GroundArt, ICE_TINT, MUD_TINT};
+use crate::hazards::Hazard;
+use crate::layers::Layer;
+use crate::ldtk;
+use crate::music::DEFAULT_MUSIC;
+use crate::paralax_background::DEFAULT_BACKDROP;
+use crate::physics::{OneWayPlatform, Physics, PhysicsZone, Solid, SurfaceMaterial};
+use crate::player::Player;
+use crate::pushable;
+use crate::triggers::{self, TriggerEnteredEvent, TriggerZone};
+
+// Generated arena past the end of the handmade rooms, rebuilt with a new seed every run
+const ARENA_MIN_X: f32 = 12000.0;
+const ARENA_MAX_X: f32 = 16000.0;
+
+// Backdrop of the mountain areas, the other rooms keep level 1's
+const MOUNTAIN_BACKDROP: &str = "world/levels/Mountain Dusk/mountains.parallax.ron";
+const MOUNTAIN_MUSIC: &str = "audio/music/mountains.wav";
+
+// Rooms drawn in the LDtk editor, placed where the editor's world puts them
+const LEVEL_FILE_PATH: &str = "world/levels/world.ldtk";
+
+// Vertical extent shared by the rooms laid out along the level 1 ground strip
+const ROOM_MIN_Y: f32 = -2000.0;
+const ROOM_MAX_Y: f32 = 2000.0;
+
+// Enemy spawn point defaults
+pub const ENEMY_SPAWN_Y: f32 = -50.0; // Enemies drop onto the ground from here
+const ENEMY_ACTIVATION_DISTANCE: f32 = 700.0;
+const ENEMY_ELITE_CHANCE: f32 = 0.15;
+
+// Bench constants
+const BENCH_Y: f32 = -300.0; // Resting on top of the ground strip
+const BENCH_SIZE: Vec2 = Vec2::new(64.0, 20.0);
+const BENCH_COLOR: Color = Color::srgb(0.45, 0.3, 0.2);
+
+// Key items float a little above the ground strip
+const KEY_ITEM_Y: f32 = -270.0;
+
+// Characters to talk to stand on the ground strip
+pub const NPC_HEIGHT: f32 = 80.0;
+
+// Pushable blocks are dropped onto the ground strip
+const PUSHABLE_BLOCK_SPAWN_Y: f32 = -200.0;
+
+// Static geometry constants
+pub const GROUND_TOP_Y: f32 = -311.0; // Walls stand on the ground strip
+pub const PLATFORM_HEIGHT: f32 = 20.0;
+const WALL_WIDTH: f32 = 40.0;
+const CEILING_THICKNESS: f32 = 60.0;
+const TERRAIN_COLOR: Color = Color::srgb(0.3, 0.27, 0.25);
+const ONE_WAY_PLATFORM_HEIGHT: f32 = 10.0;
+const ONE_WAY_PLATFORM_COLOR: Color = Color::srgb(0.42, 0.36, 0.3);
+const TERRAIN_MERGE_TOLERANCE: f32 = 0.5; // Gaps this small still count as touching
+
+// Ground segment constants
+const GROUND_SEGMENT_COLOR: Color = Color::srgb(0.36, 0.4, 0.26); // Grass topped rock
+
+// Secret alcove constants
+const SECRET_ALCOVE_CEILING_Y: f32 = -150.0;
+const SECRET_WALL_HEALTH: f32 = 30.0; // Three swings
+
+// Hazard constants
+const SPIKES_COLOR: Color = Color::srgb(0.75, 0.75, 0.8);
+const ACID_COLOR: Color = Color::srgba(0.4, 0.9, 0.2, 0.8);
+
+// Physics zone constants
+const WATER_GRAVITY_SCALE: f32 = 0.35;
+const WATER_MAX_FALL_SPEED: f32 = -180.0;
+const WATER_DRAG: f32 = 2.5;
+const WATER_COLOR: Color = Color::srgba(0.2, 0.4, 0.8, 0.35);
+const UPDRAFT_GRAVITY_SCALE: f32 = -0.8; // Lifts instead of pulling down
+const UPDRAFT_MAX_FALL_SPEED: f32 = -150.0;
+const UPDRAFT_DRAG: f32 = 3.0; // Caps how fast the wind carries things up
+const UPDRAFT_COLOR: Color = Color::srgba(0.85, 0.9, 1.0, 0.15);
+const DREAM_GRAVITY_SCALE: f32 = 0.4;
+const DREAM_MAX_FALL_SPEED: f32 = -350.0;
+const DREAM_DRAG: f32 = 0.5;
+const DREAM_COLOR: Color = Color::srgba(0.8, 0.6, 1.0, 0.2);
+
+// Enemy placed by the level data
+#[derive(Clone)]
+pub struct EnemySpawnDefinition {
+    pub kind: EnemyKind,
+    pub position: Vec2,
+    pub respawn: RespawnRule,
+    pub activation_distance: f32,
+    pub elite_chance: f32,
+    pub area: SpawnArea,
+    pub boss: Option<String>, // Name on the boss bar, for the enemy a room is built around
+}
+
+// Resting spot placed by the level data
+#[derive(Clone)]
+pub struct BenchDefinition {
+    pub name: String,
+    pub position: Vec2,
+}
+
+// Static ledge placed by the level data
+#[derive(Clone)]
+pub struct PlatformDefinition {
+    pub position: Vec2,
+    pub width: f32,
+    pub one_way: bool, // Jumped onto from below, dropped through with down and jump
+}
+
+// Wall standing on the ground, placed by the level data
+#[derive(Clone)]
+pub struct WallDefinition {
+    pub x: f32,
+    pub height: f32,
+}
+
+// Overhead slab, placed by the level data, that bodies bump their heads on
+#[derive(Clone)]
+pub struct CeilingDefinition {
+    pub min_x: f32,
+    pub max_x: f32,
+    pub bottom_y: f32,
+}
+
+// Ground placed by the level data at any height: a raised step standing on the strip or
+// a floating slab, stood on like the strip and solid from the sides and below
+#[derive(Clone)]
+pub struct GroundSegmentDefinition {
+    pub area: Rect,
+    pub material: SurfaceMaterial,
+}
+
+// Stretch of ground made of something other than stone
+#[derive(Clone)]
+pub struct SurfaceZone {
+    pub min_x: f32,
+    pub max_x: f32,
+    pub material: SurfaceMaterial,
+}
+
+// Area where bodies move differently, placed by the level data
+#[derive(Clone)]
+pub struct PhysicsZoneDefinition {
+    pub area: Rect,
+    pub kind: PhysicsZoneKind,
+}
+
+#[derive(Clone, Copy)]
+pub enum PhysicsZoneKind {
+    Water,   // Slow sinking, everything drags
+    Updraft, // Wind that carries bodies up
+    Dream,   // Floaty jumps and slow falls
+}
+
+impl PhysicsZoneKind {
+    fn zone(self, size: Vec2) -> PhysicsZone {
+        let (gravity_scale, max_fall_speed, drag) = match self {
+            PhysicsZoneKind::Water => (WATER_GRAVITY_SCALE, WATER_MAX_FALL_SPEED, WATER_DRAG),
+            PhysicsZoneKind::Updraft => {
+                (UPDRAFT_GRAVITY_SCALE, UPDRAFT_MAX_FALL_SPEED, UPDRAFT_DRAG)
+            }
+            PhysicsZoneKind::Dream => (DREAM_GRAVITY_SCALE, DREAM_MAX_FALL_SPEED, DREAM_DRAG),
+        };
+        PhysicsZone {
+            size,
+            gravity_scale,
+            max_fall_speed,
+            drag,
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            PhysicsZoneKind::Water => WATER_COLOR,
+            PhysicsZoneKind::Updraft => UPDRAFT_COLOR,
+            PhysicsZoneKind::Dream => DREAM_COLOR,
+        }
+    }
+}
+
+// Area where the camera stops following the player freely, like an arena
+#[derive(Clone)]
+pub struct CameraLockDefinition {
+    pub area: Rect, // Applies while the player is inside
+    pub lock: CameraLock,
+}
+
+#[derive(Clone, Copy)]
+pub enum CameraLock {
+    Fixed(Vec2),   // The camera holds on one spot
+    Confine(Rect), // The view stays inside the area
+}
+
+impl CameraLock {
+    // Closest camera position to the target that keeps to the lock. A view wider than
+    // the area it's confined to is centered on it instead
+    pub fn clamp(self, target: Vec2, half_view: Vec2) -> Vec2 {
+        match self {
+            CameraLock::Fixed(position) => position,
+            CameraLock::Confine(area) => {
+                let clamp_axis = |value: f32, min: f32, max: f32, half: f32| {
+                    if max - min <= half * 2.0 {
+                        (min + max) / 2.0
+                    } else {
+                        value.clamp(min + half, max - half)
+                    }
+                };
+                Vec2::new(
+                    clamp_axis(target.x, area.min.x, area.max.x, half_view.x),
+                    clamp_axis(target.y, area.min.y, area.max.y, half_view.y),
+                )
+            }
+        }
+    }
+}
+
+// Spikes or acid, placed by the level data
+#[derive(Clone)]
+pub struct HazardDefinition {
+    pub area: Rect,
+    pub kind: HazardKind,
+}
+
+#[derive(Clone, Copy)]
+pub enum HazardKind {
+    Spikes,
+    Acid,
+}
+
+impl HazardKind {
+    fn color(self) -> Color {
+        match self {
+            HazardKind::Spikes => SPIKES_COLOR,
+            HazardKind::Acid => ACID_COLOR,
+        }
+    }
+}
+
+// Doorway that sends the player on to another room
+#[derive(Clone)]
+pub struct TransitionDefinition {
+    pub area: Rect,
+    pub target_room: String,
+}
+
+// Wall or terrain piece, placed by the level data, that attacks can break open
+#[derive(Clone)]
+pub struct BreakableWallDefinition {
+    pub area: Rect,
+    pub health: f32,
+}
+
+// Gap in the ground; falling in triggers a hazard respawn
+#[derive(Clone)]
+pub struct PitZone {
+    pub min_x: f32,
+    pub max_x: f32,
+}
+
+// Gate that opens for good once its toll is paid, placed by the level data
+#[derive(Clone)]
+pub struct TollGateDefinition {
+    pub id: String,
+    pub x: f32,
+    pub cost: u32,
+}
+
+// Door that stays shut until it is unlocked, placed by the level data
+#[derive(Clone)]
+pub struct DoorDefinition {
+    pub id: String,
+    pub x: f32,
+    pub lock: DoorLock,
+}
+
+#[derive(Clone)]
+pub enum DoorLock {
+    Key(String),       // Opens when the player walks up holding this key
+    Switch { x: f32 }, // Opens when the lever standing here is struck
+}
+
+// Key lying in the level, opens every door locked with its id
+#[derive(Clone)]
+pub struct KeyItemDefinition {
+    pub id: String,
+    pub name: String,
+    pub position: Vec2,
+}
+
+// Character the player can talk to, with the conversation it opens
+#[derive(Clone)]
+pub struct NpcDefinition {
+    pub name: String,
+    pub position: Vec2,
+    pub conversation: String,
+}
+
+// One-shot event played when the player walks into its trigger
+#[derive(Clone)]
+pub struct SetPieceDefinition {
+    pub id: String,
+    pub trigger_x: f32,
+    pub kind: SetPieceKind,
+}
+
+#[derive(Clone, Copy)]
+pub enum SetPieceKind {
+    // Bridge over a pit that falls apart once the player is across
+    BridgeCollapse {
+        min_x: f32,
+        max_x: f32,
+    },
+    // Rocks coming down from the ceiling onto a stretch of ground
+    CaveIn {
+        min_x: f32,
+        max_x: f32,
+        rocks: usize,
+    },
+}
+
+// Metadata for a room: display name, the area it belongs to and its world bounds
+#[derive(Clone)]
+pub struct RoomDefinition {
+    pub id: String,
+    pub name: String,
+    pub area: Option<String>,
+    pub bounds: Rect,
+    pub enemy_spawns: Vec<EnemySpawnDefinition>,
+    pub benches: Vec<BenchDefinition>,
+    pub pushable_blocks: Vec<Vec2>,
+    pub platforms: Vec<PlatformDefinition>,
+    pub walls: Vec<WallDefinition>,
+    pub terrain: Vec<Rect>, // Blocks of any shape, from level files
+    pub ground_segments: Vec<GroundSegmentDefinition>,
+    pub ceilings: Vec<CeilingDefinition>,
+    pub surfaces: Vec<SurfaceZone>,
+    pub pits: Vec<PitZone>,
+    pub physics_zones: Vec<PhysicsZoneDefinition>,
+    pub hazards: Vec<HazardDefinition>,
+    pub toll_gates: Vec<TollGateDefinition>,
+    pub doors: Vec<DoorDefinition>,
+    pub key_items: Vec<KeyItemDefinition>,
+    pub npcs: Vec<NpcDefinition>,
+    pub breakable_walls: Vec<BreakableWallDefinition>,
+    pub set_pieces: Vec<SetPieceDefinition>,
+    pub transitions: Vec<TransitionDefinition>,
+    pub camera_bounds: Option<Rect>, // The view never shows past this; unbounded unless set
+    pub camera_locks: Vec<CameraLockDefinition>,
+    pub reveal: Option<Vec2>, // Shown off by the camera the first time the player comes in
+    pub backdrop: String,     // Parallax file drawn behind the room
+    pub music: String,        // Track playing while the player is in the room
+    pub ambient: Ambient,     // Tint of the backdrop while the player is in the room
+    pub player_spawn: Option<Vec2>, // Where a new game starts, if it starts in this room
+    pub ground_strip: bool,   // Stands on the ground strip, all along it but for the pits
+}
+
+impl RoomDefinition {
+    // Room with no ground of its own, for level files that lay theirs out cell by cell
+    pub fn new(id: &str, name: &str, area: Option<&str>, bounds: Rect) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            area: area.map(str::to_string),
+            bounds,
+            enemy_spawns: Vec::new(),
+            benches: Vec::new(),
+            pushable_blocks: Vec::new(),
+            platforms: Vec::new(),
+            walls: Vec::new(),
+            terrain: Vec::new(),
+            ground_segments: Vec::new(),
+            ceilings: Vec::new(),
+            surfaces: Vec::new(),
+            pits: Vec::new(),
+            physics_zones: Vec::new(),
+            hazards: Vec::new(),
+            toll_gates: Vec::new(),
+            doors: Vec::new(),
+            key_items: Vec::new(),
+            npcs: Vec::new(),
+            breakable_walls: Vec::new(),
+            set_pieces: Vec::new(),
+            transitions: Vec::new(),
+            camera_bounds: None,
+            camera_locks: Vec::new(),
+            reveal: None,
+            backdrop: DEFAULT_BACKDROP.to_string(),
+            music: DEFAULT_MUSIC.to_string(),
+            ambient: Ambient::default(),
+            player_spawn: None,
+            ground_strip: false,
+        }
+    }
+
+    // Room along the ground strip, at the height every handmade room shares
+    pub fn strip(id: &str, name: &str, area: Option<&str>, min_x: f32, max_x: f32) -> Self {
+        Self {
+            ground_strip: true,
+            ..Self::new(
+                id,
+                name,
+                area,
+                Rect::new(min_x, ROOM_MIN_Y, max_x, ROOM_MAX_Y),
+            )
+        }
+    }
+
+    // The room lays its ground out itself, a piece at a time
+    pub fn with_own_ground(mut self) -> Self {
+        self.ground_strip = false;
+        self
+    }
+
+    pub fn with_bench(self, name: &str, x: f32) -> Self {
+        self.with_bench_at(name, Vec2::new(x, BENCH_Y))
+    }
+
+    pub fn with_bench_at(mut self, name: &str, position: Vec2) -> Self {
+        self.benches.push(BenchDefinition {
+            name: name.to_string(),
+            position,
+        });
+        self
+    }
+
+    fn with_pushable_block(mut self, x: f32) -> Self {
+        self.pushable_blocks
+            .push(Vec2::new(x, PUSHABLE_BLOCK_SPAWN_Y));
+        self
+    }
+
+    pub fn with_platform(mut self, x: f32, y: f32, width: f32) -> Self {
+        self.platforms.push(PlatformDefinition {
+            position: Vec2::new(x, y),
+            width,
+            one_way: false,
+        });
+        self
+    }
+
+    pub fn with_one_way_platform(mut self, x: f32, y: f32, width: f32) -> Self {
+        self.platforms.push(PlatformDefinition {
+            position: Vec2::new(x, y),
+            width,
+            one_way: true,
+        });
+        self
+    }
+
+    // One-way ledge given by its span and the height it is stood on at
+    pub fn with_one_way_ledge(self, min_x: f32, max_x: f32, top_y: f32) -> Self {
+        self.with_one_way_platform(
+            (min_x + max_x) / 2.0,
+            top_y - ONE_WAY_PLATFORM_HEIGHT / 2.0,
+            max_x - min_x,
+        )
+    }
+
+    pub fn with_terrain(mut self, rect: Rect) -> Self {
+        self.terrain.push(rect);
+        self
+    }
+
+    pub fn with_ground(mut self, area: Rect, material: SurfaceMaterial) -> Self {
+        self.ground_segments
+            .push(GroundSegmentDefinition { area, material });
+        self
+    }
+
+    pub fn with_player_spawn(mut self, position: Vec2) -> Self {
+        self.player_spawn = Some(position);
+        self
+    }
+
+    pub fn with_transition(mut self, area: Rect, target_room: &str) -> Self {
+        self.transitions.push(TransitionDefinition {
+            area,
+            target_room: target_room.to_string(),
+        });
+        self
+    }
+
+    pub fn with_camera_bounds(mut self, bounds: Rect) -> Self {
+        self.camera_bounds = Some(bounds);
+        self
+    }
+
+    pub fn with_camera_lock(mut self, area: Rect, lock: CameraLock) -> Self {
+        self.camera_locks.push(CameraLockDefinition { area, lock });
+        self
+    }
+
+    pub fn with_backdrop(mut self, path: &str) -> Self {
+        self.backdrop = path.to_string();
+        self
+    }
+
+    pub fn with_music(mut self, path: &str) -> Self {
+        self.music = path.to_string();
+        self
+    }
+
+    pub fn with_ambient(mut self, ambient: Ambient) -> Self {
+        self.ambient = ambient;
+        self
+    }
+
+    pub fn with_reveal(mut self, position: Vec2) -> Self {
+        self.reveal = Some(position);
+        self
+    }
+
+    // The lock the camera keeps to with the player at this position, if any. Rooms run
+    // into each other, so the camera only stops where the level asks it to
+    pub fn camera_lock_at(&self, position: Vec2) -> Option<CameraLock> {
+        self.camera_locks
+            .iter()
+            .find(|zone| zone.area.contains(position))
+            .map(|zone| zone.lock)
+            .or(self.camera_bounds.map(CameraLock::Confine))
+    }
+
+    fn with_wall(mut self, x: f32, height: f32) -> Self {
+        self.walls.push(WallDefinition { x, height });
+        self
+    }
+
+    // With walls at both ends, closes a stretch of the room into a chamber
+    fn with_ceiling(mut self, min_x: f32, max_x: f32, bottom_y: f32) -> Self {
+        self.ceilings.push(CeilingDefinition {
+            min_x,
+            max_x,
+            bottom_y,
+        });
+        self
+    }
+
+    pub fn with_surface(mut self, min_x: f32, max_x: f32, material: SurfaceMaterial) -> Self {
+        self.surfaces.push(SurfaceZone {
+            min_x,
+            max_x,
+            material,
+        });
+        self
+    }
+
+    pub fn with_pit(mut self, min_x: f32, max_x: f32) -> Self {
+        self.pits.push(PitZone { min_x, max_x });
+        self
+    }
+
+    pub fn with_physics_zone(mut self, area: Rect, kind: PhysicsZoneKind) -> Self {
+        self.physics_zones
+            .push(PhysicsZoneDefinition { area, kind });
+        self
+    }
+
+    pub fn with_hazard(mut self, area: Rect, kind: HazardKind) -> Self {
+        self.hazards.push(HazardDefinition { area, kind });
+        self
+    }
+
+    fn with_toll_gate(mut self, id: &str, x: f32, cost: u32) -> Self {
+        self.toll_gates.push(TollGateDefinition {
+            id: id.to_string(),
+            x,
+            cost,
+        });
+        self
+    }
+
+    // The bridge covers a pit of its own, which is what's left once it collapses
+    pub fn with_locked_door(mut self, id: &str, x: f32, key: &str) -> Self {
+        self.doors.push(DoorDefinition {
+            id: id.to_string(),
+            x,
+            lock: DoorLock::Key(key.to_string()),
+        });
+        self
+    }
+
+    pub fn with_switch_door(mut self, id: &str, x: f32, switch_x: f32) -> Self {
+        self.doors.push(DoorDefinition {
+            id: id.to_string(),
+            x,
+            lock: DoorLock::Switch { x: switch_x },
+        });
+        self
+    }
+
+    pub fn with_key_item(mut self, id: &str, name: &str, x: f32) -> Self {
+        self.key_items.push(KeyItemDefinition {
+            id: id.to_string(),
+            name: name.to_string(),
+            position: Vec2::new(x, KEY_ITEM_Y),
+        });
+        self
+    }
+
+    pub fn with_npc(mut self, name: &str, x: f32, conversation: &str) -> Self {
+        self.npcs.push(NpcDefinition {
+            name: name.to_string(),
+            position: Vec2::new(x, GROUND_TOP_Y + NPC_HEIGHT / 2.0),
+            conversation: conversation.to_string(),
+        });
+        self
+    }
+
+    pub fn with_breakable_wall(mut self, area: Rect, health: f32) -> Self {
+        self.breakable_walls
+            .push(BreakableWallDefinition { area, health });
+        self
+    }
+
+    fn with_collapsing_bridge(mut self, id: &str, min_x: f32, max_x: f32, trigger_x: f32) -> Self {
+        self.set_pieces.push(SetPieceDefinition {
+            id: id.to_string(),
+            trigger_x,
+            kind: SetPieceKind::BridgeCollapse { min_x, max_x },
+        });
+        self.with_pit(min_x, max_x)
+    }
+
+    fn with_cave_in(
+        mut self,
+        id: &str,
+        trigger_x: f32,
+        min_x: f32,
+        max_x: f32,
+        rocks: usize,
+    ) -> Self {
+        self.set_pieces.push(SetPieceDefinition {
+            id: id.to_string(),
+            trigger_x,
+            kind: SetPieceKind::CaveIn {
+                min_x,
+                max_x,
+                rocks,
+            },
+        });
+        self
+    }
+
+    // Where the player should appear when sent to this room without a specific bench
+    pub fn entry_point(&self) -> Vec2 {
+        self.player_spawn
+            .or_else(|| self.benches.first().map(|bench| bench.position))
+            .unwrap_or(Vec2::new(self.bounds.min.x + 200.0, 0.0))
+    }
+
+    pub fn with_enemy(self, kind: EnemyKind, x: f32, respawn: RespawnRule) -> Self {
+        self.with_enemy_at(kind, Vec2::new(x, ENEMY_SPAWN_Y), respawn)
+    }
+
+    pub fn with_enemy_at(self, kind: EnemyKind, position: Vec2, respawn: RespawnRule) -> Self {
+        self.with_enemy_in_area(kind, position, respawn, SpawnArea::AT_SPAWNER)
+    }
+
+    // Enemy that appears somewhere in an area instead of on its spawn point
+    fn with_enemy_in_area(
+        mut self,
+        kind: EnemyKind,
+        position: Vec2,
+        respawn: RespawnRule,
+        area: SpawnArea,
+    ) -> Self {
+        self.enemy_spawns.push(EnemySpawnDefinition {
+            kind,
+            position,
+            respawn,
+            activation_distance: ENEMY_ACTIVATION_DISTANCE,
+            elite_chance: ENEMY_ELITE_CHANCE,
+            area,
+            boss: None,
+        });
+        self
+    }
+
+    // Always elite and never back once beaten, with its own health bar across the screen
+    pub fn with_boss(mut self, kind: EnemyKind, x: f32, name: &str) -> Self {
+        self.enemy_spawns.push(EnemySpawnDefinition {
+            kind,
+            position: Vec2::new(x, ENEMY_SPAWN_Y),
+            respawn: RespawnRule::Never,
+            activation_distance: ENEMY_ACTIVATION_DISTANCE,
+            elite_chance: 1.0,
+            area: SpawnArea::AT_SPAWNER,
+            boss: Some(name.to_string()),
+        });
+        self
+    }
+}
+
+// Every room known to the game, looked up by id
+#[derive(Resource)]
+pub struct RoomRegistry {
+    pub rooms: Vec<RoomDefinition>,
+}
+
+impl RoomRegistry {
+    pub fn get(&self, id: &str) -> Option<&RoomDefinition> {
+        self.rooms.iter().find(|room| room.id == id)
+    }
+
+    // Where a new game puts the player, when a room sets it
+    pub fn player_spawn(&self) -> Option<Vec2> {
+        self.rooms.iter().find_map(|room| room.player_spawn)
+    }
+
+    // Whether any pit overlaps the given horizontal range
+    pub fn has_pit_between(&self, min_x: f32, max_x: f32) -> bool {
+        self.rooms
+            .iter()
+            .flat_map(|room| &room.pits)
+            .any(|pit| min_x < pit.max_x && max_x > pit.min_x)
+    }
+}
+
+impl Default for RoomRegistry {
+    fn default() -> Self {
+        let arena_seed = rand::random();
+        info!("Proving Grounds arena seed: {arena_seed}");
+
+        let mut rooms = vec![
+            RoomDefinition::strip(
+                "crossroads_entrance",
+                "Crossroads Entrance",
+                Some("Forgotten Crossroads"),
+                -5000.0,
+                1500.0,
+            )
+            .with_bench("Crossroads Bench", -300.0)
+            .with_npc("Old Wanderer", -120.0, "wanderer")
+            // Sealed alcove at the west end, opened by knocking down its cracked wall
+            .with_wall(-4900.0, SECRET_ALCOVE_CEILING_Y - GROUND_TOP_Y)
+            .with_ceiling(-4900.0, -4500.0, SECRET_ALCOVE_CEILING_Y)
+            .with_breakable_wall(
+                Rect::new(-4520.0, GROUND_TOP_Y, -4480.0, SECRET_ALCOVE_CEILING_Y),
+                SECRET_WALL_HEALTH,
+            )
+            .with_bench("Hidden Alcove", -4700.0)
+            .with_key_item("cliffs_key", "Cliffside Key", -4820.0)
+            // Too high to jump onto from the ground, push the block under it
+            .with_pushable_block(-650.0)
+            .with_platform(-1000.0, -150.0, 220.0)
+            .with_pit(-1700.0, -1500.0)
+            // Flooded hollow: slow jumps and a slow sink
+            .with_physics_zone(
+                Rect::new(-3400.0, GROUND_TOP_Y, -2900.0, GROUND_TOP_Y + 160.0),
+                PhysicsZoneKind::Water,
+            )
+            // Ledges to climb by jumping up through them
+            .with_one_way_platform(-2300.0, -220.0, 180.0)
+            .with_one_way_platform(-2100.0, -120.0, 180.0)
+            .with_enemy(EnemyKind::Skeleton, 450.0, RespawnRule::AfterDelay(8.0)),
+            RoomDefinition::strip(
+                "crossroads_east",
+                "Eastern Crossroads",
+                Some("Forgotten Crossroads"),
+                1500.0,
+                3500.0,
+            )
+            .with_enemy(EnemyKind::Skeleton, 2000.0, RespawnRule::Never)
+            .with_enemy(
+                EnemyKind::ChargingSkeleton,
+                2900.0,
+                RespawnRule::AfterDelay(30.0),
+            )
+            // Low enough to jump over, solid enough to stop a charge
+            .with_wall(3300.0, 90.0)
+            // Sliding charges on the ice in front of the wall
+            .with_surface(2500.0, 3300.0, SurfaceMaterial::Ice)
+            // The view holds on the rink while the player is out on it
+            .with_camera_lock(
+                Rect::new(2500.0, GROUND_TOP_Y, 3300.0, GROUND_TOP_Y + 400.0),
+                CameraLock::Confine(Rect::new(2300.0, ROOM_MIN_Y, 3500.0, ROOM_MAX_Y)),
+            ),
+            RoomDefinition::strip(
+                "dusk_mountains",
+                "Dusk Mountains",
+                Some("Dusk Mountains"),
+                3500.0,
+                7000.0,
+            )
+            .with_bench("Mountain Shrine", 4800.0)
+            .with_pit(3900.0, 4100.0)
+            .with_surface(5600.0, 6500.0, SurfaceMaterial::Mud)
+            // The slope starts climbing: a step up out of the cave and a slab above it
+            .with_ground(
+                Rect::new(6600.0, GROUND_TOP_Y, 6950.0, GROUND_TOP_Y + 70.0),
+                SurfaceMaterial::Stone,
+            )
+            .with_ground(
+                Rect::new(6700.0, -170.0, 6850.0, -150.0),
+                SurfaceMaterial::Stone,
+            )
+            // Low cave over the mud: jumps are cut short against the rock
+            .with_ceiling(5700.0, 6400.0, -100.0)
+            // Portcullis in front of the cave, its lever just before it
+            .with_switch_door("mountain_portcullis", 5500.0, 5420.0)
+            // First time in, the camera runs ahead to show the portcullis barring the way
+            .with_reveal(Vec2::new(5500.0, 0.0))
+            .with_backdrop(MOUNTAIN_BACKDROP)
+            .with_music(MOUNTAIN_MUSIC)
+            .with_ambient(Ambient::Fixed(Palette::DUSK))
+            .with_enemy(EnemyKind::Skeleton, 4200.0, RespawnRule::Never)
+            .with_enemy(EnemyKind::ShieldedSkeleton, 5200.0, RespawnRule::Never)
+            .with_enemy(EnemyKind::Skeleton, 6100.0, RespawnRule::AfterDelay(30.0)),
+            RoomDefinition::strip(
+                "howling_cliffs",
+                "Howling Cliffs",
+                Some("Howling Cliffs"),
+                7000.0,
+                12000.0,
+            )
+            .with_backdrop(MOUNTAIN_BACKDROP)
+            .with_music(MOUNTAIN_MUSIC)
+            // Night has fallen by the time the player reaches the cliffs
+            .with_ambient(Ambient::Fixed(Palette::NIGHT))
+            .with_toll_gate("howling_cliffs_gate", 7600.0, 40)
+            // Past the cliffs, only the key from the hidden alcove gets through
+            .with_locked_door("cliffs_door", 11900.0, "cliffs_key")
+            .with_bench("Cliffside Rest", 10000.0)
+            // Gives way behind the player, the only way back is a jump over the gap
+            .with_collapsing_bridge("cliffs_bridge", 10500.0, 10700.0, 10800.0)
+            .with_cave_in("cliffs_cave_in", 11250.0, 11300.0, 11650.0, 9)
+            // Waits on the far side of the bridge, with no way back across
+            .with_boss(EnemyKind::ChargingSkeleton, 11100.0, "Warden of the Cliffs")
+            .with_surface(8400.0, 9200.0, SurfaceMaterial::Ice)
+            // Wind coming up the cliff face, carries jumps high just before the bridge
+            .with_physics_zone(
+                Rect::new(10350.0, GROUND_TOP_Y, 10500.0, GROUND_TOP_Y + 350.0),
+                PhysicsZoneKind::Updraft,
+            )
+            .with_enemy(EnemyKind::Skeleton, 8000.0, RespawnRule::Never)
+            // Comes back from just past either edge of the screen
+            .with_enemy_in_area(
+                EnemyKind::Skeleton,
+                Vec2::new(9500.0, ENEMY_SPAWN_Y),
+                RespawnRule::AfterDelay(45.0),
+                SpawnArea::around_camera(SpawnSide::Either, 600.0, 750.0),
+            ),
+            arena::generate_arena(
+                arena_seed,
+                "proving_grounds",
+                "Proving Grounds",
+                ARENA_MIN_X,
+                ARENA_MAX_X,
+            ),
+        ];
+
+        // The editor's rooms join these once the level file has loaded
+        rooms.push(endless::road_room(ARENA_MAX_X));
+
+        Self { rooms }
+    }
+}
+
+impl RoomRegistry {
+    // Swaps in the rooms of the level file, dropping the ones it had before. The endless
+    // run lays its chunks out past everything else, so the road moves past them
+    fn replace_level_rooms(&mut self, previous: &[String], level_rooms: Vec<RoomDefinition>) {
+        self.rooms
+            .retain(|room| room.id != endless::ENDLESS_ROAD_ID && !previous.contains(&room.id));
+        self.rooms.extend(level_rooms);
+
+        let world_end = self
+            .rooms
+            .iter()
+            .map(|room| room.bounds.max.x)
+            .fold(ARENA_MAX_X, f32::max);
+        self.rooms.push(endless::road_room(world_end));
+    }
+}
+
+// The LDtk file, and the ids of the rooms it last added to the registry
+#[derive(Resource)]
+pub struct LevelFile {
+    pub handle: Handle<ldtk::LdtkWorld>,
+    rooms: Vec<String>,
+}
+
+impl FromWorld for LevelFile {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            handle: world.resource::<AssetServer>().load(LEVEL_FILE_PATH),
+            rooms: Vec::new(),
+        }
+    }
+}
+
+// Bench placed in the world
+#[derive(Component)]
+pub struct Bench {
+    pub name: String,
+    pub room_id: String,
+}
+
+// Room the player is currently standing in
+#[derive(Resource, Default)]
+pub struct CurrentRoom {
+    pub id: Option<String>,
+}
+
+// Trigger that sends the player to another room's entry point
+#[derive(Component)]
+struct RoomTransition {
+    target_room: String,
+}
+
+// Links a trigger zone to the room it covers
+#[derive(Component)]
+pub struct RoomTrigger {
+    pub room_id: String,
+}
+
+// Sent when the player walks into a different room
+#[derive(Event)]
+pub struct RoomEnteredEvent {
+    pub room_id: String,
+    pub area: Option<String>,
+}
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<ldtk::LdtkWorld>()
+            .init_asset_loader::<ldtk::LdtkLoader>()
+            .init_resource::<RoomRegistry>()
+            .init_resource::<LevelFile>()
+            .init_resource::<CurrentRoom>()
+            .add_event::<RoomEnteredEvent>()
+            .add_systems(
+                OnEnter(InGame),
+                (
+                    reset_current_room,
+                    spawn_room_triggers,
+                    spawn_enemy_spawn_points,
+                    spawn_benches,
+                    spawn_room_props,
+                ),
+            )
+            // The game still runs without the editor's rooms, the world just ends at the
+            // arena. Edits to the file reach the next run
+            .add_systems(Update, apply_level_file)
+            .add_systems(
+                Update,
+                (update_current_room, follow_room_transitions)
+                    .after(triggers::detect_trigger_overlaps)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+// Swaps the level's rooms in once the file has loaded, and again whenever it is saved
+fn apply_level_file(
+    mut events: EventReader<AssetEvent<ldtk::LdtkWorld>>,
+    mut level_file: ResMut<LevelFile>,
+    worlds: Res<Assets<ldtk::LdtkWorld>>,
+    mut registry: ResMut<RoomRegistry>,
+) {
+    for event in events.read() {
+        if let AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } = *event
+            && id == level_file.handle.id()
+            && let Some(world) = worlds.get(id)
+        {
+            registry.replace_level_rooms(&level_file.rooms, world.rooms.clone());
+            level_file.rooms = world.rooms.iter().map(|room| room.id.clone()).collect();
+        }
+    }
+}
+
+// Starts each run outside any room, so entering the first one is announced again
+fn reset_current_room(mut current_room: ResMut<CurrentRoom>) {
+    *current_room = CurrentRoom::default();
+}
+
+fn spawn_room_triggers(mut commands: Commands, registry: Res<RoomRegistry>) {
+    for room in &registry.rooms {
+        commands.spawn((
+            StateScoped(InGame),
+            TriggerZone::new(room.bounds.size()),
+            RoomTrigger {
+                room_id: room.id.clone(),
+            },
+            Transform::from_translation(room.bounds.center().extend(0.0)),
+        ));
+
+        for transition in &room.transitions {
+            if registry.get(&transition.target_room).is_none() {
+                warn!(
+                    "Transition in {} leads to unknown room {}",
+                    room.id, transition.target_room
+                );
+                continue;
+            }
+            commands.spawn((
+                StateScoped(InGame),
+                TriggerZone::new(transition.area.size()),
+                RoomTransition {
+                    target_room: transition.target_room.clone(),
+                },
+                Transform::from_translation(transition.area.center().extend(0.0)),
+            ));
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn follow_room_transitions(
+    mut trigger_events: EventReader<TriggerEnteredEvent>,
+    transitions: Query<&RoomTransition>,
+    registry: Res<RoomRegistry>,
+    mut player_query: Query<(&mut Transform, &mut Physics), With<Player>>,
+    mut camera_query: Query<
+        (&mut Transform, &OrthographicProjection),
+        (With<Camera2d>, Without<Player>),
+    >,
+    windows: Query<&Window>,
+) {
+    for event in trigger_events.read() {
+        let Ok(transition) = transitions.get(event.trigger) else {
+            continue;
+        };
+        let Some(room) = registry.get(&transition.target_room) else {
+            continue;
+        };
+
+        let target = room.entry_point();
+        if let Ok((mut transform, mut physics)) = player_query.get_single_mut() {
+            transform.translation.x = target.x;
+            transform.translation.y = target.y;
+            physics.velocity = Vec2::ZERO;
+        }
+        // Start inside the new room's camera bounds instead of easing into them
+        if let Ok((mut camera_transform, projection)) = camera_query.get_single_mut() {
+            let half_view = windows
+                .get_single()
+                .map_or(Vec2::ZERO, |window| window.size() * projection.scale / 2.0);
+            let mut camera = Vec2::new(target.x, camera_transform.translation.y);
+            if let Some(lock) = room.camera_lock_at(target) {
+                camera = lock.clamp(camera, half_view);
+            }
+            camera_transform.translation.x = camera.x;
+            camera_transform.translation.y = camera.y;
+        }
+    }
+}
+
+fn spawn_enemy_spawn_points(mut commands: Commands, registry: Res<RoomRegistry>) {
+    for spawn in registry.rooms.iter().flat_map(|room| &room.enemy_spawns) {
+        let mut spawn_point = commands.spawn((
+            StateScoped(InGame),
+            EnemySpawnPoint::new(
+                spawn.kind,
+                spawn.respawn,
+                spawn.activation_distance,
+                spawn.elite_chance,
+                spawn.area,
+            ),
+            Transform::from_translation(spawn.position.extend(0.0)),
+        ));
+        if let Some(name) = &spawn.boss {
+            spawn_point.insert(Boss { name: name.clone() });
+        }
+    }
+}
+
+fn spawn_benches(mut commands: Commands, registry: Res<RoomRegistry>) {
+    for room in &registry.rooms {
+        for bench in &room.benches {
+            commands.spawn((
+                StateScoped(InGame),
+                Bench {
+                    name: bench.name.clone(),
+                    room_id: room.id.clone(),
+                },
+                Sprite {
+                    color: BENCH_COLOR,
+                    custom_size: Some(BENCH_SIZE),
+                    ..default()
+                },
+                Transform::from_translation(bench.position.extend(Layer::Props.z())),
+            ));
+        }
+    }
+}
+
+fn spawn_room_props(
+    mut commands: Commands,
+    registry: Res<RoomRegistry>,
+    ground_art: Res<GroundArt>,
+) {
+    let mut terrain = Vec::new();
+    let mut one_way_platforms = Vec::new();
+    for room in &registry.rooms {
+        if room.ground_strip {
+            let (min_x, max_x) = (room.bounds.min.x, room.bounds.max.x);
+            for (min_x, max_x, material) in
+                strip_ground_spans(min_x, max_x, &room.pits, &room.surfaces)
+            {
+                ground::spawn_ground_span(&mut commands, &ground_art, min_x, max_x, material);
+            }
+        }
+        for &position in &room.pushable_blocks {
+            pushable::spawn_pushable_block(&mut commands, position);
+        }
+        for platform in &room.platforms {
+            if platform.one_way {
+                one_way_platforms.push(Rect::from_center_size(
+                    platform.position,
+                    Vec2::new(platform.width, ONE_WAY_PLATFORM_HEIGHT),
+                ));
+            } else {
+                terrain.push(Rect::from_center_size(
+                    platform.position,
+                    Vec2::new(platform.width, PLATFORM_HEIGHT),
+                ));
+            }
+        }
+        for wall in &room.walls {
+            terrain.push(Rect::from_center_size(
+                Vec2::new(wall.x, GROUND_TOP_Y + wall.height / 2.0),
+                Vec2::new(WALL_WIDTH, wall.height),
+            ));
+        }
+        for zone in &room.physics_zones {
+            spawn_physics_zone(&mut commands, zone);
+        }
+        for hazard in &room.hazards {
+            spawn_hazard(&mut commands, hazard);
+        }
+        for segment in &room.ground_segments {
+            spawn_ground_segment(&mut commands, segment);
+        }
+        terrain.extend(room.terrain.iter().copied());
+        for ceiling in &room.ceilings {
+            terrain.push(Rect::new(
+                ceiling.min_x,
+                ceiling.bottom_y,
+                ceiling.max_x,
+                ceiling.bottom_y + CEILING_THICKNESS,
+            ));
+        }
+    }
+
+    // Rooms are laid out next to each other, so a run can cross from one into the next
+    for rect in merge_terrain(terrain) {
+        spawn_terrain(&mut commands, rect.center(), rect.size());
+    }
+    for rect in merge_terrain(one_way_platforms) {
+        spawn_one_way_platform(&mut commands, rect.center(), rect.size());
+    }
+}
+
+// Boxes lined up edge to edge, like platforms laid end to end or a wall built from
+// stacked pieces, become a single collider so physics and sight checks go through
+// far fewer solids
+fn merge_terrain(mut rects: Vec<Rect>) -> Vec<Rect> {
+    let mut merged_any = true;
+    while merged_any {
+        merged_any = false;
+        'search: for i in 0..rects.len() {
+            for j in i + 1..rects.len() {
+                if let Some(merged) = merge_terrain_pair(rects[i], rects[j]) {
+                    rects[i] = merged;
+                    rects.swap_remove(j);
+                    merged_any = true;
+                    break 'search;
+                }
+            }
+        }
+    }
+
+    rects
+}
+
+// Only boxes whose union is still a box: same rows touching sideways, or same columns
+// touching vertically
+fn merge_terrain_pair(a: Rect, b: Rect) -> Option<Rect> {
+    let same_rows = (a.min.y - b.min.y).abs() <= TERRAIN_MERGE_TOLERANCE
+        && (a.max.y - b.max.y).abs() <= TERRAIN_MERGE_TOLERANCE;
+    let same_columns = (a.min.x - b.min.x).abs() <= TERRAIN_MERGE_TOLERANCE
+        && (a.max.x - b.max.x).abs() <= TERRAIN_MERGE_TOLERANCE;
+    let touching_x = a.min.x <= b.max.x + TERRAIN_MERGE_TOLERANCE
+        && b.min.x <= a.max.x + TERRAIN_MERGE_TOLERANCE;
+    let touching_y = a.min.y <= b.max.y + TERRAIN_MERGE_TOLERANCE
+        && b.min.y <= a.max.y + TERRAIN_MERGE_TOLERANCE;
+
+    ((same_rows && touching_x) || (same_columns && touching_y)).then(|| a.union(b))
+}
+
+// Ground of a strip between two x positions: all of it but the pits, split where the
+// material changes
+pub fn strip_ground_spans(
+    min_x: f32,
+    max_x: f32,
+    pits: &[PitZone],
+    surfaces: &[SurfaceZone],
+) -> Vec<(f32, f32, SurfaceMaterial)> {
+    let mut cuts = vec![min_x, max_x];
+    cuts.extend(
+        pits.iter()
+            .flat_map(|pit| [pit.min_x, pit.max_x])
+            .chain(surfaces.iter().flat_map(|zone| [zone.min_x, zone.max_x]))
+            .filter(|x| *x > min_x && *x < max_x),
+    );
+    cuts.sort_by(f32::total_cmp);
+    cuts.dedup();
+
+    let mut spans: Vec<(f32, f32, SurfaceMaterial)> = Vec::new();
+    for pair in cuts.windows(2) {
+        let (start, end) = (pair[0], pair[1]);
+        let middle = (start + end) / 2.0;
+        if pits
+            .iter()
+            .any(|pit| middle >= pit.min_x && middle < pit.max_x)
+        {
+            continue;
+        }
+        let material = surfaces
+            .iter()
+            .find(|zone| middle >= zone.min_x && middle < zone.max_x)
+            .map(|zone| zone.material)
+            .unwrap_or_default();
+
+        match spans.last_mut() {
+            Some(last) if last.1 == start && last.2 == material => last.1 = end,
+            _ => spans.push((start, end, material)),
+        }
+    }
+
+    spans
+}
+
+// Static box that blocks movement and sight
+pub fn spawn_terrain(commands: &mut Commands, position: Vec2, size: Vec2) -> Entity {
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Solid { size },
+            SightBlocker { size },
+            Sprite {
+                color: TERRAIN_COLOR,
+                custom_size: Some(size),
+                ..default()
+            },
+            Transform::from_translation(position.extend(Layer::Terrain.z())),
+        ))
+        .id()
+}
+
+pub fn spawn_hazard(commands: &mut Commands, hazard: &HazardDefinition) -> Entity {
+    let size = hazard.area.size();
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Hazard { size },
+            Sprite {
+                color: hazard.kind.color(),
+                custom_size: Some(size),
+                ..default()
+            },
+            Transform::from_translation(hazard.area.center().extend(Layer::Terrain.z())),
+        ))
+        .id()
+}
+
+// Thin ledge that only holds from above; enemies can see through it
+fn spawn_one_way_platform(commands: &mut Commands, position: Vec2, size: Vec2) {
+    commands.spawn((
+        StateScoped(InGame),
+        Solid { size },
+        OneWayPlatform,
+        Sprite {
+            color: ONE_WAY_PLATFORM_COLOR,
+            custom_size: Some(size),
+            ..default()
+        },
+        Transform::from_translation(position.extend(Layer::Terrain.z())),
+    ));
+}
+
+fn spawn_ground_segment(commands: &mut Commands, segment: &GroundSegmentDefinition) {
+    let size = segment.area.size();
+    commands.spawn((
+        StateScoped(InGame),
+        Ground { size },
+        segment.material,
+        Solid { size },
+        SightBlocker { size },
+        Sprite {
+            color: match segment.material {
+                SurfaceMaterial::Stone => GROUND_SEGMENT_COLOR,
+                SurfaceMaterial::Ice => ICE_TINT,
+                SurfaceMaterial::Mud => MUD_TINT,
+            },
+            custom_size: Some(size),
+            ..default()
+        },
+        Transform::from_translation(segment.area.center().extend(Layer::Ground.z())),
+    ));
+}
+
+fn spawn_physics_zone(commands: &mut Commands, zone: &PhysicsZoneDefinition) {
+    let size = zone.area.size();
+    commands.spawn((
+        StateScoped(InGame),
+        zone.kind.zone(size),
+        Sprite {
+            color: zone.kind.color(),
+            custom_size: Some(size),
+            ..default()
+        },
+        // In front of the characters, so they look submerged
+        Transform::from_translation(zone.area.center().extend(Layer::Effects.z())),
+    ));
+}
+
+pub fn update_current_room(
+    mut trigger_events: EventReader<TriggerEnteredEvent>,
+    room_triggers: Query<&RoomTrigger>,
+    registry: Res<RoomRegistry>,
+    mut current_room: ResMut<CurrentRoom>,
+    mut room_events: EventWriter<RoomEnteredEvent>,
+) {
+    for event in trigger_events.read() {
+        let Ok(room_trigger) = room_triggers.get(event.trigger) else {
+            continue;
+        };
+
+        if current_room.id.as_deref() == Some(room_trigger.room_id.as_str()) {
+            continue;
+        }
+
+        if let Some(room) = registry.get(&room_trigger.room_id) {
+            current_room.id = Some(room.id.clone());
+            room_events.send(RoomEnteredEvent {
+                room_id: room.id.clone(),
+                area: room.area.clone(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boxes_side_by_side_merge() {
+        let merged = merge_terrain(vec![
+            Rect::new(0.0, 0.0, 32.0, 32.0),
+            Rect::new(32.0, 0.0, 96.0, 32.0),
+            Rect::new(96.2, 0.0, 128.0, 32.0),
+        ]);
+        assert_eq!(merged, [Rect::new(0.0, 0.0, 128.0, 32.0)]);
+    }
+
+    #[test]
+    fn stacked_boxes_merge() {
+        let merged = merge_terrain(vec![
+            Rect::new(0.0, 0.0, 32.0, 32.0),
+            Rect::new(0.0, 64.0, 32.0, 96.0),
+            Rect::new(0.0, 32.0, 32.0, 64.0),
+        ]);
+        assert_eq!(merged, [Rect::new(0.0, 0.0, 32.0, 96.0)]);
+    }
+
+    #[test]
+    fn overlapping_boxes_merge() {
+        let merged = merge_terrain(vec![
+            Rect::new(0.0, 0.0, 64.0, 32.0),
+            Rect::new(48.0, 0.0, 96.0, 32.0),
+        ]);
+        assert_eq!(merged, [Rect::new(0.0, 0.0, 96.0, 32.0)]);
+    }
+
+    #[test]
+    fn boxes_whose_union_is_not_a_box_stay_apart() {
+        // Corner to corner, an L, and a gap
+        let rects = vec![
+            Rect::new(0.0, 0.0, 32.0, 32.0),
+            Rect::new(32.0, 32.0, 64.0, 64.0),
+            Rect::new(0.0, 32.0, 16.0, 64.0),
+            Rect::new(100.0, 0.0, 132.0, 32.0),
+        ];
+        assert_eq!(merge_terrain(rects.clone()).len(), rects.len());
+    }
+
+    #[test]
+    fn strip_ground_leaves_out_pits_and_splits_on_materials() {
+        let pits = [PitZone {
+            min_x: 100.0,
+            max_x: 200.0,
+        }];
+        let surfaces = [SurfaceZone {
+            min_x: 300.0,
+            max_x: 400.0,
+            material: SurfaceMaterial::Ice,
+        }];
+        assert_eq!(
+            strip_ground_spans(0.0, 500.0, &pits, &surfaces),
+            [
+                (0.0, 100.0, SurfaceMaterial::Stone),
+                (200.0, 300.0, SurfaceMaterial::Stone),
+                (300.0, 400.0, SurfaceMaterial::Ice),
+                (400.0, 500.0, SurfaceMaterial::Stone),
+            ]
+        );
+    }
+}
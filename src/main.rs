@@ -1,15 +1,69 @@
 use bevy::prelude::*;
 
+pub mod aggro_indicators;
+pub mod ambient;
+pub mod animation_manifest;
 pub mod animations;
+pub mod arena;
+pub mod aseprite;
+pub mod atlas;
+pub mod audio;
+#[cfg(feature = "avian")]
+pub mod avian_backend;
+pub mod barrier;
+pub mod boss;
+pub mod breakable;
+pub mod camera_director;
+pub mod camera_shake;
+pub mod camera_zoom;
+pub mod cleanup;
+pub mod companion;
+pub mod config;
+pub mod damage_numbers;
+pub mod debug;
+pub mod dialogue;
+pub mod doors;
+pub mod endless;
 pub mod enemy;
+pub mod fade;
 pub mod game;
+pub mod game_over;
 pub mod ground;
+pub mod hazards;
+pub mod health_bar;
+pub mod hitbox;
+pub mod hud;
+pub mod hurt_flash;
+pub mod input;
+pub mod input_display;
+pub mod intro;
+pub mod inventory;
+pub mod layers;
+pub mod ldtk;
+pub mod ledge_grab;
+pub mod level;
+pub mod map;
 pub mod menu;
+pub mod menu_navigation;
+pub mod music;
+pub mod notifications;
 pub mod paralax_background;
 pub mod pause;
 pub mod physics;
+pub mod pickups;
 pub mod player;
+pub mod pushable;
 pub mod resolution;
+pub mod save;
+pub mod save_slots;
+pub mod save_transfer;
+pub mod set_pieces;
+pub mod settings;
+pub mod settings_menu;
+pub mod spatial;
+pub mod status_effects;
+pub mod toll_gate;
+pub mod triggers;
 pub mod utils;
 
 fn main() {
@@ -28,7 +82,7 @@ fn main() {
                     ..default()
                 })
                 .set(ImagePlugin::default_nearest()),
-                game::GamePlugin,
+            game::GamePlugin,
         ))
         .run();
 }
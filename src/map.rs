@@ -0,0 +1,269 @@
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+use crate::game::{GameState, InGame};
+use crate::input::{ActionState, InputAction};
+use crate::layers::UiLayer;
+use crate::level::{self, CurrentRoom, RoomEnteredEvent, RoomRegistry};
+use crate::notifications::NotificationEvent;
+use crate::pause;
+
+// Map constants
+const MAP_SIZE: Vec2 = Vec2::new(960.0, 420.0);
+const MAP_PADDING: f32 = 24.0;
+const MAP_MIN_ROOM_HEIGHT: f32 = 48.0; // Rooms along the ground strip are much wider than tall
+const MAP_OVERLAY_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.8);
+const MAP_PANEL_COLOR: Color = Color::srgba(0.12, 0.11, 0.1, 0.95);
+const MAP_ROOM_COLOR: Color = Color::srgb(0.3, 0.3, 0.34);
+const MAP_CURRENT_ROOM_COLOR: Color = Color::srgb(0.45, 0.55, 0.75);
+const MAP_ROOM_BORDER_COLOR: Color = Color::srgb(0.85, 0.82, 0.75);
+const MAP_BENCH_SIZE: f32 = 10.0;
+const MAP_BENCH_COLOR: Color = Color::srgb(0.95, 0.8, 0.35);
+const MAP_LABEL_FONT_SIZE: f32 = 14.0;
+
+// Rooms the player has been to, saved with the game at benches
+#[derive(Resource, Default, Clone)]
+pub struct MapProgress {
+    pub visited: HashSet<String>,
+}
+
+// Whether the map is up; the player stands still while it is
+#[derive(Resource, Default)]
+pub struct MapScreen {
+    pub open: bool,
+}
+
+// Run condition for player controls, which stop while the map is up
+pub fn map_closed(map: Res<MapScreen>) -> bool {
+    !map.open
+}
+
+#[derive(Component)]
+struct MapRoot;
+
+pub struct MapPlugin;
+
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MapProgress>()
+            .init_resource::<MapScreen>()
+            .add_systems(OnExit(InGame), close_map)
+            .add_systems(OnEnter(GameState::Paused), close_map)
+            .add_systems(
+                Update,
+                (
+                    record_visited_rooms.after(level::update_current_room),
+                    close_map_on_keys.before(pause::handle_pause_input),
+                    sync_map_screen
+                        .after(record_visited_rooms)
+                        .after(pause::handle_pause_input)
+                        .run_if(resource_changed::<MapScreen>),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn record_visited_rooms(
+    mut room_events: EventReader<RoomEnteredEvent>,
+    mut progress: ResMut<MapProgress>,
+    mut notifications: EventWriter<NotificationEvent>,
+) {
+    let mut discovered = false;
+    for event in room_events.read() {
+        discovered |= progress.visited.insert(event.room_id.clone());
+    }
+    if discovered {
+        notifications.send(NotificationEvent::new("Map updated"));
+    }
+}
+
+// The map opens with its own key, after the same grace period as the pause menu (see
+// pause::handle_pause_input). It closes with that key or the pause key, which would
+// otherwise open the pause menu over it, or open the map again
+fn close_map_on_keys(mut actions: ResMut<ActionState>, mut map: ResMut<MapScreen>) {
+    if !map.open {
+        return;
+    }
+    for action in [InputAction::Map, InputAction::Pause] {
+        if actions.just_pressed(action) {
+            actions.consume(action);
+            map.open = false;
+        }
+    }
+}
+
+fn sync_map_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    map: Res<MapScreen>,
+    roots: Query<Entity, With<MapRoot>>,
+    registry: Res<RoomRegistry>,
+    progress: Res<MapProgress>,
+    current_room: Res<CurrentRoom>,
+) {
+    for root in &roots {
+        commands.entity(root).despawn_recursive();
+    }
+    if map.open {
+        spawn_map(
+            &mut commands,
+            &asset_server,
+            &registry,
+            &progress,
+            current_room.id.as_deref(),
+        );
+    }
+}
+
+// Rooms are drawn where they sit in the world, scaled to fit the panel. Only visited
+// ones are shown, so the map fills in as the player explores
+fn spawn_map(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    registry: &RoomRegistry,
+    progress: &MapProgress,
+    current_room: Option<&str>,
+) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let visited: Vec<_> = registry
+        .rooms
+        .iter()
+        .filter(|room| progress.visited.contains(&room.id))
+        .collect();
+
+    // Fit the width of the explored world; rooms are too tall to fit their height as well
+    let min_x = visited
+        .iter()
+        .map(|room| room.bounds.min.x)
+        .fold(f32::INFINITY, f32::min);
+    let max_x = visited
+        .iter()
+        .map(|room| room.bounds.max.x)
+        .fold(f32::NEG_INFINITY, f32::max);
+    let inner = MAP_SIZE - Vec2::splat(MAP_PADDING * 2.0);
+    let scale = if max_x > min_x {
+        inner.x / (max_x - min_x)
+    } else {
+        0.0
+    };
+
+    commands
+        .spawn((
+            MapRoot,
+            StateScoped(InGame),
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(16.0),
+                ..default()
+            },
+            BackgroundColor(MAP_OVERLAY_COLOR),
+            GlobalZIndex(UiLayer::Overlay.z_index()),
+        ))
+        .with_children(|parent| {
+            let area = current_room
+                .and_then(|id| registry.get(id))
+                .and_then(|room| room.area.clone())
+                .unwrap_or_else(|| "MAP".to_string());
+            parent.spawn((
+                Text::new(area),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 32.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(MAP_SIZE.x),
+                        height: Val::Px(MAP_SIZE.y),
+                        ..default()
+                    },
+                    BorderRadius::all(Val::Px(8.0)),
+                    BackgroundColor(MAP_PANEL_COLOR),
+                ))
+                .with_children(|parent| {
+                    if visited.is_empty() {
+                        return;
+                    }
+
+                    let room_top = (MAP_SIZE.y - MAP_MIN_ROOM_HEIGHT) / 2.0;
+                    for room in &visited {
+                        let left = MAP_PADDING + (room.bounds.min.x - min_x) * scale;
+                        let width = (room.bounds.width() * scale).max(2.0);
+                        let is_current = current_room == Some(room.id.as_str());
+                        parent
+                            .spawn((
+                                Node {
+                                    position_type: PositionType::Absolute,
+                                    left: Val::Px(left),
+                                    top: Val::Px(room_top),
+                                    width: Val::Px(width),
+                                    height: Val::Px(MAP_MIN_ROOM_HEIGHT),
+                                    border: UiRect::all(Val::Px(if is_current {
+                                        3.0
+                                    } else {
+                                        1.0
+                                    })),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    overflow: Overflow::clip(),
+                                    ..default()
+                                },
+                                BorderColor(MAP_ROOM_BORDER_COLOR),
+                                BackgroundColor(if is_current {
+                                    MAP_CURRENT_ROOM_COLOR
+                                } else {
+                                    MAP_ROOM_COLOR
+                                }),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    Text::new(room.name.clone()),
+                                    TextFont {
+                                        font: font.clone(),
+                                        font_size: MAP_LABEL_FONT_SIZE,
+                                        ..default()
+                                    },
+                                    TextColor(Color::WHITE),
+                                ));
+                            });
+
+                        // Benches sit on top of their room's box
+                        for bench in &room.benches {
+                            let bench_left = MAP_PADDING + (bench.position.x - min_x) * scale;
+                            parent.spawn((
+                                Node {
+                                    position_type: PositionType::Absolute,
+                                    left: Val::Px(bench_left - MAP_BENCH_SIZE / 2.0),
+                                    top: Val::Px(room_top - MAP_BENCH_SIZE - 4.0),
+                                    width: Val::Px(MAP_BENCH_SIZE),
+                                    height: Val::Px(MAP_BENCH_SIZE),
+                                    ..default()
+                                },
+                                BorderRadius::MAX,
+                                BackgroundColor(MAP_BENCH_COLOR),
+                            ));
+                        }
+                    }
+                });
+        });
+}
+
+fn close_map(
+    mut commands: Commands,
+    mut map: ResMut<MapScreen>,
+    roots: Query<Entity, With<MapRoot>>,
+) {
+    map.open = false;
+    for root in &roots {
+        commands.entity(root).despawn_recursive();
+    }
+}
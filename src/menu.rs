@@ -1,18 +1,36 @@
 use bevy::prelude::*;
 
+use crate::endless::RunMode;
 use crate::game::GameState;
+use crate::level::RoomRegistry;
+use crate::save::{ActiveSave, SaveData};
+use crate::save_slots::{self, SaveSlotsMenu};
+use crate::save_transfer::{self, SAVE_EXPORT_PATH};
+use crate::settings_menu::{self, SettingsMenu};
 
 const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
 const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
 const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
+const MENU_OVERLAY_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.35); // Dims the backdrop behind
 
-// Component to mark the start button
+// Buttons that start a run, one per mode. The story goes through the save slots first
 #[derive(Component)]
-struct StartButton;
+struct StartButton(RunMode);
 
-// Component to mark the menu UI
+// Button that opens the settings screen
 #[derive(Component)]
-struct MenuUI;
+struct SettingsButton;
+
+// Buttons that move the save to or from a portable file
+#[derive(Component, Clone, Copy)]
+enum SaveTransferButton {
+    Export,
+    Import,
+}
+
+// Text reporting how the last export or import went
+#[derive(Component)]
+struct SaveTransferStatus;
 
 pub struct MenuPlugin;
 
@@ -21,9 +39,17 @@ impl Plugin for MenuPlugin {
         app.add_systems(OnEnter(GameState::Menu), setup_menu)
             .add_systems(
                 Update,
-                handle_start_button.run_if(in_state(GameState::Menu)),
-            )
-            .add_systems(OnExit(GameState::Menu), cleanup_menu);
+                (
+                    handle_start_button,
+                    handle_settings_button,
+                    handle_save_transfer_buttons,
+                )
+                    .run_if(
+                        in_state(GameState::Menu)
+                            .and(settings_menu::settings_closed)
+                            .and(save_slots::slots_closed),
+                    ),
+            );
     }
 }
 
@@ -40,24 +66,21 @@ fn setup_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
                 display: Display::Flex,
                 ..default()
             },
-            BackgroundColor(Color::srgba(0.1, 0.1, 0.1, 1.)),
-            MenuUI,
+            BackgroundColor(MENU_OVERLAY_COLOR),
+            StateScoped(GameState::Menu),
         ))
         .with_children(|parent| {
             // Menu container
             parent
-                .spawn((
-                    Node {
-                        width: Val::Percent(100.0),
-                        height: Val::Percent(100.0),
-                        align_items: AlignItems::Center,
-                        justify_content: JustifyContent::SpaceAround,
-                        flex_direction: FlexDirection::Column,
-                        display: Display::Flex,
-                        ..default()
-                    },
-                    BackgroundColor(Color::srgba(0.1, 0.1, 0.1, 0.9)),
-                ))
+                .spawn((Node {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::SpaceAround,
+                    flex_direction: FlexDirection::Column,
+                    display: Display::Flex,
+                    ..default()
+                },))
                 .with_children(|parent| {
                     // Game title
                     parent.spawn((
@@ -70,86 +93,249 @@ fn setup_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
                         TextColor(Color::WHITE),
                     ));
 
-                    // Start button
+                    // Start buttons
+                    for mode in [RunMode::Story, RunMode::Endless] {
+                        parent
+                            .spawn((
+                                Button,
+                                Node {
+                                    width: Val::Px(150.0),
+                                    height: Val::Px(65.0),
+                                    border: UiRect::all(Val::Px(5.0)),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    flex_direction: FlexDirection::Column,
+                                    ..default()
+                                },
+                                BorderColor(Color::BLACK),
+                                BorderRadius::MAX,
+                                BackgroundColor(NORMAL_BUTTON),
+                                StartButton(mode),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    Text::new(start_label(mode)),
+                                    TextFont {
+                                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                        font_size: 24.0,
+                                        ..default()
+                                    },
+                                    TextColor(Color::WHITE),
+                                ));
+                            });
+                    }
+
+                    // Option buttons
                     parent
-                        .spawn((
-                            Button,
-                            Node {
-                                width: Val::Px(150.0),
-                                height: Val::Px(65.0),
-                                border: UiRect::all(Val::Px(5.0)),
-                                justify_content: JustifyContent::Center,
-                                align_items: AlignItems::Center,
-                                flex_direction: FlexDirection::Column,
-                                ..default()
-                            },
-                            BorderColor(Color::BLACK),
-                            BorderRadius::MAX,
-                            BackgroundColor(NORMAL_BUTTON),
-                            StartButton,
-                        ))
+                        .spawn(Node {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            row_gap: Val::Px(10.0),
+                            ..default()
+                        })
                         .with_children(|parent| {
+                            parent
+                                .spawn((
+                                    Button,
+                                    Node {
+                                        width: Val::Px(260.0),
+                                        height: Val::Px(45.0),
+                                        border: UiRect::all(Val::Px(3.0)),
+                                        justify_content: JustifyContent::Center,
+                                        align_items: AlignItems::Center,
+                                        ..default()
+                                    },
+                                    BorderColor(Color::BLACK),
+                                    BorderRadius::MAX,
+                                    BackgroundColor(NORMAL_BUTTON),
+                                    SettingsButton,
+                                ))
+                                .with_children(|parent| {
+                                    parent.spawn((
+                                        Text::new("Settings"),
+                                        TextFont {
+                                            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                            font_size: 20.0,
+                                            ..default()
+                                        },
+                                        TextColor(Color::WHITE),
+                                    ));
+                                });
+
+                            // Save export and import
+                            for (button, label) in [
+                                (SaveTransferButton::Export, "Export save"),
+                                (SaveTransferButton::Import, "Import save"),
+                            ] {
+                                parent
+                                    .spawn((
+                                        Button,
+                                        Node {
+                                            width: Val::Px(260.0),
+                                            height: Val::Px(45.0),
+                                            border: UiRect::all(Val::Px(3.0)),
+                                            justify_content: JustifyContent::Center,
+                                            align_items: AlignItems::Center,
+                                            ..default()
+                                        },
+                                        BorderColor(Color::BLACK),
+                                        BorderRadius::MAX,
+                                        BackgroundColor(NORMAL_BUTTON),
+                                        button,
+                                    ))
+                                    .with_children(|parent| {
+                                        parent.spawn((
+                                            Text::new(label),
+                                            TextFont {
+                                                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                                font_size: 20.0,
+                                                ..default()
+                                            },
+                                            TextColor(Color::WHITE),
+                                        ));
+                                    });
+                            }
                             parent.spawn((
-                                Text::new("Start Game"),
+                                Text::new(""),
                                 TextFont {
                                     font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                                    font_size: 24.0,
+                                    font_size: 16.0,
                                     ..default()
                                 },
-                                TextColor(Color::WHITE),
+                                TextColor(Color::srgb(0.8, 0.8, 0.8)),
+                                SaveTransferStatus,
                             ));
                         });
                 });
         });
 }
 
-// Remove menu UI when exiting Menu state
-fn cleanup_menu(mut commands: Commands, menu_query: Query<Entity, With<MenuUI>>) {
-    for menu_entity in menu_query.iter() {
-        commands.entity(menu_entity).despawn_recursive();
+fn start_label(mode: RunMode) -> &'static str {
+    match mode {
+        RunMode::Story => "Start Game",
+        RunMode::Endless => "Endless Run",
     }
 }
 
 // Handle button interactions to transition to the Playing state
+#[allow(clippy::type_complexity)]
 fn handle_start_button(
     mut next_state: ResMut<NextState<GameState>>,
+    mut run_mode: ResMut<RunMode>,
+    mut save: ResMut<ActiveSave>,
+    mut slots: ResMut<SaveSlotsMenu>,
     mut interaction_query: Query<
         (
+            &StartButton,
             &Interaction,
             &mut BackgroundColor,
             &mut BorderColor,
             &Children,
         ),
-        (Changed<Interaction>, With<StartButton>),
+        Changed<Interaction>,
     >,
     mut text_query: Query<&mut Text>,
     keyboard: Res<ButtonInput<KeyCode>>,
 ) {
     // Check for button press
-    for (interaction, mut color, mut border_color, children) in &mut interaction_query {
+    for (button, interaction, mut color, mut border_color, children) in &mut interaction_query {
         let mut text = text_query.get_mut(children[0]).unwrap();
         match *interaction {
+            Interaction::Pressed if button.0 == RunMode::Story => {
+                *color = PRESSED_BUTTON.into();
+                slots.open = true;
+            }
             Interaction::Pressed => {
                 **text = "Starting...".to_string();
                 *color = PRESSED_BUTTON.into();
                 border_color.0 = Color::srgb(1.0, 0.0, 0.0);
+                *run_mode = button.0;
+                // Endless runs start from nothing and are never saved
+                save.data = SaveData::default();
                 next_state.set(GameState::Playing);
             }
             Interaction::Hovered => {
-                **text = "Start Game".to_string();
+                **text = start_label(button.0).to_string();
                 *color = HOVERED_BUTTON.into();
                 border_color.0 = Color::WHITE;
             }
             Interaction::None => {
-                **text = "Start Game".to_string();
+                **text = start_label(button.0).to_string();
                 *color = NORMAL_BUTTON.into();
                 border_color.0 = Color::BLACK;
             }
         }
     }
 
-    // Also allow starting with Enter key
-    if keyboard.just_pressed(KeyCode::Backspace) || keyboard.just_pressed(KeyCode::Space) {
-        next_state.set(GameState::Playing);
+    // Space goes straight to the save slots; Enter presses whichever button has the focus
+    if keyboard.just_pressed(KeyCode::Space) {
+        slots.open = true;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn handle_settings_button(
+    mut settings_menu: ResMut<SettingsMenu>,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<SettingsButton>),
+    >,
+) {
+    for (interaction, mut color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                settings_menu.open = true;
+                *color = PRESSED_BUTTON.into();
+            }
+            Interaction::Hovered => *color = HOVERED_BUTTON.into(),
+            Interaction::None => *color = NORMAL_BUTTON.into(),
+        }
+    }
+}
+
+// Export writes the slot last played to a portable file, import replaces that slot with
+// the file's save
+fn handle_save_transfer_buttons(
+    registry: Res<RoomRegistry>,
+    active_save: Res<ActiveSave>,
+    mut interaction_query: Query<
+        (&Interaction, &SaveTransferButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    mut status_query: Query<&mut Text, With<SaveTransferStatus>>,
+) {
+    for (interaction, button, mut color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = PRESSED_BUTTON.into();
+
+                let status = match button {
+                    SaveTransferButton::Export => {
+                        let slot = active_save.slot;
+                        let save = SaveData::load(slot).unwrap_or_default();
+                        match save_transfer::export_save(&save) {
+                            Ok(()) => {
+                                format!("Slot {} exported to {SAVE_EXPORT_PATH}", slot + 1)
+                            }
+                            Err(error) => format!("Export failed: {error}"),
+                        }
+                    }
+                    SaveTransferButton::Import => match save_transfer::import_save(&registry) {
+                        Ok(save) => {
+                            let slot = active_save.slot;
+                            // Its profile, if any, is applied when the slot is played
+                            save.write(slot);
+                            format!("Slot {} imported from {SAVE_EXPORT_PATH}", slot + 1)
+                        }
+                        Err(error) => format!("Import failed: {error}"),
+                    },
+                };
+                for mut text in &mut status_query {
+                    **text = status.clone();
+                }
+            }
+            Interaction::Hovered => *color = HOVERED_BUTTON.into(),
+            Interaction::None => *color = NORMAL_BUTTON.into(),
+        }
     }
 }
@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+use bevy::ui::UiStack;
+
+use crate::game::GameState;
+use crate::input::{self, ActionState, InputAction};
+use crate::settings_menu;
+
+// Menu navigation constants
+const NAVIGATION_STICK_THRESHOLD: f32 = 0.5;
+const FOCUS_OUTLINE_WIDTH: f32 = 3.0;
+const FOCUS_OUTLINE_COLOR: Color = Color::srgb(0.95, 0.85, 0.4);
+const CONFIRM_KEY: KeyCode = KeyCode::Enter;
+const CANCEL_KEY: KeyCode = KeyCode::Backspace;
+
+// Button picked with a controller, outlined until the mouse or another pick takes over
+#[derive(Resource, Default)]
+struct MenuFocus {
+    focused: Option<Entity>,
+    pressed: Option<Entity>, // Let go of on the next frame, the way a click would be
+    stick: IVec2,            // Direction the left stick leaned last frame
+}
+
+pub struct MenuNavigationPlugin;
+
+impl Plugin for MenuNavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MenuFocus>()
+            .add_systems(
+                PreUpdate,
+                cancel_menus
+                    .after(input::update_action_state)
+                    .run_if(not(in_state(GameState::Playing))),
+            )
+            .add_systems(
+                Update,
+                // In game the confirm button jumps, and the only buttons are debug tools. A key
+                // being rebound shouldn't move the focus either. Running after the capture, a
+                // rebind button pressed with the confirm key starts listening only next frame,
+                // so it doesn't take that same key
+                navigate_menus.after(settings_menu::capture_binding).run_if(
+                    not(in_state(GameState::Playing)).and(not(settings_menu::capturing_binding)),
+                ),
+            );
+    }
+}
+
+// Backspace and the east face button back out the way the pause key does, so every screen
+// that closes on pause closes on them too
+fn cancel_menus(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut actions: ResMut<ActionState>,
+) {
+    if keyboard.just_pressed(CANCEL_KEY)
+        || gamepads
+            .iter()
+            .any(|gamepad| gamepad.just_pressed(GamepadButton::East))
+    {
+        actions.trigger(InputAction::Pause);
+    }
+}
+
+// Arrow keys, D-pad or stick move between the buttons of the screen on top, in reading
+// order, and Enter or the south face button presses the one picked. Buttons see it as a
+// click. Confirming with nothing picked presses the first button
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+fn navigate_menus(
+    mut commands: Commands,
+    mut focus: ResMut<MenuFocus>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    ui_stack: Res<UiStack>,
+    buttons: Query<(Entity, &GlobalTransform, &ViewVisibility), With<Button>>,
+    parents: Query<&Parent>,
+    mut interactions: Query<&mut Interaction>,
+) {
+    if let Some(pressed) = focus.pressed.take()
+        && let Ok(mut interaction) = interactions.get_mut(pressed)
+        && *interaction == Interaction::Pressed
+    {
+        *interaction = Interaction::None;
+    }
+
+    // Only the buttons of the topmost screen, so a menu under the settings isn't reachable
+    let root_of = |entity: Entity| parents.iter_ancestors(entity).last().unwrap_or(entity);
+    let top_root = ui_stack
+        .uinodes
+        .iter()
+        .rev()
+        .find(|&&entity| {
+            buttons
+                .get(entity)
+                .is_ok_and(|(_, _, visibility)| visibility.get())
+        })
+        .map(|&entity| root_of(entity));
+    let mut candidates: Vec<(Entity, Vec2)> = buttons
+        .iter()
+        .filter(|(entity, _, visibility)| visibility.get() && Some(root_of(*entity)) == top_root)
+        .map(|(entity, transform, _)| (entity, transform.translation().truncate()))
+        .collect();
+    candidates.sort_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+
+    let mut step = 0;
+    if keyboard.just_pressed(KeyCode::ArrowDown) || keyboard.just_pressed(KeyCode::ArrowRight) {
+        step = 1;
+    } else if keyboard.just_pressed(KeyCode::ArrowUp) || keyboard.just_pressed(KeyCode::ArrowLeft) {
+        step = -1;
+    }
+    let mut confirm = keyboard.just_pressed(CONFIRM_KEY);
+    let mut stick = IVec2::ZERO;
+    for gamepad in &gamepads {
+        if gamepad.just_pressed(GamepadButton::DPadDown)
+            || gamepad.just_pressed(GamepadButton::DPadRight)
+        {
+            step = 1;
+        } else if gamepad.just_pressed(GamepadButton::DPadUp)
+            || gamepad.just_pressed(GamepadButton::DPadLeft)
+        {
+            step = -1;
+        }
+        confirm |= gamepad.just_pressed(GamepadButton::South);
+
+        let left_stick = gamepad.left_stick();
+        stick.x = stick_direction(left_stick.x);
+        stick.y = stick_direction(left_stick.y);
+    }
+    // The stick steps once per lean, not every frame it's held
+    if stick != focus.stick {
+        if stick.y < 0 || stick.x > 0 {
+            step = 1;
+        } else if stick.y > 0 || stick.x < 0 {
+            step = -1;
+        }
+    }
+    focus.stick = stick;
+
+    let index = focus
+        .focused
+        .and_then(|focused| candidates.iter().position(|&(entity, _)| entity == focused));
+    let next = match (index, step) {
+        _ if candidates.is_empty() => None,
+        (None, 0) if confirm => Some(0),
+        (None, 0) => None,
+        (None, _) => Some(0),
+        (Some(index), step) => {
+            Some((index as i32 + step).rem_euclid(candidates.len() as i32) as usize)
+        }
+    }
+    .map(|index| candidates[index].0);
+
+    if next != focus.focused {
+        if let Some(old) = focus.focused
+            && let Some(mut entity) = commands.get_entity(old)
+        {
+            entity.remove::<Outline>();
+        }
+        if let Some(new) = next {
+            commands.entity(new).insert(Outline::new(
+                Val::Px(FOCUS_OUTLINE_WIDTH),
+                Val::ZERO,
+                FOCUS_OUTLINE_COLOR,
+            ));
+        }
+        focus.focused = next;
+    }
+
+    if confirm
+        && let Some(focused) = focus.focused
+        && let Ok(mut interaction) = interactions.get_mut(focused)
+    {
+        *interaction = Interaction::Pressed;
+        focus.pressed = Some(focused);
+    }
+}
+
+fn stick_direction(value: f32) -> i32 {
+    if value > NAVIGATION_STICK_THRESHOLD {
+        1
+    } else if value < -NAVIGATION_STICK_THRESHOLD {
+        -1
+    } else {
+        0
+    }
+}
@@ -0,0 +1,127 @@
+use bevy::audio::Volume;
+use bevy::prelude::*;
+
+use crate::audio::AudioChannel;
+use crate::boss::BossHealthBar;
+use crate::game::GameState;
+use crate::level::{CurrentRoom, RoomRegistry};
+use crate::settings::GameSettings;
+
+// Music constants
+pub const DEFAULT_MUSIC: &str = "audio/music/crossroads.wav"; // Rooms without their own track
+const MENU_MUSIC: &str = "audio/music/menu.wav";
+const BOSS_MUSIC: &str = "audio/music/boss.wav";
+const MUSIC_VOLUME: f32 = 0.5;
+const MUSIC_CROSSFADE_TIME: f32 = 1.5; // Seconds for one track to hand over to the next
+
+// Track that should be playing, worked out from the game state, the room the player is
+// in and whether a boss fight is on. The track on is swapped whenever that changes
+#[derive(Resource, Default)]
+pub struct MusicManager {
+    pub current: Option<String>,
+}
+
+// A looping track, fading in as the current one or out after being replaced
+#[derive(Component)]
+struct MusicTrack {
+    fade: f32, // 0 is silent, 1 full volume
+    fading_out: bool,
+}
+
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MusicManager>()
+            .add_systems(Update, (choose_music, fade_music).chain());
+    }
+}
+
+fn desired_track(
+    state: &GameState,
+    current_room: &CurrentRoom,
+    registry: &RoomRegistry,
+    boss_bar: &BossHealthBar,
+) -> Option<String> {
+    match state {
+        GameState::Loading => None,
+        GameState::Menu => Some(MENU_MUSIC.to_string()),
+        // Pausing and dying keep the run's music going
+        GameState::Playing | GameState::Paused | GameState::GameOver | GameState::Restarting => {
+            if boss_bar.boss.is_some() {
+                return Some(BOSS_MUSIC.to_string());
+            }
+            let music = current_room
+                .id
+                .as_deref()
+                .and_then(|id| registry.get(id))
+                .map_or(DEFAULT_MUSIC, |room| room.music.as_str());
+            Some(music.to_string())
+        }
+    }
+}
+
+// Rooms in the same area share a track, so it only changes when the area does
+#[allow(clippy::too_many_arguments)]
+fn choose_music(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut manager: ResMut<MusicManager>,
+    state: Res<State<GameState>>,
+    current_room: Res<CurrentRoom>,
+    registry: Res<RoomRegistry>,
+    boss_bar: Res<BossHealthBar>,
+    mut tracks: Query<&mut MusicTrack>,
+) {
+    // A retry passes through with the room cleared; the track carries on into the new run
+    if *state.get() == GameState::Restarting {
+        return;
+    }
+    let desired = desired_track(state.get(), &current_room, &registry, &boss_bar);
+    if desired == manager.current {
+        return;
+    }
+
+    for mut track in &mut tracks {
+        track.fading_out = true;
+    }
+    if let Some(path) = &desired {
+        commands.spawn((
+            AudioPlayer::new(asset_server.load(path.clone())),
+            PlaybackSettings::LOOP.with_volume(Volume::new(0.0)),
+            MusicTrack {
+                fade: 0.0,
+                fading_out: false,
+            },
+        ));
+    }
+    manager.current = desired;
+}
+
+// Fades run on real time so they carry on while the game is paused. The music volume
+// setting is applied here too, on top of each track's fade
+fn fade_music(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    settings: Res<GameSettings>,
+    mut tracks: Query<(Entity, &mut MusicTrack, Option<&AudioSink>)>,
+) {
+    let step = time.delta_secs() / MUSIC_CROSSFADE_TIME;
+    let volume = MUSIC_VOLUME * settings.volume(AudioChannel::Music);
+
+    for (entity, mut track, sink) in &mut tracks {
+        track.fade = if track.fading_out {
+            (track.fade - step).max(0.0)
+        } else {
+            (track.fade + step).min(1.0)
+        };
+
+        if track.fading_out && track.fade == 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        if let Some(sink) = sink {
+            sink.set_volume(volume * track.fade);
+        }
+    }
+}
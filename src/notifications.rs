@@ -0,0 +1,298 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+use crate::game::{GameState, InGame};
+use crate::layers::UiLayer;
+use crate::level::{self, RoomEnteredEvent};
+
+// Area banner constants
+const AREA_BANNER_FADE_IN: f32 = 0.8;
+const AREA_BANNER_HOLD: f32 = 2.5;
+const AREA_BANNER_FADE_OUT: f32 = 1.2;
+const AREA_BANNER_FONT_SIZE: f32 = 64.0;
+const AREA_BANNER_TOP: f32 = 18.0; // Percent from the top of the screen
+const AREA_BANNER_LINE_WIDTH: f32 = 360.0;
+const AREA_BANNER_LINE_HEIGHT: f32 = 2.0;
+
+// Toast constants
+const TOAST_SLIDE_IN: f32 = 0.3;
+const TOAST_HOLD: f32 = 2.0;
+const TOAST_FADE_OUT: f32 = 0.6;
+const TOAST_WIDTH: f32 = 260.0;
+const TOAST_MARGIN: f32 = 24.0;
+const TOAST_TOP: f32 = 120.0;
+const TOAST_FONT_SIZE: f32 = 20.0;
+const TOAST_BACKGROUND: Color = Color::srgba(0.05, 0.05, 0.08, 0.85);
+const TOAST_BORDER_COLOR: Color = Color::srgb(0.95, 0.8, 0.35);
+
+// Short message popped up in the corner, e.g. for an item picked up. Any system can send one;
+// they're shown one after another
+#[derive(Event)]
+pub struct NotificationEvent {
+    pub text: String,
+}
+
+impl NotificationEvent {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self { text: text.into() }
+    }
+}
+
+// Messages waiting for the one on screen to go
+#[derive(Resource, Default)]
+struct NotificationQueue {
+    pending: VecDeque<String>,
+}
+
+// Toast on screen, sliding in from the right edge
+#[derive(Component)]
+struct Toast {
+    elapsed: f32,
+}
+
+// Areas whose banner has already been shown this session
+#[derive(Resource, Default)]
+pub struct VisitedAreas {
+    pub areas: HashSet<String>,
+}
+
+// Root node of the area-name banner
+#[derive(Component)]
+struct AreaBanner {
+    elapsed: f32,
+}
+
+// Any text or decoration of the banner that fades with it
+#[derive(Component)]
+struct AreaBannerPart {
+    base_alpha: f32,
+}
+
+pub struct NotificationsPlugin;
+
+impl Plugin for NotificationsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<VisitedAreas>()
+            .init_resource::<NotificationQueue>()
+            .add_event::<NotificationEvent>()
+            .add_systems(OnExit(InGame), clear_notifications)
+            .add_systems(
+                Update,
+                (
+                    show_area_banner.after(level::update_current_room),
+                    fade_area_banner,
+                    (queue_notifications, show_next_toast, animate_toasts).chain(),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn show_area_banner(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut room_events: EventReader<RoomEnteredEvent>,
+    mut visited_areas: ResMut<VisitedAreas>,
+    banners: Query<Entity, With<AreaBanner>>,
+) {
+    for event in room_events.read() {
+        let Some(area) = &event.area else {
+            continue;
+        };
+
+        // Only the first visit of each area gets a banner
+        if !visited_areas.areas.insert(area.clone()) {
+            continue;
+        }
+
+        for banner in &banners {
+            commands.entity(banner).despawn_recursive();
+        }
+
+        spawn_area_banner(&mut commands, &asset_server, area);
+    }
+}
+
+fn spawn_area_banner(commands: &mut Commands, asset_server: &AssetServer, area: &str) {
+    let line = || {
+        (
+            Node {
+                width: Val::Px(AREA_BANNER_LINE_WIDTH),
+                height: Val::Px(AREA_BANNER_LINE_HEIGHT),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(1.0, 1.0, 1.0, 0.0)),
+            AreaBannerPart { base_alpha: 0.7 },
+        )
+    };
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                top: Val::Percent(AREA_BANNER_TOP),
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(8.0),
+                ..default()
+            },
+            AreaBanner { elapsed: 0.0 },
+            StateScoped(InGame),
+        ))
+        .with_children(|parent| {
+            parent.spawn(line());
+            parent.spawn((
+                Text::new(area.to_uppercase()),
+                TextFont {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: AREA_BANNER_FONT_SIZE,
+                    ..default()
+                },
+                TextColor(Color::srgba(1.0, 1.0, 1.0, 0.0)),
+                AreaBannerPart { base_alpha: 1.0 },
+            ));
+            parent.spawn(line());
+        });
+}
+
+// Each banner fades only its own parts, not every banner part on screen
+fn fade_area_banner(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut banners: Query<(Entity, &mut AreaBanner)>,
+    children: Query<&Children>,
+    mut parts: Query<(
+        &AreaBannerPart,
+        Option<&mut TextColor>,
+        Option<&mut BackgroundColor>,
+    )>,
+) {
+    for (entity, mut banner) in &mut banners {
+        banner.elapsed += time.delta_secs();
+
+        let fade_out_start = AREA_BANNER_FADE_IN + AREA_BANNER_HOLD;
+        let alpha = if banner.elapsed < AREA_BANNER_FADE_IN {
+            banner.elapsed / AREA_BANNER_FADE_IN
+        } else if banner.elapsed < fade_out_start {
+            1.0
+        } else {
+            1.0 - (banner.elapsed - fade_out_start) / AREA_BANNER_FADE_OUT
+        };
+
+        if alpha <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        for descendant in children.iter_descendants(entity) {
+            let Ok((part, text_color, background_color)) = parts.get_mut(descendant) else {
+                continue;
+            };
+            if let Some(mut text_color) = text_color {
+                text_color.0.set_alpha(part.base_alpha * alpha);
+            }
+            if let Some(mut background_color) = background_color {
+                background_color.0.set_alpha(part.base_alpha * alpha);
+            }
+        }
+    }
+}
+
+fn queue_notifications(
+    mut notification_events: EventReader<NotificationEvent>,
+    mut queue: ResMut<NotificationQueue>,
+) {
+    for event in notification_events.read() {
+        queue.pending.push_back(event.text.clone());
+    }
+}
+
+fn show_next_toast(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut queue: ResMut<NotificationQueue>,
+    toasts: Query<(), With<Toast>>,
+) {
+    if !toasts.is_empty() {
+        return;
+    }
+    let Some(text) = queue.pending.pop_front() else {
+        return;
+    };
+
+    commands
+        .spawn((
+            Toast { elapsed: 0.0 },
+            StateScoped(InGame),
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(-TOAST_WIDTH),
+                top: Val::Px(TOAST_TOP),
+                width: Val::Px(TOAST_WIDTH),
+                padding: UiRect::axes(Val::Px(16.0), Val::Px(10.0)),
+                border: UiRect::left(Val::Px(4.0)),
+                ..default()
+            },
+            BorderColor(TOAST_BORDER_COLOR),
+            BackgroundColor(TOAST_BACKGROUND),
+            GlobalZIndex(UiLayer::Hud.z_index()),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(text),
+                TextFont {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: TOAST_FONT_SIZE,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
+}
+
+#[allow(clippy::type_complexity)]
+fn animate_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut toasts: Query<(
+        Entity,
+        &mut Toast,
+        &mut Node,
+        &mut BackgroundColor,
+        &mut BorderColor,
+        &Children,
+    )>,
+    mut texts: Query<&mut TextColor>,
+) {
+    for (entity, mut toast, mut node, mut background, mut border, children) in &mut toasts {
+        toast.elapsed += time.delta_secs();
+
+        let fade_out_start = TOAST_SLIDE_IN + TOAST_HOLD;
+        if toast.elapsed >= fade_out_start + TOAST_FADE_OUT {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        // Eases out as it comes to rest against the margin
+        let slide = (toast.elapsed / TOAST_SLIDE_IN).min(1.0);
+        let slide = 1.0 - (1.0 - slide).powi(3);
+        node.right = Val::Px(-TOAST_WIDTH + (TOAST_WIDTH + TOAST_MARGIN) * slide);
+
+        let alpha = 1.0 - ((toast.elapsed - fade_out_start) / TOAST_FADE_OUT).clamp(0.0, 1.0);
+        background.0 = TOAST_BACKGROUND.with_alpha(TOAST_BACKGROUND.alpha() * alpha);
+        border.0 = TOAST_BORDER_COLOR.with_alpha(alpha);
+        for &child in children {
+            if let Ok(mut text_color) = texts.get_mut(child) {
+                text_color.0.set_alpha(alpha);
+            }
+        }
+    }
+}
+
+// Messages from a run that ended aren't shown in the next one
+fn clear_notifications(mut queue: ResMut<NotificationQueue>) {
+    queue.pending.clear();
+}
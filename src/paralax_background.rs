@@ -1,15 +1,43 @@
-use bevy::prelude::*;
+use std::fmt;
 
-use crate::{enemy::Enemy, game::GameState, player::Player};
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    animations::Facing,
+    enemy::Enemy,
+    game::{GameState, InGame},
+    layers::Layer,
+    level::{self, CurrentRoom, RoomRegistry},
+    player::Player,
+};
+
+// Camera follow constants
+const CAMERA_REST_Y: f32 = 0.0; // Keeps the ground strip at the bottom of the screen
+
+// Backdrop constants
+pub const DEFAULT_BACKDROP: &str = "world/levels/1/level.parallax.ron";
+const BACKDROP_ART_SIZE: Vec2 = Vec2::new(320.0, 240.0); // Scaled up to fill the window width
+const PARALLAX_LAYER_Z_STEP: f32 = 8.0; // Nearer layers sit this much in front of the last
+const FOREGROUND_LAYER_Z_STEP: f32 = 0.5; // Less room between the ground and world text
+const PARALLAX_MAX_VIEW_SCALE: f32 = 1.5; // Widest zoom the layers have enough copies to cover
+const MENU_SCROLL_SPEED: f32 = 30.0; // Camera drift behind the main menu, in pixels per second
 
 // Plugin for the parallax background system
 pub struct ParallaxPlugin;
 
 impl Plugin for ParallaxPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<ParallaxSettings>()
+        app.init_asset::<ParallaxBackdrop>()
+            .init_asset_loader::<ParallaxBackdropLoader>()
+            .init_resource::<ParallaxSettings>()
+            .init_resource::<ActiveBackdrop>()
+            .init_resource::<CameraHold>()
             .init_resource::<ParallaxMonitor>()
-            .add_systems(Startup, setup_parallax_background)
+            .add_systems(OnEnter(GameState::Menu), show_menu_backdrop)
+            .add_systems(OnEnter(InGame), reset_backdrop)
             .configure_sets(
                 Update,
                 (
@@ -20,114 +48,273 @@ impl Plugin for ParallaxPlugin {
             .add_systems(
                 Update,
                 (
+                    (choose_backdrop, spawn_backdrop)
+                        .chain()
+                        .after(level::update_current_room)
+                        .before(ParallaxSystems::BackgroundUpdate),
                     camera_follow_player.in_set(ParallaxSystems::CameraMovement),
-                    update_parallax_background_recycled.in_set(ParallaxSystems::BackgroundUpdate),
+                    update_parallax_layers.in_set(ParallaxSystems::BackgroundUpdate),
                     update_static_background.in_set(ParallaxSystems::BackgroundUpdate),
                     monitor_performance,
                 )
                     .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                (
+                    spawn_backdrop.before(ParallaxSystems::BackgroundUpdate),
+                    drift_menu_camera.in_set(ParallaxSystems::CameraMovement),
+                    update_parallax_layers.in_set(ParallaxSystems::BackgroundUpdate),
+                    update_static_background.in_set(ParallaxSystems::BackgroundUpdate),
+                )
+                    .run_if(in_state(GameState::Menu)),
             );
     }
 }
 
 #[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
-enum ParallaxSystems {
+pub enum ParallaxSystems {
     CameraMovement,
     BackgroundUpdate,
 }
 
+// While active the camera stays where it is instead of following the player
+#[derive(Resource, Default)]
+pub struct CameraHold {
+    pub active: bool,
+}
+
 // Define the parallax background components
 #[derive(Component)]
 pub struct ParallaxLayer {
     pub speed_factor: f32,
-    pub sprite_width: f32,       // Width of the sprite
-    pub original_position: Vec3, // Original spawn position
-    pub position_index: i32,     // -1 = Left, 0 = Center, 1 = Right
+    pub sprite_width: f32,      // Width of the sprite
+    pub slot: usize,            // Place of this copy in the row tiling the view, left first
+    pub auto_scroll_speed: f32, // Drift of its own in pixels per second, for clouds
+    pub scroll_offset: f32,     // Drift so far, kept under one sprite width
 }
 
 #[derive(Component)]
 pub struct ParallaxBackground;
 
+// Backdrop sized to the window, scaled up with the camera's zoom so it still covers the view
 #[derive(Component)]
-pub struct StaticBackground;
+pub struct StaticBackground {
+    scale: f32,
+}
 
-// Resource to store the background state
+// Resource to store the camera follow settings
 #[derive(Resource)]
 pub struct ParallaxSettings {
-    pub camera_move_threshold: f32,
-    pub player_move_boundary: f32,
-    pub layer_configurations: Vec<LayerConfig>,
+    pub camera_deadzone: Vec2, // Half size of the box the player moves in freely, window fraction
+    pub camera_smoothing: f32, // How quickly the camera catches up, per second
+    pub camera_lookahead: f32, // How far ahead of the player it looks, window width fraction
+}
+
+impl Default for ParallaxSettings {
+    fn default() -> Self {
+        Self {
+            camera_deadzone: Vec2::new(0.08, 0.25),
+            camera_smoothing: 5.0,
+            camera_lookahead: 0.15,
+        }
+    }
+}
+
+// Backdrop of a level, read from its .parallax.ron file: an image that stays behind
+// everything and the parallax layers in front of it, farthest first
+#[derive(Asset, TypePath, Deserialize)]
+pub struct ParallaxBackdrop {
+    pub background: String,
+    pub layers: Vec<LayerConfig>,
 }
 
 // Configuration for each parallax layer
-#[derive(Clone)]
+#[derive(Clone, Deserialize)]
 pub struct LayerConfig {
     pub path: String,
-    pub speed_factor: f32,
-    pub z_value: f32,
-    pub dimensions: Vec2,
+    pub speed_factor: f32, // Fraction of the camera movement the layer follows
+    pub dimensions: (f32, f32),
+    #[serde(default)]
+    pub auto_scroll_speed: f32, // Art pixels per second it drifts even with the camera still
+    #[serde(default)]
+    pub foreground: bool, // Drawn in front of the world, usually with a speed factor above 1
+    #[serde(default = "default_layer_alpha")]
+    pub alpha: f32, // Lets the player show through foreground fog and foliage
 }
 
-impl Default for ParallaxSettings {
-    fn default() -> Self {
-        Self {
-            camera_move_threshold: 0.25,
-            player_move_boundary: 0.0,
-            layer_configurations: vec![
-                LayerConfig {
-                    path: "world/levels/1/1.png".to_string(),
-                    speed_factor: 0.01, // Farthest background (nubes) moves very little (5% of camera movement)
-                    z_value: -40.0,
-                    dimensions: Vec2::new(128., 240.),
-                },
-                LayerConfig {
-                    path: "world/levels/1/2.png".to_string(),
-                    speed_factor: 0.02, // Distant clouds move slightly (10% of camera movement)
-                    z_value: -30.0,
-                    dimensions: Vec2::new(144., 240.),
-                },
-                LayerConfig {
-                    path: "world/levels/1/3.png".to_string(),
-                    speed_factor: 0.04, // Mountains (30% of camera movement)
-                    z_value: -20.0,
-                    dimensions: Vec2::new(160., 240.),
-                },
-                LayerConfig {
-                    path: "world/levels/1/4.png".to_string(),
-                    speed_factor: 0.1, // Forest (50% of camera movement)
-                    z_value: -10.0,
-                    dimensions: Vec2::new(320., 240.),
-                },
-                LayerConfig {
-                    path: "world/levels/1/5.png".to_string(),
-                    speed_factor: 0.20, // Closest to foreground, moves the most (80% of camera movement)
-                    z_value: -5.0,
-                    dimensions: Vec2::new(240., 240.),
-                },
-            ],
+fn default_layer_alpha() -> f32 {
+    1.0
+}
+
+#[derive(Debug)]
+pub enum ParallaxBackdropError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+}
+
+impl fmt::Display for ParallaxBackdropError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParallaxBackdropError::Io(error) => write!(f, "could not read backdrop: {error}"),
+            ParallaxBackdropError::Parse(error) => write!(f, "could not parse backdrop: {error}"),
         }
     }
 }
 
+impl std::error::Error for ParallaxBackdropError {}
+
+impl From<std::io::Error> for ParallaxBackdropError {
+    fn from(error: std::io::Error) -> Self {
+        ParallaxBackdropError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for ParallaxBackdropError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        ParallaxBackdropError::Parse(error)
+    }
+}
+
+#[derive(Default)]
+struct ParallaxBackdropLoader;
+
+impl AssetLoader for ParallaxBackdropLoader {
+    type Asset = ParallaxBackdrop;
+    type Settings = ();
+    type Error = ParallaxBackdropError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<ParallaxBackdrop, ParallaxBackdropError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["parallax.ron"]
+    }
+}
+
+// Backdrop on screen, or the one replacing it as soon as its file has loaded. The old
+// layers stay up until then so a room change never shows an empty sky
+#[derive(Resource, Default)]
+struct ActiveBackdrop {
+    path: Option<String>,
+    handle: Handle<ParallaxBackdrop>,
+    spawned: bool,
+}
+
 fn scale_factor(window_width: f32, sprite_dimensions: Vec2) -> f32 {
     window_width / sprite_dimensions.x
 }
 
-// Function to set up the parallax background
-fn setup_parallax_background(
+// The entities are gone with the last run, the backdrop is spawned again for the new one
+fn reset_backdrop(mut active: ResMut<ActiveBackdrop>) {
+    active.spawned = false;
+}
+
+// The menu always shows the first level's backdrop, whatever room the last run ended in
+fn show_menu_backdrop(asset_server: Res<AssetServer>, mut active: ResMut<ActiveBackdrop>) {
+    active.path = Some(DEFAULT_BACKDROP.to_string());
+    active.handle = asset_server.load(DEFAULT_BACKDROP);
+    active.spawned = false;
+}
+
+// Pans slowly across the backdrop so the layers scroll past at their own speeds
+fn drift_menu_camera(time: Res<Time>, mut cameras: Query<&mut Transform, With<Camera2d>>) {
+    for mut transform in &mut cameras {
+        transform.translation.x += MENU_SCROLL_SPEED * time.delta_secs();
+        transform.translation.y = CAMERA_REST_Y;
+    }
+}
+
+// Each room names its backdrop; rooms before the first one is entered get the default
+fn choose_backdrop(
+    asset_server: Res<AssetServer>,
+    current_room: Res<CurrentRoom>,
+    registry: Res<RoomRegistry>,
+    mut active: ResMut<ActiveBackdrop>,
+) {
+    let path = current_room
+        .id
+        .as_deref()
+        .and_then(|id| registry.get(id))
+        .map_or(DEFAULT_BACKDROP, |room| room.backdrop.as_str());
+
+    if active.path.as_deref() != Some(path) {
+        active.path = Some(path.to_string());
+        active.handle = asset_server.load(path.to_string());
+        active.spawned = false;
+    }
+}
+
+// Swaps the layers on screen for the chosen backdrop once its file is in. The menu's
+// backdrop goes with the menu, a run's with the run
+#[allow(clippy::type_complexity)]
+fn spawn_backdrop(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    state: Res<State<GameState>>,
+    backdrops: Res<Assets<ParallaxBackdrop>>,
+    mut active: ResMut<ActiveBackdrop>,
     windows: Query<&Window>,
-    mut parallax_settings: ResMut<ParallaxSettings>,
+    current: Query<Entity, Or<(With<ParallaxBackground>, With<StaticBackground>)>>,
 ) {
-    // Get window dimensions
-    let window = windows.single();
-    let window_width = window.width();
-    // Calculate the player move boundary in pixels
-    parallax_settings.player_move_boundary = window_width * parallax_settings.camera_move_threshold;
+    if active.spawned {
+        return;
+    }
+    let Some(backdrop) = backdrops.get(&active.handle) else {
+        // Keep whatever is up rather than trying again every frame
+        if asset_server.load_state(active.handle.id()).is_failed() {
+            warn!("Could not load backdrop {:?}", active.path);
+            active.spawned = true;
+        }
+        return;
+    };
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+
+    for entity in &current {
+        commands.entity(entity).despawn_recursive();
+    }
+    if *state.get() == GameState::Menu {
+        let scope = StateScoped(GameState::Menu);
+        setup_parallax_background(
+            &mut commands,
+            &asset_server,
+            window.width(),
+            backdrop,
+            scope,
+        );
+    } else {
+        let scope = StateScoped(InGame);
+        setup_parallax_background(
+            &mut commands,
+            &asset_server,
+            window.width(),
+            backdrop,
+            scope,
+        );
+    }
+    active.spawned = true;
+}
 
+// Function to set up the parallax background
+fn setup_parallax_background(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    window_width: f32,
+    backdrop: &ParallaxBackdrop,
+    scope: impl Component + Clone,
+) {
     // Create a parent entity for all parallax layers
-    let static_background_scale_factor = scale_factor(window_width, Vec2::new(320., 240.));
+    let static_background_scale_factor = scale_factor(window_width, BACKDROP_ART_SIZE);
     let parallax_parent = commands
         .spawn((
             Transform::default(),
@@ -135,55 +322,61 @@ fn setup_parallax_background(
             InheritedVisibility::default(),
             ViewVisibility::default(),
             ParallaxBackground,
+            scope.clone(),
         ))
         .id();
 
     commands.spawn((
         Sprite {
-            image: asset_server.load("world/levels/1/0.png"),
+            image: asset_server.load(&backdrop.background),
             ..default()
         },
-        Transform::from_xyz(0.0, 0.0, -100.0).with_scale(Vec3::new(
+        Transform::from_xyz(0.0, 0.0, Layer::Background.z()).with_scale(Vec3::new(
             static_background_scale_factor,
             static_background_scale_factor,
             1.0,
         )),
-        StaticBackground,
+        StaticBackground {
+            scale: static_background_scale_factor,
+        },
+        scope,
     ));
 
-    // Spawn each layer with exactly 3 instances (left, center, right)
-    for (layer_index, layer_config) in parallax_settings.layer_configurations.iter().enumerate() {
+    // Each layer is a row of copies of its sprite, enough to cover the widest view. Layers
+    // behind and in front of the world are stacked separately, each in listed order
+    let (mut background_layers, mut foreground_layers) = (0, 0);
+    for layer_config in &backdrop.layers {
         // Load the texture
         let texture = asset_server.load(&layer_config.path);
-        let _parallax_scale_factor = scale_factor(window_width, layer_config.dimensions);
+        let z_value = if layer_config.foreground {
+            foreground_layers += 1;
+            Layer::Foreground.z() + (foreground_layers - 1) as f32 * FOREGROUND_LAYER_Z_STEP
+        } else {
+            background_layers += 1;
+            Layer::Parallax.z() + (background_layers - 1) as f32 * PARALLAX_LAYER_Z_STEP
+        };
 
         // Width of each sprite after scaling
-        let scaled_width = layer_config.dimensions.x * static_background_scale_factor;
+        let scaled_width = layer_config.dimensions.0 * static_background_scale_factor;
+        let copies = (window_width * PARALLAX_MAX_VIEW_SCALE / scaled_width).ceil() as usize + 2;
 
         commands.entity(parallax_parent).with_children(|parent| {
-            // Para las capas 0 y 1 (índices 0 y 1, que corresponden a las nubes lejanas)
-            // usamos 5 instancias en lugar de 3 para cubrir mejor la pantalla
-            let instance_range = if layer_index == 0 || layer_index == 1 {
-                -5..=5 // 5 instancias para nubes (-2, -1, 0, 1, 2)
-            } else {
-                -1..=1 // 3 instancias para el resto (-1, 0, 1)
-            };
-
-            for i in instance_range {
-                let x_pos = i as f32 * scaled_width;
-
+            for slot in 0..copies {
                 parent.spawn((
                     Sprite {
                         image: texture.clone(),
+                        color: Color::WHITE.with_alpha(layer_config.alpha),
                         ..default()
                     },
                     ParallaxLayer {
                         speed_factor: layer_config.speed_factor,
                         sprite_width: scaled_width,
-                        original_position: Vec3::new(x_pos, 0.0, layer_config.z_value),
-                        position_index: i,
+                        slot,
+                        auto_scroll_speed: layer_config.auto_scroll_speed
+                            * static_background_scale_factor,
+                        scroll_offset: 0.0,
                     },
-                    Transform::from_xyz(x_pos, 0., layer_config.z_value).with_scale(Vec3::new(
+                    Transform::from_xyz(0.0, 0.0, z_value).with_scale(Vec3::new(
                         static_background_scale_factor,
                         static_background_scale_factor,
                         1.0,
@@ -198,174 +391,117 @@ fn setup_parallax_background(
 }
 
 // System to update the static background position
+#[allow(clippy::type_complexity)]
 fn update_static_background(
-    mut static_bg_query: Query<&mut Transform, With<StaticBackground>>,
-    camera_query: Query<&Transform, (With<Camera2d>, Without<StaticBackground>)>,
+    mut static_bg_query: Query<(&mut Transform, &StaticBackground)>,
+    camera_query: Query<
+        (&Transform, &OrthographicProjection),
+        (With<Camera2d>, Without<StaticBackground>),
+    >,
 ) {
-    if let (Ok(mut bg_transform), Ok(camera_transform)) =
+    if let (Ok((mut bg_transform, background)), Ok((camera_transform, projection))) =
         (static_bg_query.get_single_mut(), camera_query.get_single())
     {
         bg_transform.translation.x = camera_transform.translation.x;
         bg_transform.translation.y = camera_transform.translation.y;
+        let scale = background.scale * projection.scale;
+        bg_transform.scale = Vec3::new(scale, scale, 1.0);
     }
 }
 
-// New system that uses exactly 3 sprites per layer and recycles them
-fn update_parallax_background_recycled(
+// Lays each layer's copies side by side from the left edge of the view. Where the row
+// starts only depends on the camera and the layer's offset, so there's nothing to recycle
+#[allow(clippy::type_complexity)]
+fn update_parallax_layers(
+    time: Res<Time>,
     mut parallax_query: Query<(&mut Transform, &mut ParallaxLayer)>,
-    camera_query: Query<&Transform, (With<Camera2d>, Without<ParallaxLayer>)>,
+    camera_query: Query<
+        (&Transform, &OrthographicProjection),
+        (With<Camera2d>, Without<ParallaxLayer>),
+    >,
     windows: Query<&Window>,
 ) {
-    let window = if let Ok(window) = windows.get_single() {
-        window
-    } else {
+    let Ok(window) = windows.get_single() else {
         return; // Skip this frame if window is not available
     };
-    let window_width = window.width();
+    let Ok((camera_transform, projection)) = camera_query.get_single() else {
+        return;
+    };
+    let camera_x = camera_transform.translation.x;
+    let half_view = window.width() * projection.scale / 2.0;
 
-    if let Ok(camera_transform) = camera_query.get_single() {
-        let camera_x = camera_transform.translation.x;
-
-        for (mut transform, mut layer) in parallax_query.iter_mut() {
-            // Calculate position based on parallax effect
-            // Instead of moving the background by the full camera position,
-            // we only move it by a fraction determined by the speed_factor
-            let parallax_offset = camera_x * (1.0 - layer.speed_factor);
-
-            // Update position to be centered on camera but offset by parallax factor
-            transform.translation.x = layer.original_position.x + parallax_offset;
-
-            // Check if this sprite is now off-screen
-            let half_window = window_width / 2.0;
-
-            if transform.translation.x < camera_x - half_window - (layer.sprite_width / 2.0) {
-                // This sprite is off-screen to the left, move it to the right
-                // Determine how many sprite widths to move based on position index range
-                let max_index = if layer.position_index >= -1 && layer.position_index <= 1 {
-                    1 // Capas normales (-1, 0, 1)
-                } else {
-                    2 // Capas especiales con 5 instancias (-2, -1, 0, 1, 2)
-                };
-
-                // Move to the rightmost position - convertimos a f32 para evitar error de tipo
-                let movement = (2 * max_index + 1) as f32;
-                transform.translation.x += layer.sprite_width * movement;
-
-                // Update position index
-                // Para las capas con rango -2..=2
-                if max_index == 2 {
-                    if layer.position_index == -2 {
-                        layer.position_index = 2;
-                    } else if layer.position_index == -1 {
-                        layer.position_index = -2;
-                    } else if layer.position_index == 0 {
-                        layer.position_index = -1;
-                    } else if layer.position_index == 1 {
-                        layer.position_index = 0;
-                    } else if layer.position_index == 2 {
-                        layer.position_index = 1;
-                    }
-                } else {
-                    // Para las capas con rango -1..=1
-                    if layer.position_index == -1 {
-                        layer.position_index = 1;
-                    } else if layer.position_index == 0 {
-                        layer.position_index = -1;
-                    } else if layer.position_index == 1 {
-                        layer.position_index = 0;
-                    }
-                }
-
-                // Update original position
-                layer.original_position.x = transform.translation.x - parallax_offset;
-            } else if transform.translation.x > camera_x + half_window + (layer.sprite_width / 2.0)
-            {
-                // This sprite is off-screen to the right, move it to the left
-                // Determine how many sprite widths to move based on position index range
-                let max_index = if layer.position_index >= -1 && layer.position_index <= 1 {
-                    1 // Capas normales (-1, 0, 1)
-                } else {
-                    2 // Capas especiales con 5 instancias (-2, -1, 0, 1, 2)
-                };
-
-                // Move to the leftmost position - convertimos a f32 para evitar error de tipo
-                let movement = (2 * max_index + 1) as f32;
-                transform.translation.x -= layer.sprite_width * movement;
-
-                // Update position index
-                // Para las capas con rango -2..=2
-                if max_index == 2 {
-                    if layer.position_index == 2 {
-                        layer.position_index = -2;
-                    } else if layer.position_index == 1 {
-                        layer.position_index = 2;
-                    } else if layer.position_index == 0 {
-                        layer.position_index = 1;
-                    } else if layer.position_index == -1 {
-                        layer.position_index = 0;
-                    } else if layer.position_index == -2 {
-                        layer.position_index = -1;
-                    }
-                } else {
-                    // Para las capas con rango -1..=1
-                    if layer.position_index == 1 {
-                        layer.position_index = -1;
-                    } else if layer.position_index == 0 {
-                        layer.position_index = 1;
-                    } else if layer.position_index == -1 {
-                        layer.position_index = 0;
-                    }
-                }
-
-                // Update original position
-                layer.original_position.x = transform.translation.x - parallax_offset;
-            }
-        }
+    for (mut transform, mut layer) in &mut parallax_query {
+        // Drifting layers wrap a whole sprite width back, which looks the same since the
+        // copies tile
+        layer.scroll_offset = (layer.scroll_offset + layer.auto_scroll_speed * time.delta_secs())
+            .rem_euclid(layer.sprite_width);
+
+        // The layer only follows a fraction of the camera movement
+        let offset = camera_x * (1.0 - layer.speed_factor) + layer.scroll_offset;
+
+        // First copy whose sprite reaches the left edge of the view
+        let first = ((camera_x - half_view - offset) / layer.sprite_width + 0.5).floor();
+        transform.translation.x = offset + (first + layer.slot as f32) * layer.sprite_width;
     }
 }
 
-// System to make the camera follow the player when they get close to the edge
+// Eases toward a point ahead of the player in the direction they face. Inside the
+// deadzone the player moves without dragging the camera along, and the camera only
+// rises above its resting height for ledges high up. The room's camera bounds and lock
+// zones have the last word, and the same easing carries it into and out of them
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 fn camera_follow_player(
-    mut camera_query: Query<&mut Transform, With<Camera2d>>,
-    player_query: Query<&Transform, (With<crate::player::Player>, Without<Camera2d>)>,
+    mut camera_query: Query<(&mut Transform, &OrthographicProjection), With<Camera2d>>,
+    player_query: Query<(&Transform, &Facing), (With<Player>, Without<Camera2d>)>,
     time: Res<Time>,
     parallax_settings: Res<ParallaxSettings>,
     windows: Query<&Window>,
-    keyboard: Res<ButtonInput<KeyCode>>,
+    camera_hold: Res<CameraHold>,
+    current_room: Res<CurrentRoom>,
+    registry: Res<RoomRegistry>,
 ) {
-    let window = if let Ok(window) = windows.get_single() {
-        window
-    } else {
+    if camera_hold.active {
+        return;
+    }
+
+    let Ok(window) = windows.get_single() else {
         return; // Skip this frame if window is not available
     };
-
-    if let (Ok(mut camera_transform), Ok(player_transform)) =
+    let (Ok((mut camera_transform, projection)), Ok((player_transform, facing))) =
         (camera_query.get_single_mut(), player_query.get_single())
-    {
-        let window_width = window.width();
-        let half_window = window_width / 2.0;
-
-        // Calcular los umbrales (25% desde cada borde)
-        let left_threshold =
-            camera_transform.translation.x - half_window + parallax_settings.player_move_boundary;
-        let right_threshold =
-            camera_transform.translation.x + half_window - parallax_settings.player_move_boundary;
-
-        // Velocidad de movimiento de la cámara basada en la velocidad del jugador
-        let camera_speed = 250.0 * time.delta_secs();
-
-        // Comprobar si el jugador está más allá del umbral y mover la cámara en consecuencia
-        if player_transform.translation.x < left_threshold && keyboard.pressed(KeyCode::ArrowLeft) {
-            camera_transform.translation.x -= camera_speed;
-        } else if player_transform.translation.x > right_threshold
-            && keyboard.pressed(KeyCode::ArrowRight)
-        {
-            camera_transform.translation.x += camera_speed;
-        }
+    else {
+        return;
+    };
 
-        // Asegurarse de que la cámara se mueva de manera precisa
-        camera_transform.translation.z = camera_transform.translation.z.round();
+    let window_size = Vec2::new(window.width(), window.height());
+    let deadzone = window_size * parallax_settings.camera_deadzone;
+    let focus = player_transform.translation.truncate()
+        + Vec2::X * facing.sign() * window_size.x * parallax_settings.camera_lookahead;
+
+    let camera = camera_transform.translation.truncate();
+    let target = Vec2::new(
+        focus.x.clamp(camera.x - deadzone.x, camera.x + deadzone.x),
+        focus.y.clamp(camera.y - deadzone.y, camera.y + deadzone.y),
+    );
+    // Only the part of the focus outside the deadzone pulls the camera
+    let target = camera + (focus - target);
+    let mut target = Vec2::new(target.x, target.y.max(CAMERA_REST_Y));
+    // Switching between clamps, or out of one, still eases over with the blend below
+    if let Some(lock) = current_room
+        .id
+        .as_deref()
+        .and_then(|id| registry.get(id))
+        .and_then(|room| room.camera_lock_at(player_transform.translation.truncate()))
+    {
+        let half_view = window_size * projection.scale / 2.0;
+        target = lock.clamp(target, half_view);
     }
+
+    // Same catch up rate whatever the frame rate
+    let blend = 1.0 - (-parallax_settings.camera_smoothing * time.delta_secs()).exp();
+    let position = camera.lerp(target, blend);
+    camera_transform.translation.x = position.x;
+    camera_transform.translation.y = position.y;
 }
 
 pub fn extend_world(
@@ -437,14 +573,4 @@ pub fn monitor_performance(
     monitor.fps = 1.0 / time.delta_secs();
     monitor.frame_time = time.delta_secs() * 1000.0; // Convert to milliseconds
     monitor.last_update = time.elapsed_secs_f64();
-
-    // Print debug info if needed
-    println!(
-        "FPS: {:.2}, Active layers: {}, Player pos: {:.2}, camera_position: {:.2}, Enemy pos: {:.2}",
-        monitor.fps,
-        monitor.active_layers,
-        monitor.player_position,
-        monitor.camera_position,
-        monitor.enemy_position,
-    );
 }
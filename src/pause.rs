@@ -1,27 +1,91 @@
+use crate::animations::{AnimationController, CharacterState};
+use crate::config::GameConfig;
+use crate::enemy::{Enemy, EnemyAi, EnemyAiState};
 use crate::game::GameState;
+use crate::input::{ActionState, InputAction};
+use crate::inventory::{self, InventoryScreen};
+use crate::layers::UiLayer;
+use crate::map::MapScreen;
+use crate::settings_menu::{self, SettingsMenu};
 use bevy::prelude::*;
 
-// Component to mark pause menu elements
+// Pause menu constants
+const PAUSE_OVERLAY_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.7);
+const PAUSE_PANEL_COLOR: Color = Color::srgba(0.1, 0.1, 0.1, 0.9);
+const PAUSE_BUTTON_SIZE: Vec2 = Vec2::new(220.0, 60.0);
+const PAUSE_BUTTON_GAP: f32 = 16.0;
+const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
+const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
+const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.35, 0.35);
+const HOVERED_BORDER: Color = Color::srgb(0.8, 0.8, 0.8);
+
+// Component to mark the resume button
+#[derive(Component)]
+struct ResumeButton;
+
+// Component to mark the button that opens the inventory
+#[derive(Component)]
+struct InventoryButton;
+
+// Component to mark the button that opens the settings screen
+#[derive(Component)]
+struct SettingsButton;
+
+// Component to mark the button that ends the run and goes back to the main menu
 #[derive(Component)]
-struct PauseMenu;
+struct QuitButton;
+
+// Screens that take the player out of the fight. The inventory opens from the pause
+// menu, so it goes through the same wait
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PauseTarget {
+    Menu,
+    Map,
+}
+
+// Pause asked for while an enemy attack was underway; the game keeps running until the
+// attack ends or the grace period runs out, ignoring player controls in the meantime
+#[derive(Resource, Default)]
+pub struct PendingPause {
+    grace: Option<(Timer, PauseTarget)>,
+}
+
+// Run condition for player controls, which stop as soon as a pause is asked for
+pub fn pause_requested(pending_pause: Res<PendingPause>) -> bool {
+    pending_pause.grace.is_some()
+}
 
 pub struct PausePlugin;
 
 impl Plugin for PausePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Paused), setup_pause_menu)
+        app.init_resource::<PendingPause>()
+            .add_systems(
+                OnEnter(GameState::Paused),
+                (setup_pause_menu, freeze_game_time),
+            )
+            .add_systems(OnExit(GameState::Paused), resume_game_time)
+            // A run that ends mid-grace mustn't pause the next one
+            .add_systems(OnExit(GameState::Playing), clear_pending_pause)
             .add_systems(
                 Update,
                 (
-                    handle_resume_button.run_if(in_state(GameState::Paused)),
-                    handle_pause_input.run_if(in_state(GameState::Playing)),
+                    handle_pause_buttons.run_if(
+                        in_state(GameState::Paused)
+                            .and(settings_menu::settings_closed)
+                            .and(inventory::inventory_closed),
+                    ),
+                    (handle_pause_input, update_pending_pause)
+                        .chain()
+                        .run_if(in_state(GameState::Playing)),
                 ),
-            )
-            .add_systems(OnExit(GameState::Paused), cleanup_pause_menu);
+            );
     }
 }
 
 fn setup_pause_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+
     commands
         .spawn((
             Node {
@@ -29,98 +93,216 @@ fn setup_pause_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
                 height: Val::Percent(100.0),
                 align_items: AlignItems::Center,
                 justify_content: JustifyContent::Center,
-
                 ..default()
             },
-            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
-            PauseMenu,
+            BackgroundColor(PAUSE_OVERLAY_COLOR),
+            GlobalZIndex(UiLayer::Overlay.z_index()),
+            StateScoped(GameState::Paused),
         ))
         .with_children(|parent| {
             parent
                 .spawn((
                     Node {
-                        width: Val::Percent(100.0),
-                        height: Val::Percent(100.0),
-                        align_items: AlignItems::Center,
-                        justify_content: JustifyContent::SpaceAround,
                         flex_direction: FlexDirection::Column,
-                        display: Display::Flex,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(PAUSE_BUTTON_GAP),
+                        padding: UiRect::axes(Val::Px(48.0), Val::Px(32.0)),
                         ..default()
                     },
-                    BackgroundColor(Color::srgba(0.1, 0.1, 0.1, 0.9)),
+                    BorderRadius::all(Val::Px(12.0)),
+                    BackgroundColor(PAUSE_PANEL_COLOR),
                 ))
                 .with_children(|parent| {
                     // Pause title
                     parent.spawn((
                         Text::new("PAUSED"),
                         TextFont {
-                            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                            font: font.clone(),
                             font_size: 32.0,
                             ..default()
                         },
                         TextColor(Color::WHITE),
+                        Node {
+                            margin: UiRect::bottom(Val::Px(PAUSE_BUTTON_GAP)),
+                            ..default()
+                        },
                     ));
 
-                    // Resume button
-                    parent
-                        .spawn((
-                            Button,
-                            Node {
-                                width: Val::Px(150.0),
-                                height: Val::Px(65.0),
-                                border: UiRect::all(Val::Px(5.0)),
-                                justify_content: JustifyContent::Center,
-                                align_items: AlignItems::Center,
-                                ..default()
-                            },
-                            BorderColor(Color::BLACK),
-                            BorderRadius::MAX,
-                            BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
-                        ))
-                        .with_children(|parent| {
-                            parent.spawn((
-                                Text::new("Resume"),
-                                TextFont {
-                                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                                    font_size: 24.0,
-                                    ..default()
-                                },
-                                TextColor(Color::WHITE),
-                            ));
-                        });
+                    spawn_pause_button(parent, &font, "Resume", ResumeButton);
+                    spawn_pause_button(parent, &font, "Inventory", InventoryButton);
+                    spawn_pause_button(parent, &font, "Settings", SettingsButton);
+                    spawn_pause_button(parent, &font, "Quit to Menu", QuitButton);
                 });
         });
 }
 
-fn cleanup_pause_menu(mut commands: Commands, pause_menu_query: Query<Entity, With<PauseMenu>>) {
-    for entity in pause_menu_query.iter() {
-        commands.entity(entity).despawn_recursive();
-    }
+// Stopping virtual time holds every timer, animation and cooldown where it was, including
+// anything that isn't gated on the Playing state
+fn freeze_game_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn resume_game_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+fn spawn_pause_button(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    label: &str,
+    marker: impl Component,
+) {
+    parent
+        .spawn((
+            Button,
+            marker,
+            Node {
+                width: Val::Px(PAUSE_BUTTON_SIZE.x),
+                height: Val::Px(PAUSE_BUTTON_SIZE.y),
+                border: UiRect::all(Val::Px(3.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BorderColor(Color::BLACK),
+            BorderRadius::MAX,
+            BackgroundColor(NORMAL_BUTTON),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(label),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
 }
 
-fn handle_resume_button(
+// Quitting ends the run; everything spawned for it goes away on the way to the menu
+#[allow(clippy::type_complexity)]
+fn handle_pause_buttons(
     mut next_state: ResMut<NextState<GameState>>,
-    interaction_query: Query<&Interaction, Changed<Interaction>>,
-    keyboard: Res<ButtonInput<KeyCode>>,
+    mut settings_menu: ResMut<SettingsMenu>,
+    mut inventory_screen: ResMut<InventoryScreen>,
+    mut interaction_query: Query<
+        (
+            &Interaction,
+            &mut BackgroundColor,
+            &mut BorderColor,
+            Has<ResumeButton>,
+            Has<InventoryButton>,
+            Has<SettingsButton>,
+            Has<QuitButton>,
+        ),
+        Changed<Interaction>,
+    >,
+    actions: Res<ActionState>,
 ) {
-    // Check for button press
-    for interaction in &interaction_query {
-        if *interaction == Interaction::Pressed {
-            next_state.set(GameState::Playing);
+    for (interaction, mut color, mut border, resume, inventory, settings, quit) in
+        &mut interaction_query
+    {
+        match *interaction {
+            Interaction::Pressed => {
+                color.0 = PRESSED_BUTTON;
+                if resume {
+                    next_state.set(GameState::Playing);
+                } else if inventory {
+                    inventory_screen.open = true;
+                } else if settings {
+                    settings_menu.open = true;
+                } else if quit {
+                    next_state.set(GameState::Menu);
+                }
+            }
+            Interaction::Hovered => {
+                color.0 = HOVERED_BUTTON;
+                border.0 = HOVERED_BORDER;
+            }
+            Interaction::None => {
+                color.0 = NORMAL_BUTTON;
+                border.0 = Color::BLACK;
+            }
         }
     }
 
-    // Also allow resuming with Escape or P key
-    if keyboard.just_pressed(KeyCode::Escape) || keyboard.just_pressed(KeyCode::KeyP) {
+    // Also allow resuming with the pause key
+    if actions.just_pressed(InputAction::Pause) {
         next_state.set(GameState::Playing);
     }
 }
 
-fn handle_pause_input(
+// The pause key opens the pause menu and the map key the map, once it is closed
+pub fn handle_pause_input(
     mut next_state: ResMut<NextState<GameState>>,
-    keyboard: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
+    config: Res<GameConfig>,
+    mut pending_pause: ResMut<PendingPause>,
+    mut map: ResMut<MapScreen>,
+    enemies: Query<(&Enemy, &EnemyAi, &AnimationController)>,
 ) {
-    if keyboard.just_pressed(KeyCode::Escape) || keyboard.just_pressed(KeyCode::KeyP) {
-        next_state.set(GameState::Paused);
+    if pending_pause.grace.is_some() {
+        return;
     }
+    let target = if actions.just_pressed(InputAction::Pause) {
+        PauseTarget::Menu
+    } else if actions.just_pressed(InputAction::Map) && !map.open {
+        PauseTarget::Map
+    } else {
+        return;
+    };
+
+    let grace_period = config.rules.pause_grace_period;
+    if grace_period > 0.0 && enemy_attack_underway(&enemies) {
+        pending_pause.grace = Some((Timer::from_seconds(grace_period, TimerMode::Once), target));
+    } else {
+        open_pause_target(target, &mut next_state, &mut map);
+    }
+}
+
+fn update_pending_pause(
+    time: Res<Time>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut pending_pause: ResMut<PendingPause>,
+    mut map: ResMut<MapScreen>,
+    enemies: Query<(&Enemy, &EnemyAi, &AnimationController)>,
+) {
+    let Some((grace, target)) = pending_pause.grace.as_mut() else {
+        return;
+    };
+
+    grace.tick(time.delta());
+    if grace.finished() || !enemy_attack_underway(&enemies) {
+        open_pause_target(*target, &mut next_state, &mut map);
+        pending_pause.grace = None;
+    }
+}
+
+fn open_pause_target(
+    target: PauseTarget,
+    next_state: &mut NextState<GameState>,
+    map: &mut MapScreen,
+) {
+    match target {
+        PauseTarget::Menu => next_state.set(GameState::Paused),
+        PauseTarget::Map => map.open = true,
+    }
+}
+
+fn clear_pending_pause(mut pending_pause: ResMut<PendingPause>) {
+    pending_pause.grace = None;
+}
+
+// Swings and charges count, from the telegraph until the hitbox is gone
+fn enemy_attack_underway(enemies: &Query<(&Enemy, &EnemyAi, &AnimationController)>) -> bool {
+    enemies.iter().any(|(enemy, ai, animation_controller)| {
+        !enemy.is_dead
+            && (animation_controller.get_current_state() == CharacterState::Attacking
+                || matches!(
+                    ai.state,
+                    EnemyAiState::Telegraphing | EnemyAiState::Charging
+                ))
+    })
 }
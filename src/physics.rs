@@ -1,14 +1,31 @@
 use bevy::prelude::*;
+use serde::Deserialize;
 
+use crate::config::GameConfig;
 use crate::game::GameState;
+use crate::ground::ground_collision;
+use crate::spatial::SpatialHash;
+use crate::utils;
 
 // Physics Constants
-const GRAVITY_STRENGTH: f32 = 980.0; // Approximately 9.8 m/s² in pixels
-const MAX_FALL_SPEED: f32 = -1000.0;
 const DEFAULT_GRAVITY_SCALE: f32 = 1.0;
+const SOLID_CONTACT_TOLERANCE: f32 = 2.0; // Keeps bodies resting on a solid grounded
+const ONE_WAY_LANDING_DEPTH: f32 = 16.0; // Deeper than this, the body came from below
+const DROP_THROUGH_TIME: f32 = 0.25;
+const SOLID_CELL_SIZE: f32 = 256.0;
+
+// Surface traction constants (px/s²); stone is effectively instant, like before surfaces existed
+const STONE_ACCELERATION: f32 = 100_000.0;
+const STONE_FRICTION: f32 = 100_000.0;
+const ICE_ACCELERATION: f32 = 700.0;
+const ICE_FRICTION: f32 = 250.0;
+const MUD_ACCELERATION: f32 = 2500.0;
+const MUD_FRICTION: f32 = 4000.0;
+const MUD_SPEED_FACTOR: f32 = 0.6;
 
 // Componente para física básica
 #[derive(Component)]
+#[require(PhysicsInterpolation, CurrentPhysicsZone)]
 pub struct Physics {
     pub velocity: Vec2,
     pub acceleration: Vec2,
@@ -27,6 +44,121 @@ impl Default for Physics {
     }
 }
 
+// Posición del cuerpo en los dos últimos pasos fijos; el transform se dibuja entre ambas
+#[derive(Component, Default)]
+pub struct PhysicsInterpolation {
+    previous: Vec2,
+    current: Vec2,
+    rendered: Vec2, // Lo último que se escribió en el transform
+}
+
+// Área que cambia la física de los cuerpos dentro: agua, corrientes de aire, sueños
+#[derive(Component, Clone, Copy, Debug)]
+pub struct PhysicsZone {
+    pub size: Vec2,
+    pub gravity_scale: f32, // Multiplica la del cuerpo; negativa empuja hacia arriba
+    pub max_fall_speed: f32, // Reemplaza la del archivo de configuración
+    pub drag: f32,          // Fracción de la velocidad que se pierde por segundo
+}
+
+// Zona en la que está el cuerpo, si hay alguna
+#[derive(Component, Default)]
+pub struct CurrentPhysicsZone(pub Option<PhysicsZone>);
+
+// Caja sólida sobre la que se puede estar de pie y que bloquea el paso
+#[derive(Component)]
+pub struct Solid {
+    pub size: Vec2,
+}
+
+// Sólido que solo sostiene desde arriba: se atraviesa saltando desde abajo
+#[derive(Component)]
+pub struct OneWayPlatform;
+
+// Cuerpo que se deja caer a través de las plataformas de un solo sentido
+#[derive(Component)]
+pub struct DroppingThrough(pub Timer);
+
+impl Default for DroppingThrough {
+    fn default() -> Self {
+        Self(Timer::from_seconds(DROP_THROUGH_TIME, TimerMode::Once))
+    }
+}
+
+// Caja centrada en el transform que se resuelve contra los sólidos
+#[derive(Component)]
+pub struct BodyCollider {
+    pub size: Vec2,
+}
+
+// Cuerpo que mueve el motor de física opcional: aquí solo se integra su velocidad, sin
+// moverlo ni resolver sus colisiones
+#[derive(Component)]
+pub struct KinematicCharacter;
+
+// Material del suelo, cambia la tracción y los sonidos de pasos
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Default, Deserialize)]
+pub enum SurfaceMaterial {
+    #[default]
+    Stone,
+    Ice,
+    Mud,
+}
+
+// Cómo responde un cuerpo sobre una superficie
+pub struct SurfaceTraction {
+    pub acceleration: f32,
+    pub friction: f32,
+    pub speed_factor: f32,
+}
+
+impl SurfaceMaterial {
+    pub fn traction(self) -> SurfaceTraction {
+        match self {
+            SurfaceMaterial::Stone => SurfaceTraction {
+                acceleration: STONE_ACCELERATION,
+                friction: STONE_FRICTION,
+                speed_factor: 1.0,
+            },
+            SurfaceMaterial::Ice => SurfaceTraction {
+                acceleration: ICE_ACCELERATION,
+                friction: ICE_FRICTION,
+                speed_factor: 1.0,
+            },
+            SurfaceMaterial::Mud => SurfaceTraction {
+                acceleration: MUD_ACCELERATION,
+                friction: MUD_FRICTION,
+                speed_factor: MUD_SPEED_FACTOR,
+            },
+        }
+    }
+}
+
+// Qué tan rápido un personaje alcanza la velocidad que pide (px/s²). En el suelo la
+// superficie puede limitarlo (el hielo resbala aunque el personaje frene en seco)
+#[derive(Component, Deserialize, Clone, Copy, Debug)]
+pub struct MovementTuning {
+    pub ground_acceleration: f32,
+    pub ground_friction: f32,
+    pub air_acceleration: f32,
+    pub air_friction: f32,
+}
+
+// Superficie que pisa un personaje, actualizada por la colisión con el suelo
+#[derive(Component, Default)]
+pub struct SurfaceContact {
+    pub surface: SurfaceMaterial,
+    pub on_one_way_platform: bool, // De pie sobre una plataforma que se puede atravesar
+    previous_velocity_x: f32,
+}
+
+impl SurfaceContact {
+    // Velocidad impuesta de golpe (un dash): la tracción la mantiene en vez de acelerar hasta ella
+    pub fn snap_velocity_x(&mut self, velocity_x: f32) {
+        self.previous_velocity_x = velocity_x;
+    }
+}
+
 // Recurso global para configurar la gravedad
 #[derive(Resource)]
 pub struct GravitySettings {
@@ -35,7 +167,9 @@ pub struct GravitySettings {
 
 impl Default for GravitySettings {
     fn default() -> Self {
-        Self { strength: GRAVITY_STRENGTH }
+        Self {
+            strength: GameConfig::default().physics.gravity,
+        }
     }
 }
 
@@ -44,45 +178,386 @@ pub struct GravityPlugin;
 impl Plugin for GravityPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<GravitySettings>()
-            .add_systems(Update, apply_gravity.run_if(in_state(GameState::Playing)))
             .add_systems(
                 Update,
-                apply_physics
-                    .after(apply_gravity)
+                apply_gravity_config.run_if(resource_changed::<GameConfig>),
+            )
+            // Paso fijo: la altura del salto y las colisiones no dependen de los FPS
+            .add_systems(
+                FixedUpdate,
+                (
+                    update_physics_zones,
+                    apply_gravity,
+                    apply_physics,
+                    ground_collision,
+                    resolve_solid_collisions,
+                )
+                    .chain()
                     .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(FixedFirst, store_previous_positions)
+            .add_systems(
+                RunFixedMainLoop,
+                (
+                    restore_physics_positions.in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop),
+                    interpolate_physics_positions
+                        .in_set(RunFixedMainLoopSystem::AfterFixedMainLoop),
+                ),
+            )
+            // La tracción convierte la velocidad pedida en la real una vez por frame, justo
+            // después de que la jugabilidad la escribe
+            .add_systems(
+                Update,
+                apply_surface_traction.run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                update_dropping_through.run_if(in_state(GameState::Playing)),
             );
     }
 }
 
+// La gravedad base viene del archivo de configuración
+fn apply_gravity_config(config: Res<GameConfig>, mut gravity: ResMut<GravitySettings>) {
+    gravity.strength = config.physics.gravity;
+}
+
+// El centro del cuerpo decide en qué zona está; si se solapan manda la primera
+fn update_physics_zones(
+    zones: Query<(&Transform, &PhysicsZone)>,
+    mut bodies: Query<(&Transform, &mut CurrentPhysicsZone), Without<PhysicsZone>>,
+) {
+    for (transform, mut current_zone) in &mut bodies {
+        let position = transform.translation.truncate();
+        let zone = zones
+            .iter()
+            .find(|(zone_transform, zone)| {
+                utils::point_in_rect(position, zone_transform.translation.truncate(), zone.size)
+            })
+            .map(|(_, zone)| *zone);
+        if current_zone.0.is_some() || zone.is_some() {
+            current_zone.0 = zone;
+        }
+    }
+}
+
 // Sistema que aplica la gravedad a los objetos con física
-fn apply_gravity(_time: Res<Time>, gravity: Res<GravitySettings>, mut query: Query<&mut Physics>) {
-    for mut physics in &mut query {
-        if !physics.on_ground {
+fn apply_gravity(
+    gravity: Res<GravitySettings>,
+    mut query: Query<(&mut Physics, &CurrentPhysicsZone)>,
+) {
+    for (mut physics, current_zone) in &mut query {
+        let zone_scale = current_zone.0.map_or(1.0, |zone| zone.gravity_scale);
+        // Una corriente hacia arriba levanta incluso lo que está en el suelo
+        if !physics.on_ground || zone_scale < 0.0 {
             // Aplicar aceleración de gravedad
-            physics.acceleration.y -= gravity.strength * physics.gravity_scale;
+            physics.acceleration.y -= gravity.strength * physics.gravity_scale * zone_scale;
         }
     }
 }
 
+// La velocidad horizontal escrita por la jugabilidad es el objetivo; la superficie decide
+// qué tan rápido se alcanza (hielo resbala, barro frena)
+// Cuerpos sin MovementTuning siguen solo a la superficie y en el aire cambian al instante
+pub fn apply_surface_traction(
+    time: Res<Time>,
+    mut query: Query<(&mut Physics, &mut SurfaceContact, Option<&MovementTuning>)>,
+) {
+    let delta = time.delta_secs();
+
+    for (mut physics, mut contact, tuning) in &mut query {
+        let traction = if physics.on_ground {
+            let surface = contact.surface.traction();
+            Some(match tuning {
+                Some(tuning) => SurfaceTraction {
+                    acceleration: surface.acceleration.min(tuning.ground_acceleration),
+                    friction: surface.friction.min(tuning.ground_friction),
+                    speed_factor: surface.speed_factor,
+                },
+                None => surface,
+            })
+        } else {
+            tuning.map(|tuning| SurfaceTraction {
+                acceleration: tuning.air_acceleration,
+                friction: tuning.air_friction,
+                speed_factor: 1.0,
+            })
+        };
+
+        if let Some(traction) = traction {
+            let target = physics.velocity.x * traction.speed_factor;
+            let previous = contact.previous_velocity_x;
+
+            // Speeding up in the same direction uses acceleration, anything else is friction
+            let rate = if target.abs() > previous.abs() && target * previous >= 0.0 {
+                traction.acceleration
+            } else {
+                traction.friction
+            };
+
+            let max_change = rate * delta;
+            physics.velocity.x = previous + (target - previous).clamp(-max_change, max_change);
+        }
+
+        contact.previous_velocity_x = physics.velocity.x;
+    }
+}
+
 // Sistema que actualiza la posición basada en la física
-fn apply_physics(time: Res<Time>, mut query: Query<(&mut Transform, &mut Physics)>) {
+#[allow(clippy::type_complexity)]
+pub fn apply_physics(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    mut query: Query<(
+        &mut Transform,
+        &mut Physics,
+        &CurrentPhysicsZone,
+        Has<SurfaceContact>,
+        Has<KinematicCharacter>,
+    )>,
+) {
     let delta = time.delta_secs();
 
-    for (mut transform, mut physics) in &mut query {
+    for (mut transform, mut physics, current_zone, steered, kinematic) in &mut query {
         // Actualizar velocidad basada en aceleración
-        let acceleration = physics.acceleration.clone();
+        let acceleration = physics.acceleration;
         physics.velocity += acceleration * delta;
 
+        // Lo que nadie conduce (escombros, objetos lanzados) se frena solo, mucho más
+        // rápido rozando el suelo que en el aire
+        if !steered {
+            let drag = if physics.on_ground {
+                config.physics.ground_drag
+            } else {
+                config.physics.air_drag
+            };
+            physics.velocity.x *= (-drag * delta).exp();
+        }
+
+        let mut max_fall_speed = config.physics.max_fall_speed;
+        if let Some(zone) = current_zone.0 {
+            // El agua y el aire espeso frenan todo lo que se mueve dentro
+            physics.velocity *= (-zone.drag * delta).exp();
+            max_fall_speed = zone.max_fall_speed;
+        }
+
         // Limitar la velocidad de caída para evitar problemas con colisiones
-        if physics.velocity.y < MAX_FALL_SPEED {
-            physics.velocity.y = MAX_FALL_SPEED;
+        if physics.velocity.y < max_fall_speed {
+            physics.velocity.y = max_fall_speed;
         }
 
         // Aplicar velocidad a la posición
-        transform.translation.x += physics.velocity.x * delta;
-        transform.translation.y += physics.velocity.y * delta;
+        if !kinematic {
+            transform.translation.x += physics.velocity.x * delta;
+            transform.translation.y += physics.velocity.y * delta;
+        }
 
         // Reiniciar aceleración después de aplicarla
         physics.acceleration = Vec2::ZERO;
     }
 }
+
+// Sistema que saca a los cuerpos de los sólidos por el eje de menor penetración. Así
+// chocan de lado contra las paredes y de cabeza contra los techos, además de pisarlos
+#[allow(clippy::type_complexity)]
+pub fn resolve_solid_collisions(
+    mut solid_sets: ParamSet<(
+        Query<(Entity, &Transform, &Solid, Has<OneWayPlatform>)>,
+        Query<(Entity, &mut Transform, &mut Physics, &BodyCollider), With<Solid>>,
+    )>,
+    mut bodies: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Physics,
+            &BodyCollider,
+            Option<&mut SurfaceContact>,
+            Has<DroppingThrough>,
+        ),
+        (Without<Solid>, Without<KinematicCharacter>),
+    >,
+) {
+    let mut solid_boxes: Vec<SolidBox> = solid_sets
+        .p0()
+        .iter()
+        .map(|(entity, transform, solid, one_way)| SolidBox {
+            entity,
+            position: transform.translation.truncate(),
+            size: solid.size,
+            one_way,
+        })
+        .collect();
+
+    // Solids that move (pushable blocks) first, so characters resolve against their final position
+    for (entity, mut transform, mut physics, collider) in &mut solid_sets.p1() {
+        resolve_body(
+            entity,
+            &mut transform,
+            &mut physics,
+            collider,
+            solid_boxes.iter(),
+            false,
+        );
+
+        if let Some(solid_box) = solid_boxes.iter_mut().find(|solid| solid.entity == entity) {
+            solid_box.position = transform.translation.truncate();
+        }
+    }
+
+    // Cada cuerpo solo se prueba contra los sólidos de sus celdas
+    let mut grid = SpatialHash::new(SOLID_CELL_SIZE);
+    for (index, solid) in solid_boxes.iter().enumerate() {
+        grid.insert(index, solid.position, solid.size);
+    }
+
+    for (entity, mut transform, mut physics, collider, surface_contact, dropping_through) in
+        &mut bodies
+    {
+        // Un poco más grande que el cuerpo para no perder sólidos que apenas toca
+        let mut nearby = grid.query(
+            transform.translation.truncate(),
+            collider.size + Vec2::splat(SOLID_CONTACT_TOLERANCE * 2.0),
+        );
+        nearby.sort_unstable();
+        let on_one_way_platform = resolve_body(
+            entity,
+            &mut transform,
+            &mut physics,
+            collider,
+            nearby.iter().map(|&index| &solid_boxes[index]),
+            dropping_through,
+        );
+        if let Some(mut contact) = surface_contact {
+            contact.on_one_way_platform = on_one_way_platform;
+        }
+    }
+}
+
+// Si una caja cabe sin tocar ningún sólido: sirve para preguntar si hay suelo, pared o
+// techo en un sitio antes de mover algo allí
+pub fn area_is_clear(solids: &[(Vec2, Vec2)], position: Vec2, size: Vec2) -> bool {
+    !solids.iter().any(|&(solid_pos, solid_size)| {
+        utils::check_rect_collision(position, size, solid_pos, solid_size)
+    })
+}
+
+struct SolidBox {
+    entity: Entity,
+    position: Vec2,
+    size: Vec2,
+    one_way: bool,
+}
+
+// Devuelve si el cuerpo quedó de pie sobre una plataforma de un solo sentido
+fn resolve_body<'a>(
+    entity: Entity,
+    transform: &mut Transform,
+    physics: &mut Physics,
+    collider: &BodyCollider,
+    solids: impl IntoIterator<Item = &'a SolidBox>,
+    dropping_through: bool,
+) -> bool {
+    let mut on_one_way_platform = false;
+
+    for solid in solids {
+        if solid.entity == entity || (solid.one_way && dropping_through) {
+            continue;
+        }
+
+        let body_pos = transform.translation.truncate();
+        let delta = body_pos - solid.position;
+        let overlap = (collider.size + solid.size) / 2.0 - delta.abs();
+
+        if overlap.x <= 0.0 {
+            continue;
+        }
+
+        // Resting on top (or just landed on it)
+        if delta.y > 0.0
+            && physics.velocity.y <= 0.0
+            && overlap.y > -SOLID_CONTACT_TOLERANCE
+            && (overlap.y <= overlap.x || overlap.y <= 0.0)
+            && (!solid.one_way || overlap.y <= ONE_WAY_LANDING_DEPTH)
+        {
+            transform.translation.y += overlap.y;
+            physics.velocity.y = 0.0;
+            physics.on_ground = true;
+            on_one_way_platform |= solid.one_way;
+            continue;
+        }
+
+        // Bodies pass through one-way platforms from below and from the sides
+        if solid.one_way {
+            continue;
+        }
+
+        if overlap.y <= 0.0 {
+            continue;
+        }
+
+        if overlap.y < overlap.x {
+            // Bumped the underside
+            transform.translation.y -= overlap.y;
+            physics.velocity.y = physics.velocity.y.min(0.0);
+        } else {
+            // Blocked from the side
+            transform.translation.x += overlap.x * delta.x.signum();
+            if physics.velocity.x * delta.x < 0.0 {
+                physics.velocity.x = 0.0;
+            }
+        }
+    }
+
+    on_one_way_platform
+}
+
+// Tras un momento las plataformas vuelven a sostener al cuerpo, ya por debajo de ellas
+fn update_dropping_through(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut bodies: Query<(Entity, &mut DroppingThrough)>,
+) {
+    for (entity, mut dropping) in &mut bodies {
+        dropping.0.tick(time.delta());
+        if dropping.0.finished() {
+            commands.entity(entity).remove::<DroppingThrough>();
+        }
+    }
+}
+
+// Antes de los pasos fijos el transform vuelve a la posición física. Si otro sistema lo
+// movió desde el último frame (respawn, teletransporte), esa pasa a ser la posición física
+fn restore_physics_positions(mut bodies: Query<(&mut Transform, &mut PhysicsInterpolation)>) {
+    for (mut transform, mut interpolation) in &mut bodies {
+        let position = transform.translation.truncate();
+        if position != interpolation.rendered {
+            interpolation.previous = position;
+            interpolation.current = position;
+        }
+        transform.translation.x = interpolation.current.x;
+        transform.translation.y = interpolation.current.y;
+    }
+}
+
+// Al empezar cada paso se guarda de dónde parte el cuerpo
+fn store_previous_positions(mut bodies: Query<(&Transform, &mut PhysicsInterpolation)>) {
+    for (transform, mut interpolation) in &mut bodies {
+        interpolation.previous = transform.translation.truncate();
+    }
+}
+
+// Después de los pasos fijos se dibuja el cuerpo entre los dos últimos, según cuánto tiempo
+// sobró; así se ve suave aunque el frame no coincida con el paso
+fn interpolate_physics_positions(
+    time: Res<Time<Fixed>>,
+    mut bodies: Query<(&mut Transform, &mut PhysicsInterpolation)>,
+) {
+    let alpha = time.overstep_fraction();
+
+    for (mut transform, mut interpolation) in &mut bodies {
+        interpolation.current = transform.translation.truncate();
+        interpolation.rendered = interpolation.previous.lerp(interpolation.current, alpha);
+        transform.translation.x = interpolation.rendered.x;
+        transform.translation.y = interpolation.rendered.y;
+    }
+}
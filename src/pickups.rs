@@ -0,0 +1,317 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::animations::CharacterDimensions;
+use crate::barrier::Barrier;
+use crate::cleanup::DespawnOnRoomExit;
+use crate::enemy::EnemyDefeatedEvent;
+use crate::game::{GameState, InGame};
+use crate::layers::Layer;
+use crate::level::CurrentRoom;
+use crate::physics::Physics;
+use crate::player::Player;
+
+// Pickup constants
+const PICKUP_RADIUS: f32 = 6.0;
+const PICKUP_SCATTER_SPEED_X: f32 = 160.0;
+const PICKUP_SCATTER_MIN_SPEED_Y: f32 = 250.0;
+const PICKUP_SCATTER_MAX_SPEED_Y: f32 = 450.0;
+const PICKUP_GROUND_FRICTION: f32 = 8.0;
+const PICKUP_MAGNET_DELAY: f32 = 0.5; // Let the loot scatter before pulling it in
+const PICKUP_MAGNET_RADIUS: f32 = 160.0;
+const PICKUP_MAGNET_SPEED: f32 = 420.0;
+const PICKUP_COLLECT_RADIUS: f32 = 40.0;
+const PICKUP_LIFETIME: f32 = 20.0;
+
+const CURRENCY_COLOR: Color = Color::srgb(0.95, 0.8, 0.2);
+const SOUL_COLOR: Color = Color::srgb(0.85, 0.9, 1.0);
+const HEALTH_COLOR: Color = Color::srgb(0.9, 0.2, 0.3);
+const BARRIER_COLOR: Color = Color::srgb(0.45, 0.8, 1.0);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PickupKind {
+    Currency,
+    Soul,
+    Health,
+    Barrier,
+}
+
+// Collectible dropped in the world
+#[derive(Component)]
+pub struct Pickup {
+    pub kind: PickupKind,
+    pub amount: f32,
+    pub magnet_delay: Timer,
+    pub lifetime: Timer,
+    pub attracted: bool,
+}
+
+// What an enemy drops when it is defeated
+#[derive(Clone, Copy, Debug)]
+pub struct LootTable {
+    pub currency_drops: (u32, u32), // Min and max number of currency pieces
+    pub currency_value: f32,
+    pub soul_drops: u32,
+    pub soul_value: f32,
+    pub health_chance: f32,
+    pub health_value: f32,
+    pub barrier_chance: f32,
+}
+
+impl LootTable {
+    // Same drops scaled up, used for stronger enemies
+    pub fn multiplied(self, factor: f32) -> Self {
+        Self {
+            currency_drops: (
+                (self.currency_drops.0 as f32 * factor).round() as u32,
+                (self.currency_drops.1 as f32 * factor).round() as u32,
+            ),
+            soul_drops: (self.soul_drops as f32 * factor).round() as u32,
+            health_chance: (self.health_chance * factor).min(1.0),
+            barrier_chance: (self.barrier_chance * factor).min(1.0),
+            ..self
+        }
+    }
+}
+
+// Sent when the player collects a pickup
+#[derive(Event)]
+pub struct PickupCollectedEvent {
+    pub kind: PickupKind,
+    pub amount: f32,
+}
+
+// Meshes and materials shared by every pickup
+#[derive(Resource)]
+struct PickupAssets {
+    mesh: Handle<Mesh>,
+    currency_material: Handle<ColorMaterial>,
+    soul_material: Handle<ColorMaterial>,
+    health_material: Handle<ColorMaterial>,
+    barrier_material: Handle<ColorMaterial>,
+}
+
+pub struct PickupPlugin;
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PickupCollectedEvent>()
+            .add_systems(Startup, setup_pickup_assets)
+            .add_systems(
+                Update,
+                (
+                    spawn_enemy_loot,
+                    update_pickups,
+                    collect_pickups,
+                    apply_collected_pickups,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn setup_pickup_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    commands.insert_resource(PickupAssets {
+        mesh: meshes.add(Circle::new(PICKUP_RADIUS)),
+        currency_material: materials.add(CURRENCY_COLOR),
+        soul_material: materials.add(SOUL_COLOR),
+        health_material: materials.add(HEALTH_COLOR),
+        barrier_material: materials.add(BARRIER_COLOR),
+    });
+}
+
+fn spawn_enemy_loot(
+    mut commands: Commands,
+    assets: Res<PickupAssets>,
+    current_room: Res<CurrentRoom>,
+    mut defeated_events: EventReader<EnemyDefeatedEvent>,
+) {
+    let mut rng = rand::thread_rng();
+    let room = current_room.id.as_deref();
+
+    for event in defeated_events.read() {
+        let loot = event.loot;
+        let currency_count = rng.gen_range(loot.currency_drops.0..=loot.currency_drops.1);
+
+        for _ in 0..currency_count {
+            spawn_pickup(
+                &mut commands,
+                &assets,
+                PickupKind::Currency,
+                loot.currency_value,
+                event.position,
+                room,
+            );
+        }
+        for _ in 0..loot.soul_drops {
+            spawn_pickup(
+                &mut commands,
+                &assets,
+                PickupKind::Soul,
+                loot.soul_value,
+                event.position,
+                room,
+            );
+        }
+        if rng.gen_bool(loot.health_chance.clamp(0.0, 1.0) as f64) {
+            spawn_pickup(
+                &mut commands,
+                &assets,
+                PickupKind::Health,
+                loot.health_value,
+                event.position,
+                room,
+            );
+        }
+        if rng.gen_bool(loot.barrier_chance.clamp(0.0, 1.0) as f64) {
+            spawn_pickup(
+                &mut commands,
+                &assets,
+                PickupKind::Barrier,
+                1.0,
+                event.position,
+                room,
+            );
+        }
+    }
+}
+
+fn spawn_pickup(
+    commands: &mut Commands,
+    assets: &PickupAssets,
+    kind: PickupKind,
+    amount: f32,
+    position: Vec2,
+    room: Option<&str>,
+) {
+    let mut rng = rand::thread_rng();
+    let material = match kind {
+        PickupKind::Currency => assets.currency_material.clone(),
+        PickupKind::Soul => assets.soul_material.clone(),
+        PickupKind::Health => assets.health_material.clone(),
+        PickupKind::Barrier => assets.barrier_material.clone(),
+    };
+
+    let mut pickup = commands.spawn((
+        Pickup {
+            kind,
+            amount,
+            magnet_delay: Timer::from_seconds(PICKUP_MAGNET_DELAY, TimerMode::Once),
+            lifetime: Timer::from_seconds(PICKUP_LIFETIME, TimerMode::Once),
+            attracted: false,
+        },
+        Physics {
+            velocity: Vec2::new(
+                rng.gen_range(-PICKUP_SCATTER_SPEED_X..=PICKUP_SCATTER_SPEED_X),
+                rng.gen_range(PICKUP_SCATTER_MIN_SPEED_Y..=PICKUP_SCATTER_MAX_SPEED_Y),
+            ),
+            ..default()
+        },
+        CharacterDimensions {
+            height: PICKUP_RADIUS * 2.0,
+            feet_offset: PICKUP_RADIUS,
+        },
+        Mesh2d(assets.mesh.clone()),
+        MeshMaterial2d(material),
+        Transform::from_translation(position.extend(Layer::Pickups.z())),
+        StateScoped(InGame),
+    ));
+    // Loot left behind doesn't wait for the player to come back for it
+    if let Some(room) = room {
+        pickup.insert(DespawnOnRoomExit(room.to_string()));
+    }
+}
+
+// Scatter physics, ground friction, magnet pull and expiry
+fn update_pickups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pickups: Query<(Entity, &mut Pickup, &mut Physics, &Transform)>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let player_pos = player_query
+        .get_single()
+        .map(|transform| transform.translation.truncate())
+        .ok();
+
+    for (entity, mut pickup, mut physics, transform) in &mut pickups {
+        pickup.lifetime.tick(time.delta());
+        if pickup.lifetime.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        pickup.magnet_delay.tick(time.delta());
+
+        let pickup_pos = transform.translation.truncate();
+        if let Some(player_pos) = player_pos
+            && pickup.magnet_delay.finished()
+            && (pickup.attracted || pickup_pos.distance(player_pos) < PICKUP_MAGNET_RADIUS)
+        {
+            // Once attracted, fly straight at the player ignoring gravity
+            pickup.attracted = true;
+            physics.gravity_scale = 0.0;
+            physics.velocity = (player_pos - pickup_pos).normalize_or_zero() * PICKUP_MAGNET_SPEED;
+            continue;
+        }
+
+        if physics.on_ground {
+            let decay = (1.0 - PICKUP_GROUND_FRICTION * time.delta_secs()).max(0.0);
+            physics.velocity.x *= decay;
+        }
+    }
+}
+
+fn collect_pickups(
+    mut commands: Commands,
+    pickups: Query<(Entity, &Pickup, &Transform)>,
+    player_query: Query<&Transform, With<Player>>,
+    mut collected_events: EventWriter<PickupCollectedEvent>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let player_pos = player_transform.translation.truncate();
+
+    for (entity, pickup, transform) in &pickups {
+        if transform.translation.truncate().distance(player_pos) < PICKUP_COLLECT_RADIUS {
+            collected_events.send(PickupCollectedEvent {
+                kind: pickup.kind,
+                amount: pickup.amount,
+            });
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn apply_collected_pickups(
+    mut commands: Commands,
+    mut collected_events: EventReader<PickupCollectedEvent>,
+    mut player_query: Query<(Entity, &mut Player, Option<&mut Barrier>)>,
+) {
+    let Ok((player_entity, mut player, mut barrier)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    for event in collected_events.read() {
+        match event.kind {
+            PickupKind::Currency => player.currency += event.amount as u32,
+            PickupKind::Soul => player.soul = (player.soul + event.amount).min(player.max_soul),
+            PickupKind::Health => {
+                player.health = (player.health + event.amount).min(player.max_health)
+            }
+            // Picking up another one while shielded just restarts the timer
+            PickupKind::Barrier => match barrier.as_mut() {
+                Some(barrier) => barrier.timer.reset(),
+                None => {
+                    commands.entity(player_entity).insert(Barrier::default());
+                }
+            },
+        }
+    }
+}
@@ -1,60 +1,109 @@
+use crate::animation_manifest::{AnimationSet, AttackDefinitions, CharacterAssetCache};
 use crate::animations::{
-    AnimationController, AnimationData, CharacterAnimations, CharacterState, CurrentAnimation,
+    self, AnimationController, AnimationFinishedEvent, CharacterDimensions, CharacterState,
+    CurrentAnimation, Facing,
+};
+use crate::barrier::{self, Barrier, BarrierBrokenEvent};
+use crate::camera_director;
+use crate::config::GameConfig;
+use crate::dialogue;
+use crate::enemy::Enemy;
+use crate::game::{GameState, InGame};
+use crate::game_over;
+use crate::hitbox::{
+    self, AttackSet, AttackWindow, CollisionHitbox, DamageEvent, DeathEvent, Faction, HitEvent,
+    HitReaction, Knockback, KnockbackEvent,
+};
+use crate::input::{ActionState, InputAction};
+use crate::intro;
+use crate::layers::Layer;
+use crate::ledge_grab::LedgeHang;
+use crate::level::RoomRegistry;
+use crate::map;
+use crate::pause;
+use crate::physics::{
+    self, BodyCollider, DroppingThrough, MovementTuning, Physics, SurfaceContact,
 };
-use crate::enemy::{AttackHitbox, CollisionHitbox, Enemy};
-use crate::game::GameState;
-use crate::physics::Physics;
 use crate::resolution;
+use crate::spatial::HitboxGrid;
+use crate::status_effects::StatusEffects;
 use crate::utils;
 
 use bevy::prelude::*;
 use bevy::sprite::Anchor;
+use std::time::Duration;
 
 // Constants
-const PLAYER_INITIAL_HEALTH: f32 = 100.0;
-const PLAYER_MAX_HEALTH: f32 = 100.0;
-const PLAYER_ATTACK: f32 = 10.0;
-const PLAYER_DEFENSE: f32 = 5.0;
-const PLAYER_SPEED: f32 = 250.0;
-const PLAYER_JUMP_FORCE: f32 = 500.0;
-const PLAYER_HURT_IMMUNITY_TIME: f32 = 0.4;
+const PLAYER_MAX_SOUL: f32 = 99.0;
 const PLAYER_COLLISION_SIZE: Vec2 = Vec2::new(45.0, 45.0);
 const PLAYER_ATTACK_HITBOX_SIZE: Vec2 = Vec2::new(40.0, 30.0);
 const PLAYER_CHARGE_ATTACK_HITBOX_SIZE: Vec2 = Vec2::new(84.0, 30.0);
-const PLAYER_ATTACK_HITBOX_DURATION: f32 = 0.1;
+const PLAYER_ATTACK_HITBOX_DURATION: f32 = 0.05;
 const PLAYER_ATTACK_HITBOX_OFFSET: f32 = 0.5;
+const PLAYER_ATTACK_KNOCKBACK: Knockback = Knockback {
+    strength: 650.0,
+    lift: 120.0,
+    decay: 12.0,
+};
+const PLAYER_CHARGE_ATTACK_LAUNCH: f32 = 650.0;
 const PLAYER_FEET_OFFSET: f32 = 10.0;
-
-// Animation Constants
-const PLAYER_IDLE_FRAMES: usize = 11;
-const PLAYER_ATTACK_FRAMES: usize = 7;
-const PLAYER_CHARGE_ATTACK_FRAMES: usize = 7;
-const PLAYER_RUN_FRAMES: usize = 8;
-const PLAYER_JUMP_FRAMES: usize = 3;
-const PLAYER_HURT_FRAMES: usize = 4;
-const PLAYER_FALL_FRAMES: usize = 3;
-
-const PLAYER_IDLE_FPS: f32 = 10.0;
-const PLAYER_ATTACK_FPS: f32 = 20.0;
-const PLAYER_CHARGE_ATTACK_FPS: f32 = 12.0;
-const PLAYER_RUN_FPS: f32 = 15.0;
-const PLAYER_JUMP_FPS: f32 = 18.0;
-const PLAYER_HURT_FPS: f32 = 10.0;
-const PLAYER_FALL_FPS: f32 = 10.0;
+const PLAYER_BODY_SIZE: Vec2 = Vec2::new(30.0, 50.0); // Bottom matches the ground collision feet
+const PLAYER_LANDING_SQUASH_TIME: f32 = 0.15;
+const PLAYER_DASH_SPEED: f32 = 900.0;
+const PLAYER_DASH_DURATION: f32 = 0.18;
+const PLAYER_DASH_COOLDOWN: f32 = 0.4; // Desde que termina un dash hasta poder hacer otro
 
 // Plugin principal del jugador
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_player).add_systems(
+        app.add_systems(OnEnter(InGame), setup_player).add_systems(
             Update,
             ((
-                process_player_input,
-                player_jump.after(process_player_input),
+                process_player_input
+                    .after(hitbox::apply_knockback)
+                    .before(physics::apply_surface_traction)
+                    .run_if(
+                        intro::intro_finished
+                            .and(camera_director::director_idle)
+                            .and(not(pause::pause_requested))
+                            .and(game_over::player_alive)
+                            .and(map::map_closed)
+                            .and(dialogue::dialogue_closed),
+                    ),
+                player_jump.after(process_player_input).run_if(
+                    intro::intro_finished
+                        .and(camera_director::director_idle)
+                        .and(not(pause::pause_requested))
+                        .and(game_over::player_alive)
+                        .and(map::map_closed)
+                        .and(dialogue::dialogue_closed),
+                ),
+                player_dash
+                    .after(player_jump)
+                    .before(physics::apply_surface_traction)
+                    .run_if(
+                        intro::intro_finished
+                            .and(camera_director::director_idle)
+                            .and(not(pause::pause_requested))
+                            .and(game_over::player_alive)
+                            .and(map::map_closed)
+                            .and(dialogue::dialogue_closed),
+                    ),
                 update_animations,
-                update_attack_hitbox,
-                handle_damage,
+                update_landing_squash.after(update_animations),
+                finish_player_animations.after(animations::animate_current_state),
+                apply_hits_to_player.after(hitbox::detect_hits),
+                handle_contact_damage
+                    .after(apply_hits_to_player)
+                    .before(hitbox::apply_knockback),
+                take_player_damage
+                    .after(handle_contact_damage)
+                    .before(process_player_input),
+                apply_player_config.run_if(
+                    resource_changed::<GameConfig>.or(resource_changed::<AttackDefinitions>),
+                ),
             )
                 .run_if(in_state(GameState::Playing)),),
         );
@@ -63,6 +112,7 @@ impl Plugin for PlayerPlugin {
 
 // Componente de estadísticas del jugador
 #[derive(Component)]
+#[require(PlayerDash)]
 pub struct Player {
     pub name: String,
     pub health: f32,
@@ -70,120 +120,118 @@ pub struct Player {
     pub attack: f32,
     pub defense: f32,
     pub speed: f32,
-    pub facing_right: bool,
     pub hurt_timer: Timer,
+    pub currency: u32,
+    pub soul: f32,
+    pub max_soul: f32,
+}
+
+// Dash del jugador: una ráfaga horizontal sin gravedad, una vez por salto
+#[derive(Component, Default)]
+pub struct PlayerDash {
+    active: Option<Timer>, // Lo que queda del dash en curso
+    cooldown: Timer,
+    available: bool, // Se gasta en el aire y el suelo lo recarga
+}
+
+// Aplastamiento del sprite tras aterrizar
+#[derive(Component)]
+pub struct LandingSquash {
+    timer: Timer,
+    base_scale: Vec3, // Escala a la que vuelve
+    amount: f32,
 }
 
-fn update_attack_hitbox(
+// Golpes de los enemigos, uno por frame gracias a la inmunidad
+fn apply_hits_to_player(
     mut commands: Commands,
     time: Res<Time>,
-    mut query: Query<(
-        Entity,
-        &AnimationController,
-        &Transform,
-        &Player,
-        &CurrentAnimation,
-    )>,
-    mut hitbox_query: Query<(Entity, &Parent, &mut AttackHitbox)>,
-    // mut meshes: ResMut<Assets<Mesh>>,
-    // mut materials: ResMut<Assets<ColorMaterial>>,
-    _resolution: Res<resolution::Resolution>,
+    mut hit_events: EventReader<HitEvent>,
+    mut player_query: Query<(Entity, &mut Player, Option<&Barrier>)>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut knockback_events: EventWriter<KnockbackEvent>,
+    mut barrier_broken_events: EventWriter<BarrierBrokenEvent>,
 ) {
-    // Primero actualizamos los timers y removemos hitboxes expiradas
-    for (hitbox_entity, _parent, mut hitbox) in &mut hitbox_query {
-        hitbox.timer.tick(time.delta());
+    let Ok((player_entity, mut player, barrier)) = player_query.get_single_mut() else {
+        return;
+    };
 
-        if hitbox.timer.finished() {
-            hitbox.active = false;
-            commands.entity(hitbox_entity).despawn_recursive();
-        }
-    }
+    // Si el timer de hurt está activo, el jugador es inmune
+    player.hurt_timer.tick(time.delta());
 
-    for (entity, animation_controller, _transform, player, current_animation) in &mut query {
-        let current_state = animation_controller.get_current_state();
-        let is_attacking = matches!(
-            current_state,
-            CharacterState::Attacking | CharacterState::ChargeAttacking
-        );
-
-        // Verificar si ya existe un hitbox activo
-        let has_active_hitbox = hitbox_query
-            .iter()
-            .any(|(_, parent, hitbox)| parent.get() == entity && hitbox.active);
+    for event in hit_events.read() {
+        if event.target != player_entity || !player.hurt_timer.finished() {
+            continue;
+        }
 
-        // Eliminar hitboxes antiguas si ya no está atacando
-        if !is_attacking {
-            for (hitbox_entity, parent, _) in hitbox_query.iter() {
-                if parent.get() == entity {
-                    commands.entity(hitbox_entity).despawn();
-                }
-            }
+        // La barrera se come el golpe entero y da la inmunidad de siempre
+        if barrier::absorb_hit(
+            &mut commands,
+            &mut barrier_broken_events,
+            player_entity,
+            barrier,
+            event.target_pos,
+        ) {
+            player.hurt_timer.reset();
             continue;
         }
 
-        // Solo crear nuevo hitbox si no hay uno activo y estamos en el rango de tiempo deseado
-        if is_attacking && !has_active_hitbox {
-            let should_create_hitbox = match current_state {
-                CharacterState::Attacking => current_animation.current_frame == 3,
-                CharacterState::ChargeAttacking => current_animation.current_frame == 4,
-                _ => false,
+        let damage = event.damage - player.defense;
+        if damage > 0.0 {
+            let reaction = if event.launch > 0.0 {
+                HitReaction::Launch
+            } else {
+                event
+                    .reaction
+                    .unwrap_or_else(|| HitReaction::from_damage(damage, player.max_health))
             };
-
-            if should_create_hitbox {
-                let damage = if current_state == CharacterState::Attacking {
-                    player.attack
-                } else {
-                    player.attack * 2.0
-                };
-
-                let hitbox_size = if current_state == CharacterState::Attacking {
-                    PLAYER_ATTACK_HITBOX_SIZE
+            damage_events.send(DamageEvent {
+                target: player_entity,
+                source: Some(event.attacker),
+                amount: damage,
+                position: event.target_pos,
+                reaction: Some(reaction),
+                status: event.status,
+            });
+
+            // Empujar al jugador lejos del atacante si el ataque tiene retroceso
+            let knockback = Knockback {
+                lift: event.knockback.lift + event.launch,
+                ..event.knockback
+            };
+            if !knockback.is_none() {
+                let direction = if event.target_pos.x < event.attacker_pos.x {
+                    -1.0
                 } else {
-                    PLAYER_CHARGE_ATTACK_HITBOX_SIZE
+                    1.0
                 };
-                let offset_x = hitbox_size.x * PLAYER_ATTACK_HITBOX_OFFSET;
-
-                commands.entity(entity).with_children(|parent| {
-                    parent.spawn((
-                        AttackHitbox {
-                            damage,
-                            active: true,
-                            size: hitbox_size,
-                            timer: Timer::from_seconds(
-                                PLAYER_ATTACK_HITBOX_DURATION,
-                                TimerMode::Once,
-                            ),
-                        },
-                        Transform::from_translation(Vec3::new(offset_x, 0., 0.)),
-                        // Mesh2d(meshes.add(Rectangle::from_size(hitbox_size))),
-                        // MeshMaterial2d(materials.add(Color::Srgba(Srgba {
-                        //     red: 0.,
-                        //     green: 255.,
-                        //     blue: 0.,
-                        //     alpha: 0.7,
-                        // }))),
-                    ));
+                knockback_events.send(KnockbackEvent {
+                    target: player_entity,
+                    direction,
+                    knockback,
                 });
             }
+            player.hurt_timer.reset(); // Reiniciar el timer de inmunidad
         }
     }
 }
 
-fn handle_damage(
-    mut player_query: Query<(
-        &mut Player,
-        &mut AnimationController,
-        &Children,
-        &mut Transform,
-    )>,
-    player_hitboxes: Query<(&CollisionHitbox, &GlobalTransform)>,
-    enemy_attack_hitboxes: Query<(&AttackHitbox, &GlobalTransform, &Parent)>,
-    enemy_query: Query<Entity, With<Enemy>>,
-    time: Res<Time>,
+// Daño por contacto con el cuerpo de los enemigos, aparte de sus ataques
+#[allow(clippy::too_many_arguments)]
+fn handle_contact_damage(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    mut player_query: Query<(Entity, &mut Player, &Transform, &Children, Option<&Barrier>)>,
+    player_hitboxes: Query<(&CollisionHitbox, &Transform)>,
+    grid: Res<HitboxGrid>,
+    enemy_body_hitboxes: Query<(&CollisionHitbox, &Transform, &Parent)>,
+    enemy_query: Query<(&Enemy, &Transform)>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut knockback_events: EventWriter<KnockbackEvent>,
+    mut barrier_broken_events: EventWriter<BarrierBrokenEvent>,
 ) {
-    for (mut player, mut animation_controller, children, mut _transform) in &mut player_query {
-        // Si el timer de hurt está activo, el jugador es inmune
-        player.hurt_timer.tick(time.delta());
+    for (player_entity, mut player, player_transform, children, barrier) in &mut player_query {
+        // El golpe de un ataque ya activó la inmunidad
         if !player.hurt_timer.finished() {
             continue;
         }
@@ -191,11 +239,14 @@ fn handle_damage(
         // Encuentra el hitbox del jugador
         let mut player_hitbox_data = None;
         for &child in children.iter() {
-            if let Ok((hitbox, transform)) = player_hitboxes.get(child) {
-                if hitbox.active {
-                    player_hitbox_data = Some((hitbox.size, transform.translation().truncate()));
-                    break;
-                }
+            if let Ok((hitbox, transform)) = player_hitboxes.get(child)
+                && hitbox.active
+            {
+                player_hitbox_data = Some((
+                    hitbox.size,
+                    hitbox::hitbox_position(player_transform, transform),
+                ));
+                break;
             }
         }
 
@@ -204,53 +255,125 @@ fn handle_damage(
             None => continue,
         };
 
-        // Verificar colisión con los hitboxes de ataque de los enemigos
-        for (attack_hitbox, attack_transform, parent) in &enemy_attack_hitboxes {
-            if !attack_hitbox.active {
+        for candidate in grid.0.query(player_pos, player_size) {
+            let Ok((body_hitbox, body_transform, parent)) = enemy_body_hitboxes.get(candidate)
+            else {
                 continue;
-            }
-
-            // Verificar que el hitbox pertenece a un enemigo
-            if !enemy_query.contains(parent.get()) {
+            };
+            let Ok((enemy, enemy_transform)) = enemy_query.get(parent.get()) else {
+                continue;
+            };
+            if !body_hitbox.active || enemy.is_dead {
                 continue;
             }
 
-            let attack_pos = attack_transform.translation().truncate();
+            let body_pos = hitbox::hitbox_position(enemy_transform, body_transform);
+            if utils::check_rect_collision(player_pos, player_size, body_pos, body_hitbox.size) {
+                if barrier::absorb_hit(
+                    &mut commands,
+                    &mut barrier_broken_events,
+                    player_entity,
+                    barrier,
+                    player_pos,
+                ) {
+                    player.hurt_timer.reset();
+                    break;
+                }
 
-            // Usar la función de utilidad para verificar la colisión
-            if utils::check_rect_collision(player_pos, player_size, attack_pos, attack_hitbox.size)
-            {
-                let damage = attack_hitbox.damage - player.defense;
+                let damage = enemy.contact_damage - player.defense;
                 if damage > 0.0 {
-                    player.health -= damage;
-                    animation_controller.change_state(CharacterState::Hurt);
-                    player.hurt_timer.reset(); // Reiniciar el timer de inmunidad
+                    damage_events.send(DamageEvent {
+                        target: player_entity,
+                        source: Some(parent.get()),
+                        amount: damage,
+                        position: player_pos,
+                        reaction: Some(HitReaction::from_damage(damage, player.max_health)),
+                        status: None,
+                    });
+                    player.hurt_timer.reset();
+
+                    // Empujar al jugador lejos del enemigo
+                    let direction = if player_pos.x < body_pos.x { -1.0 } else { 1.0 };
+                    knockback_events.send(KnockbackEvent {
+                        target: player_entity,
+                        direction,
+                        knockback: config.player.contact_knockback,
+                    });
                 }
-                break; // evita múltiples daños por frame
+                break;
             }
         }
     }
 }
 
-fn process_player_input(
-    keyboard: Res<ButtonInput<KeyCode>>,
+fn take_player_damage(
+    config: Res<GameConfig>,
+    mut damage_events: EventReader<DamageEvent>,
+    mut player_query: Query<(Entity, &mut Player, &mut AnimationController, &Transform)>,
+    mut death_events: EventWriter<DeathEvent>,
+) {
+    let Ok((player_entity, mut player, mut animation_controller, transform)) =
+        player_query.get_single_mut()
+    else {
+        return;
+    };
+
+    for event in damage_events.read() {
+        // Los golpes a un jugador ya muerto no lo sacan de la animación de muerte
+        if event.target != player_entity || player.health <= 0.0 {
+            continue;
+        }
+
+        player.health -= event.amount;
+
+        // El daño continuo (quemaduras, veneno) no interrumpe ni da inmunidad
+        if let Some(reaction) = event.reaction {
+            animation_controller.change_state(reaction.state());
+
+            // El tambaleo alarga el hurt y con él la inmunidad
+            let hurt_duration = match reaction {
+                HitReaction::Stagger => config.player.stagger_time,
+                HitReaction::Flinch | HitReaction::Launch => config.player.hurt_immunity_time,
+            };
+            player
+                .hurt_timer
+                .set_duration(Duration::from_secs_f32(hurt_duration));
+            player.hurt_timer.reset();
+        }
+
+        if player.health <= 0.0 {
+            animation_controller.change_state(CharacterState::Dead);
+            death_events.send(DeathEvent {
+                entity: player_entity,
+                position: transform.translation.truncate(),
+            });
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn process_player_input(
+    actions: Res<ActionState>,
     _time: Res<Time>,
     mut query: Query<
         (
             &mut AnimationController,
-            &mut Player,
-            &mut Transform,
+            &Player,
+            &mut Facing,
             &mut Physics,
+            Option<&StatusEffects>,
         ),
-        With<Player>,
+        (With<Player>, Without<LedgeHang>),
     >,
 ) {
-    for (mut animation_controller, mut player, mut transform, mut physics) in &mut query {
+    for (mut animation_controller, player, mut facing, mut physics, status_effects) in &mut query {
         let current_state = animation_controller.get_current_state();
         let can_move_now = can_move(&current_state);
+        // La lentitud reduce la velocidad de carrera
+        let speed = player.speed * status_effects.map_or(1.0, StatusEffects::speed_factor);
 
-        // Ataque con Z en lugar de Espacio
-        if keyboard.just_pressed(KeyCode::KeyZ)
+        // Ataque (Z por defecto)
+        if actions.just_pressed(InputAction::Attack)
             && current_state != CharacterState::Attacking
             && current_state != CharacterState::ChargeAttacking
             && current_state != CharacterState::Jumping
@@ -258,8 +381,8 @@ fn process_player_input(
             animation_controller.change_state(CharacterState::Attacking);
         }
 
-        // Ataque cargado con V
-        if keyboard.just_pressed(KeyCode::KeyV)
+        // Ataque cargado (V por defecto)
+        if actions.just_pressed(InputAction::ChargeAttack)
             && current_state != CharacterState::ChargeAttacking
             && current_state != CharacterState::Attacking
             && current_state != CharacterState::Jumping
@@ -270,69 +393,187 @@ fn process_player_input(
         // Solo aplicar movimiento horizontal si puede moverse
         if can_move_now {
             // Manejar movimiento a la derecha
-            if keyboard.pressed(KeyCode::ArrowRight) {
-                player.facing_right = true;
-                physics.velocity.x = player.speed;
+            if actions.pressed(InputAction::MoveRight) {
+                *facing = Facing::Right;
+                physics.velocity.x = speed;
             }
             // Manejar movimiento a la izquierda
-            else if keyboard.pressed(KeyCode::ArrowLeft) {
-                player.facing_right = false;
-                physics.velocity.x = -player.speed;
+            else if actions.pressed(InputAction::MoveLeft) {
+                *facing = Facing::Left;
+                physics.velocity.x = -speed;
             }
             // Si no se presiona ninguna tecla de movimiento, detener el movimiento horizontal
             else {
                 physics.velocity.x = 0.0;
             }
-        } else {
-            // Si no puede moverse (durante ataques), detener el movimiento horizontal
+        } else if !current_state.is_hurt() || physics.on_ground {
+            // Si no puede moverse (durante ataques), detener el movimiento horizontal;
+            // en el aire el retroceso del golpe se conserva
             physics.velocity.x = 0.0;
         }
-
-        // Actualizar la escala para voltear el sprite según la dirección
-        let scale_x = transform.scale.x.abs() * if player.facing_right { 1.0 } else { -1.0 };
-        transform.scale.x = scale_x;
     }
 }
 
-// Modificar el sistema de salto para usar la tecla de espacio
+// Salto con la tecla asignada (Espacio por defecto)
+// Abajo + salto sobre una plataforma de un solo sentido la atraviesa en lugar de saltar
+#[allow(clippy::type_complexity)]
 fn player_jump(
-    keyboard: Res<ButtonInput<KeyCode>>,
-    mut query: Query<(&mut Physics, &AnimationController), With<Player>>,
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    config: Res<GameConfig>,
+    mut query: Query<
+        (Entity, &mut Physics, &AnimationController, &SurfaceContact),
+        (With<Player>, Without<LedgeHang>),
+    >,
 ) {
-    for (mut physics, animation_controller) in &mut query {
+    for (entity, mut physics, animation_controller, contact) in &mut query {
         let current_state = animation_controller.get_current_state();
         let can_jump = can_move(&current_state);
 
-        if keyboard.just_pressed(KeyCode::Space) && physics.on_ground && can_jump {
-            physics.velocity.y = PLAYER_JUMP_FORCE;
+        if actions.just_pressed(InputAction::Jump) && physics.on_ground && can_jump {
+            if actions.pressed(InputAction::MoveDown) && contact.on_one_way_platform {
+                commands.entity(entity).insert(DroppingThrough::default());
+            } else {
+                physics.velocity.y = config.player.jump_force;
+            }
             physics.on_ground = false;
         }
     }
 }
 
+// El dash va hacia donde mira el jugador y pisa la velocidad que pidió el movimiento;
+// un golpe lo corta
+#[allow(clippy::type_complexity)]
+fn player_dash(
+    time: Res<Time>,
+    actions: Res<ActionState>,
+    mut query: Query<
+        (
+            &mut PlayerDash,
+            &AnimationController,
+            &Facing,
+            &mut Physics,
+            &mut SurfaceContact,
+        ),
+        (With<Player>, Without<LedgeHang>),
+    >,
+) {
+    for (mut dash, animation_controller, facing, mut physics, mut contact) in &mut query {
+        dash.cooldown.tick(time.delta());
+        if physics.on_ground {
+            dash.available = true;
+        }
+
+        let current_state = animation_controller.get_current_state();
+        if current_state.is_hurt() {
+            dash.active = None;
+        } else if actions.just_pressed(InputAction::Dash)
+            && dash.active.is_none()
+            && dash.available
+            && dash.cooldown.finished()
+            && can_move(&current_state)
+        {
+            dash.active = Some(Timer::from_seconds(PLAYER_DASH_DURATION, TimerMode::Once));
+            dash.cooldown =
+                Timer::from_seconds(PLAYER_DASH_DURATION + PLAYER_DASH_COOLDOWN, TimerMode::Once);
+            dash.available = false;
+        }
+
+        let Some(timer) = dash.active.as_mut() else {
+            continue;
+        };
+        timer.tick(time.delta());
+        if timer.finished() {
+            dash.active = None;
+            continue;
+        }
+
+        let velocity_x = facing.sign() * PLAYER_DASH_SPEED;
+        physics.velocity = Vec2::new(velocity_x, 0.0);
+        contact.snap_velocity_x(velocity_x);
+    }
+}
+
 fn can_move(state: &CharacterState) -> bool {
-    match state {
-        CharacterState::Attacking => false,
-        CharacterState::ChargeAttacking => false,
-        CharacterState::Hurt => false,
-        _ => true,
+    !matches!(
+        state,
+        CharacterState::Attacking | CharacterState::ChargeAttacking
+    ) && !state.is_hurt()
+}
+
+// Vuelve a Idle cuando termina la animación de un ataque o del aterrizaje
+fn finish_player_animations(
+    mut finished_events: EventReader<AnimationFinishedEvent>,
+    mut query: Query<&mut AnimationController, With<Player>>,
+) {
+    for event in finished_events.read() {
+        let Ok(mut animation_controller) = query.get_mut(event.entity) else {
+            continue;
+        };
+        if matches!(
+            event.state,
+            CharacterState::Attacking | CharacterState::ChargeAttacking | CharacterState::Landing
+        ) && animation_controller.get_current_state() == event.state
+        {
+            animation_controller.change_state(CharacterState::Idle);
+        }
     }
 }
 
-fn update_animations(mut query: Query<(&mut AnimationController, &Physics, &Player)>) {
-    for (mut animation_controller, physics, player) in &mut query {
+#[allow(clippy::type_complexity)]
+pub fn update_animations(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    mut query: Query<
+        (
+            Entity,
+            &mut AnimationController,
+            &Physics,
+            &Player,
+            &Transform,
+            Option<&LandingSquash>,
+        ),
+        Without<LedgeHang>,
+    >,
+) {
+    for (entity, mut animation_controller, physics, player, transform, squash) in &mut query {
         let current_state = animation_controller.get_current_state();
 
-        // Si está en estado Hurt y el timer ha terminado, volver a Idle
-        if current_state == CharacterState::Hurt && player.hurt_timer.finished() {
+        // Si está herido y el timer ha terminado, volver a Idle; si salió despedido, al aterrizar
+        let landed = current_state != CharacterState::Launched || physics.on_ground;
+        if current_state.is_hurt() && player.hurt_timer.finished() && landed {
             animation_controller.change_state(CharacterState::Idle);
             continue;
         }
 
-        // No cambiar las animaciones si está atacando o herido
+        // No cambiar las animaciones si está atacando, herido, muerto o en la intro
         if current_state == CharacterState::Attacking
             || current_state == CharacterState::ChargeAttacking
-            || current_state == CharacterState::Hurt
+            || current_state == CharacterState::WakingUp
+            || current_state == CharacterState::Dead
+            || current_state.is_hurt()
+        {
+            continue;
+        }
+
+        // Al tocar el suelo después de una caída, aterrizar antes de volver a Idle o correr
+        if current_state == CharacterState::Falling && physics.on_ground {
+            animation_controller.change_state(CharacterState::Landing);
+            if config.player.landing_squash > 0.0 {
+                commands.entity(entity).insert(LandingSquash {
+                    timer: Timer::from_seconds(PLAYER_LANDING_SQUASH_TIME, TimerMode::Once),
+                    // Un aterrizaje durante otro vuelve a partir de la escala normal
+                    base_scale: squash.map_or(transform.scale, |squash| squash.base_scale),
+                    amount: config.player.landing_squash,
+                });
+            }
+            continue;
+        }
+
+        // El aterrizaje dura hasta que acaba su animación, salvo que empiece a correr o saltar
+        if current_state == CharacterState::Landing
+            && physics.on_ground
+            && physics.velocity.x.abs() < 0.1
         {
             continue;
         }
@@ -352,22 +593,40 @@ fn update_animations(mut query: Query<(&mut AnimationController, &Physics, &Play
             }
         }
         // Si está en el suelo y se está moviendo, usar animación de correr
-        else if physics.on_ground {
-            if current_state != CharacterState::Running {
-                animation_controller.change_state(CharacterState::Running);
-            }
+        else if physics.on_ground && current_state != CharacterState::Running {
+            animation_controller.change_state(CharacterState::Running);
+        }
+    }
+}
+
+// Aplasta el sprite al aterrizar y lo devuelve poco a poco a su escala normal. Los pies
+// siguen en el suelo porque la colisión con el suelo usa la escala vertical
+fn update_landing_squash(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut LandingSquash, &mut Transform)>,
+) {
+    for (entity, mut squash, mut transform) in &mut query {
+        squash.timer.tick(time.delta());
+        if squash.timer.finished() {
+            transform.scale = squash.base_scale;
+            commands.entity(entity).remove::<LandingSquash>();
+            continue;
         }
+
+        let amount = squash.amount * (1.0 - squash.timer.fraction());
+        transform.scale = squash.base_scale * Vec3::new(1.0 + amount, 1.0 - amount, 1.0);
     }
 }
 
-fn setup_player(
+pub fn setup_player(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    character_assets: Res<CharacterAssetCache>,
     resolution: Res<resolution::Resolution>,
+    config: Res<GameConfig>,
+    attack_definitions: Res<AttackDefinitions>,
+    registry: Res<RoomRegistry>,
     windows: Query<&Window>,
-    // mut meshes: ResMut<Assets<Mesh>>,
-    // mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
     // Get window dimensions to position player properly
     let window = windows.single();
@@ -378,136 +637,23 @@ fn setup_player(
     let ground_height = -window_height * 0.3;
     let _player_y = ground_height + 90.0 * resolution.pixel_ratio;
 
-    // Cargar texturas
-    let idle_texture = asset_server.load("hero/Idle.png");
-    let attack_texture = asset_server.load("hero/Attack1.png");
-    let charge_attack_texture = asset_server.load("hero/Attack2.png");
-    let run_texture = asset_server.load("hero/Run.png");
-    let jump_texture = asset_server.load("hero/Jump.png");
-    let hurt_texture = asset_server.load("hero/Hurt.png"); // Agregar textura de hurt
-    let fall_texture = asset_server.load("hero/Fall.png");
-
-    // Crear layouts de atlas
-    let idle_layout = TextureAtlasLayout::from_grid(UVec2::splat(180), 11, 1, None, None);
-    let attack_layout = TextureAtlasLayout::from_grid(UVec2::splat(180), 7, 1, None, None);
-    let charge_attack_layout = TextureAtlasLayout::from_grid(UVec2::splat(180), 7, 1, None, None);
-    let run_layout = TextureAtlasLayout::from_grid(UVec2::splat(180), 8, 1, None, None);
-    let jump_layout = TextureAtlasLayout::from_grid(UVec2::splat(180), 3, 1, None, None);
-    let hurt_layout = TextureAtlasLayout::from_grid(UVec2::splat(180), 4, 1, None, None); // Layout para hurt
-    let fall_layout = TextureAtlasLayout::from_grid(UVec2::splat(180), 3, 1, None, None);
-
-    let idle_atlas_layout = texture_atlas_layouts.add(idle_layout);
-    let attack_atlas_layout = texture_atlas_layouts.add(attack_layout);
-    let charge_attack_attlas_layout = texture_atlas_layouts.add(charge_attack_layout);
-    let run_atlas_layout = texture_atlas_layouts.add(run_layout);
-    let jump_atlas_layout = texture_atlas_layouts.add(jump_layout);
-    let hurt_atlas_layout = texture_atlas_layouts.add(hurt_layout); // Atlas para hurt
-    let fall_atlas_layout = texture_atlas_layouts.add(fall_layout);
-
-    // Crear datos de animación
-    let animations = CharacterAnimations {
-        animations: vec![
-            // Animación de idle
-            AnimationData {
-                state: CharacterState::Idle,
-                texture: idle_texture.clone(),
-                atlas_layout: idle_atlas_layout.clone(),
-                frames: PLAYER_IDLE_FRAMES,
-                fps: PLAYER_IDLE_FPS,
-                looping: true,
-                ping_pong: true,
-            },
-            // Animación de ataque
-            AnimationData {
-                state: CharacterState::Attacking,
-                texture: attack_texture.clone(),
-                atlas_layout: attack_atlas_layout.clone(),
-                frames: PLAYER_ATTACK_FRAMES,
-                fps: PLAYER_ATTACK_FPS,
-                looping: false,
-                ping_pong: false,
-            },
-            AnimationData {
-                state: CharacterState::ChargeAttacking,
-                texture: charge_attack_texture.clone(),
-                atlas_layout: charge_attack_attlas_layout.clone(),
-                frames: PLAYER_CHARGE_ATTACK_FRAMES,
-                fps: PLAYER_CHARGE_ATTACK_FPS,
-                looping: false,
-                ping_pong: false,
-            },
-            AnimationData {
-                state: CharacterState::Running,
-                texture: run_texture.clone(),
-                atlas_layout: run_atlas_layout.clone(),
-                frames: PLAYER_RUN_FRAMES,
-                fps: PLAYER_RUN_FPS,
-                looping: true,
-                ping_pong: false,
-            },
-            // Animación de salto
-            AnimationData {
-                state: CharacterState::Jumping,
-                texture: jump_texture.clone(),
-                atlas_layout: jump_atlas_layout.clone(),
-                frames: PLAYER_JUMP_FRAMES,
-                fps: PLAYER_JUMP_FPS,
-                looping: true,
-                ping_pong: false,
-            },
-            // Animación de hurt
-            AnimationData {
-                state: CharacterState::Hurt,
-                texture: hurt_texture.clone(),
-                atlas_layout: hurt_atlas_layout.clone(),
-                frames: PLAYER_HURT_FRAMES,
-                fps: PLAYER_HURT_FPS,
-                looping: false,
-                ping_pong: false,
-            },
-            // Animación de caída
-            AnimationData {
-                state: CharacterState::Falling,
-                texture: fall_texture.clone(),
-                atlas_layout: fall_atlas_layout.clone(),
-                frames: PLAYER_FALL_FRAMES,
-                fps: PLAYER_FALL_FPS,
-                looping: true,
-                ping_pong: false,
-            },
-        ],
-    };
-
-    // Animación inicial (idle)
-    let initial_animation = CurrentAnimation {
-        current_frame: 0,
-        timer: Timer::from_seconds(0.01, TimerMode::Repeating),
-        total_frames: PLAYER_IDLE_FRAMES,
-        looping: true,
-        reverse_direction: false,
-    };
-
     // Crear entidad del jugador
     commands
         .spawn((
-            // Sprite inicial
-            Sprite::from_atlas_image(
-                idle_texture,
-                TextureAtlas {
-                    layout: idle_atlas_layout,
-                    index: 0,
-                },
-            ),
+            // La imagen se pone cuando carga el manifiesto de animaciones
+            Sprite::default(),
             // Estadísticas del jugador
             Player {
                 name: "Hero".to_string(),
-                health: PLAYER_INITIAL_HEALTH,
-                max_health: PLAYER_MAX_HEALTH,
-                attack: PLAYER_ATTACK,
-                defense: PLAYER_DEFENSE,
-                speed: PLAYER_SPEED,
-                facing_right: true, // Inicialmente mirando a la derecha
-                hurt_timer: Timer::from_seconds(PLAYER_HURT_IMMUNITY_TIME, TimerMode::Once), // Timer para inmunidad
+                health: config.player.max_health,
+                max_health: config.player.max_health,
+                attack: config.player.attack,
+                defense: config.player.defense,
+                speed: config.player.speed,
+                hurt_timer: Timer::from_seconds(config.player.hurt_immunity_time, TimerMode::Once), // Timer para inmunidad
+                currency: 0,
+                soul: 0.0,
+                max_soul: PLAYER_MAX_SOUL,
             },
             Physics {
                 velocity: Vec2::ZERO,
@@ -515,11 +661,29 @@ fn setup_player(
                 on_ground: true, // Comienza en el suelo
                 gravity_scale: 1.0,
             },
-            Transform::from_xyz(0.0, 400., 0.0).with_scale(Vec3::splat(resolution.pixel_ratio)),
+            BodyCollider {
+                size: PLAYER_BODY_SIZE * resolution.pixel_ratio,
+            },
+            (SurfaceContact::default(), config.player.movement),
+            CharacterDimensions {
+                height: PLAYER_BODY_SIZE.y,
+                feet_offset: PLAYER_BODY_SIZE.y / 2.0,
+            },
+            Transform::from_translation(
+                registry
+                    .player_spawn()
+                    .unwrap_or(Vec2::new(0.0, 400.0))
+                    .extend(Layer::Player.z()),
+            )
+            .with_scale(Vec3::splat(resolution.pixel_ratio)),
             Anchor::Center,
+            Facing::Right, // Inicialmente mirando a la derecha
             AnimationController::default(),
-            animations,
-            initial_animation,
+            AnimationSet(character_assets.player.clone()),
+            CurrentAnimation::default(),
+            player_attacks(&config, &attack_definitions),
+            StatusEffects::new(Color::WHITE),
+            (Faction::Player, StateScoped(InGame)),
         ))
         .with_children(|parent| {
             parent.spawn((
@@ -527,16 +691,74 @@ fn setup_player(
                     active: true,
                     size: PLAYER_COLLISION_SIZE * resolution.pixel_ratio,
                 },
-                // Mesh2d(meshes.add(Rectangle::from_size(PLAYER_COLLISION_SIZE))),
-                // MeshMaterial2d(materials.add(Color::Srgba(Srgba {
-                //     red: 255.,
-                //     green: 0.,
-                //     blue: 0.,
-                //     alpha: 0.1,
-                // }))),
                 Transform::from_scale(Vec3::splat(resolution.pixel_ratio))
                     .with_translation(Vec3::new(0.0, -PLAYER_FEET_OFFSET * 0.5, 0.0)),
                 Anchor::Center,
             ));
         });
 }
+
+// Ataques del manifiesto de animaciones si los define, si no los de por defecto
+fn player_attacks(config: &GameConfig, definitions: &AttackDefinitions) -> AttackSet {
+    AttackSet {
+        damage: config.player.attack,
+        windows: definitions
+            .windows("player")
+            .unwrap_or_else(default_player_attacks),
+    }
+}
+
+// Ataque normal con Z y ataque cargado con V, que lanza a los enemigos ligeros y rompe escudos
+fn default_player_attacks() -> Vec<AttackWindow> {
+    vec![
+        AttackWindow {
+            state: CharacterState::Attacking,
+            frames: 3..4,
+            damage_multiplier: 1.0,
+            size: PLAYER_ATTACK_HITBOX_SIZE,
+            offset: Vec2::new(
+                PLAYER_ATTACK_HITBOX_SIZE.x * PLAYER_ATTACK_HITBOX_OFFSET,
+                0.0,
+            ),
+            duration: PLAYER_ATTACK_HITBOX_DURATION,
+            knockback: PLAYER_ATTACK_KNOCKBACK,
+            launch: 0.0,
+            guard_break: false,
+            reaction: None,
+            status: None,
+        },
+        AttackWindow {
+            state: CharacterState::ChargeAttacking,
+            frames: 4..5,
+            damage_multiplier: 2.0,
+            size: PLAYER_CHARGE_ATTACK_HITBOX_SIZE,
+            offset: Vec2::new(
+                PLAYER_CHARGE_ATTACK_HITBOX_SIZE.x * PLAYER_ATTACK_HITBOX_OFFSET,
+                0.0,
+            ),
+            duration: PLAYER_ATTACK_HITBOX_DURATION,
+            knockback: PLAYER_ATTACK_KNOCKBACK,
+            launch: PLAYER_CHARGE_ATTACK_LAUNCH,
+            guard_break: true,
+            reaction: Some(HitReaction::Launch),
+            status: None,
+        },
+    ]
+}
+
+// Aplica los cambios del archivo de configuración al jugador sin reiniciar la partida
+pub fn apply_player_config(
+    config: Res<GameConfig>,
+    attack_definitions: Res<AttackDefinitions>,
+    mut player_query: Query<(&mut Player, &mut AttackSet, &mut MovementTuning)>,
+) {
+    for (mut player, mut attack_set, mut movement) in &mut player_query {
+        player.max_health = config.player.max_health;
+        player.health = player.health.min(player.max_health);
+        player.attack = config.player.attack;
+        player.defense = config.player.defense;
+        player.speed = config.player.speed;
+        *movement = config.player.movement;
+        *attack_set = player_attacks(&config, &attack_definitions);
+    }
+}
@@ -0,0 +1,97 @@
+use bevy::prelude::*;
+
+use crate::animations::CharacterDimensions;
+use crate::enemy::SightBlocker;
+use crate::game::{GameState, InGame};
+use crate::layers::Layer;
+use crate::physics::{BodyCollider, Physics, Solid};
+use crate::player::Player;
+
+// Pushable block constants
+const PUSHABLE_BLOCK_SIZE: Vec2 = Vec2::new(80.0, 80.0);
+const PUSHABLE_BLOCK_COLOR: Color = Color::srgb(0.35, 0.33, 0.3);
+const PUSHABLE_BLOCK_PUSH_SPEED: f32 = 70.0; // Heavy: much slower than walking
+const PUSHABLE_BLOCK_CONTACT_DISTANCE: f32 = 4.0; // How close the player must be to push
+
+// Heavy block the player can shove by walking into it
+#[derive(Component)]
+pub struct PushableBlock;
+
+pub struct PushableBlockPlugin;
+
+impl Plugin for PushableBlockPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, push_blocks.run_if(in_state(GameState::Playing)));
+    }
+}
+
+pub fn spawn_pushable_block(commands: &mut Commands, position: Vec2) -> Entity {
+    commands
+        .spawn((
+            PushableBlock,
+            StateScoped(InGame),
+            Solid {
+                size: PUSHABLE_BLOCK_SIZE,
+            },
+            BodyCollider {
+                size: PUSHABLE_BLOCK_SIZE,
+            },
+            SightBlocker {
+                size: PUSHABLE_BLOCK_SIZE,
+            },
+            Physics::default(),
+            CharacterDimensions {
+                height: PUSHABLE_BLOCK_SIZE.y,
+                feet_offset: PUSHABLE_BLOCK_SIZE.y / 2.0,
+            },
+            Sprite {
+                color: PUSHABLE_BLOCK_COLOR,
+                custom_size: Some(PUSHABLE_BLOCK_SIZE),
+                ..default()
+            },
+            Transform::from_translation(position.extend(Layer::Terrain.z())),
+        ))
+        .id()
+}
+
+// Blocks only move while the player walks into their side; otherwise they stay put
+#[allow(clippy::type_complexity)]
+fn push_blocks(
+    mut blocks: Query<(Entity, &Transform, &mut Physics), (With<PushableBlock>, Without<Player>)>,
+    mut player_query: Query<(&Transform, &mut Physics, &BodyCollider), With<Player>>,
+) {
+    for (_, _, mut block_physics) in &mut blocks {
+        block_physics.velocity.x = 0.0;
+    }
+
+    let Ok((player_transform, mut player_physics, player_collider)) = player_query.get_single_mut()
+    else {
+        return;
+    };
+
+    let direction = player_physics.velocity.x.signum();
+    if player_physics.velocity.x == 0.0 {
+        return;
+    }
+
+    let player_pos = player_transform.translation.truncate();
+    let pushed_block = blocks.iter().find_map(|(entity, block_transform, _)| {
+        let delta = block_transform.translation.truncate() - player_pos;
+        let gap = delta.abs() - (player_collider.size + PUSHABLE_BLOCK_SIZE) / 2.0;
+
+        // Beside the block (not standing on it) and walking towards it
+        (delta.x * direction > 0.0
+            && gap.x <= PUSHABLE_BLOCK_CONTACT_DISTANCE
+            && gap.y < -PUSHABLE_BLOCK_CONTACT_DISTANCE)
+            .then_some(entity)
+    });
+
+    if let Some(entity) = pushed_block
+        && let Ok((_, _, mut block_physics)) = blocks.get_mut(entity)
+        && block_physics.on_ground
+    {
+        // The player slows down to the block's pace while pushing
+        block_physics.velocity.x = direction * PUSHABLE_BLOCK_PUSH_SPEED;
+        player_physics.velocity.x = direction * PUSHABLE_BLOCK_PUSH_SPEED;
+    }
+}
@@ -0,0 +1,290 @@
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use serde::{Deserialize, Serialize};
+
+use crate::doors::DoorProgress;
+use crate::endless::{self, RunMode};
+use crate::game::{GameState, InGame};
+use crate::game_over::{Checkpoint, CheckpointReachedEvent};
+use crate::input::KeyBindings;
+use crate::inventory::{self, CharmKind, Inventory};
+use crate::level::RoomRegistry;
+use crate::map::MapProgress;
+use crate::player::Player;
+use crate::save_transfer::SaveProfile;
+use crate::settings::GameSettings;
+use crate::toll_gate::OpenedTollGates;
+
+// Save constants
+pub const SAVE_SLOTS: usize = 3;
+
+// Bench the game was last saved at, where continuing picks up
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BenchSave {
+    pub name: String,
+    pub room_id: String,
+    pub position: (f32, f32),
+}
+
+// Everything that carries over from one session to the next. Health isn't kept: the
+// player always gets up from a bench rested
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SaveData {
+    pub currency: u32,
+    pub soul: f32,
+    pub charms: Vec<CharmKind>,
+    pub equipped: Vec<CharmKind>,
+    pub notches: u32,
+    pub opened_gates: Vec<String>,
+    pub opened_doors: Vec<String>,
+    pub keys: Vec<String>,
+    pub visited_rooms: Vec<String>,
+    pub bench: Option<BenchSave>,
+    #[serde(default)]
+    pub playtime: f32, // Seconds spent playing, not counting the menus or pauses
+    #[serde(default)]
+    pub profile: Option<SaveProfile>, // Controls and accessibility of whoever plays the slot
+}
+
+// A game that has only just started
+impl Default for SaveData {
+    fn default() -> Self {
+        let inventory = Inventory::default();
+        Self {
+            currency: 0,
+            soul: 0.0,
+            charms: inventory.charms,
+            equipped: inventory.equipped,
+            notches: inventory.notches,
+            opened_gates: Vec::new(),
+            opened_doors: Vec::new(),
+            keys: Vec::new(),
+            visited_rooms: Vec::new(),
+            bench: None,
+            playtime: 0.0,
+            profile: None,
+        }
+    }
+}
+
+// Slots are numbered from 1 on disk and on screen
+fn slot_path(slot: usize) -> String {
+    format!("save_{}.sav", slot + 1)
+}
+
+impl SaveData {
+    // The save in the slot, if there is one that can be read
+    pub fn load(slot: usize) -> Option<Self> {
+        let contents = std::fs::read_to_string(slot_path(slot)).ok()?;
+        ron::from_str(&contents)
+            .inspect_err(|error| warn!("Could not read save slot {}: {error}", slot + 1))
+            .ok()
+    }
+
+    pub fn write(&self, slot: usize) {
+        let contents = match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(contents) => contents,
+            Err(error) => {
+                warn!("Could not save the game: {error}");
+                return;
+            }
+        };
+        if let Err(error) = std::fs::write(slot_path(slot), contents) {
+            warn!("Could not save the game: {error}");
+        }
+    }
+
+    pub fn delete(slot: usize) {
+        match std::fs::remove_file(slot_path(slot)) {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => {
+                warn!("Could not delete save slot {}: {error}", slot + 1);
+            }
+            _ => {}
+        }
+    }
+
+    // Share of the world's rooms, doors, toll gates and keys the save has gotten to, from
+    // 0 to 1. The endless road isn't part of the story
+    pub fn completion(&self, registry: &RoomRegistry) -> f32 {
+        let total: usize = registry
+            .rooms
+            .iter()
+            .filter(|room| room.id != endless::ENDLESS_ROAD_ID)
+            .map(|room| 1 + room.doors.len() + room.toll_gates.len() + room.key_items.len())
+            .sum();
+        let reached = self.visited_rooms.len()
+            + self.opened_doors.len()
+            + self.opened_gates.len()
+            + self.keys.len();
+        if total == 0 {
+            0.0
+        } else {
+            (reached as f32 / total as f32).min(1.0)
+        }
+    }
+
+    // Area and bench the save picks up at
+    pub fn location(&self, registry: &RoomRegistry) -> String {
+        let Some(bench) = &self.bench else {
+            return "The beginning".to_string();
+        };
+        match registry.get(&bench.room_id) {
+            Some(room) => {
+                let area = room.area.as_deref().unwrap_or(&room.name);
+                format!("{area}, {}", bench.name)
+            }
+            None => bench.name.clone(),
+        }
+    }
+}
+
+fn sorted(ids: &HashSet<String>) -> Vec<String> {
+    let mut ids: Vec<String> = ids.iter().cloned().collect();
+    ids.sort_unstable();
+    ids
+}
+
+// Save the current run started from and the slot it belongs to, replaced every time the
+// game is saved at a bench. Retries go back to it, so progress since the last bench is lost
+// with the run. The playtime keeps counting regardless
+#[derive(Resource, Default)]
+pub struct ActiveSave {
+    pub slot: usize,
+    pub data: SaveData,
+}
+
+impl ActiveSave {
+    // Controls and accessibility changed during a run belong to the slot. Unlike progress
+    // they don't wait for a bench: the slot's file takes them straight away, if it has one
+    pub fn store_profile(&mut self, profile: SaveProfile) {
+        if let Some(mut on_disk) = SaveData::load(self.slot) {
+            on_disk.profile = Some(profile.clone());
+            on_disk.write(self.slot);
+        }
+        self.data.profile = Some(profile);
+    }
+}
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActiveSave>()
+            // Set up before a run's OnEnter systems spawn anything from it, whether it
+            // was started from the menu or is a retry
+            .add_systems(OnEnter(GameState::Menu), restore_global_bindings)
+            .add_systems(OnExit(GameState::Menu), restore_progress)
+            .add_systems(OnEnter(GameState::Restarting), restore_progress)
+            .add_systems(
+                OnEnter(InGame),
+                restore_player_stats.after(inventory::apply_charms),
+            )
+            .add_systems(
+                Update,
+                (count_playtime, autosave_at_benches).run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn restore_progress(
+    save: Res<ActiveSave>,
+    mut opened_gates: ResMut<OpenedTollGates>,
+    mut door_progress: ResMut<DoorProgress>,
+    mut map_progress: ResMut<MapProgress>,
+    mut inventory: ResMut<Inventory>,
+    mut checkpoint: ResMut<Checkpoint>,
+    mut bindings: ResMut<KeyBindings>,
+    mut settings: ResMut<GameSettings>,
+) {
+    let save = &save.data;
+    opened_gates.gates = save.opened_gates.iter().cloned().collect();
+    door_progress.opened = save.opened_doors.iter().cloned().collect();
+    door_progress.keys = save.keys.iter().cloned().collect();
+    map_progress.visited = save.visited_rooms.iter().cloned().collect();
+    *inventory = Inventory {
+        charms: save.charms.clone(),
+        equipped: save.equipped.clone(),
+        notches: save.notches,
+    };
+    checkpoint.position = save
+        .bench
+        .as_ref()
+        .map(|bench| Vec2::new(bench.position.0, bench.position.1));
+
+    // A slot without its own profile goes back to the global bindings, so it doesn't keep
+    // the last slot's
+    match &save.profile {
+        Some(profile) => {
+            *bindings = profile.bindings.clone();
+            settings.input_display = profile.input_display;
+            settings.shake_intensity = profile.shake_intensity;
+        }
+        None => {
+            *bindings = KeyBindings::load();
+            let defaults = GameSettings::default();
+            settings.input_display = defaults.input_display;
+            settings.shake_intensity = defaults.shake_intensity;
+        }
+    }
+}
+
+// The title screen edits the global controls, not those of the slot last played
+fn restore_global_bindings(mut bindings: ResMut<KeyBindings>) {
+    *bindings = KeyBindings::load();
+}
+
+fn restore_player_stats(save: Res<ActiveSave>, mut players: Query<&mut Player>) {
+    for mut player in &mut players {
+        player.currency = save.data.currency;
+        player.soul = save.data.soul.min(player.max_soul);
+    }
+}
+
+fn count_playtime(time: Res<Time>, mut save: ResMut<ActiveSave>) {
+    save.data.playtime += time.delta_secs();
+}
+
+// Endless runs have nothing worth keeping, and mustn't overwrite the story's save. The
+// profile is the slot's own, if it has one
+#[allow(clippy::too_many_arguments)]
+fn autosave_at_benches(
+    run_mode: Res<RunMode>,
+    mut checkpoint_events: EventReader<CheckpointReachedEvent>,
+    mut save: ResMut<ActiveSave>,
+    players: Query<&Player>,
+    inventory: Res<Inventory>,
+    opened_gates: Res<OpenedTollGates>,
+    door_progress: Res<DoorProgress>,
+    map_progress: Res<MapProgress>,
+) {
+    let Some(event) = checkpoint_events.read().last() else {
+        return;
+    };
+    if *run_mode != RunMode::Story {
+        return;
+    }
+    let Ok(player) = players.get_single() else {
+        return;
+    };
+
+    save.data = SaveData {
+        currency: player.currency,
+        soul: player.soul,
+        charms: inventory.charms.clone(),
+        equipped: inventory.equipped.clone(),
+        notches: inventory.notches,
+        opened_gates: sorted(&opened_gates.gates),
+        opened_doors: sorted(&door_progress.opened),
+        keys: sorted(&door_progress.keys),
+        visited_rooms: sorted(&map_progress.visited),
+        bench: Some(BenchSave {
+            name: event.bench.clone(),
+            room_id: event.room_id.clone(),
+            position: (event.position.x, event.position.y),
+        }),
+        playtime: save.data.playtime,
+        profile: save.data.profile.clone(),
+    };
+    save.data.write(save.slot);
+}
@@ -0,0 +1,329 @@
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+
+use crate::endless::RunMode;
+use crate::game::GameState;
+use crate::input::{ActionState, InputAction};
+use crate::layers::UiLayer;
+use crate::level::RoomRegistry;
+use crate::save::{ActiveSave, SAVE_SLOTS, SaveData};
+
+const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
+const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
+const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
+const DISABLED_BUTTON: Color = Color::srgb(0.1, 0.1, 0.1);
+const DISABLED_TEXT: Color = Color::srgb(0.4, 0.4, 0.4);
+const DELETE_CONFIRM_BUTTON: Color = Color::srgb(0.55, 0.15, 0.15);
+const SLOTS_BACKGROUND: Color = Color::srgba(0.05, 0.05, 0.05, 0.95);
+const SLOT_PANEL_COLOR: Color = Color::srgb(0.1, 0.1, 0.12);
+const SLOT_PANEL_WIDTH: f32 = 720.0;
+const SLOT_BUTTON_WIDTH: f32 = 130.0;
+const DETAIL_COLOR: Color = Color::srgb(0.7, 0.7, 0.7);
+
+// Save slot screen, opened from the main menu when starting the story
+#[derive(Resource, Default)]
+pub struct SaveSlotsMenu {
+    pub open: bool,
+    confirm_delete: Option<usize>, // Slot whose delete button has been pressed once
+}
+
+// Run condition for the main menu, which waits while the slots are up
+pub fn slots_closed(menu: Res<SaveSlotsMenu>) -> bool {
+    !menu.open
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SlotAction {
+    Continue,
+    NewGame,
+    Delete,
+}
+
+// Continue and delete are greyed out on an empty slot
+#[derive(Component)]
+struct SlotButton {
+    slot: usize,
+    action: SlotAction,
+    enabled: bool,
+}
+
+#[derive(Component)]
+struct SaveSlotsScreen;
+
+#[derive(Component)]
+struct SlotsBackButton;
+
+pub struct SaveSlotsPlugin;
+
+impl Plugin for SaveSlotsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SaveSlotsMenu>()
+            .add_systems(OnExit(GameState::Menu), close_save_slots)
+            .add_systems(
+                Update,
+                (
+                    sync_save_slots_screen.run_if(resource_changed::<SaveSlotsMenu>),
+                    handle_slot_buttons,
+                    handle_slots_back,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Menu)),
+            );
+    }
+}
+
+fn close_save_slots(mut menu: ResMut<SaveSlotsMenu>) {
+    *menu = SaveSlotsMenu::default();
+}
+
+fn format_playtime(seconds: f32) -> String {
+    let minutes = (seconds / 60.0) as u32;
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
+
+// Spawns the screen when it's opened and takes it down when it's closed. Slots are read
+// from disk each time, so a deleted one shows up empty straight away
+fn sync_save_slots_screen(
+    mut commands: Commands,
+    menu: Res<SaveSlotsMenu>,
+    registry: Res<RoomRegistry>,
+    asset_server: Res<AssetServer>,
+    screens: Query<Entity, With<SaveSlotsScreen>>,
+) {
+    for entity in &screens {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !menu.open {
+        return;
+    }
+
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let text = |value: String, size: f32, color: Color| {
+        (
+            Text::new(value),
+            TextFont {
+                font: font.clone(),
+                font_size: size,
+                ..default()
+            },
+            TextColor(color),
+        )
+    };
+
+    commands
+        .spawn((
+            SaveSlotsScreen,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(14.0),
+                ..default()
+            },
+            BackgroundColor(SLOTS_BACKGROUND),
+            // Keeps clicks off the menu underneath
+            FocusPolicy::Block,
+            GlobalZIndex(UiLayer::Submenu.z_index()),
+        ))
+        .with_children(|parent| {
+            parent.spawn(text("SELECT A SAVE".to_string(), 32.0, Color::WHITE));
+
+            for slot in 0..SAVE_SLOTS {
+                let save = SaveData::load(slot);
+                parent
+                    .spawn((
+                        Node {
+                            width: Val::Px(SLOT_PANEL_WIDTH),
+                            padding: UiRect::all(Val::Px(14.0)),
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(10.0),
+                            ..default()
+                        },
+                        BorderRadius::all(Val::Px(8.0)),
+                        BackgroundColor(SLOT_PANEL_COLOR),
+                    ))
+                    .with_children(|parent| {
+                        parent
+                            .spawn(Node {
+                                flex_direction: FlexDirection::Column,
+                                flex_grow: 1.0,
+                                row_gap: Val::Px(4.0),
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                parent.spawn(text(
+                                    format!("Slot {}", slot + 1),
+                                    24.0,
+                                    Color::WHITE,
+                                ));
+                                match &save {
+                                    Some(save) => {
+                                        parent.spawn(text(
+                                            save.location(&registry),
+                                            18.0,
+                                            DETAIL_COLOR,
+                                        ));
+                                        parent.spawn(text(
+                                            format!(
+                                                "{}  -  {}% complete",
+                                                format_playtime(save.playtime),
+                                                (save.completion(&registry) * 100.0).round()
+                                            ),
+                                            18.0,
+                                            DETAIL_COLOR,
+                                        ));
+                                    }
+                                    None => {
+                                        parent.spawn(text("Empty".to_string(), 18.0, DETAIL_COLOR));
+                                    }
+                                }
+                            });
+
+                        let has_save = save.is_some();
+                        for (action, label) in [
+                            (SlotAction::Continue, "Continue"),
+                            (SlotAction::NewGame, "New Game"),
+                            (SlotAction::Delete, "Delete"),
+                        ] {
+                            let enabled = has_save || action == SlotAction::NewGame;
+                            let confirming =
+                                action == SlotAction::Delete && menu.confirm_delete == Some(slot);
+                            let (color, label) = match (enabled, confirming) {
+                                (false, _) => (DISABLED_BUTTON, label),
+                                (true, true) => (DELETE_CONFIRM_BUTTON, "Sure?"),
+                                (true, false) => (NORMAL_BUTTON, label),
+                            };
+                            parent
+                                .spawn((
+                                    Button,
+                                    SlotButton {
+                                        slot,
+                                        action,
+                                        enabled,
+                                    },
+                                    Node {
+                                        width: Val::Px(SLOT_BUTTON_WIDTH),
+                                        height: Val::Px(45.0),
+                                        border: UiRect::all(Val::Px(3.0)),
+                                        justify_content: JustifyContent::Center,
+                                        align_items: AlignItems::Center,
+                                        ..default()
+                                    },
+                                    BorderColor(Color::BLACK),
+                                    BorderRadius::MAX,
+                                    BackgroundColor(color),
+                                ))
+                                .with_child(text(
+                                    label.to_string(),
+                                    20.0,
+                                    if enabled { Color::WHITE } else { DISABLED_TEXT },
+                                ));
+                        }
+                    });
+            }
+
+            parent
+                .spawn((
+                    Button,
+                    SlotsBackButton,
+                    Node {
+                        width: Val::Px(150.0),
+                        height: Val::Px(55.0),
+                        border: UiRect::all(Val::Px(3.0)),
+                        margin: UiRect::top(Val::Px(20.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BorderColor(Color::BLACK),
+                    BorderRadius::MAX,
+                    BackgroundColor(NORMAL_BUTTON),
+                ))
+                .with_child(text("Back".to_string(), 24.0, Color::WHITE));
+        });
+}
+
+// A new game in a used slot only replaces its save at the first bench. Deleting takes a
+// second press on the same button
+#[allow(clippy::type_complexity)]
+fn handle_slot_buttons(
+    mut menu: ResMut<SaveSlotsMenu>,
+    mut save: ResMut<ActiveSave>,
+    mut run_mode: ResMut<RunMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut interaction_query: Query<
+        (&SlotButton, &Interaction, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+) {
+    for (button, interaction, mut color) in &mut interaction_query {
+        if !button.enabled {
+            continue;
+        }
+        let confirming =
+            button.action == SlotAction::Delete && menu.confirm_delete == Some(button.slot);
+        match *interaction {
+            Interaction::Pressed => {
+                *color = PRESSED_BUTTON.into();
+                let data = match button.action {
+                    SlotAction::Continue => SaveData::load(button.slot),
+                    SlotAction::NewGame => Some(SaveData::default()),
+                    SlotAction::Delete => {
+                        if confirming {
+                            SaveData::delete(button.slot);
+                            menu.confirm_delete = None;
+                        } else {
+                            menu.confirm_delete = Some(button.slot);
+                        }
+                        continue;
+                    }
+                };
+                // The file may have gone since the screen was drawn
+                let Some(data) = data else {
+                    menu.set_changed();
+                    continue;
+                };
+                *save = ActiveSave {
+                    slot: button.slot,
+                    data,
+                };
+                *run_mode = RunMode::Story;
+                next_state.set(GameState::Playing);
+            }
+            Interaction::Hovered if !confirming => *color = HOVERED_BUTTON.into(),
+            Interaction::None if !confirming => *color = NORMAL_BUTTON.into(),
+            _ => {}
+        }
+    }
+}
+
+// The pause key backs out to the main menu, as it does from the settings screen
+#[allow(clippy::type_complexity)]
+fn handle_slots_back(
+    mut menu: ResMut<SaveSlotsMenu>,
+    mut actions: ResMut<ActionState>,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<SlotsBackButton>),
+    >,
+) {
+    if !menu.open {
+        return;
+    }
+
+    for (interaction, mut color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => *menu = SaveSlotsMenu::default(),
+            Interaction::Hovered => *color = HOVERED_BUTTON.into(),
+            Interaction::None => *color = NORMAL_BUTTON.into(),
+        }
+    }
+
+    if actions.just_pressed(InputAction::Pause) {
+        actions.consume(InputAction::Pause);
+        *menu = SaveSlotsMenu::default();
+    }
+}
@@ -0,0 +1,231 @@
+use std::fmt;
+
+use bevy::utils::HashSet;
+use serde::{Deserialize, Serialize};
+
+use crate::input::KeyBindings;
+use crate::level::RoomRegistry;
+use crate::save::SaveData;
+use crate::settings::GameSettings;
+
+// Portable save file constants
+pub const SAVE_EXPORT_PATH: &str = "save_export.hksave";
+const SAVE_VERSION: u32 = 5; // Versions 1 to 4 were a line format that is no longer read
+
+// Why an exported save could not be written or read back
+#[derive(Debug)]
+pub enum SaveTransferError {
+    Io(std::io::Error),
+    UnsupportedVersion(u32),
+    Malformed(String),
+}
+
+impl fmt::Display for SaveTransferError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveTransferError::Io(error) => write!(f, "{error}"),
+            SaveTransferError::UnsupportedVersion(version) if *version > SAVE_VERSION => {
+                write!(f, "made by a newer version of the game (v{version})")
+            }
+            SaveTransferError::UnsupportedVersion(version) => {
+                write!(f, "made by an older version of the game (v{version})")
+            }
+            SaveTransferError::Malformed(reason) => write!(f, "{reason}"),
+        }
+    }
+}
+
+impl From<std::io::Error> for SaveTransferError {
+    fn from(error: std::io::Error) -> Self {
+        SaveTransferError::Io(error)
+    }
+}
+
+// Controls and accessibility options carried by a save, so whoever plays it on a shared
+// machine gets their own setup back. Saves without one use the global settings
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SaveProfile {
+    pub bindings: KeyBindings,
+    pub input_display: bool,
+    #[serde(default = "default_shake_intensity")]
+    pub shake_intensity: f32,
+}
+
+fn default_shake_intensity() -> f32 {
+    GameSettings::default().shake_intensity
+}
+
+impl SaveProfile {
+    pub fn current(bindings: &KeyBindings, settings: &GameSettings) -> Self {
+        Self {
+            bindings: bindings.clone(),
+            input_display: settings.input_display,
+            shake_intensity: settings.shake_intensity,
+        }
+    }
+}
+
+// The whole save, profile included, in the same RON as the save slots. The version comes
+// first so a file from another version of the game is turned down before anything else
+#[derive(Serialize, Deserialize)]
+struct SaveExport {
+    version: u32,
+    save: SaveData,
+}
+
+// Only the version, read on its own so any version can be reported
+#[derive(Deserialize)]
+struct SaveExportVersion {
+    version: u32,
+}
+
+fn to_export_string(save: &SaveData) -> Result<String, SaveTransferError> {
+    let export = SaveExport {
+        version: SAVE_VERSION,
+        save: save.clone(),
+    };
+    ron::ser::to_string_pretty(&export, ron::ser::PrettyConfig::default())
+        .map_err(|error| SaveTransferError::Malformed(error.to_string()))
+}
+
+fn from_export_str(contents: &str) -> Result<SaveData, SaveTransferError> {
+    let SaveExportVersion { version } = ron::from_str(contents)
+        .map_err(|_| SaveTransferError::Malformed("not an exported save".to_string()))?;
+    if version != SAVE_VERSION {
+        return Err(SaveTransferError::UnsupportedVersion(version));
+    }
+    let export: SaveExport =
+        ron::from_str(contents).map_err(|error| SaveTransferError::Malformed(error.to_string()))?;
+    Ok(export.save)
+}
+
+// Everything the save names has to exist in this build of the world
+fn check_ids(save: &SaveData, registry: &RoomRegistry) -> Result<(), SaveTransferError> {
+    let unknown = |kind: &str, id: &str| {
+        Err(SaveTransferError::Malformed(format!(
+            "unknown {kind} '{id}'"
+        )))
+    };
+
+    let known_gates: HashSet<&str> = registry
+        .rooms
+        .iter()
+        .flat_map(|room| &room.toll_gates)
+        .map(|gate| gate.id.as_str())
+        .collect();
+    if let Some(gate) = save
+        .opened_gates
+        .iter()
+        .find(|gate| !known_gates.contains(gate.as_str()))
+    {
+        return unknown("toll gate", gate);
+    }
+
+    let known_doors: HashSet<&str> = registry
+        .rooms
+        .iter()
+        .flat_map(|room| &room.doors)
+        .map(|door| door.id.as_str())
+        .collect();
+    if let Some(door) = save
+        .opened_doors
+        .iter()
+        .find(|door| !known_doors.contains(door.as_str()))
+    {
+        return unknown("door", door);
+    }
+
+    let known_keys: HashSet<&str> = registry
+        .rooms
+        .iter()
+        .flat_map(|room| &room.key_items)
+        .map(|key| key.id.as_str())
+        .collect();
+    if let Some(key) = save
+        .keys
+        .iter()
+        .find(|key| !known_keys.contains(key.as_str()))
+    {
+        return unknown("key", key);
+    }
+
+    let rooms = save
+        .visited_rooms
+        .iter()
+        .chain(save.bench.as_ref().map(|bench| &bench.room_id));
+    for room in rooms {
+        if registry.get(room).is_none() {
+            return unknown("room", room);
+        }
+    }
+    Ok(())
+}
+
+// Writes the save to the portable file
+pub fn export_save(save: &SaveData) -> Result<(), SaveTransferError> {
+    std::fs::write(SAVE_EXPORT_PATH, to_export_string(save)?)?;
+    Ok(())
+}
+
+// Reads an exported save back; nothing is applied unless it is valid
+pub fn import_save(registry: &RoomRegistry) -> Result<SaveData, SaveTransferError> {
+    let save = from_export_str(&std::fs::read_to_string(SAVE_EXPORT_PATH)?)?;
+    check_ids(&save, registry)?;
+    Ok(save)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::CharmKind;
+    use crate::save::BenchSave;
+
+    #[test]
+    fn export_round_trips_the_whole_save() {
+        let save = SaveData {
+            currency: 420,
+            soul: 33.0,
+            charms: vec![CharmKind::StalwartShell, CharmKind::HeavyBlow],
+            equipped: vec![CharmKind::HeavyBlow],
+            notches: 5,
+            opened_gates: vec!["gate".to_string()],
+            opened_doors: vec!["door".to_string()],
+            keys: vec!["key".to_string()],
+            visited_rooms: vec!["room".to_string()],
+            bench: Some(BenchSave {
+                name: "Bench".to_string(),
+                room_id: "room".to_string(),
+                position: (12.0, -300.0),
+            }),
+            playtime: 3600.5,
+            profile: Some(SaveProfile {
+                bindings: KeyBindings::default(),
+                input_display: true,
+                shake_intensity: 0.4,
+            }),
+        };
+
+        let contents = to_export_string(&save).unwrap();
+        assert_eq!(from_export_str(&contents).unwrap(), save);
+    }
+
+    #[test]
+    fn other_versions_are_turned_down() {
+        let contents = to_export_string(&SaveData::default())
+            .unwrap()
+            .replace(&format!("version: {SAVE_VERSION}"), "version: 9");
+        assert!(matches!(
+            from_export_str(&contents),
+            Err(SaveTransferError::UnsupportedVersion(9))
+        ));
+    }
+
+    #[test]
+    fn old_line_format_is_not_read() {
+        let contents = "hollow-knight-like-game save\nversion 4\n[toll_gates]\n";
+        assert!(matches!(
+            from_export_str(contents),
+            Err(SaveTransferError::Malformed(_))
+        ));
+    }
+}
@@ -0,0 +1,319 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::animations::CharacterDimensions;
+use crate::enemy::SightBlocker;
+use crate::game::{GameState, InGame};
+use crate::hitbox::{AttackHitbox, DEFAULT_KNOCKBACK_DECAY, Faction, Knockback, ManagedHitbox};
+use crate::layers::Layer;
+use crate::level::{GROUND_TOP_Y, RoomRegistry, SetPieceKind};
+use crate::physics::{Physics, Solid};
+use crate::triggers::{TriggerEnteredEvent, TriggerZone};
+
+// Set piece constants
+const SET_PIECE_TRIGGER_SIZE: Vec2 = Vec2::new(40.0, 600.0);
+const SET_PIECE_RUMBLE_TIME: f32 = 0.7; // Warning before anything falls
+const BRIDGE_PLANK_WIDTH: f32 = 40.0;
+const BRIDGE_PLANK_HEIGHT: f32 = 14.0;
+const BRIDGE_COLOR: Color = Color::srgb(0.45, 0.33, 0.22);
+const BRIDGE_SHAKE: f32 = 2.5;
+const BRIDGE_COLLAPSE_STAGGER: f32 = 0.08; // Planks give way one after another
+const CAVE_IN_ROCK_INTERVAL: f32 = 0.15;
+const CAVE_IN_DROP_HEIGHT: f32 = 520.0; // Above the ground top, out of view
+const DEBRIS_SIZE_MIN: f32 = 18.0;
+const DEBRIS_SIZE_MAX: f32 = 34.0;
+const DEBRIS_COLOR: Color = Color::srgb(0.38, 0.35, 0.33);
+const DEBRIS_DAMAGE: f32 = 15.0;
+const DEBRIS_KNOCKBACK: Knockback = Knockback {
+    strength: 250.0,
+    lift: 150.0,
+    decay: DEFAULT_KNOCKBACK_DECAY,
+};
+const DEBRIS_LIFETIME: f32 = 4.0;
+const DEBRIS_FADE_TIME: f32 = 1.0;
+
+// Where a set piece is in its script; each one only ever plays once
+enum SetPiecePhase {
+    Armed,
+    Rumbling(Timer),
+    Collapsing { timer: Timer, remaining: usize },
+    Finished,
+}
+
+// Trigger of a one-shot environmental event
+#[derive(Component)]
+struct SetPiece {
+    id: String,
+    kind: SetPieceKind,
+    phase: SetPiecePhase,
+}
+
+// Piece of a collapsing bridge, solid until its set piece plays
+#[derive(Component)]
+struct BridgePlank {
+    set_piece: String,
+    home: Vec2,
+    order: usize, // Planks closest to the trigger fall first
+}
+
+// Plank that will drop once its timer runs out
+#[derive(Component)]
+struct FallingPlank(Timer);
+
+// Rock from a cave-in: hurts whatever it lands on, then lies there for a while
+#[derive(Component)]
+struct Debris {
+    lifetime: Timer,
+}
+
+pub struct SetPiecePlugin;
+
+impl Plugin for SetPiecePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(InGame), spawn_set_pieces)
+            .add_systems(
+                Update,
+                (
+                    start_set_pieces,
+                    update_set_pieces,
+                    drop_falling_planks,
+                    update_debris,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn spawn_set_pieces(mut commands: Commands, registry: Res<RoomRegistry>) {
+    for set_piece in registry.rooms.iter().flat_map(|room| &room.set_pieces) {
+        commands.spawn((
+            SetPiece {
+                id: set_piece.id.clone(),
+                kind: set_piece.kind,
+                phase: SetPiecePhase::Armed,
+            },
+            StateScoped(InGame),
+            TriggerZone::new(SET_PIECE_TRIGGER_SIZE),
+            Transform::from_xyz(set_piece.trigger_x, GROUND_TOP_Y, 0.0),
+        ));
+
+        let SetPieceKind::BridgeCollapse { min_x, max_x } = set_piece.kind else {
+            continue;
+        };
+        let plank_count = ((max_x - min_x) / BRIDGE_PLANK_WIDTH).ceil() as usize;
+        let trigger_on_right = set_piece.trigger_x > max_x;
+        for index in 0..plank_count {
+            let home = Vec2::new(
+                min_x + (index as f32 + 0.5) * BRIDGE_PLANK_WIDTH,
+                GROUND_TOP_Y - BRIDGE_PLANK_HEIGHT / 2.0,
+            );
+            let size = Vec2::new(BRIDGE_PLANK_WIDTH, BRIDGE_PLANK_HEIGHT);
+            commands.spawn((
+                BridgePlank {
+                    set_piece: set_piece.id.clone(),
+                    home,
+                    order: if trigger_on_right {
+                        plank_count - 1 - index
+                    } else {
+                        index
+                    },
+                },
+                StateScoped(InGame),
+                Solid { size },
+                SightBlocker { size },
+                Sprite {
+                    color: BRIDGE_COLOR,
+                    custom_size: Some(size),
+                    ..default()
+                },
+                Transform::from_translation(home.extend(Layer::Terrain.z())),
+            ));
+        }
+    }
+}
+
+fn start_set_pieces(
+    mut trigger_events: EventReader<TriggerEnteredEvent>,
+    mut set_pieces: Query<&mut SetPiece>,
+) {
+    for event in trigger_events.read() {
+        if let Ok(mut set_piece) = set_pieces.get_mut(event.trigger)
+            && matches!(set_piece.phase, SetPiecePhase::Armed)
+        {
+            info!("Set piece {} started", set_piece.id);
+            set_piece.phase = SetPiecePhase::Rumbling(Timer::from_seconds(
+                SET_PIECE_RUMBLE_TIME,
+                TimerMode::Once,
+            ));
+        }
+    }
+}
+
+// Rumble, then the bridge drops plank by plank or the rocks come down one at a time
+fn update_set_pieces(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut set_pieces: Query<&mut SetPiece>,
+    mut planks: Query<(Entity, &BridgePlank, &mut Transform)>,
+) {
+    let mut rng = rand::thread_rng();
+
+    for mut set_piece in &mut set_pieces {
+        let set_piece = &mut *set_piece;
+        match (&mut set_piece.phase, set_piece.kind) {
+            (SetPiecePhase::Rumbling(timer), kind) => {
+                timer.tick(time.delta());
+
+                if let SetPieceKind::BridgeCollapse { .. } = kind {
+                    for (_, plank, mut transform) in &mut planks {
+                        if plank.set_piece == set_piece.id {
+                            let shake = if timer.finished() {
+                                Vec2::ZERO
+                            } else {
+                                Vec2::new(
+                                    rng.gen_range(-BRIDGE_SHAKE..BRIDGE_SHAKE),
+                                    rng.gen_range(-BRIDGE_SHAKE..BRIDGE_SHAKE),
+                                )
+                            };
+                            transform.translation.x = plank.home.x + shake.x;
+                            transform.translation.y = plank.home.y + shake.y;
+                        }
+                    }
+                }
+
+                if timer.finished() {
+                    set_piece.phase = match kind {
+                        SetPieceKind::BridgeCollapse { .. } => {
+                            for (entity, plank, _) in &planks {
+                                if plank.set_piece == set_piece.id {
+                                    commands.entity(entity).insert(FallingPlank(
+                                        Timer::from_seconds(
+                                            plank.order as f32 * BRIDGE_COLLAPSE_STAGGER,
+                                            TimerMode::Once,
+                                        ),
+                                    ));
+                                }
+                            }
+                            SetPiecePhase::Finished
+                        }
+                        SetPieceKind::CaveIn { rocks, .. } => SetPiecePhase::Collapsing {
+                            timer: Timer::from_seconds(CAVE_IN_ROCK_INTERVAL, TimerMode::Repeating),
+                            remaining: rocks,
+                        },
+                    };
+                }
+            }
+            (
+                SetPiecePhase::Collapsing { timer, remaining },
+                SetPieceKind::CaveIn { min_x, max_x, .. },
+            ) => {
+                timer.tick(time.delta());
+                if timer.just_finished() {
+                    let x = rng.gen_range(min_x..max_x);
+                    let size = rng.gen_range(DEBRIS_SIZE_MIN..DEBRIS_SIZE_MAX);
+                    spawn_debris(
+                        &mut commands,
+                        Vec2::new(x, GROUND_TOP_Y + CAVE_IN_DROP_HEIGHT),
+                        size,
+                    );
+                    *remaining -= 1;
+                    if *remaining == 0 {
+                        set_piece.phase = SetPiecePhase::Finished;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn spawn_debris(commands: &mut Commands, position: Vec2, size: f32) {
+    commands
+        .spawn((
+            Debris {
+                lifetime: Timer::from_seconds(DEBRIS_LIFETIME, TimerMode::Once),
+            },
+            StateScoped(InGame),
+            Physics::default(),
+            CharacterDimensions {
+                height: size,
+                feet_offset: size / 2.0,
+            },
+            Faction::Neutral,
+            Sprite {
+                color: DEBRIS_COLOR,
+                custom_size: Some(Vec2::splat(size)),
+                ..default()
+            },
+            Transform::from_translation(position.extend(Layer::Effects.z())),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                AttackHitbox {
+                    damage: DEBRIS_DAMAGE,
+                    active: true,
+                    size: Vec2::splat(size),
+                    timer: Timer::from_seconds(DEBRIS_LIFETIME, TimerMode::Once),
+                    launch: 0.0,
+                    guard_break: true, // A shield held sideways doesn't stop a rock from above
+                    reaction: None,
+                    status: None,
+                },
+                DEBRIS_KNOCKBACK,
+                ManagedHitbox,
+                Transform::default(),
+            ));
+        });
+}
+
+// Planks stop holding anything up and fall into the pit under the bridge
+fn drop_falling_planks(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut planks: Query<(Entity, &mut FallingPlank)>,
+) {
+    let mut rng = rand::thread_rng();
+
+    for (entity, mut falling) in &mut planks {
+        falling.0.tick(time.delta());
+        if !falling.0.finished() {
+            continue;
+        }
+
+        commands
+            .entity(entity)
+            .remove::<(Solid, SightBlocker, FallingPlank, BridgePlank)>()
+            .insert(Physics {
+                velocity: Vec2::new(rng.gen_range(-40.0..40.0), 0.0),
+                ..default()
+            });
+    }
+}
+
+// Rocks only hurt while falling, then fade away where they landed
+fn update_debris(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut debris: Query<(Entity, &mut Debris, &Physics, &Children, &mut Sprite)>,
+    mut hitboxes: Query<&mut AttackHitbox>,
+) {
+    for (entity, mut debris, physics, children, mut sprite) in &mut debris {
+        debris.lifetime.tick(time.delta());
+        if debris.lifetime.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        if physics.on_ground {
+            for &child in children {
+                if let Ok(mut hitbox) = hitboxes.get_mut(child) {
+                    hitbox.active = false;
+                }
+            }
+        }
+
+        let alpha = (debris.lifetime.remaining_secs() / DEBRIS_FADE_TIME).min(1.0);
+        sprite.color = DEBRIS_COLOR.with_alpha(alpha);
+    }
+}
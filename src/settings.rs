@@ -0,0 +1,178 @@
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use bevy::window::{MonitorSelection, PresentMode, PrimaryWindow, WindowMode};
+
+use crate::audio::AudioChannel;
+
+// Step the volume and shake settings move by
+pub const SETTINGS_STEP: f32 = 0.1;
+
+// Frame rate cap applied when the frame finishes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum FrameRateLimit {
+    Fps30,
+    #[default]
+    Fps60,
+    Fps120,
+    Uncapped,
+}
+
+impl FrameRateLimit {
+    pub fn next(self) -> Self {
+        match self {
+            FrameRateLimit::Fps30 => FrameRateLimit::Fps60,
+            FrameRateLimit::Fps60 => FrameRateLimit::Fps120,
+            FrameRateLimit::Fps120 => FrameRateLimit::Uncapped,
+            FrameRateLimit::Uncapped => FrameRateLimit::Fps30,
+        }
+    }
+
+    pub fn frame_duration(self) -> Option<Duration> {
+        match self {
+            FrameRateLimit::Fps30 => Some(Duration::from_secs_f64(1.0 / 30.0)),
+            FrameRateLimit::Fps60 => Some(Duration::from_secs_f64(1.0 / 60.0)),
+            FrameRateLimit::Fps120 => Some(Duration::from_secs_f64(1.0 / 120.0)),
+            FrameRateLimit::Uncapped => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FrameRateLimit::Fps30 => "30",
+            FrameRateLimit::Fps60 => "60",
+            FrameRateLimit::Fps120 => "120",
+            FrameRateLimit::Uncapped => "Uncapped",
+        }
+    }
+}
+
+// How the game window fills the screen
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+impl DisplayMode {
+    pub fn next(self) -> Self {
+        match self {
+            DisplayMode::Windowed => DisplayMode::Borderless,
+            DisplayMode::Borderless => DisplayMode::Fullscreen,
+            DisplayMode::Fullscreen => DisplayMode::Windowed,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DisplayMode::Windowed => "Windowed",
+            DisplayMode::Borderless => "Borderless",
+            DisplayMode::Fullscreen => "Fullscreen",
+        }
+    }
+
+    fn window_mode(self) -> WindowMode {
+        match self {
+            DisplayMode::Windowed => WindowMode::Windowed,
+            DisplayMode::Borderless => WindowMode::BorderlessFullscreen(MonitorSelection::Current),
+            DisplayMode::Fullscreen => WindowMode::Fullscreen(MonitorSelection::Current),
+        }
+    }
+}
+
+// User-facing options, applied live whenever they change
+#[derive(Resource)]
+pub struct GameSettings {
+    pub frame_rate_limit: FrameRateLimit,
+    pub vsync: bool,
+    pub input_display: bool, // Overlay of held actions, for streams and tutorial videos
+    pub display_mode: DisplayMode,
+    pub master_volume: f32, // Volumes and shake go from 0 to 1
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub shake_intensity: f32, // Scales screen shake, 0 turns it off
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        Self {
+            frame_rate_limit: FrameRateLimit::default(),
+            vsync: true,
+            input_display: false,
+            display_mode: DisplayMode::default(),
+            master_volume: 1.0,
+            music_volume: 0.8,
+            sfx_volume: 1.0,
+            shake_intensity: 1.0,
+        }
+    }
+}
+
+impl GameSettings {
+    // Volume a sound on the channel plays at, master included
+    pub fn volume(&self, channel: AudioChannel) -> f32 {
+        let channel_volume = match channel {
+            AudioChannel::Music => self.music_volume,
+            AudioChannel::Sfx => self.sfx_volume,
+        };
+        self.master_volume * channel_volume
+    }
+}
+
+// Time at which the previous frame ended, used to pace the next one
+#[derive(Resource)]
+struct FrameLimiter {
+    last_frame: Instant,
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameSettings>()
+            .insert_resource(FrameLimiter {
+                last_frame: Instant::now(),
+            })
+            .add_systems(
+                Update,
+                apply_window_settings.run_if(resource_changed::<GameSettings>),
+            )
+            .add_systems(Last, limit_frame_rate);
+    }
+}
+
+fn apply_window_settings(
+    settings: Res<GameSettings>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let present_mode = if settings.vsync {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    };
+
+    let window_mode = settings.display_mode.window_mode();
+
+    for mut window in &mut windows {
+        if window.present_mode != present_mode {
+            window.present_mode = present_mode;
+        }
+        if window.mode != window_mode {
+            window.mode = window_mode;
+        }
+    }
+}
+
+// Sleeps away whatever is left of the frame budget for the selected cap
+fn limit_frame_rate(settings: Res<GameSettings>, mut limiter: ResMut<FrameLimiter>) {
+    if let Some(frame_duration) = settings.frame_rate_limit.frame_duration() {
+        let elapsed = limiter.last_frame.elapsed();
+        if elapsed < frame_duration {
+            std::thread::sleep(frame_duration - elapsed);
+        }
+    }
+
+    limiter.last_frame = Instant::now();
+}
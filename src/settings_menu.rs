@@ -0,0 +1,638 @@
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+
+use crate::endless::RunMode;
+use crate::game::GameState;
+use crate::input::{self, ActionState, InputAction, KeyBindings};
+use crate::layers::UiLayer;
+use crate::save::ActiveSave;
+use crate::save_transfer::SaveProfile;
+use crate::settings::{GameSettings, SETTINGS_STEP};
+
+const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
+const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
+const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
+const SETTINGS_BACKGROUND: Color = Color::srgba(0.05, 0.05, 0.05, 0.95);
+const SETTING_LABEL_WIDTH: f32 = 260.0;
+const SETTING_ROW_HEIGHT: f32 = 40.0;
+const STEP_BUTTON_WIDTH: f32 = 45.0;
+
+// Settings screen, opened over the main menu or the pause menu
+#[derive(Resource, Default)]
+pub struct SettingsMenu {
+    pub open: bool,
+    rebinding: Option<InputAction>, // Action waiting for the next key press
+}
+
+// Run condition for the menus under the settings screen, which wait while it's open
+pub fn settings_closed(menu: Res<SettingsMenu>) -> bool {
+    !menu.open
+}
+
+// Run condition for menu navigation, which leaves keys alone while one is being rebound
+pub fn capturing_binding(menu: Res<SettingsMenu>) -> bool {
+    menu.rebinding.is_some()
+}
+
+// One line of the settings screen
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SettingControl {
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    ShakeIntensity,
+    DisplayMode,
+    Vsync,
+    FrameRate,
+    InputDisplay,
+}
+
+impl SettingControl {
+    const ALL: [SettingControl; 8] = [
+        SettingControl::MasterVolume,
+        SettingControl::MusicVolume,
+        SettingControl::SfxVolume,
+        SettingControl::ShakeIntensity,
+        SettingControl::DisplayMode,
+        SettingControl::Vsync,
+        SettingControl::FrameRate,
+        SettingControl::InputDisplay,
+    ];
+
+    // Sliders get minus and plus buttons, the rest cycle when clicked
+    fn slider(self, settings: &mut GameSettings) -> Option<&mut f32> {
+        match self {
+            SettingControl::MasterVolume => Some(&mut settings.master_volume),
+            SettingControl::MusicVolume => Some(&mut settings.music_volume),
+            SettingControl::SfxVolume => Some(&mut settings.sfx_volume),
+            SettingControl::ShakeIntensity => Some(&mut settings.shake_intensity),
+            _ => None,
+        }
+    }
+
+    // Accessibility options travel with the save slot, like the controls
+    fn in_profile(self) -> bool {
+        matches!(
+            self,
+            SettingControl::ShakeIntensity | SettingControl::InputDisplay
+        )
+    }
+
+    fn is_slider(self) -> bool {
+        matches!(
+            self,
+            SettingControl::MasterVolume
+                | SettingControl::MusicVolume
+                | SettingControl::SfxVolume
+                | SettingControl::ShakeIntensity
+        )
+    }
+
+    fn adjust(self, settings: &mut GameSettings, direction: f32) {
+        if let Some(value) = self.slider(settings) {
+            // Snapped to the step so repeated presses land on round numbers
+            *value = ((*value + direction * SETTINGS_STEP) / SETTINGS_STEP).round() * SETTINGS_STEP;
+            *value = value.clamp(0.0, 1.0);
+            return;
+        }
+
+        match self {
+            SettingControl::DisplayMode => settings.display_mode = settings.display_mode.next(),
+            SettingControl::Vsync => settings.vsync = !settings.vsync,
+            SettingControl::FrameRate => {
+                settings.frame_rate_limit = settings.frame_rate_limit.next();
+            }
+            SettingControl::InputDisplay => settings.input_display = !settings.input_display,
+            _ => {}
+        }
+    }
+
+    fn label(self, settings: &GameSettings) -> String {
+        let percent = |value: f32| format!("{}%", (value * 100.0).round());
+        let on_off = |value: bool| if value { "On" } else { "Off" };
+
+        match self {
+            SettingControl::MasterVolume => {
+                format!("Master volume: {}", percent(settings.master_volume))
+            }
+            SettingControl::MusicVolume => {
+                format!("Music volume: {}", percent(settings.music_volume))
+            }
+            SettingControl::SfxVolume => {
+                format!("Effects volume: {}", percent(settings.sfx_volume))
+            }
+            SettingControl::ShakeIntensity => {
+                format!("Screen shake: {}", percent(settings.shake_intensity))
+            }
+            SettingControl::DisplayMode => format!("Window: {}", settings.display_mode.label()),
+            SettingControl::Vsync => format!("VSync: {}", on_off(settings.vsync)),
+            SettingControl::FrameRate => {
+                format!("Frame rate: {}", settings.frame_rate_limit.label())
+            }
+            SettingControl::InputDisplay => {
+                format!("Input display: {}", on_off(settings.input_display))
+            }
+        }
+    }
+}
+
+// Root of the settings screen
+#[derive(Component)]
+struct SettingsScreen;
+
+// Moves a setting one step; cycling settings ignore the direction
+#[derive(Component, Clone, Copy)]
+struct SettingButton {
+    control: SettingControl,
+    direction: f32,
+}
+
+#[derive(Component)]
+struct SettingLabel(SettingControl);
+
+// Starts listening for a new key for the action
+#[derive(Component)]
+struct BindingButton(InputAction);
+
+#[derive(Component)]
+struct BindingLabel(InputAction);
+
+// Puts every action back on its default key
+#[derive(Component)]
+struct ResetBindingsButton;
+
+#[derive(Component)]
+struct SettingsBackButton;
+
+// Reads Cancel while a key is being picked
+#[derive(Component)]
+struct SettingsBackLabel;
+
+pub struct SettingsMenuPlugin;
+
+impl Plugin for SettingsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SettingsMenu>()
+            .add_systems(OnExit(GameState::Menu), close_settings_menu)
+            .add_systems(OnExit(GameState::Paused), close_settings_menu)
+            .add_systems(
+                Update,
+                (
+                    sync_settings_screen.run_if(resource_changed::<SettingsMenu>),
+                    handle_setting_buttons,
+                    handle_binding_buttons,
+                    handle_reset_bindings,
+                    capture_binding,
+                    handle_settings_back,
+                    update_setting_labels.run_if(resource_changed::<GameSettings>),
+                    update_binding_labels.run_if(
+                        resource_changed::<KeyBindings>.or(resource_changed::<SettingsMenu>),
+                    ),
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn close_settings_menu(mut menu: ResMut<SettingsMenu>) {
+    *menu = SettingsMenu::default();
+}
+
+// Spawns the screen when it's opened and takes it down when it's closed
+fn sync_settings_screen(
+    mut commands: Commands,
+    menu: Res<SettingsMenu>,
+    settings: Res<GameSettings>,
+    bindings: Res<KeyBindings>,
+    asset_server: Res<AssetServer>,
+    screens: Query<Entity, With<SettingsScreen>>,
+) {
+    if !menu.open {
+        for entity in &screens {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+    if !screens.is_empty() {
+        return;
+    }
+
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    commands
+        .spawn((
+            SettingsScreen,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+            BackgroundColor(SETTINGS_BACKGROUND),
+            // Keeps clicks off the menu underneath
+            FocusPolicy::Block,
+            GlobalZIndex(UiLayer::Submenu.z_index()),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("SETTINGS"),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 32.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+
+            // Options on the left, controls on the right
+            parent
+                .spawn(Node {
+                    column_gap: Val::Px(40.0),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn(Node {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            row_gap: Val::Px(10.0),
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            for control in SettingControl::ALL {
+                                spawn_setting_row(parent, &font, control, &settings);
+                            }
+                        });
+
+                    parent
+                        .spawn(Node {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            row_gap: Val::Px(6.0),
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            for action in InputAction::ALL {
+                                parent
+                                    .spawn((
+                                        BindingButton(action),
+                                        setting_button_node(SETTING_LABEL_WIDTH),
+                                    ))
+                                    .with_child((
+                                        BindingLabel(action),
+                                        Text::new(binding_label(action, &bindings, None)),
+                                        TextFont {
+                                            font: font.clone(),
+                                            font_size: 20.0,
+                                            ..default()
+                                        },
+                                        TextColor(Color::WHITE),
+                                    ));
+                            }
+
+                            parent
+                                .spawn((
+                                    ResetBindingsButton,
+                                    setting_button_node(SETTING_LABEL_WIDTH),
+                                ))
+                                .with_child((
+                                    Text::new("Reset to defaults"),
+                                    TextFont {
+                                        font: font.clone(),
+                                        font_size: 20.0,
+                                        ..default()
+                                    },
+                                    TextColor(Color::WHITE),
+                                ));
+                        });
+                });
+
+            parent
+                .spawn((
+                    Button,
+                    SettingsBackButton,
+                    Node {
+                        width: Val::Px(150.0),
+                        height: Val::Px(55.0),
+                        border: UiRect::all(Val::Px(3.0)),
+                        margin: UiRect::top(Val::Px(20.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BorderColor(Color::BLACK),
+                    BorderRadius::MAX,
+                    BackgroundColor(NORMAL_BUTTON),
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        SettingsBackLabel,
+                        Text::new("Back"),
+                        TextFont {
+                            font: font.clone(),
+                            font_size: 24.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                    ));
+                });
+        });
+}
+
+fn spawn_setting_row(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    control: SettingControl,
+    settings: &GameSettings,
+) {
+    let label = (
+        SettingLabel(control),
+        Text::new(control.label(settings)),
+        TextFont {
+            font: font.clone(),
+            font_size: 20.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+    );
+
+    if !control.is_slider() {
+        parent
+            .spawn(setting_button(
+                SettingButton {
+                    control,
+                    direction: 1.0,
+                },
+                SETTING_LABEL_WIDTH + 2.0 * (STEP_BUTTON_WIDTH + 10.0),
+            ))
+            .with_child(label);
+        return;
+    }
+
+    parent
+        .spawn(Node {
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(10.0),
+            ..default()
+        })
+        .with_children(|parent| {
+            spawn_step_button(parent, font, control, -1.0, "-");
+            parent
+                .spawn(Node {
+                    width: Val::Px(SETTING_LABEL_WIDTH),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                })
+                .with_child(label);
+            spawn_step_button(parent, font, control, 1.0, "+");
+        });
+}
+
+fn spawn_step_button(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    control: SettingControl,
+    direction: f32,
+    symbol: &str,
+) {
+    parent
+        .spawn(setting_button(
+            SettingButton { control, direction },
+            STEP_BUTTON_WIDTH,
+        ))
+        .with_child((
+            Text::new(symbol),
+            TextFont {
+                font: font.clone(),
+                font_size: 24.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+        ));
+}
+
+fn setting_button(button: SettingButton, width: f32) -> impl Bundle {
+    (button, setting_button_node(width))
+}
+
+fn setting_button_node(width: f32) -> impl Bundle {
+    (
+        Button,
+        Node {
+            width: Val::Px(width),
+            height: Val::Px(SETTING_ROW_HEIGHT),
+            border: UiRect::all(Val::Px(3.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BorderColor(Color::BLACK),
+        BorderRadius::MAX,
+        BackgroundColor(NORMAL_BUTTON),
+    )
+}
+
+// In a story run, controls and accessibility belong to the slot being played. On the title
+// screen, and in endless runs, the bindings are the global ones
+fn keep_controls(
+    state: &GameState,
+    run_mode: RunMode,
+    save: &mut ActiveSave,
+    bindings: &KeyBindings,
+    settings: &GameSettings,
+) {
+    if *state != GameState::Menu && run_mode == RunMode::Story {
+        save.store_profile(SaveProfile::current(bindings, settings));
+    } else {
+        bindings.save();
+    }
+}
+
+// Changes apply as soon as they're made, so the effect can be judged right away
+#[allow(clippy::type_complexity)]
+fn handle_setting_buttons(
+    state: Res<State<GameState>>,
+    run_mode: Res<RunMode>,
+    mut save: ResMut<ActiveSave>,
+    bindings: Res<KeyBindings>,
+    mut settings: ResMut<GameSettings>,
+    mut interaction_query: Query<
+        (&Interaction, &SettingButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+) {
+    for (interaction, button, mut color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                button.control.adjust(&mut settings, button.direction);
+                if button.control.in_profile() {
+                    keep_controls(state.get(), *run_mode, &mut save, &bindings, &settings);
+                }
+                *color = PRESSED_BUTTON.into();
+            }
+            Interaction::Hovered => *color = HOVERED_BUTTON.into(),
+            Interaction::None => *color = NORMAL_BUTTON.into(),
+        }
+    }
+}
+
+fn binding_label(
+    action: InputAction,
+    bindings: &KeyBindings,
+    rebinding: Option<InputAction>,
+) -> String {
+    if rebinding == Some(action) {
+        format!("{}: press a key", action.label())
+    } else {
+        format!(
+            "{}: {}",
+            action.label(),
+            input::key_name(bindings.key(action))
+        )
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn handle_binding_buttons(
+    mut menu: ResMut<SettingsMenu>,
+    mut interaction_query: Query<
+        (&Interaction, &BindingButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+) {
+    for (interaction, button, mut color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                menu.rebinding = Some(button.0);
+                *color = PRESSED_BUTTON.into();
+            }
+            Interaction::Hovered => *color = HOVERED_BUTTON.into(),
+            Interaction::None => *color = NORMAL_BUTTON.into(),
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn handle_reset_bindings(
+    state: Res<State<GameState>>,
+    run_mode: Res<RunMode>,
+    mut save: ResMut<ActiveSave>,
+    settings: Res<GameSettings>,
+    mut menu: ResMut<SettingsMenu>,
+    mut bindings: ResMut<KeyBindings>,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<ResetBindingsButton>),
+    >,
+) {
+    for (interaction, mut color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                *bindings = KeyBindings::default();
+                keep_controls(state.get(), *run_mode, &mut save, &bindings, &settings);
+                menu.rebinding = None;
+                *color = PRESSED_BUTTON.into();
+            }
+            Interaction::Hovered => *color = HOVERED_BUTTON.into(),
+            Interaction::None => *color = NORMAL_BUTTON.into(),
+        }
+    }
+}
+
+// The next bindable key goes to the action being rebound and is saved right away, Escape
+// included; other keys are ignored. The Back button or a controller's east face button
+// cancels, since no key is kept back for it. Nothing pressed meanwhile backs out of the screen
+#[allow(clippy::too_many_arguments)]
+pub fn capture_binding(
+    state: Res<State<GameState>>,
+    run_mode: Res<RunMode>,
+    mut save: ResMut<ActiveSave>,
+    settings: Res<GameSettings>,
+    mut menu: ResMut<SettingsMenu>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut actions: ResMut<ActionState>,
+    mut bindings: ResMut<KeyBindings>,
+) {
+    let Some(action) = menu.rebinding else {
+        return;
+    };
+    actions.consume(InputAction::Pause);
+
+    if gamepads
+        .iter()
+        .any(|gamepad| gamepad.just_pressed(GamepadButton::East))
+    {
+        menu.rebinding = None;
+        return;
+    }
+    let Some(&key) = keyboard.get_just_pressed().next() else {
+        return;
+    };
+    if input::is_bindable(key) {
+        bindings.rebind(action, key);
+        keep_controls(state.get(), *run_mode, &mut save, &bindings, &settings);
+        menu.rebinding = None;
+    }
+}
+
+// The pause key backs out of the screen without also closing the pause menu under it. While
+// a key is being picked, Back only cancels that
+#[allow(clippy::type_complexity)]
+fn handle_settings_back(
+    mut menu: ResMut<SettingsMenu>,
+    mut actions: ResMut<ActionState>,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<SettingsBackButton>),
+    >,
+) {
+    if !menu.open {
+        return;
+    }
+
+    for (interaction, mut color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed if menu.rebinding.is_some() => menu.rebinding = None,
+            Interaction::Pressed => menu.open = false,
+            Interaction::Hovered => *color = HOVERED_BUTTON.into(),
+            Interaction::None => *color = NORMAL_BUTTON.into(),
+        }
+    }
+
+    if actions.just_pressed(InputAction::Pause) {
+        actions.consume(InputAction::Pause);
+        menu.open = false;
+    }
+}
+
+fn update_setting_labels(
+    settings: Res<GameSettings>,
+    mut labels: Query<(&SettingLabel, &mut Text)>,
+) {
+    for (label, mut text) in &mut labels {
+        text.0 = label.0.label(&settings);
+    }
+}
+
+fn update_binding_labels(
+    menu: Res<SettingsMenu>,
+    bindings: Res<KeyBindings>,
+    mut labels: Query<(&BindingLabel, &mut Text), Without<SettingsBackLabel>>,
+    mut back_labels: Query<&mut Text, With<SettingsBackLabel>>,
+) {
+    for (label, mut text) in &mut labels {
+        text.0 = binding_label(label.0, &bindings, menu.rebinding);
+    }
+    for mut text in &mut back_labels {
+        text.0 = if menu.rebinding.is_some() {
+            "Cancel"
+        } else {
+            "Back"
+        }
+        .to_string();
+    }
+}
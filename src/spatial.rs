@@ -0,0 +1,106 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::game::GameState;
+use crate::hitbox::{self, CollisionHitbox};
+
+// Spatial hash constants
+const HITBOX_CELL_SIZE: f32 = 256.0; // About two characters across
+
+// Uniform grid that buckets boxes by the cells they cover, so collision checks only look
+// at what is nearby instead of testing every pair
+pub struct SpatialHash<T> {
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<T>>,
+}
+
+impl<T: Copy + PartialEq> SpatialHash<T> {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    pub fn insert(&mut self, item: T, position: Vec2, size: Vec2) {
+        let (min, max) = self.cell_range(position, size);
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                self.cells.entry(IVec2::new(x, y)).or_default().push(item);
+            }
+        }
+    }
+
+    // Everything sharing a cell with the box, each item once. Only a broadphase: callers
+    // still run the exact overlap test on what comes back
+    pub fn query(&self, position: Vec2, size: Vec2) -> Vec<T> {
+        let (min, max) = self.cell_range(position, size);
+        let mut found = Vec::new();
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                let Some(items) = self.cells.get(&IVec2::new(x, y)) else {
+                    continue;
+                };
+                for &item in items {
+                    if !found.contains(&item) {
+                        found.push(item);
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    fn cell_range(&self, position: Vec2, size: Vec2) -> (IVec2, IVec2) {
+        let half_size = size / 2.0;
+        let min = ((position - half_size) / self.cell_size).floor().as_ivec2();
+        let max = ((position + half_size) / self.cell_size).floor().as_ivec2();
+        (min, max)
+    }
+}
+
+// Active body hitboxes by where they are this frame, for hit and contact checks
+#[derive(Resource)]
+pub struct HitboxGrid(pub SpatialHash<Entity>);
+
+impl Default for HitboxGrid {
+    fn default() -> Self {
+        Self(SpatialHash::new(HITBOX_CELL_SIZE))
+    }
+}
+
+pub struct SpatialHashPlugin;
+
+impl Plugin for SpatialHashPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HitboxGrid>().add_systems(
+            Update,
+            rebuild_hitbox_grid
+                .before(hitbox::detect_hits)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+fn rebuild_hitbox_grid(
+    mut grid: ResMut<HitboxGrid>,
+    body_hitboxes: Query<(Entity, &CollisionHitbox, &Transform, &Parent)>,
+    owners: Query<&Transform>,
+) {
+    grid.0.clear();
+    for (entity, hitbox, transform, owner) in &body_hitboxes {
+        if hitbox.active
+            && let Ok(owner_transform) = owners.get(owner.get())
+        {
+            grid.0.insert(
+                entity,
+                hitbox::hitbox_position(owner_transform, transform),
+                hitbox.size,
+            );
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::game::GameState;
+use crate::hitbox::{self, DamageEvent, DeathEvent};
+
+// Status effect constants
+const STATUS_TICK_INTERVAL: f32 = 0.5; // Damage over time lands in small chunks
+const STATUS_TINT_STRENGTH: f32 = 0.5;
+const BURN_DURATION: f32 = 3.0;
+const BURN_DAMAGE_PER_SECOND: f32 = 8.0;
+const BURN_MAX_STACKS: u32 = 3;
+const BURN_COLOR: Color = Color::srgb(1.0, 0.45, 0.15);
+const POISON_DURATION: f32 = 6.0;
+const POISON_DAMAGE_PER_SECOND: f32 = 3.0;
+const POISON_MAX_STACKS: u32 = 5;
+const POISON_COLOR: Color = Color::srgb(0.45, 0.9, 0.3);
+const SLOW_DURATION: f32 = 2.5;
+const SLOW_SPEED_FACTOR: f32 = 0.75; // Per stack
+const SLOW_MAX_STACKS: u32 = 3;
+const SLOW_COLOR: Color = Color::srgb(0.45, 0.65, 1.0);
+
+// Lingering effects an attack can leave on whoever it hits
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatusEffectKind {
+    Burn,
+    Poison,
+    Slow,
+}
+
+impl StatusEffectKind {
+    fn duration(self) -> f32 {
+        match self {
+            StatusEffectKind::Burn => BURN_DURATION,
+            StatusEffectKind::Poison => POISON_DURATION,
+            StatusEffectKind::Slow => SLOW_DURATION,
+        }
+    }
+
+    fn max_stacks(self) -> u32 {
+        match self {
+            StatusEffectKind::Burn => BURN_MAX_STACKS,
+            StatusEffectKind::Poison => POISON_MAX_STACKS,
+            StatusEffectKind::Slow => SLOW_MAX_STACKS,
+        }
+    }
+
+    fn damage_per_second(self) -> f32 {
+        match self {
+            StatusEffectKind::Burn => BURN_DAMAGE_PER_SECOND,
+            StatusEffectKind::Poison => POISON_DAMAGE_PER_SECOND,
+            StatusEffectKind::Slow => 0.0,
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            StatusEffectKind::Burn => BURN_COLOR,
+            StatusEffectKind::Poison => POISON_COLOR,
+            StatusEffectKind::Slow => SLOW_COLOR,
+        }
+    }
+}
+
+// One effect currently on a character; reapplying adds a stack and refreshes the duration
+struct ActiveStatusEffect {
+    kind: StatusEffectKind,
+    stacks: u32,
+    remaining: Timer,
+}
+
+// Effects on a character and the sprite color they were applied over
+#[derive(Component)]
+pub struct StatusEffects {
+    effects: Vec<ActiveStatusEffect>,
+    base_tint: Color,
+    tick: Timer,
+    tinted: Option<StatusEffectKind>,
+}
+
+impl StatusEffects {
+    pub fn new(base_tint: Color) -> Self {
+        Self {
+            effects: Vec::new(),
+            base_tint,
+            tick: Timer::from_seconds(STATUS_TICK_INTERVAL, TimerMode::Repeating),
+            tinted: None,
+        }
+    }
+
+    pub fn apply(&mut self, kind: StatusEffectKind) {
+        if let Some(effect) = self.effects.iter_mut().find(|effect| effect.kind == kind) {
+            effect.stacks = (effect.stacks + 1).min(kind.max_stacks());
+            effect.remaining.reset();
+        } else {
+            self.effects.push(ActiveStatusEffect {
+                kind,
+                stacks: 1,
+                remaining: Timer::from_seconds(kind.duration(), TimerMode::Once),
+            });
+        }
+    }
+
+    // Multiplier for movement speed, 1.0 when nothing slows the character
+    pub fn speed_factor(&self) -> f32 {
+        self.effects
+            .iter()
+            .filter(|effect| effect.kind == StatusEffectKind::Slow)
+            .map(|effect| SLOW_SPEED_FACTOR.powi(effect.stacks as i32))
+            .product()
+    }
+
+    // The most recently applied effect decides the tint
+    fn tint_kind(&self) -> Option<StatusEffectKind> {
+        self.effects.last().map(|effect| effect.kind)
+    }
+}
+
+pub struct StatusEffectsPlugin;
+
+impl Plugin for StatusEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                apply_status_from_damage,
+                tick_status_effects,
+                clear_status_on_death,
+                tint_status_effects,
+            )
+                .chain()
+                .after(hitbox::detect_hits)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+fn apply_status_from_damage(
+    mut damage_events: EventReader<DamageEvent>,
+    mut targets: Query<&mut StatusEffects>,
+) {
+    for event in damage_events.read() {
+        if let Some(kind) = event.status
+            && let Ok(mut status_effects) = targets.get_mut(event.target)
+        {
+            status_effects.apply(kind);
+        }
+    }
+}
+
+// Expires effects and deals the damage over time of the ones still running
+fn tick_status_effects(
+    time: Res<Time>,
+    mut targets: Query<(Entity, &mut StatusEffects, &Transform)>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for (entity, mut status_effects, transform) in &mut targets {
+        if status_effects.effects.is_empty() {
+            continue;
+        }
+
+        for effect in &mut status_effects.effects {
+            effect.remaining.tick(time.delta());
+        }
+        status_effects
+            .effects
+            .retain(|effect| !effect.remaining.finished());
+
+        status_effects.tick.tick(time.delta());
+        if !status_effects.tick.just_finished() {
+            continue;
+        }
+
+        let amount: f32 = status_effects
+            .effects
+            .iter()
+            .map(|effect| effect.kind.damage_per_second() * effect.stacks as f32)
+            .sum::<f32>()
+            * STATUS_TICK_INTERVAL;
+        if amount > 0.0 {
+            // No hit reaction, a burning character can still fight back
+            damage_events.send(DamageEvent {
+                target: entity,
+                source: None,
+                amount,
+                position: transform.translation.truncate(),
+                reaction: None,
+                status: None,
+            });
+        }
+    }
+}
+
+fn clear_status_on_death(
+    mut death_events: EventReader<DeathEvent>,
+    mut targets: Query<&mut StatusEffects>,
+) {
+    for event in death_events.read() {
+        if let Ok(mut status_effects) = targets.get_mut(event.entity) {
+            status_effects.effects.clear();
+        }
+    }
+}
+
+// Only touches the sprite when the tint changes, so other color flashes aren't overridden
+fn tint_status_effects(mut targets: Query<(&mut StatusEffects, &mut Sprite)>) {
+    for (mut status_effects, mut sprite) in &mut targets {
+        let tint_kind = status_effects.tint_kind();
+        if tint_kind == status_effects.tinted {
+            continue;
+        }
+
+        status_effects.tinted = tint_kind;
+        sprite.color = match tint_kind {
+            Some(kind) => status_effects
+                .base_tint
+                .mix(&kind.color(), STATUS_TINT_STRENGTH),
+            None => status_effects.base_tint,
+        };
+    }
+}
@@ -0,0 +1,237 @@
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+use crate::enemy::SightBlocker;
+use crate::game::{GameState, InGame};
+use crate::input::{ActionState, InputAction, KeyBindings};
+use crate::layers::Layer;
+use crate::level::RoomRegistry;
+use crate::physics::Solid;
+use crate::player::Player;
+use crate::triggers::TriggerZone;
+
+// Toll gate constants
+const TOLL_GATE_SIZE: Vec2 = Vec2::new(40.0, 220.0); // Too tall to jump over
+const TOLL_GATE_COLOR: Color = Color::srgb(0.25, 0.22, 0.3);
+const TOLL_GATE_Y: f32 = -201.0; // Standing on the ground strip
+const TOLL_GATE_REACH: f32 = 70.0; // How far in front of the gate the prompt appears
+
+// Prompt constants
+const PROMPT_FONT_SIZE: f32 = 18.0;
+const PROMPT_OFFSET_Y: f32 = 30.0; // Above the top of the gate
+const PROMPT_COLOR: Color = Color::srgb(0.95, 0.9, 0.75);
+
+// Gate that stays shut until the player pays its toll
+#[derive(Component)]
+pub struct TollGate {
+    pub id: String,
+    pub cost: u32,
+}
+
+// What the prompt above a gate is asking
+#[derive(Component, Default, PartialEq)]
+enum TollPrompt {
+    #[default]
+    Hidden,
+    Offer,
+    Confirm,
+    TooPoor,
+}
+
+// Gates the player has paid for, saved with the game at benches
+#[derive(Resource, Default)]
+pub struct OpenedTollGates {
+    pub gates: HashSet<String>,
+}
+
+impl OpenedTollGates {
+    pub fn is_open(&self, id: &str) -> bool {
+        self.gates.contains(id)
+    }
+}
+
+pub struct TollGatePlugin;
+
+impl Plugin for TollGatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OpenedTollGates>()
+            // Gates only exist during a run, so an import from the menu waits for the next one
+            .add_systems(OnEnter(InGame), sync_toll_gates)
+            .add_systems(
+                Update,
+                sync_toll_gates.run_if(in_state(InGame).and(resource_changed::<OpenedTollGates>)),
+            )
+            .add_systems(
+                Update,
+                (interact_with_toll_gates, update_toll_prompts)
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+// Spawns every gate still shut and removes the ones opened since, e.g. by an imported save
+fn sync_toll_gates(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    registry: Res<RoomRegistry>,
+    opened_gates: Res<OpenedTollGates>,
+    gates: Query<(Entity, &TollGate)>,
+) {
+    for (entity, gate) in &gates {
+        if opened_gates.is_open(&gate.id) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    for definition in registry.rooms.iter().flat_map(|room| &room.toll_gates) {
+        let spawned = gates.iter().any(|(_, gate)| gate.id == definition.id);
+        if !spawned && !opened_gates.is_open(&definition.id) {
+            spawn_toll_gate(
+                &mut commands,
+                &asset_server,
+                &definition.id,
+                Vec2::new(definition.x, TOLL_GATE_Y),
+                definition.cost,
+            );
+        }
+    }
+}
+
+fn spawn_toll_gate(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    id: &str,
+    position: Vec2,
+    cost: u32,
+) {
+    commands
+        .spawn((
+            TollGate {
+                id: id.to_string(),
+                cost,
+            },
+            StateScoped(InGame),
+            Solid {
+                size: TOLL_GATE_SIZE,
+            },
+            SightBlocker {
+                size: TOLL_GATE_SIZE,
+            },
+            Sprite {
+                color: TOLL_GATE_COLOR,
+                custom_size: Some(TOLL_GATE_SIZE),
+                ..default()
+            },
+            Transform::from_translation(position.extend(Layer::Terrain.z())),
+        ))
+        .with_children(|parent| {
+            // Covers both sides so the gate can be paid from either direction
+            parent.spawn((
+                TriggerZone::new(Vec2::new(
+                    TOLL_GATE_SIZE.x + TOLL_GATE_REACH * 2.0,
+                    TOLL_GATE_SIZE.y,
+                )),
+                Transform::default(),
+            ));
+            parent.spawn((
+                TollPrompt::Hidden,
+                Text2d::new(""),
+                TextFont {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: PROMPT_FONT_SIZE,
+                    ..default()
+                },
+                TextColor(PROMPT_COLOR),
+                Transform::from_xyz(0.0, TOLL_GATE_SIZE.y / 2.0 + PROMPT_OFFSET_Y, 1.0),
+                Visibility::Hidden,
+            ));
+        });
+}
+
+// Interacting offers to pay, then interacting again confirms the payment and down cancels
+fn interact_with_toll_gates(
+    actions: Res<ActionState>,
+    mut opened_gates: ResMut<OpenedTollGates>,
+    mut player_query: Query<&mut Player>,
+    gates: Query<(&TollGate, &Children)>,
+    zones: Query<&TriggerZone>,
+    mut prompts: Query<&mut TollPrompt>,
+) {
+    let Ok(mut player) = player_query.get_single_mut() else {
+        return;
+    };
+
+    for (gate, children) in &gates {
+        let player_near = children
+            .iter()
+            .filter_map(|&child| zones.get(child).ok())
+            .any(|zone| zone.player_inside);
+        let Some(mut prompt) = children
+            .iter()
+            .find(|&&child| prompts.contains(child))
+            .and_then(|&child| prompts.get_mut(child).ok())
+        else {
+            continue;
+        };
+
+        if !player_near {
+            prompt.set_if_neq(TollPrompt::Hidden);
+            continue;
+        }
+
+        match *prompt {
+            TollPrompt::Hidden => *prompt = TollPrompt::Offer,
+            TollPrompt::Offer | TollPrompt::TooPoor => {
+                if actions.just_pressed(InputAction::Interact) {
+                    *prompt = if player.currency >= gate.cost {
+                        TollPrompt::Confirm
+                    } else {
+                        TollPrompt::TooPoor
+                    };
+                }
+            }
+            TollPrompt::Confirm => {
+                if actions.just_pressed(InputAction::Interact) {
+                    player.currency -= gate.cost;
+                    opened_gates.gates.insert(gate.id.clone());
+                } else if actions.just_pressed(InputAction::MoveDown) {
+                    *prompt = TollPrompt::Offer;
+                }
+            }
+        }
+    }
+}
+
+// Prompts name the keys they're bound to, and follow them when they're rebound
+fn update_toll_prompts(
+    gates: Query<&TollGate>,
+    bindings: Res<KeyBindings>,
+    mut prompts: Query<(Ref<TollPrompt>, &Parent, &mut Text2d, &mut Visibility)>,
+) {
+    for (prompt, parent, mut text, mut visibility) in &mut prompts {
+        if !prompt.is_changed() && !bindings.is_changed() {
+            continue;
+        }
+        let Ok(gate) = gates.get(parent.get()) else {
+            continue;
+        };
+        let interact = bindings.prompt(InputAction::Interact);
+
+        text.0 = match *prompt {
+            TollPrompt::Hidden => String::new(),
+            TollPrompt::Offer => format!("{interact} Pay {} to open", gate.cost),
+            TollPrompt::Confirm => format!(
+                "Pay {}? {interact} Yes  {} No",
+                gate.cost,
+                bindings.prompt(InputAction::MoveDown)
+            ),
+            TollPrompt::TooPoor => format!("Not enough currency ({} needed)", gate.cost),
+        };
+        *visibility = if *prompt == TollPrompt::Hidden {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+    }
+}
@@ -0,0 +1,71 @@
+use bevy::prelude::*;
+
+use crate::game::GameState;
+use crate::player::Player;
+use crate::utils;
+
+// Invisible box that reports when the player walks in or out of it
+#[derive(Component)]
+pub struct TriggerZone {
+    pub size: Vec2,
+    pub player_inside: bool,
+}
+
+impl TriggerZone {
+    pub fn new(size: Vec2) -> Self {
+        Self {
+            size,
+            player_inside: false,
+        }
+    }
+}
+
+// Sent the frame the player enters a trigger zone
+#[derive(Event)]
+pub struct TriggerEnteredEvent {
+    pub trigger: Entity,
+}
+
+// Sent the frame the player leaves a trigger zone
+#[derive(Event)]
+pub struct TriggerExitedEvent {
+    pub trigger: Entity,
+}
+
+pub struct TriggerPlugin;
+
+impl Plugin for TriggerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TriggerEnteredEvent>()
+            .add_event::<TriggerExitedEvent>()
+            .add_systems(
+                Update,
+                detect_trigger_overlaps.run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+pub fn detect_trigger_overlaps(
+    mut triggers: Query<(Entity, &GlobalTransform, &mut TriggerZone)>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    mut entered_events: EventWriter<TriggerEnteredEvent>,
+    mut exited_events: EventWriter<TriggerExitedEvent>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let player_pos = player_transform.translation().truncate();
+
+    for (entity, transform, mut zone) in &mut triggers {
+        let inside =
+            utils::point_in_rect(player_pos, transform.translation().truncate(), zone.size);
+
+        if inside && !zone.player_inside {
+            entered_events.send(TriggerEnteredEvent { trigger: entity });
+        } else if !inside && zone.player_inside {
+            exited_events.send(TriggerExitedEvent { trigger: entity });
+        }
+
+        zone.player_inside = inside;
+    }
+}
@@ -59,3 +59,235 @@ pub fn degrees_to_radians(degrees: f32) -> f32 {
 pub fn radians_to_degrees(radians: f32) -> f32 {
     radians * 180.0 / std::f32::consts::PI
 }
+
+/// Where a ray or a moving box first touched a rectangle
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+    pub distance: f32, // Along the ray, or the fraction of the move for swept boxes
+    pub point: Vec2,
+    pub normal: Vec2, // Side of the rectangle that was hit, zero when starting inside it
+}
+
+/// Casts a ray against an axis aligned box, returning the first hit within max_distance
+pub fn raycast_aabb(
+    origin: Vec2,
+    direction: Vec2,
+    max_distance: f32,
+    rect_pos: Vec2,
+    rect_size: Vec2,
+) -> Option<RayHit> {
+    let direction = direction.normalize_or_zero();
+    if direction == Vec2::ZERO {
+        return None;
+    }
+
+    let half_size = rect_size / 2.0;
+    let min = rect_pos - half_size;
+    let max = rect_pos + half_size;
+
+    let mut t_enter = 0.0_f32;
+    let mut t_exit = max_distance;
+    let mut normal = Vec2::ZERO;
+
+    // Slab test on each axis
+    for (axis, start, dir, low, high) in [
+        (Vec2::X, origin.x, direction.x, min.x, max.x),
+        (Vec2::Y, origin.y, direction.y, min.y, max.y),
+    ] {
+        if dir.abs() < f32::EPSILON {
+            // Parallel to this slab: only hits if already inside it
+            if start < low || start > high {
+                return None;
+            }
+            continue;
+        }
+
+        let t1 = (low - start) / dir;
+        let t2 = (high - start) / dir;
+        if t1.min(t2) > t_enter {
+            t_enter = t1.min(t2);
+            normal = -axis * dir.signum();
+        }
+        t_exit = t_exit.min(t1.max(t2));
+
+        if t_enter > t_exit {
+            return None;
+        }
+    }
+
+    Some(RayHit {
+        distance: t_enter,
+        point: origin + direction * t_enter,
+        normal,
+    })
+}
+
+/// Casts a ray against a rectangle, returning the distance to the first hit within max_distance
+pub fn raycast_rect(
+    origin: Vec2,
+    direction: Vec2,
+    max_distance: f32,
+    rect_pos: Vec2,
+    rect_size: Vec2,
+) -> Option<f32> {
+    raycast_aabb(origin, direction, max_distance, rect_pos, rect_size).map(|hit| hit.distance)
+}
+
+/// Moves a box by displacement and returns when it first touches a rectangle, as a fraction
+/// of the move (0 to 1). Catches fast bodies that would skip over thin walls in one frame
+pub fn swept_aabb_collision(
+    position: Vec2,
+    size: Vec2,
+    displacement: Vec2,
+    rect_pos: Vec2,
+    rect_size: Vec2,
+) -> Option<RayHit> {
+    // Growing the rectangle by the box turns the box into a point moving along a ray
+    let length = displacement.length();
+    let hit = raycast_aabb(position, displacement, length, rect_pos, rect_size + size)?;
+    Some(RayHit {
+        distance: hit.distance / length,
+        point: hit.point,
+        normal: hit.normal,
+    })
+}
+
+/// Casts a ray against several rectangles, returning the distance to the closest hit
+pub fn raycast_rects(
+    origin: Vec2,
+    direction: Vec2,
+    max_distance: f32,
+    rects: impl IntoIterator<Item = (Vec2, Vec2)>,
+) -> Option<f32> {
+    rects
+        .into_iter()
+        .filter_map(|(rect_pos, rect_size)| {
+            raycast_rect(origin, direction, max_distance, rect_pos, rect_size)
+        })
+        .min_by(|a, b| a.total_cmp(b))
+}
+
+/// Checks if the segment between two points crosses a rectangle
+pub fn segment_intersects_rect(start: Vec2, end: Vec2, rect_pos: Vec2, rect_size: Vec2) -> bool {
+    raycast_rect(start, end - start, start.distance(end), rect_pos, rect_size).is_some()
+}
+
+/// Checks if nothing blocks the straight line between two points
+pub fn line_of_sight(
+    from: Vec2,
+    to: Vec2,
+    blockers: impl IntoIterator<Item = (Vec2, Vec2)>,
+) -> bool {
+    raycast_rects(from, to - from, from.distance(to), blockers).is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raycast_hits_the_near_side() {
+        let hit = raycast_aabb(
+            Vec2::ZERO,
+            Vec2::X,
+            100.0,
+            Vec2::new(50.0, 0.0),
+            Vec2::splat(20.0),
+        )
+        .unwrap();
+        assert_eq!(hit.distance, 40.0);
+        assert_eq!(hit.point, Vec2::new(40.0, 0.0));
+        assert_eq!(hit.normal, Vec2::NEG_X);
+    }
+
+    #[test]
+    fn raycast_reports_the_face_it_enters() {
+        let hit = raycast_aabb(
+            Vec2::new(50.0, 100.0),
+            Vec2::NEG_Y,
+            200.0,
+            Vec2::new(50.0, 0.0),
+            Vec2::splat(20.0),
+        )
+        .unwrap();
+        assert_eq!(hit.distance, 90.0);
+        assert_eq!(hit.normal, Vec2::Y);
+    }
+
+    #[test]
+    fn raycast_stops_at_max_distance() {
+        let rect = (Vec2::new(50.0, 0.0), Vec2::splat(20.0));
+        assert!(raycast_aabb(Vec2::ZERO, Vec2::X, 30.0, rect.0, rect.1).is_none());
+        assert!(raycast_aabb(Vec2::ZERO, Vec2::NEG_X, 100.0, rect.0, rect.1).is_none());
+        assert!(raycast_aabb(Vec2::ZERO, Vec2::ZERO, 100.0, rect.0, rect.1).is_none());
+    }
+
+    #[test]
+    fn raycast_from_inside_hits_at_the_origin() {
+        let hit = raycast_aabb(Vec2::ZERO, Vec2::Y, 10.0, Vec2::ZERO, Vec2::splat(20.0)).unwrap();
+        assert_eq!(hit.distance, 0.0);
+        assert_eq!(hit.normal, Vec2::ZERO);
+    }
+
+    #[test]
+    fn swept_box_catches_thin_walls() {
+        // Moves 200 px in one step past a wall 4 px thick
+        let hit = swept_aabb_collision(
+            Vec2::ZERO,
+            Vec2::splat(20.0),
+            Vec2::new(200.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(4.0, 100.0),
+        )
+        .unwrap();
+        assert_eq!(hit.distance, 0.44);
+        assert_eq!(hit.normal, Vec2::NEG_X);
+        assert!(!check_rect_collision(
+            Vec2::new(200.0, 0.0),
+            Vec2::splat(20.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(4.0, 100.0)
+        ));
+    }
+
+    #[test]
+    fn swept_box_misses_what_it_passes_by() {
+        let hit = swept_aabb_collision(
+            Vec2::ZERO,
+            Vec2::splat(20.0),
+            Vec2::new(200.0, 0.0),
+            Vec2::new(100.0, 40.0),
+            Vec2::splat(20.0),
+        );
+        assert!(hit.is_none());
+        assert!(
+            swept_aabb_collision(
+                Vec2::ZERO,
+                Vec2::splat(20.0),
+                Vec2::ZERO,
+                Vec2::ZERO,
+                Vec2::splat(20.0)
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn segments_and_line_of_sight() {
+        let wall = (Vec2::new(50.0, 0.0), Vec2::new(10.0, 100.0));
+        assert!(segment_intersects_rect(
+            Vec2::ZERO,
+            Vec2::new(100.0, 0.0),
+            wall.0,
+            wall.1
+        ));
+        assert!(!segment_intersects_rect(
+            Vec2::ZERO,
+            Vec2::new(40.0, 0.0),
+            wall.0,
+            wall.1
+        ));
+        assert!(!line_of_sight(Vec2::ZERO, Vec2::new(100.0, 0.0), [wall]));
+        assert!(line_of_sight(Vec2::ZERO, Vec2::new(0.0, 100.0), [wall]));
+    }
+}